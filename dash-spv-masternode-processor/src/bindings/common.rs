@@ -1,13 +1,18 @@
-use std::ffi::CString;
+use std::ffi::{c_void, CStr, CString};
 use std::fs::File;
 use std::os::raw::c_char;
+use std::slice;
+use std::sync::Arc;
 use simplelog::{ColorChoice, CombinedLogger, ConfigBuilder, LevelFilter, TerminalMode, TermLogger, WriteLogger};
+use crate::common::target;
 use crate::crypto::byte_util::ConstDecodable;
 use crate::crypto::UInt256;
-use crate::ffi::boxer::boxed;
-use crate::ffi::callbacks::{AddInsightBlockingLookup, GetBlockHashByHeight, GetBlockHeightByHash, GetCLSignatureByBlockHash, GetLLMQSnapshotByBlockHash, HashDestroy, LLMQSnapshotDestroy, MasternodeListDestroy, MasternodeListLookup, MasternodeListSave, MerkleRootLookup, SaveCLSignature, SaveLLMQSnapshot, ShouldProcessDiffWithRange};
+use crate::ffi::{boxer::boxed, ByteArray};
+use crate::ffi::callbacks::{AddInsightBlockingLookup, FFICoreProvider, GetBlockHashByHeight, GetBlockHeightByHash, GetCLSignatureByBlockHash, GetLLMQSnapshotByBlockHash, HashDestroy, LLMQSnapshotDestroy, MasternodeListDestroy, MasternodeListLookup, MasternodeListSave, MerkleRootLookup, SaveCLSignature, SaveLLMQSnapshot, ShouldProcessDiffWithRange};
 use crate::ffi::unboxer::{unbox_any, unbox_vec_ptr};
-use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::processing::{CachePolicy, MasternodeProcessor, MasternodeProcessorCache, MetricsSink, MetricsSnapshot, ProcessorMetrics};
+use crate::processing::metrics::{FFIMetricsSink, MetricCallback};
+use crate::storage::{cache_from_snapshot_data, cache_snapshot_data, load_cache_snapshot, save_cache_snapshot};
 use crate::types;
 
 
@@ -28,8 +33,7 @@ pub unsafe extern "C" fn register_rust_logger() {
 
     // Create the log file inside the cache directory.
     let log_file_path = log_dir.join("processor.log");
-    println!("Log file create at: {:?}", log_file_path);
-    let log_file = File::create(log_file_path)
+    let log_file = File::create(&log_file_path)
         .expect("Failed to create log file");
     let config = ConfigBuilder::new().build();
     //let config = ConfigBuilder::new().set_time_level(LevelFilter::Off).set_max_level(LevelFilter::Off).build();
@@ -42,8 +46,9 @@ pub unsafe extern "C" fn register_rust_logger() {
             WriteLogger::new(LevelFilter::Info, config.clone(), log_file.try_clone().unwrap()),
         ]
     ) {
-        Ok(()) => println!("Logger initialized"),
-        Err(err) => println!("Failed to init logger: {}", err)
+        Ok(()) => info!(target: target::FFI, "Logger initialized, log file: {:?}", log_file_path),
+        // The logger installed before this call keeps receiving the records
+        Err(err) => warn!(target: target::FFI, "Failed to init logger: {}", err)
     }
 }
 
@@ -65,8 +70,8 @@ pub unsafe extern "C" fn register_processor(
     destroy_hash: HashDestroy,
     destroy_snapshot: LLMQSnapshotDestroy,
     should_process_diff_with_range: ShouldProcessDiffWithRange,
-) -> *mut MasternodeProcessor {
-    let processor = MasternodeProcessor::new(
+) -> *mut MasternodeProcessor<FFICoreProvider> {
+    let provider = FFICoreProvider::new(
         get_merkle_root_by_hash,
         get_block_height_by_hash,
         get_block_hash_by_height,
@@ -82,24 +87,56 @@ pub unsafe extern "C" fn register_processor(
         destroy_snapshot,
         should_process_diff_with_range,
     );
-    println!("register_processor: {:?}", processor);
+    let processor = MasternodeProcessor::new(provider);
+    debug!(target: target::FFI, "register_processor: {:?}", processor);
     boxed(processor)
 }
 
 /// Unregister all the callbacks for use across FFI
 /// # Safety
 #[no_mangle]
-pub unsafe extern "C" fn unregister_processor(processor: *mut MasternodeProcessor) {
-    println!("unregister_processor: {:?}", processor);
+pub unsafe extern "C" fn unregister_processor(processor: *mut MasternodeProcessor<FFICoreProvider>) {
+    debug!(target: target::FFI, "unregister_processor: {:p}", processor);
     let unboxed = unbox_any(processor);
 }
 
+/// Set number of threads used to verify quorum signatures (0 or 1 means sequential verification)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_set_quorum_verification_threads(processor: *mut MasternodeProcessor<FFICoreProvider>, threads: usize) {
+    (*processor).quorum_verification_threads = threads.max(1);
+}
+
+/// Start accumulating processing metrics, they can be read with `processor_metrics_snapshot`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_enable_metrics(processor: *mut MasternodeProcessor<FFICoreProvider>) {
+    (*processor).metrics = Some(Arc::new(ProcessorMetrics::default()));
+}
+
+/// Forward processing metrics to the host callback (replaces accumulating metrics), NULL callback disables metrics
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_set_metrics_callback(processor: *mut MasternodeProcessor<FFICoreProvider>, callback: Option<MetricCallback>, context: *const c_void) {
+    (*processor).metrics = callback.map(|callback| Arc::new(FFIMetricsSink { callback, context }) as Arc<dyn MetricsSink>);
+}
+
+/// Accumulated processing metrics (zeroed if metrics are not accumulated by the processor)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_metrics_snapshot(processor: *const MasternodeProcessor<FFICoreProvider>) -> MetricsSnapshot {
+    (*processor).metrics
+        .as_ref()
+        .and_then(|metrics| metrics.snapshot())
+        .unwrap_or_default()
+}
+
 /// Initialize opaque cache to store needed information between FFI calls
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn processor_create_cache() -> *mut MasternodeProcessorCache {
     let cache = MasternodeProcessorCache::default();
-    println!("processor_create_cache: {:?}", cache);
+    debug!(target: target::FFI, "processor_create_cache");
     boxed(cache)
 }
 
@@ -107,15 +144,97 @@ pub unsafe extern "C" fn processor_create_cache() -> *mut MasternodeProcessorCac
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn processor_destroy_cache(cache: *mut MasternodeProcessorCache) {
-    println!("processor_destroy_cache: {:?}", cache);
+    debug!(target: target::FFI, "processor_destroy_cache: {:p}", cache);
     let cache = unbox_any(cache);
 }
 
+/// Limit the cache: 0 means no limit for the corresponding entity
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_set_cache_policy(
+    max_masternode_lists: usize,
+    keep_last_masternode_lists: usize,
+    rotation_window: u32,
+    max_snapshots: usize,
+    max_cl_signatures: usize,
+    max_memory_usage: usize,
+    cache: *mut MasternodeProcessorCache,
+) {
+    let limit = |value: usize| (value > 0).then_some(value);
+    (*cache).policy = CachePolicy {
+        max_masternode_lists: limit(max_masternode_lists),
+        keep_last_masternode_lists,
+        rotation_window,
+        max_snapshots: limit(max_snapshots),
+        max_cl_signatures: limit(max_cl_signatures),
+        max_memory_usage: limit(max_memory_usage),
+    };
+    (*cache).enforce_policy();
+}
+
+/// Estimated number of bytes held by the cache
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_cache_memory_usage(cache: *const MasternodeProcessorCache) -> usize {
+    (*cache).memory_usage()
+}
+
+/// Binary snapshot of the cache (destroy with processor_destroy_byte_array)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_cache_snapshot(cache: *const MasternodeProcessorCache) -> ByteArray {
+    // Capacity should match the length to be released with processor_destroy_byte_array
+    cache_snapshot_data(&*cache).into_boxed_slice().into_vec().into()
+}
+
+/// Restore cache from the binary snapshot, the policy of the cache is kept
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_restore_cache_snapshot(data: *const u8, len: usize, cache: *mut MasternodeProcessorCache) -> bool {
+    match cache_from_snapshot_data(slice::from_raw_parts(data, len)) {
+        Ok(restored) => {
+            (*cache).restore_from(restored);
+            true
+        },
+        Err(err) => {
+            warn!(target: target::CACHE, "processor_restore_cache_snapshot: error={}", err);
+            false
+        }
+    }
+}
+
+/// Save the binary snapshot of the cache into the file at path
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_save_cache_snapshot(path: *const c_char, cache: *const MasternodeProcessorCache) -> bool {
+    let path = CStr::from_ptr(path).to_string_lossy();
+    save_cache_snapshot(&*cache, path.as_ref())
+        .map_err(|err| warn!(target: target::CACHE, "processor_save_cache_snapshot: error={}", err))
+        .is_ok()
+}
+
+/// Restore cache from the binary snapshot stored in the file at path
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_load_cache_snapshot(path: *const c_char, cache: *mut MasternodeProcessorCache) -> bool {
+    let path = CStr::from_ptr(path).to_string_lossy();
+    match load_cache_snapshot(path.as_ref()) {
+        Ok(restored) => {
+            (*cache).restore_from(restored);
+            true
+        },
+        Err(err) => {
+            warn!(target: target::CACHE, "processor_load_cache_snapshot: error={}", err);
+            false
+        }
+    }
+}
+
 /// Remove models list from cache
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn processor_remove_masternode_list_from_cache_for_block_hash(block_hash: *const u8, cache: *mut MasternodeProcessorCache) {
-    println!("processor_remove_masternode_list_from_cache_for_block_hash: {:?} {:p}", block_hash, cache);
+    debug!(target: target::CACHE, "processor_remove_masternode_list_from_cache_for_block_hash: block_hash={:p} cache={:p}", block_hash, cache);
     if let Some(hash) = UInt256::from_const(block_hash) {
         (*cache).remove_masternode_list(&hash);
     }
@@ -125,7 +244,7 @@ pub unsafe extern "C" fn processor_remove_masternode_list_from_cache_for_block_h
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn processor_remove_llmq_snapshot_from_cache_for_block_hash(block_hash: *const u8, cache: *mut MasternodeProcessorCache) {
-    println!("processor_remove_llmq_snapshot_from_cache_for_block_hash: {:?} {:p}", block_hash, cache);
+    debug!(target: target::CACHE, "processor_remove_llmq_snapshot_from_cache_for_block_hash: block_hash={:p} cache={:p}", block_hash, cache);
     if let Some(hash) = UInt256::from_const(block_hash) {
         (*cache).remove_snapshot(&hash);
     }
@@ -135,7 +254,7 @@ pub unsafe extern "C" fn processor_remove_llmq_snapshot_from_cache_for_block_has
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn processor_remove_llmq_members_from_cache_for_block_hash(block_hash: *const u8, cache: *mut MasternodeProcessorCache) {
-    println!("processor_remove_llmq_members_from_cache_for_block_hash: {:?} {:p}", block_hash, cache);
+    debug!(target: target::CACHE, "processor_remove_llmq_members_from_cache_for_block_hash: block_hash={:p} cache={:p}", block_hash, cache);
     if let Some(hash) = UInt256::from_const(block_hash) {
         (*cache).remove_quorum_members(&hash);
     }
@@ -145,7 +264,7 @@ pub unsafe extern "C" fn processor_remove_llmq_members_from_cache_for_block_hash
 /// # Safety
 #[no_mangle]
 pub unsafe extern "C" fn processor_clear_cache(cache: *mut MasternodeProcessorCache) {
-    println!("processor_clear_cache: {:p}", cache);
+    debug!(target: target::CACHE, "processor_clear_cache: cache={:p}", cache);
     (*cache).clear();
 }
 
@@ -186,6 +305,20 @@ pub unsafe extern "C" fn processor_destroy_mnlistdiff_result(result: *mut types:
     unbox_any(result);
 }
 
+/// Destroys types::InstantLockValidationReport
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_destroy_instant_lock_validation_report(report: *mut types::InstantLockValidationReport) {
+    unbox_any(report);
+}
+
+/// Destroys types::ChainLockValidationReport
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_destroy_chain_lock_validation_report(report: *mut types::ChainLockValidationReport) {
+    unbox_any(report);
+}
+
 /// Destroys types::LLMQRotationInfoResult
 /// # Safety
 #[no_mangle]
@@ -1,13 +1,14 @@
-use std::ptr::null_mut;
+use std::os::raw::c_char;
 use std::slice;
-use byte::BytesExt;
+use std::sync::Arc;
 use crate::{models, types};
 use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType};
-use crate::consensus::encode;
-use crate::crypto::{UInt256, byte_util::{BytesDecodable, ConstDecodable}, UInt768};
-use crate::ffi::{boxer::{boxed, boxed_vec}, ByteArray, from::FromFFI, to::ToFFI};
-use crate::models::{LLMQModifierType, LLMQVerificationContext};
-use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, ProcessingError};
+use crate::common::{target, SignatureValidationStatus};
+use crate::crypto::{UInt256, byte_util::ConstDecodable, UInt768};
+use crate::ffi::{boxer::boxed, ByteArray, callbacks::FFICoreProvider, from::FromFFI};
+use crate::models::LLMQModifierType;
+use crate::types::opaque_key::AsCStringPtr;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
 
 /// Read and process message received as a response for 'GETMNLISTDIFF' call
 /// Here we calculate quorums according to Core v0.17
@@ -21,30 +22,26 @@ pub unsafe extern "C" fn process_mnlistdiff_from_message(
     use_insight_as_backup: bool,
     is_from_snapshot: bool,
     protocol_version: u32,
-    processor: *mut MasternodeProcessor,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
     cache: *mut MasternodeProcessorCache,
     context: *const std::ffi::c_void,
 ) -> *mut types::MNListDiffResult {
     let instant = std::time::Instant::now();
     let processor = &mut *processor;
     let cache = &mut *cache;
-    println!("process_mnlistdiff_from_message -> {:?} {:p} {:p} {:p}", instant, processor, cache, context);
-    processor.opaque_context = context;
+    debug!(target: target::FFI, "process_mnlistdiff_from_message -> processor={:p} cache={:p} context={:p}", processor, cache, context);
+    processor.provider.opaque_context = context;
     processor.use_insight_as_backup = use_insight_as_backup;
     processor.chain_type = chain_type;
     let message: &[u8] = slice::from_raw_parts(message_arr, message_length);
-    let list_diff = unwrap_or_failure!(models::MNListDiff::new(message, &mut 0, |hash| processor
-        .lookup_block_height_by_hash(hash), protocol_version));
-    if !is_from_snapshot {
-        let error = processor
-            .should_process_diff_with_range(list_diff.base_block_hash, list_diff.block_hash);
-        if error != ProcessingError::None {
-            println!("process_mnlistdiff_from_message <- {:?} ms [{:?}]", instant.elapsed().as_millis(), error);
-            return boxed(types::MNListDiffResult::default_with_error(error));
+    let result = match processor.mn_list_diff_result_from_message(message, is_from_snapshot, protocol_version, cache) {
+        Ok(result) => result.encode(),
+        Err(err) => {
+            warn!(target: target::LIST_DIFF, "process_mnlistdiff_from_message <- elapsed_ms={} error={}", instant.elapsed().as_millis(), err);
+            return boxed(types::MNListDiffResult::default_with_message_error(&err));
         }
-    }
-    let result = processor.get_list_diff_result_with_base_lookup(list_diff, LLMQVerificationContext::MNListDiff, cache);
-    println!("process_mnlistdiff_from_message <- {:?} ms", instant.elapsed().as_millis());
+    };
+    debug!(target: target::FFI, "process_mnlistdiff_from_message <- elapsed_ms={}", instant.elapsed().as_millis());
     boxed(result)
 }
 
@@ -62,7 +59,7 @@ pub unsafe extern "C" fn process_qrinfo_from_message(
     is_from_snapshot: bool,
     is_rotated_quorums_presented: bool,
     protocol_version: u32,
-    processor: *mut MasternodeProcessor,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
     cache: *mut MasternodeProcessorCache,
     context: *const std::ffi::c_void,
 ) -> *mut types::QRInfoResult {
@@ -70,116 +67,184 @@ pub unsafe extern "C" fn process_qrinfo_from_message(
     let message: &[u8] = slice::from_raw_parts(message, message_length);
     let processor = &mut *processor;
     let cache = &mut *cache;
-    processor.opaque_context = context;
+    processor.provider.opaque_context = context;
     processor.use_insight_as_backup = use_insight_as_backup;
     processor.chain_type = chain_type;
-    println!( "process_qrinfo_from_message -> {:?} {:p} {:p} {:p}", instant, processor, cache, context);
-    let offset = &mut 0;
-    let mut process_list_diff = |list_diff: models::MNListDiff, verification_context: LLMQVerificationContext| {
-        processor.get_list_diff_result_with_base_lookup(list_diff, verification_context, cache)
+    debug!(target: target::FFI, "process_qrinfo_from_message -> processor={:p} cache={:p} context={:p}", processor, cache, context);
+    let result = match processor.qr_info_result_from_message(message, is_from_snapshot, is_rotated_quorums_presented, protocol_version, cache) {
+        Ok(result) => result.encode(),
+        Err(err) => {
+            warn!(target: target::QR_INFO, "process_qrinfo_from_message <- elapsed_ms={} error={}", instant.elapsed().as_millis(), err);
+            return boxed(types::QRInfoResult::default_with_message_error(&err));
+        }
     };
-    let read_list_diff =
-        |offset: &mut usize| processor.read_list_diff_from_message(message, offset, protocol_version);
-    let read_snapshot = |offset: &mut usize| models::LLMQSnapshot::from_bytes(message, offset);
-    let read_var_int = |offset: &mut usize| encode::VarInt::from_bytes(message, offset);
-    let mut get_list_diff_result =
-        |list_diff: models::MNListDiff, verification_context: LLMQVerificationContext| boxed(process_list_diff(list_diff, verification_context));
-    let snapshot_at_h_c = unwrap_or_qr_result_failure!(read_snapshot(offset));
-    let snapshot_at_h_2c = unwrap_or_qr_result_failure!(read_snapshot(offset));
-    let snapshot_at_h_3c = unwrap_or_qr_result_failure!(read_snapshot(offset));
-    let diff_tip = unwrap_or_qr_result_failure!(read_list_diff(offset));
-    if !is_from_snapshot {
-        let error =
-            processor.should_process_diff_with_range(diff_tip.base_block_hash, diff_tip.block_hash);
-        if error != ProcessingError::None {
-            println!("process_qrinfo_from_message <- {:?} ms [{:#?}]", instant.elapsed().as_millis(), error);
-            return boxed(types::QRInfoResult::default_with_error(error));
+    #[cfg(feature = "generate-dashj-tests")]
+    crate::util::java::generate_qr_state_test_file_json(chain_type, &result);
+    debug!(target: target::FFI, "process_qrinfo_from_message <- elapsed_ms={}", instant.elapsed().as_millis());
+    boxed(result)
+}
+
+/// Read and verify 'islock' or 'isdlock' message against the quorums of the masternode list it was signed with
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn verify_instant_lock_from_message(
+    message: *const u8,
+    message_length: usize,
+    is_deterministic: bool,
+    chain_type: ChainType,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
+    cache: *mut MasternodeProcessorCache,
+    context: *const std::ffi::c_void,
+) -> *mut types::InstantLockValidationReport {
+    let message: &[u8] = slice::from_raw_parts(message, message_length);
+    let processor = &mut *processor;
+    processor.provider.opaque_context = context;
+    processor.chain_type = chain_type;
+    match models::InstantLock::read(message, &mut 0, is_deterministic) {
+        Ok(instant_lock) => boxed(processor.verify_instant_lock(&instant_lock, &*cache).encode()),
+        Err(err) => {
+            warn!(target: target::LOCKS, "verify_instant_lock_from_message: error={}", err);
+            std::ptr::null_mut()
         }
     }
-    let diff_h = unwrap_or_qr_result_failure!(read_list_diff(offset));
-    let diff_h_c = unwrap_or_qr_result_failure!(read_list_diff(offset));
-    let diff_h_2c = unwrap_or_qr_result_failure!(read_list_diff(offset));
-    let diff_h_3c = unwrap_or_qr_result_failure!(read_list_diff(offset));
-    let extra_share = message.read_with::<bool>(offset, ()).unwrap_or(false);
-    let snapshot_at_h_4c = extra_share.then_some(unwrap_or_qr_result_failure!(read_snapshot(offset)));
-    let diff_h_4c = extra_share.then_some(unwrap_or_qr_result_failure!(read_list_diff(offset)));
-    processor.save_snapshot(diff_h_c.block_hash, snapshot_at_h_c.clone());
-    processor.save_snapshot(diff_h_2c.block_hash, snapshot_at_h_2c.clone());
-    processor.save_snapshot(diff_h_3c.block_hash, snapshot_at_h_3c.clone());
-    if extra_share {
-        processor.save_snapshot(
-            diff_h_4c.as_ref().unwrap().block_hash,
-            snapshot_at_h_4c.clone().unwrap(),
-        );
-    }
+}
 
-    let last_quorum_per_index_count = unwrap_or_qr_result_failure!(read_var_int(offset)).0 as usize;
-    let mut last_quorum_per_index_vec: Vec<*mut types::LLMQEntry> =
-        Vec::with_capacity(last_quorum_per_index_count);
-    for _i in 0..last_quorum_per_index_count {
-        let quorum = unwrap_or_qr_result_failure!(models::LLMQEntry::from_bytes(message, offset));
-        last_quorum_per_index_vec.push(boxed(quorum.encode()));
-    }
-    let quorum_snapshot_list_count = unwrap_or_qr_result_failure!(read_var_int(offset)).0 as usize;
-    let mut quorum_snapshot_list_vec: Vec<*mut types::LLMQSnapshot> =
-        Vec::with_capacity(quorum_snapshot_list_count);
-    let mut snapshots: Vec<models::LLMQSnapshot> = Vec::with_capacity(quorum_snapshot_list_count);
-    for _i in 0..quorum_snapshot_list_count {
-        let snapshot = unwrap_or_qr_result_failure!(read_snapshot(offset));
-        snapshots.push(snapshot);
+/// Dump of the rotation cycle calculation as JSON (to be released with `processor_destroy_string`),
+/// NULL if the data needed to rebuild the cycle is missing
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn processor_rotation_diagnostics_json(
+    llmq_type: LLMQType,
+    cycle_base_block_height: u32,
+    skip_removed_masternodes: bool,
+    chain_type: ChainType,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
+    cache: *const MasternodeProcessorCache,
+    context: *const std::ffi::c_void,
+) -> *mut c_char {
+    let processor = &mut *processor;
+    processor.provider.opaque_context = context;
+    processor.chain_type = chain_type;
+    processor.rotation_diagnostics(llmq_type, cycle_base_block_height, skip_removed_masternodes, &*cache)
+        .map_err(|err| warn!(target: target::ROTATION, "processor_rotation_diagnostics_json: error={}", err))
+        .ok()
+        .and_then(|diagnostics| diagnostics.to_json()
+            .map_err(|err| warn!(target: target::ROTATION, "processor_rotation_diagnostics_json: serialization error={}", err))
+            .ok())
+        .to_c_string_ptr()
+}
+
+/// Read and verify 'clsig' message against the quorums of the masternode list 8 blocks below the locked block
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn verify_chain_lock_from_message(
+    message: *const u8,
+    message_length: usize,
+    chain_type: ChainType,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
+    cache: *mut MasternodeProcessorCache,
+    context: *const std::ffi::c_void,
+) -> *mut types::ChainLockValidationReport {
+    let message: &[u8] = slice::from_raw_parts(message, message_length);
+    let processor = &mut *processor;
+    processor.provider.opaque_context = context;
+    processor.chain_type = chain_type;
+    match models::ChainLock::read(message, &mut 0) {
+        Ok(chain_lock) => boxed(processor.verify_chain_lock(&chain_lock, &*cache).encode()),
+        Err(err) => {
+            warn!(target: target::LOCKS, "verify_chain_lock_from_message: error={}", err);
+            std::ptr::null_mut()
+        }
     }
-    let mn_list_diff_list_count = unwrap_or_qr_result_failure!(read_var_int(offset)).0 as usize;
-    let mut mn_list_diff_list_vec: Vec<*mut types::MNListDiffResult> =
-        Vec::with_capacity(mn_list_diff_list_count);
-    assert_eq!(quorum_snapshot_list_count, mn_list_diff_list_count, "number of snapshots should be equal to number of diffs");
-    for i in 0..mn_list_diff_list_count {
-        let list_diff = unwrap_or_qr_result_failure!(read_list_diff(offset));
-        let block_hash = list_diff.block_hash;
-        mn_list_diff_list_vec.push(get_list_diff_result(list_diff, LLMQVerificationContext::None));
-        let snapshot = snapshots.get(i).unwrap();
-        quorum_snapshot_list_vec.push(boxed(snapshot.encode()));
-        processor.save_snapshot(block_hash, snapshot.clone());
+}
+
+/// Verify threshold signature of the Platform quorum over the message hash
+/// quorum_hash: u256, request_id: u256, message_hash: u256, signature: u768
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn verify_platform_signature(
+    quorum_hash: *const u8,
+    core_chain_locked_height: u32,
+    request_id: *const u8,
+    message_hash: *const u8,
+    signature: *const u8,
+    chain_type: ChainType,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
+    cache: *mut MasternodeProcessorCache,
+    context: *const std::ffi::c_void,
+) -> SignatureValidationStatus {
+    let processor = &mut *processor;
+    processor.provider.opaque_context = context;
+    processor.chain_type = chain_type;
+    match (UInt256::from_const(quorum_hash), UInt256::from_const(request_id), UInt256::from_const(message_hash), UInt768::from_const(signature)) {
+        (Some(quorum_hash), Some(request_id), Some(message_hash), Some(signature)) =>
+            processor.verify_platform_signature(quorum_hash, core_chain_locked_height, request_id, message_hash, signature, &*cache),
+        _ => SignatureValidationStatus::InvalidSignature
     }
+}
 
-    let result_at_h_4c = if extra_share {
-        get_list_diff_result(diff_h_4c.unwrap(), LLMQVerificationContext::None)
-    } else {
-        null_mut()
-    };
-    let result_at_h_3c = get_list_diff_result(diff_h_3c, LLMQVerificationContext::None);
-    let result_at_h_2c = get_list_diff_result(diff_h_2c, LLMQVerificationContext::None);
-    let result_at_h_c = get_list_diff_result(diff_h_c, LLMQVerificationContext::None);
-    let result_at_h = get_list_diff_result(diff_h, LLMQVerificationContext::QRInfo(is_rotated_quorums_presented));
-    let result_at_tip = get_list_diff_result(diff_tip, LLMQVerificationContext::None);
-    let result = types::QRInfoResult {
-        error_status: ProcessingError::None,
-        result_at_tip,
-        result_at_h,
-        result_at_h_c,
-        result_at_h_2c,
-        result_at_h_3c,
-        result_at_h_4c,
-        snapshot_at_h_c: boxed(snapshot_at_h_c.encode()),
-        snapshot_at_h_2c: boxed(snapshot_at_h_2c.encode()),
-        snapshot_at_h_3c: boxed(snapshot_at_h_3c.encode()),
-        snapshot_at_h_4c: if extra_share {
-            boxed(snapshot_at_h_4c.unwrap().encode())
-        } else {
-            null_mut()
-        },
-        extra_share,
-        last_quorum_per_index: boxed_vec(last_quorum_per_index_vec),
-        last_quorum_per_index_count,
-        quorum_snapshot_list: boxed_vec(quorum_snapshot_list_vec),
-        quorum_snapshot_list_count,
-        mn_list_diff_list: boxed_vec(mn_list_diff_list_vec),
-        mn_list_diff_list_count,
+/// Height of the most recent masternode list checkpoint at or below the height (u32::MAX if there is no such)
+#[no_mangle]
+pub extern "C" fn masternode_list_checkpoint_height(chain_type: ChainType, block_height: u32) -> u32 {
+    models::MasternodeListCheckpoint::last_checkpoint_for(chain_type, block_height)
+        .map_or(u32::MAX, |checkpoint| checkpoint.block_height)
+}
+
+/// Build, verify and cache the masternode list from the checkpoint data file for the checkpoint at height
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn apply_masternode_list_checkpoint_from_data(
+    data: *const u8,
+    data_length: usize,
+    block_height: u32,
+    chain_type: ChainType,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
+    cache: *mut MasternodeProcessorCache,
+    context: *const std::ffi::c_void,
+) -> bool {
+    let processor = &mut *processor;
+    processor.provider.opaque_context = context;
+    processor.chain_type = chain_type;
+    let Some(checkpoint) = models::MasternodeListCheckpoint::last_checkpoint_for(chain_type, block_height)
+        .filter(|checkpoint| checkpoint.block_height == block_height) else {
+        return false;
     };
+    match models::MasternodeListCheckpointData::from_data(slice::from_raw_parts(data, data_length)) {
+        Ok(data) => processor.apply_masternode_list_checkpoint(&checkpoint, &data, &mut *cache)
+            .map_err(|err| warn!(target: target::FFI, "apply_masternode_list_checkpoint_from_data: error={}", err))
+            .is_ok(),
+        Err(err) => {
+            warn!(target: target::FFI, "apply_masternode_list_checkpoint_from_data: error={:?}", err);
+            false
+        }
+    }
+}
 
-    #[cfg(feature = "generate-dashj-tests")]
-    crate::util::java::generate_qr_state_test_file_json(chain_type, &result);
-    println!("process_qrinfo_from_message <- {:?} ms", instant.elapsed().as_millis());
-    boxed(result)
+/// Build, verify and cache the masternode list from the most recent checkpoint embedded into the library
+/// at or below the height; returns height of the applied checkpoint (u32::MAX if none was applied)
+/// # Safety
+#[cfg(feature = "embedded-checkpoints")]
+#[no_mangle]
+pub unsafe extern "C" fn apply_embedded_masternode_list_checkpoint(
+    block_height: u32,
+    chain_type: ChainType,
+    processor: *mut MasternodeProcessor<FFICoreProvider>,
+    cache: *mut MasternodeProcessorCache,
+    context: *const std::ffi::c_void,
+) -> u32 {
+    let processor = &mut *processor;
+    processor.provider.opaque_context = context;
+    processor.chain_type = chain_type;
+    models::MasternodeListCheckpoint::checkpoints_for(chain_type)
+        .into_iter()
+        .rev()
+        .filter(|checkpoint| checkpoint.block_height <= block_height)
+        .find_map(|checkpoint| checkpoint.embedded_data(chain_type).map(|data| (checkpoint, data)))
+        .and_then(|(checkpoint, data)| processor.apply_masternode_list_checkpoint(&checkpoint, &data, &mut *cache)
+            .map_err(|err| warn!(target: target::FFI, "apply_embedded_masternode_list_checkpoint: error={}", err))
+            .ok()
+            .map(|_| checkpoint.block_height))
+        .unwrap_or(u32::MAX)
 }
 
 /// # Safety
@@ -187,7 +252,7 @@ pub unsafe extern "C" fn process_qrinfo_from_message(
 pub unsafe extern "C" fn processor_cache_masternode_list(block_hash: *const u8, list: *const types::MasternodeList, cache: *mut MasternodeProcessorCache) {
     let hash = UInt256::from_const(block_hash).unwrap();
     let list = (*list).decode();
-    (&mut *cache).mn_lists.insert(hash, list);
+    (&mut *cache).mn_lists.insert(hash, Arc::new(list));
 }
 
 /// # Safety
@@ -200,11 +265,15 @@ pub unsafe extern "C" fn validate_masternode_list(list: *const types::Masternode
         return false;
     }
     let quorum_modifier_type = if let Some(best_cl_signature) = UInt768::from_const(best_cl_signature) {
-        LLMQModifierType::CoreV20(quorum.llmq_type, block_height - 8, best_cl_signature)
+        let Some(work_block_height) = block_height.checked_sub(8) else {
+            warn!(target: target::FFI, "validate_masternode_list: block is below the chain lock offset block_height={}", block_height);
+            return false;
+        };
+        LLMQModifierType::CoreV20(quorum.llmq_type, work_block_height, best_cl_signature)
     } else {
         LLMQModifierType::PreCoreV20(quorum.llmq_type, quorum.llmq_hash)
     };
-    let valid_masternodes = models::MasternodeList::get_masternodes_for_quorum(&quorum, chain_type, list.masternodes, block_height, quorum_modifier_type);
+    let valid_masternodes = models::MasternodeList::get_masternodes_for_quorum(&quorum, chain_type, &list.masternodes, block_height, quorum_modifier_type);
     return quorum.validate(valid_masternodes, block_height).is_not_critical();
 }
 
@@ -1,12 +1,11 @@
 use std::slice;
 use crate::chain::common::ChainType;
-use crate::consensus::Encodable;
-use crate::crypto::byte_util::{AsBytes, Reversable};
+use crate::crypto::byte_util::AsBytes;
 use crate::crypto::{UInt160, UInt256};
 use crate::ffi::ByteArray;
 use crate::keys::ECDSAKey;
-use crate::util::address::address;
-use crate::util::data_ops::DASH_MESSAGE_MAGIC;
+use crate::tx::ProviderRegistrationTransaction;
+use crate::tx::provider_registration_transaction::dash_message_digest;
 
 
 #[no_mangle]
@@ -30,15 +29,7 @@ pub unsafe extern "C" fn pro_reg_tx_payload_collateral_digest(
     let script_payout = slice::from_raw_parts(script_payout, script_payout_len);
     let owner_key_hash = UInt160::from(slice::from_raw_parts(owner_key_hash, 20));
     let voter_key_hash = UInt160::from(slice::from_raw_parts(voter_key_hash, 20));
-    let script_map = chain_type.script_map();
-    let mut writer = Vec::<u8>::new();
-    DASH_MESSAGE_MAGIC.to_string().enc(&mut writer);
-    let payout_address = address::with_script_pub_key(&script_payout.to_vec(), &script_map)
-        .expect("Can't extract payout address");
-    let payload_hash = UInt256::sha256d(payload).reverse();
-    let owner_address = address::from_hash160_for_script_map(&owner_key_hash, &script_map);
-    let voter_address = address::from_hash160_for_script_map(&voter_key_hash, &script_map);
-    let payload_collateral_string = format!("{}|{}|{}|{}|{}", payout_address, operator_reward, owner_address, voter_address, payload_hash);
-    payload_collateral_string.enc(&mut writer);
-    ByteArray::from(UInt256::sha256d(&writer))
+    let payload_hash = UInt256::sha256d(payload);
+    let message = ProviderRegistrationTransaction::collateral_sign_string_for(&script_payout.to_vec(), operator_reward, &owner_key_hash, &voter_key_hash, payload_hash, chain_type);
+    ByteArray::from(dash_message_digest(&message))
 }
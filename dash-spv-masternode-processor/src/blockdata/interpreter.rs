@@ -0,0 +1,901 @@
+use std::ops;
+use hashes::{Hash, hash160, ripemd160, sha1, sha256, sha256d};
+use secp256k1::ecdsa::Signature;
+use secp256k1::Secp256k1;
+use crate::blockdata::opcodes::All;
+use crate::blockdata::opcodes::all::*;
+use crate::blockdata::script_error::ScriptError;
+use crate::chain::tx::protocol::TX_MAX_LOCK_HEIGHT;
+use crate::keys::ECDSAKey;
+use crate::tx::{SigHashType, Transaction};
+
+pub const MAX_SCRIPT_SIZE: usize = 10000;
+pub const MAX_SCRIPT_ELEMENT_SIZE: usize = 520;
+pub const MAX_OPS_PER_SCRIPT: usize = 201;
+pub const MAX_STACK_SIZE: usize = 1000;
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
+/// Numeric operands are limited to 4 bytes (5 bytes for the lock time checks)
+const DEFAULT_MAX_NUM_SIZE: usize = 4;
+const LOCKTIME_MAX_NUM_SIZE: usize = 5;
+
+const SEQUENCE_FINAL: u32 = u32::MAX;
+const SEQUENCE_LOCKTIME_DISABLE_FLAG: i64 = 1 << 31;
+const SEQUENCE_LOCKTIME_TYPE_FLAG: i64 = 1 << 22;
+const SEQUENCE_LOCKTIME_MASK: i64 = 0x0000ffff;
+
+/// Script verification flags (same bits as in Dash Core)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScriptVerifyFlags(u32);
+
+impl ScriptVerifyFlags {
+    pub const NONE: ScriptVerifyFlags = ScriptVerifyFlags(0);
+    /// Evaluate P2SH subscripts (BIP16)
+    pub const P2SH: ScriptVerifyFlags = ScriptVerifyFlags(1 << 0);
+    /// Enforce strict encoding of signatures (defined hash type) and public keys
+    pub const STRICTENC: ScriptVerifyFlags = ScriptVerifyFlags(1 << 1);
+    /// Enforce strict DER signatures (BIP66)
+    pub const DERSIG: ScriptVerifyFlags = ScriptVerifyFlags(1 << 2);
+    /// Enforce low S values in signatures
+    pub const LOW_S: ScriptVerifyFlags = ScriptVerifyFlags(1 << 3);
+    /// Dummy argument of CHECKMULTISIG must be empty
+    pub const NULLDUMMY: ScriptVerifyFlags = ScriptVerifyFlags(1 << 4);
+    /// scriptSig must contain only pushes
+    pub const SIGPUSHONLY: ScriptVerifyFlags = ScriptVerifyFlags(1 << 5);
+    /// Require minimal pushes and number encodings
+    pub const MINIMALDATA: ScriptVerifyFlags = ScriptVerifyFlags(1 << 6);
+    /// Fail on NOPs reserved for the soft-forks
+    pub const DISCOURAGE_UPGRADABLE_NOPS: ScriptVerifyFlags = ScriptVerifyFlags(1 << 7);
+    /// Exactly one element must remain on the stack (requires P2SH)
+    pub const CLEANSTACK: ScriptVerifyFlags = ScriptVerifyFlags(1 << 8);
+    /// OP_CHECKLOCKTIMEVERIFY (BIP65)
+    pub const CHECKLOCKTIMEVERIFY: ScriptVerifyFlags = ScriptVerifyFlags(1 << 9);
+    /// OP_CHECKSEQUENCEVERIFY (BIP112)
+    pub const CHECKSEQUENCEVERIFY: ScriptVerifyFlags = ScriptVerifyFlags(1 << 10);
+    /// Failed signature checks must have empty signatures
+    pub const NULLFAIL: ScriptVerifyFlags = ScriptVerifyFlags(1 << 14);
+    /// Re-enabled and new opcodes of DIP-0020
+    pub const DIP0020_OPCODES: ScriptVerifyFlags = ScriptVerifyFlags(1 << 15);
+
+    pub const MANDATORY: ScriptVerifyFlags = Self::P2SH;
+    pub const STANDARD: ScriptVerifyFlags = ScriptVerifyFlags(
+        Self::P2SH.0 | Self::STRICTENC.0 | Self::DERSIG.0 | Self::LOW_S.0 | Self::NULLDUMMY.0 |
+            Self::MINIMALDATA.0 | Self::DISCOURAGE_UPGRADABLE_NOPS.0 | Self::CLEANSTACK.0 |
+            Self::CHECKLOCKTIMEVERIFY.0 | Self::CHECKSEQUENCEVERIFY.0 | Self::NULLFAIL.0 | Self::DIP0020_OPCODES.0);
+
+    pub fn add(&mut self, other: ScriptVerifyFlags) -> ScriptVerifyFlags {
+        self.0 |= other.0;
+        *self
+    }
+
+    pub fn remove(&mut self, other: ScriptVerifyFlags) -> ScriptVerifyFlags {
+        self.0 &= !other.0;
+        *self
+    }
+
+    pub fn has(self, flags: ScriptVerifyFlags) -> bool {
+        (self.0 | flags.0) == self.0
+    }
+}
+
+impl From<u32> for ScriptVerifyFlags {
+    fn from(f: u32) -> Self {
+        ScriptVerifyFlags(f)
+    }
+}
+
+impl From<ScriptVerifyFlags> for u32 {
+    fn from(flags: ScriptVerifyFlags) -> Self {
+        flags.0
+    }
+}
+
+impl ops::BitOr for ScriptVerifyFlags {
+    type Output = Self;
+
+    fn bitor(mut self, rhs: Self) -> Self {
+        self.add(rhs)
+    }
+}
+
+impl ops::BitOrAssign for ScriptVerifyFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.add(rhs);
+    }
+}
+
+/// Checks of the data the script doesn't contain (signatures and lock times of the spending transaction)
+pub trait SignatureChecker {
+    fn check_signature(&self, _signature: &[u8], _public_key: &[u8], _script_code: &[u8]) -> bool {
+        false
+    }
+    fn check_lock_time(&self, _lock_time: i64) -> bool {
+        false
+    }
+    fn check_sequence(&self, _sequence: i64) -> bool {
+        false
+    }
+}
+
+/// Checker for the scripts evaluated without the transaction: all the checks fail
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BaseSignatureChecker;
+
+impl SignatureChecker for BaseSignatureChecker {}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionSignatureChecker<'a> {
+    pub tx: &'a Transaction,
+    pub input_index: usize,
+}
+
+impl<'a> TransactionSignatureChecker<'a> {
+    pub fn new(tx: &'a Transaction, input_index: usize) -> Self {
+        Self { tx, input_index }
+    }
+}
+
+impl<'a> SignatureChecker for TransactionSignatureChecker<'a> {
+    fn check_signature(&self, signature: &[u8], public_key: &[u8], script_code: &[u8]) -> bool {
+        // Undefined hash types are only rejected with STRICTENC (before the signature is checked)
+        let (sighash_type, der) = match signature.split_last() {
+            Some((sighash_type, der)) => (*sighash_type as u32, der),
+            None => return false
+        };
+        let hash = self.tx.signature_hash(self.input_index, script_code, sighash_type);
+        match (ECDSAKey::public_key_from_bytes(public_key), Signature::from_der_lax(der), ECDSAKey::message_from_bytes(&hash.0)) {
+            (Ok(public_key), Ok(mut signature), Ok(message)) => {
+                // libsecp256k1 only accepts low S values
+                signature.normalize_s();
+                Secp256k1::verification_only().verify_ecdsa(&message, &signature, &public_key).is_ok()
+            },
+            _ => false
+        }
+    }
+
+    fn check_lock_time(&self, lock_time: i64) -> bool {
+        let tx_lock_time = self.tx.lock_time as i64;
+        let threshold = TX_MAX_LOCK_HEIGHT as i64;
+        // Both must be either block heights or timestamps
+        if (tx_lock_time < threshold) != (lock_time < threshold) || lock_time > tx_lock_time {
+            return false;
+        }
+        // Final input makes the lock time ineffective
+        self.tx.inputs.get(self.input_index)
+            .is_some_and(|input| input.sequence != SEQUENCE_FINAL)
+    }
+
+    fn check_sequence(&self, sequence: i64) -> bool {
+        let tx_sequence = match self.tx.inputs.get(self.input_index) {
+            Some(input) => input.sequence as i64,
+            None => return false
+        };
+        if self.tx.version < 2 || tx_sequence & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0 {
+            return false;
+        }
+        let mask = SEQUENCE_LOCKTIME_TYPE_FLAG | SEQUENCE_LOCKTIME_MASK;
+        let tx_sequence_masked = tx_sequence & mask;
+        let sequence_masked = sequence & mask;
+        // Both must be either block based or time based
+        (tx_sequence_masked < SEQUENCE_LOCKTIME_TYPE_FLAG) == (sequence_masked < SEQUENCE_LOCKTIME_TYPE_FLAG) &&
+            sequence_masked <= tx_sequence_masked
+    }
+}
+
+/// Read the opcode at `pc` with its push data (if it's the push operation)
+fn read_op<'a>(script: &'a [u8], pc: &mut usize) -> Result<(All, Option<&'a [u8]>), ScriptError> {
+    let opcode = *script.get(*pc).ok_or(ScriptError::BadOpcode)?;
+    let mut cursor = *pc + 1;
+    let len = match opcode {
+        0x00..=0x4b => opcode as usize,
+        0x4c..=0x4e => {
+            let size = match opcode { 0x4c => 1, 0x4d => 2, _ => 4 };
+            let bytes = script.get(cursor..cursor + size).ok_or(ScriptError::BadOpcode)?;
+            cursor += size;
+            bytes.iter().rev().fold(0usize, |len, byte| (len << 8) | *byte as usize)
+        },
+        _ => {
+            *pc = cursor;
+            return Ok((All::from(opcode), None));
+        }
+    };
+    let data = script.get(cursor..cursor + len).ok_or(ScriptError::BadOpcode)?;
+    *pc = cursor + len;
+    Ok((All::from(opcode), Some(data)))
+}
+
+/// Push encoding of the data (not the minimal one, used to find the signatures in the script code)
+fn push_data(data: &[u8]) -> Vec<u8> {
+    let len = data.len();
+    let mut script = match len {
+        0..=0x4b => vec![len as u8],
+        0x4c..=0xff => vec![OP_PUSHDATA1.into_u8(), len as u8],
+        0x100..=0xffff => [&[OP_PUSHDATA2.into_u8()], &(len as u16).to_le_bytes()[..]].concat(),
+        _ => [&[OP_PUSHDATA4.into_u8()], &(len as u32).to_le_bytes()[..]].concat(),
+    };
+    script.extend_from_slice(data);
+    script
+}
+
+/// Remove all the op-aligned pushes of the data from the script
+fn find_and_delete(script: &mut Vec<u8>, data: &[u8]) {
+    let pattern = push_data(data);
+    let mut result = Vec::<u8>::with_capacity(script.len());
+    let (mut pc, mut pc2, mut found) = (0, 0, false);
+    loop {
+        result.extend_from_slice(&script[pc2..pc]);
+        while script[pc..].starts_with(&pattern) {
+            pc += pattern.len();
+            found = true;
+        }
+        pc2 = pc;
+        if pc >= script.len() || read_op(script, &mut pc).is_err() {
+            break;
+        }
+    }
+    if found {
+        result.extend_from_slice(&script[pc2..]);
+        *script = result;
+    }
+}
+
+/// Script code without the op-aligned OP_CODESEPARATORs, as it's serialized for the signature hash
+pub(crate) fn remove_code_separators(script: &[u8]) -> Vec<u8> {
+    let mut result = Vec::<u8>::with_capacity(script.len());
+    let (mut pc, mut begin) = (0, 0);
+    while pc < script.len() {
+        match read_op(script, &mut pc) {
+            Ok((OP_CODESEPARATOR, _)) => {
+                result.extend_from_slice(&script[begin..pc - 1]);
+                begin = pc;
+            },
+            Ok(_) => {},
+            Err(_) => break
+        }
+    }
+    result.extend_from_slice(&script[begin..]);
+    result
+}
+
+pub fn is_push_only(script: &[u8]) -> bool {
+    let mut pc = 0;
+    while pc < script.len() {
+        match read_op(script, &mut pc) {
+            Ok((opcode, _)) if opcode.into_u8() <= OP_PUSHNUM_16.into_u8() => {},
+            _ => return false
+        }
+    }
+    true
+}
+
+pub fn is_pay_to_script_hash(script: &[u8]) -> bool {
+    matches!(script, [0xa9/*OP_HASH160*/, 0x14, .., 0x87/*OP_EQUAL*/] if script.len() == 23)
+}
+
+/// Output can't be spent: it's OP_RETURN data carrier or too big to be evaluated
+pub fn is_unspendable(script: &[u8]) -> bool {
+    script.first() == Some(&OP_RETURN.into_u8()) || script.len() > MAX_SCRIPT_SIZE
+}
+
+pub fn cast_to_bool(data: &[u8]) -> bool {
+    match data.split_last() {
+        // Negative zero is false as well
+        Some((last, rest)) => rest.iter().any(|byte| *byte != 0) || (*last != 0 && *last != 0x80),
+        None => false
+    }
+}
+
+/// Decode the number, overflow and non-minimal encoding fail the script with the unknown error like in Dash Core
+pub fn decode_num(data: &[u8], require_minimal: bool, max_size: usize) -> Result<i64, ScriptError> {
+    if data.len() > max_size {
+        return Err(ScriptError::UnknownError);
+    }
+    if require_minimal && !is_minimally_encoded(data) {
+        return Err(ScriptError::UnknownError);
+    }
+    match data.split_last() {
+        Some((last, _)) => {
+            let value = data.iter().rev().fold(0i64, |value, byte| (value << 8) | *byte as i64);
+            if last & 0x80 != 0 {
+                // Clear the sign bit
+                Ok(-(value & !(0x80i64 << (8 * (data.len() - 1)))))
+            } else {
+                Ok(value)
+            }
+        },
+        None => Ok(0)
+    }
+}
+
+pub fn encode_num(value: i64) -> Vec<u8> {
+    let mut result = Vec::<u8>::new();
+    let negative = value < 0;
+    let mut abs = value.unsigned_abs();
+    while abs > 0 {
+        result.push((abs & 0xff) as u8);
+        abs >>= 8;
+    }
+    if let Some(last) = result.last_mut() {
+        if *last & 0x80 != 0 {
+            result.push(if negative { 0x80 } else { 0 });
+        } else if negative {
+            *last |= 0x80;
+        }
+    }
+    result
+}
+
+fn is_minimally_encoded(data: &[u8]) -> bool {
+    match data {
+        [] => true,
+        // Zero bytes (except the sign bit) in the end are allowed only to keep the sign of the next byte
+        [.., prev, last] if last & 0x7f == 0 => prev & 0x80 != 0,
+        [last] => last & 0x7f != 0,
+        _ => true,
+    }
+}
+
+/// Trim the number to its minimal encoding (DIP-0020)
+fn minimally_encode(mut data: Vec<u8>) -> Vec<u8> {
+    let last = match data.last() {
+        Some(last) if last & 0x7f == 0 => *last,
+        _ => return data
+    };
+    if data.len() == 1 {
+        return vec![];
+    }
+    if data[data.len() - 2] & 0x80 != 0 {
+        return data;
+    }
+    for i in (1..data.len()).rev() {
+        if data[i - 1] != 0 {
+            if data[i - 1] & 0x80 != 0 {
+                data[i] = last;
+                data.truncate(i + 1);
+            } else {
+                data[i - 1] |= last;
+                data.truncate(i);
+            }
+            return data;
+        }
+    }
+    vec![]
+}
+
+fn check_minimal_push(data: &[u8], opcode: u8) -> bool {
+    match data {
+        [] => opcode == OP_PUSHBYTES_0.into_u8(),
+        [value @ 1..=16] => opcode == OP_PUSHNUM_1.into_u8() + value - 1,
+        [0x81] => opcode == OP_PUSHNUM_NEG1.into_u8(),
+        _ if data.len() <= 75 => opcode as usize == data.len(),
+        _ if data.len() <= 0xff => opcode == OP_PUSHDATA1.into_u8(),
+        _ if data.len() <= 0xffff => opcode == OP_PUSHDATA2.into_u8(),
+        _ => true
+    }
+}
+
+/// Strict DER encoding with the sighash type byte (BIP66)
+fn is_valid_signature_encoding(sig: &[u8]) -> bool {
+    let size = sig.len();
+    if !(9..=73).contains(&size) || sig[0] != 0x30 || sig[1] as usize != size - 3 {
+        return false;
+    }
+    let len_r = sig[3] as usize;
+    if 5 + len_r >= size {
+        return false;
+    }
+    let len_s = sig[5 + len_r] as usize;
+    if len_r + len_s + 7 != size {
+        return false;
+    }
+    // R
+    if sig[2] != 0x02 || len_r == 0 || sig[4] & 0x80 != 0 || (len_r > 1 && sig[4] == 0 && sig[5] & 0x80 == 0) {
+        return false;
+    }
+    // S
+    if sig[len_r + 4] != 0x02 || len_s == 0 || sig[len_r + 6] & 0x80 != 0 || (len_s > 1 && sig[len_r + 6] == 0 && sig[len_r + 7] & 0x80 == 0) {
+        return false;
+    }
+    true
+}
+
+fn check_signature_encoding(sig: &[u8], flags: ScriptVerifyFlags) -> Result<(), ScriptError> {
+    // Empty signature is a compact way to provide the invalid one
+    if sig.is_empty() {
+        return Ok(());
+    }
+    if (flags.has(ScriptVerifyFlags::DERSIG) || flags.has(ScriptVerifyFlags::LOW_S) || flags.has(ScriptVerifyFlags::STRICTENC)) &&
+        !is_valid_signature_encoding(sig) {
+        return Err(ScriptError::SigDer);
+    }
+    if flags.has(ScriptVerifyFlags::LOW_S) {
+        let signature = Signature::from_der_lax(&sig[..sig.len() - 1]).map_err(|_| ScriptError::SigDer)?;
+        let mut normalized = signature;
+        normalized.normalize_s();
+        if normalized != signature {
+            return Err(ScriptError::SigHighS);
+        }
+    }
+    if flags.has(ScriptVerifyFlags::STRICTENC) && SigHashType::try_from(sig[sig.len() - 1] as u32).is_err() {
+        return Err(ScriptError::SigHashType);
+    }
+    Ok(())
+}
+
+fn check_public_key_encoding(public_key: &[u8], flags: ScriptVerifyFlags) -> Result<(), ScriptError> {
+    let is_compressed_or_uncompressed = matches!(public_key, [0x04, ..] if public_key.len() == 65) ||
+        matches!(public_key, [0x02 | 0x03, ..] if public_key.len() == 33);
+    if flags.has(ScriptVerifyFlags::STRICTENC) && !is_compressed_or_uncompressed {
+        return Err(ScriptError::PubkeyType);
+    }
+    Ok(())
+}
+
+fn is_disabled(opcode: All, flags: ScriptVerifyFlags) -> bool {
+    match opcode {
+        OP_INVERT | OP_2MUL | OP_2DIV | OP_MUL | OP_LSHIFT | OP_RSHIFT => true,
+        // OP_SUBSTR, OP_LEFT and OP_RIGHT are replaced by OP_SPLIT, OP_NUM2BIN and OP_BIN2NUM
+        OP_CAT | OP_SUBSTR | OP_LEFT | OP_RIGHT | OP_AND | OP_OR | OP_XOR | OP_DIV | OP_MOD =>
+            !flags.has(ScriptVerifyFlags::DIP0020_OPCODES),
+        _ => false
+    }
+}
+
+fn pop(stack: &mut Vec<Vec<u8>>) -> Result<Vec<u8>, ScriptError> {
+    stack.pop().ok_or(ScriptError::InvalidStackOperation)
+}
+
+/// Element at the depth from the top (1 is the top)
+fn top(stack: &[Vec<u8>], depth: usize) -> Result<&Vec<u8>, ScriptError> {
+    stack.len().checked_sub(depth)
+        .and_then(|index| stack.get(index))
+        .ok_or(ScriptError::InvalidStackOperation)
+}
+
+fn require(stack: &[Vec<u8>], count: usize) -> Result<(), ScriptError> {
+    if stack.len() < count { Err(ScriptError::InvalidStackOperation) } else { Ok(()) }
+}
+
+fn push_bool(stack: &mut Vec<Vec<u8>>, value: bool) {
+    stack.push(if value { vec![1] } else { vec![] });
+}
+
+/// Evaluate the script on top of the stack
+pub fn eval_script<C: SignatureChecker>(stack: &mut Vec<Vec<u8>>, script: &[u8], flags: ScriptVerifyFlags, checker: &C) -> Result<(), ScriptError> {
+    if script.len() > MAX_SCRIPT_SIZE {
+        return Err(ScriptError::ScriptSize);
+    }
+    let require_minimal = flags.has(ScriptVerifyFlags::MINIMALDATA);
+    let num = |data: &[u8]| decode_num(data, require_minimal, DEFAULT_MAX_NUM_SIZE);
+    let mut pc = 0;
+    let mut code_separator = 0;
+    let mut exec_stack = Vec::<bool>::new();
+    let mut alt_stack = Vec::<Vec<u8>>::new();
+    let mut op_count = 0;
+    while pc < script.len() {
+        let executing = exec_stack.iter().all(|value| *value);
+        let (opcode, push) = read_op(script, &mut pc)?;
+        let code = opcode.into_u8();
+        if push.is_some_and(|data| data.len() > MAX_SCRIPT_ELEMENT_SIZE) {
+            return Err(ScriptError::PushSize);
+        }
+        if code > OP_PUSHNUM_16.into_u8() {
+            op_count += 1;
+            if op_count > MAX_OPS_PER_SCRIPT {
+                return Err(ScriptError::OpCount);
+            }
+        }
+        // Disabled opcodes fail the script even in the unexecuted branch
+        if is_disabled(opcode, flags) {
+            return Err(ScriptError::DisabledOpcode);
+        }
+        if let Some(data) = push {
+            if executing {
+                if require_minimal && !check_minimal_push(data, code) {
+                    return Err(ScriptError::MinimalData);
+                }
+                stack.push(data.to_vec());
+            }
+        } else if executing || (OP_IF.into_u8()..=OP_ENDIF.into_u8()).contains(&code) {
+            match opcode {
+                OP_PUSHNUM_NEG1 =>
+                    stack.push(encode_num(-1)),
+                _ if (OP_PUSHNUM_1.into_u8()..=OP_PUSHNUM_16.into_u8()).contains(&code) =>
+                    stack.push(encode_num((code - OP_PUSHNUM_1.into_u8() + 1) as i64)),
+                OP_NOP => {},
+                OP_CLTV | OP_CSV => {
+                    let check_lock_time = opcode == OP_CLTV;
+                    let enabled = flags.has(if check_lock_time { ScriptVerifyFlags::CHECKLOCKTIMEVERIFY } else { ScriptVerifyFlags::CHECKSEQUENCEVERIFY });
+                    if !enabled {
+                        if flags.has(ScriptVerifyFlags::DISCOURAGE_UPGRADABLE_NOPS) {
+                            return Err(ScriptError::DiscourageUpgradableNops);
+                        }
+                    } else {
+                        // The lock time is left on the stack to keep them soft-fork compatible with NOPs
+                        let value = decode_num(top(stack, 1)?, require_minimal, LOCKTIME_MAX_NUM_SIZE)?;
+                        if value < 0 {
+                            return Err(ScriptError::NegativeLocktime);
+                        }
+                        let satisfied = if check_lock_time {
+                            checker.check_lock_time(value)
+                        } else {
+                            value & SEQUENCE_LOCKTIME_DISABLE_FLAG != 0 || checker.check_sequence(value)
+                        };
+                        if !satisfied {
+                            return Err(ScriptError::UnsatisfiedLocktime);
+                        }
+                    }
+                },
+                _ if code == OP_NOP1.into_u8() || (OP_NOP4.into_u8()..=OP_NOP10.into_u8()).contains(&code) => {
+                    if flags.has(ScriptVerifyFlags::DISCOURAGE_UPGRADABLE_NOPS) {
+                        return Err(ScriptError::DiscourageUpgradableNops);
+                    }
+                },
+                OP_IF | OP_NOTIF => {
+                    let mut value = false;
+                    if executing {
+                        let condition = stack.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                        value = cast_to_bool(&condition) == (opcode == OP_IF);
+                    }
+                    exec_stack.push(value);
+                },
+                OP_ELSE => {
+                    let value = exec_stack.last_mut().ok_or(ScriptError::UnbalancedConditional)?;
+                    *value = !*value;
+                },
+                OP_ENDIF => {
+                    exec_stack.pop().ok_or(ScriptError::UnbalancedConditional)?;
+                },
+                OP_VERIFY => {
+                    if !cast_to_bool(&pop(stack)?) {
+                        return Err(ScriptError::Verify);
+                    }
+                },
+                OP_RETURN =>
+                    return Err(ScriptError::OpReturn),
+                // Stack operations
+                OP_TOALTSTACK => {
+                    let value = pop(stack)?;
+                    alt_stack.push(value);
+                },
+                OP_FROMALTSTACK => {
+                    let value = alt_stack.pop().ok_or(ScriptError::InvalidAltstackOperation)?;
+                    stack.push(value);
+                },
+                OP_2DROP => {
+                    require(stack, 2)?;
+                    stack.truncate(stack.len() - 2);
+                },
+                OP_2DUP | OP_3DUP => {
+                    let count = if opcode == OP_2DUP { 2 } else { 3 };
+                    require(stack, count)?;
+                    stack.extend_from_within(stack.len() - count..);
+                },
+                OP_2OVER => {
+                    require(stack, 4)?;
+                    stack.extend_from_within(stack.len() - 4..stack.len() - 2);
+                },
+                OP_2ROT | OP_2SWAP => {
+                    let count = if opcode == OP_2ROT { 6 } else { 4 };
+                    require(stack, count)?;
+                    let start = stack.len() - count;
+                    let items = stack.drain(start..start + 2).collect::<Vec<_>>();
+                    stack.extend(items);
+                },
+                OP_IFDUP => {
+                    let value = top(stack, 1)?.clone();
+                    if cast_to_bool(&value) {
+                        stack.push(value);
+                    }
+                },
+                OP_DEPTH =>
+                    stack.push(encode_num(stack.len() as i64)),
+                OP_DROP => {
+                    pop(stack)?;
+                },
+                OP_DUP | OP_OVER => {
+                    let value = top(stack, if opcode == OP_DUP { 1 } else { 2 })?.clone();
+                    stack.push(value);
+                },
+                OP_NIP => {
+                    require(stack, 2)?;
+                    stack.remove(stack.len() - 2);
+                },
+                OP_PICK | OP_ROLL => {
+                    let depth = num(&pop(stack)?)?;
+                    if depth < 0 || depth as usize >= stack.len() {
+                        return Err(ScriptError::InvalidStackOperation);
+                    }
+                    let index = stack.len() - 1 - depth as usize;
+                    let value = if opcode == OP_ROLL { stack.remove(index) } else { stack[index].clone() };
+                    stack.push(value);
+                },
+                OP_ROT => {
+                    require(stack, 3)?;
+                    let value = stack.remove(stack.len() - 3);
+                    stack.push(value);
+                },
+                OP_SWAP => {
+                    require(stack, 2)?;
+                    let len = stack.len();
+                    stack.swap(len - 2, len - 1);
+                },
+                OP_TUCK => {
+                    require(stack, 2)?;
+                    let value = top(stack, 1)?.clone();
+                    stack.insert(stack.len() - 2, value);
+                },
+                OP_SIZE => {
+                    let size = top(stack, 1)?.len();
+                    stack.push(encode_num(size as i64));
+                },
+                // Splice and bitwise operations (DIP-0020)
+                OP_CAT => {
+                    let second = pop(stack)?;
+                    let first = pop(stack)?;
+                    if first.len() + second.len() > MAX_SCRIPT_ELEMENT_SIZE {
+                        return Err(ScriptError::PushSize);
+                    }
+                    stack.push([first, second].concat());
+                },
+                // OP_SPLIT
+                OP_SUBSTR => {
+                    let position = num(&pop(stack)?)?;
+                    let mut data = pop(stack)?;
+                    if position < 0 || position as usize > data.len() {
+                        return Err(ScriptError::InvalidSplitRange);
+                    }
+                    let tail = data.split_off(position as usize);
+                    stack.push(data);
+                    stack.push(tail);
+                },
+                // OP_NUM2BIN
+                OP_LEFT => {
+                    let size = num(&pop(stack)?)?;
+                    if size < 0 || size as usize > MAX_SCRIPT_ELEMENT_SIZE {
+                        return Err(ScriptError::PushSize);
+                    }
+                    let size = size as usize;
+                    let mut data = minimally_encode(pop(stack)?);
+                    if data.len() > size {
+                        return Err(ScriptError::ImpossibleEncoding);
+                    }
+                    if data.len() < size {
+                        // Move the sign bit to the new last byte
+                        let sign = data.last_mut().map_or(0, |last| {
+                            let sign = *last & 0x80;
+                            *last &= 0x7f;
+                            sign
+                        });
+                        data.resize(size - 1, 0);
+                        data.push(sign);
+                    }
+                    stack.push(data);
+                },
+                // OP_BIN2NUM
+                OP_RIGHT => {
+                    let data = minimally_encode(pop(stack)?);
+                    if data.len() > DEFAULT_MAX_NUM_SIZE {
+                        return Err(ScriptError::InvalidNumberRange);
+                    }
+                    stack.push(data);
+                },
+                OP_AND | OP_OR | OP_XOR => {
+                    let second = pop(stack)?;
+                    let first = pop(stack)?;
+                    if first.len() != second.len() {
+                        return Err(ScriptError::InvalidOperandSize);
+                    }
+                    let result = first.iter().zip(second.iter())
+                        .map(|(a, b)| match opcode {
+                            OP_AND => a & b,
+                            OP_OR => a | b,
+                            _ => a ^ b,
+                        })
+                        .collect();
+                    stack.push(result);
+                },
+                OP_EQUAL | OP_EQUALVERIFY => {
+                    let second = pop(stack)?;
+                    let first = pop(stack)?;
+                    let equal = first == second;
+                    if opcode == OP_EQUALVERIFY {
+                        if !equal {
+                            return Err(ScriptError::EqualVerify);
+                        }
+                    } else {
+                        push_bool(stack, equal);
+                    }
+                },
+                // Arithmetic
+                OP_1ADD | OP_1SUB | OP_NEGATE | OP_ABS | OP_NOT | OP_0NOTEQUAL => {
+                    let value = num(&pop(stack)?)?;
+                    let result = match opcode {
+                        OP_1ADD => value + 1,
+                        OP_1SUB => value - 1,
+                        OP_NEGATE => -value,
+                        OP_ABS => value.abs(),
+                        OP_NOT => (value == 0) as i64,
+                        _ => (value != 0) as i64,
+                    };
+                    stack.push(encode_num(result));
+                },
+                OP_ADD | OP_SUB | OP_DIV | OP_MOD | OP_BOOLAND | OP_BOOLOR | OP_NUMEQUAL | OP_NUMEQUALVERIFY |
+                OP_NUMNOTEQUAL | OP_LESSTHAN | OP_GREATERTHAN | OP_LESSTHANOREQUAL | OP_GREATERTHANOREQUAL | OP_MIN | OP_MAX => {
+                    let b = num(&pop(stack)?)?;
+                    let a = num(&pop(stack)?)?;
+                    let result = match opcode {
+                        OP_ADD => a + b,
+                        OP_SUB => a - b,
+                        OP_DIV if b == 0 => return Err(ScriptError::DivByZero),
+                        OP_DIV => a / b,
+                        OP_MOD if b == 0 => return Err(ScriptError::ModByZero),
+                        OP_MOD => a % b,
+                        OP_BOOLAND => (a != 0 && b != 0) as i64,
+                        OP_BOOLOR => (a != 0 || b != 0) as i64,
+                        OP_NUMEQUAL | OP_NUMEQUALVERIFY => (a == b) as i64,
+                        OP_NUMNOTEQUAL => (a != b) as i64,
+                        OP_LESSTHAN => (a < b) as i64,
+                        OP_GREATERTHAN => (a > b) as i64,
+                        OP_LESSTHANOREQUAL => (a <= b) as i64,
+                        OP_GREATERTHANOREQUAL => (a >= b) as i64,
+                        OP_MIN => a.min(b),
+                        _ => a.max(b),
+                    };
+                    if opcode == OP_NUMEQUALVERIFY {
+                        if result == 0 {
+                            return Err(ScriptError::NumEqualVerify);
+                        }
+                    } else {
+                        stack.push(encode_num(result));
+                    }
+                },
+                OP_WITHIN => {
+                    let max = num(&pop(stack)?)?;
+                    let min = num(&pop(stack)?)?;
+                    let value = num(&pop(stack)?)?;
+                    push_bool(stack, min <= value && value < max);
+                },
+                // Crypto
+                OP_RIPEMD160 | OP_SHA1 | OP_SHA256 | OP_HASH160 | OP_HASH256 => {
+                    let data = pop(stack)?;
+                    let hash = match opcode {
+                        OP_RIPEMD160 => ripemd160::Hash::hash(&data).into_inner().to_vec(),
+                        OP_SHA1 => sha1::Hash::hash(&data).into_inner().to_vec(),
+                        OP_SHA256 => sha256::Hash::hash(&data).into_inner().to_vec(),
+                        OP_HASH160 => hash160::Hash::hash(&data).into_inner().to_vec(),
+                        _ => sha256d::Hash::hash(&data).into_inner().to_vec(),
+                    };
+                    stack.push(hash);
+                },
+                OP_CODESEPARATOR =>
+                    code_separator = pc,
+                OP_CHECKSIG | OP_CHECKSIGVERIFY => {
+                    require(stack, 2)?;
+                    let public_key = pop(stack)?;
+                    let signature = pop(stack)?;
+                    // Signature can't sign itself
+                    let mut script_code = script[code_separator..].to_vec();
+                    find_and_delete(&mut script_code, &signature);
+                    check_signature_encoding(&signature, flags)?;
+                    check_public_key_encoding(&public_key, flags)?;
+                    let success = checker.check_signature(&signature, &public_key, &script_code);
+                    if !success && flags.has(ScriptVerifyFlags::NULLFAIL) && !signature.is_empty() {
+                        return Err(ScriptError::NullFail);
+                    }
+                    if opcode == OP_CHECKSIGVERIFY {
+                        if !success {
+                            return Err(ScriptError::CheckSigVerify);
+                        }
+                    } else {
+                        push_bool(stack, success);
+                    }
+                },
+                OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => {
+                    // [dummy] [signatures ...] <signatures count> [public keys ...] <public keys count>
+                    let mut i = 1;
+                    let mut keys_count = num(top(stack, i)?)?;
+                    if keys_count < 0 || keys_count as usize > MAX_PUBKEYS_PER_MULTISIG {
+                        return Err(ScriptError::PubkeyCount);
+                    }
+                    op_count += keys_count as usize;
+                    if op_count > MAX_OPS_PER_SCRIPT {
+                        return Err(ScriptError::OpCount);
+                    }
+                    i += 1;
+                    let mut key_index = i;
+                    // Keys which remain to be checked for the NULLFAIL
+                    let mut keys_to_check = keys_count as usize + 2;
+                    i += keys_count as usize;
+                    let mut signatures_count = num(top(stack, i)?)?;
+                    if signatures_count < 0 || signatures_count > keys_count {
+                        return Err(ScriptError::SigCount);
+                    }
+                    i += 1;
+                    let mut signature_index = i;
+                    i += signatures_count as usize;
+                    require(stack, i)?;
+                    let mut script_code = script[code_separator..].to_vec();
+                    for k in 0..signatures_count as usize {
+                        find_and_delete(&mut script_code, top(stack, signature_index + k)?);
+                    }
+                    let mut success = true;
+                    while success && signatures_count > 0 {
+                        let signature = top(stack, signature_index)?;
+                        let public_key = top(stack, key_index)?;
+                        check_signature_encoding(signature, flags)?;
+                        check_public_key_encoding(public_key, flags)?;
+                        if checker.check_signature(signature, public_key, &script_code) {
+                            signature_index += 1;
+                            signatures_count -= 1;
+                        }
+                        key_index += 1;
+                        keys_count -= 1;
+                        // There are not enough keys left for the remaining signatures
+                        if signatures_count > keys_count {
+                            success = false;
+                        }
+                    }
+                    while i > 1 {
+                        i -= 1;
+                        if !success && flags.has(ScriptVerifyFlags::NULLFAIL) && keys_to_check == 0 && !top(stack, 1)?.is_empty() {
+                            return Err(ScriptError::NullFail);
+                        }
+                        keys_to_check = keys_to_check.saturating_sub(1);
+                        pop(stack)?;
+                    }
+                    // The extra element consumed due to the off-by-one bug
+                    let dummy = pop(stack)?;
+                    if flags.has(ScriptVerifyFlags::NULLDUMMY) && !dummy.is_empty() {
+                        return Err(ScriptError::SigNullDummy);
+                    }
+                    if opcode == OP_CHECKMULTISIGVERIFY {
+                        if !success {
+                            return Err(ScriptError::CheckMultisigVerify);
+                        }
+                    } else {
+                        push_bool(stack, success);
+                    }
+                },
+                _ => return Err(ScriptError::BadOpcode),
+            }
+        }
+        if stack.len() + alt_stack.len() > MAX_STACK_SIZE {
+            return Err(ScriptError::StackSize);
+        }
+    }
+    if !exec_stack.is_empty() {
+        return Err(ScriptError::UnbalancedConditional);
+    }
+    Ok(())
+}
+
+/// Evaluate the scriptSig of the input and the scriptPubKey of the spent output
+pub fn verify_script<C: SignatureChecker>(script_sig: &[u8], script_pub_key: &[u8], flags: ScriptVerifyFlags, checker: &C) -> Result<(), ScriptError> {
+    if flags.has(ScriptVerifyFlags::SIGPUSHONLY) && !is_push_only(script_sig) {
+        return Err(ScriptError::SigPushOnly);
+    }
+    let mut stack = Vec::<Vec<u8>>::new();
+    eval_script(&mut stack, script_sig, flags, checker)?;
+    let stack_copy = flags.has(ScriptVerifyFlags::P2SH).then(|| stack.clone());
+    eval_script(&mut stack, script_pub_key, flags, checker)?;
+    if !stack.last().is_some_and(|value| cast_to_bool(value)) {
+        return Err(ScriptError::EvalFalse);
+    }
+    if let Some(stack_copy) = stack_copy.filter(|_| is_pay_to_script_hash(script_pub_key)) {
+        // scriptSig must be literals-only or the redeem script could be anything
+        if !is_push_only(script_sig) {
+            return Err(ScriptError::SigPushOnly);
+        }
+        stack = stack_copy;
+        let redeem_script = pop(&mut stack)?;
+        eval_script(&mut stack, &redeem_script, flags, checker)?;
+        if !stack.last().is_some_and(|value| cast_to_bool(value)) {
+            return Err(ScriptError::EvalFalse);
+        }
+    }
+    if flags.has(ScriptVerifyFlags::CLEANSTACK) && flags.has(ScriptVerifyFlags::P2SH) && stack.len() != 1 {
+        return Err(ScriptError::CleanStack);
+    }
+    Ok(())
+}
@@ -1 +1,6 @@
+pub mod interpreter;
 pub mod opcodes;
+pub mod script_error;
+
+pub use self::interpreter::{BaseSignatureChecker, ScriptVerifyFlags, SignatureChecker, TransactionSignatureChecker};
+pub use self::script_error::ScriptError;
@@ -0,0 +1,97 @@
+/// Reason of the script evaluation failure (mirrors `ScriptError` of Dash Core)
+#[warn(non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ScriptError {
+    UnknownError = 0,
+    EvalFalse = 1,
+    OpReturn = 2,
+    // Max sizes
+    ScriptSize = 3,
+    PushSize = 4,
+    OpCount = 5,
+    StackSize = 6,
+    SigCount = 7,
+    PubkeyCount = 8,
+    // Operands checks (DIP-0020)
+    InvalidOperandSize = 9,
+    InvalidNumberRange = 10,
+    ImpossibleEncoding = 11,
+    InvalidSplitRange = 12,
+    DivByZero = 13,
+    ModByZero = 14,
+    // Failed verify operations
+    Verify = 15,
+    EqualVerify = 16,
+    CheckMultisigVerify = 17,
+    CheckSigVerify = 18,
+    NumEqualVerify = 19,
+    // Logical/Format/Canonical errors
+    BadOpcode = 20,
+    DisabledOpcode = 21,
+    InvalidStackOperation = 22,
+    InvalidAltstackOperation = 23,
+    UnbalancedConditional = 24,
+    // CHECKLOCKTIMEVERIFY and CHECKSEQUENCEVERIFY
+    NegativeLocktime = 25,
+    UnsatisfiedLocktime = 26,
+    // Malleability
+    SigHashType = 27,
+    SigDer = 28,
+    MinimalData = 29,
+    SigPushOnly = 30,
+    SigHighS = 31,
+    SigNullDummy = 32,
+    PubkeyType = 33,
+    CleanStack = 34,
+    NullFail = 35,
+    // Softfork safeness
+    DiscourageUpgradableNops = 36,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            ScriptError::UnknownError => "unknown error",
+            ScriptError::EvalFalse => "Script evaluated without error but finished with a false/empty top stack element",
+            ScriptError::OpReturn => "OP_RETURN was encountered",
+            ScriptError::ScriptSize => "Script is too big",
+            ScriptError::PushSize => "Push value size limit exceeded",
+            ScriptError::OpCount => "Operation limit exceeded",
+            ScriptError::StackSize => "Stack size limit exceeded",
+            ScriptError::SigCount => "Signature count negative or greater than pubkey count",
+            ScriptError::PubkeyCount => "Pubkey count negative or limit exceeded",
+            ScriptError::InvalidOperandSize => "Invalid operand size",
+            ScriptError::InvalidNumberRange => "Given operand is not a number within the valid range",
+            ScriptError::ImpossibleEncoding => "The requested encoding is impossible to satisfy",
+            ScriptError::InvalidSplitRange => "Invalid OP_SPLIT range",
+            ScriptError::DivByZero => "Division by zero error",
+            ScriptError::ModByZero => "Modulo by zero error",
+            ScriptError::Verify => "Script failed an OP_VERIFY operation",
+            ScriptError::EqualVerify => "Script failed an OP_EQUALVERIFY operation",
+            ScriptError::CheckMultisigVerify => "Script failed an OP_CHECKMULTISIGVERIFY operation",
+            ScriptError::CheckSigVerify => "Script failed an OP_CHECKSIGVERIFY operation",
+            ScriptError::NumEqualVerify => "Script failed an OP_NUMEQUALVERIFY operation",
+            ScriptError::BadOpcode => "Opcode missing or not understood",
+            ScriptError::DisabledOpcode => "Attempted to use a disabled opcode",
+            ScriptError::InvalidStackOperation => "Operation not valid with the current stack size",
+            ScriptError::InvalidAltstackOperation => "Operation not valid with the current altstack size",
+            ScriptError::UnbalancedConditional => "Invalid OP_IF construction",
+            ScriptError::NegativeLocktime => "Negative locktime",
+            ScriptError::UnsatisfiedLocktime => "Locktime requirement not satisfied",
+            ScriptError::SigHashType => "Signature hash type missing or not understood",
+            ScriptError::SigDer => "Non-canonical DER signature",
+            ScriptError::MinimalData => "Data push larger than necessary",
+            ScriptError::SigPushOnly => "Only push operators allowed in signatures",
+            ScriptError::SigHighS => "Non-canonical signature: S value is unnecessarily high",
+            ScriptError::SigNullDummy => "Dummy CHECKMULTISIG argument must be zero",
+            ScriptError::PubkeyType => "Public key is neither compressed or uncompressed",
+            ScriptError::CleanStack => "Extra items left on stack after execution",
+            ScriptError::NullFail => "Signature must be zero for failed CHECK(MULTI)SIG operation",
+            ScriptError::DiscourageUpgradableNops => "NOPx reserved for soft-fork upgrades",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+impl std::error::Error for ScriptError {}
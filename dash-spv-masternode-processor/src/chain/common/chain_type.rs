@@ -418,6 +418,81 @@ impl ChainType {
         }
     }
 
+    /// Number of blocks after which the block subsidy is decreased by 1/14 (~7.14% yearly)
+    pub fn subsidy_decrease_interval(&self) -> u32 {
+        210240
+    }
+
+    /// Superblock part is taken from the subsidy of the blocks after this height (nBudgetPaymentsStartBlock in Core)
+    pub fn budget_payments_start_block(&self) -> u32 {
+        match self {
+            ChainType::MainNet => 328008,
+            _ => 4100
+        }
+    }
+
+    /// Difficulty used for the subsidy of the block after the block with `prev_bits` (ConvertBitsToDouble in Core),
+    /// early mainnet blocks were rewarded with the difficulty calculated without the exponent
+    fn subsidy_difficulty(&self, prev_height: u32, prev_bits: u32) -> f64 {
+        let difficulty = 0xffff as f64 / (prev_bits & 0x00ffffff) as f64;
+        if *self == ChainType::MainNet && prev_height <= 4500 {
+            return difficulty;
+        }
+        let shift = (prev_bits >> 24) as i32;
+        difficulty * 256f64.powi(29 - shift)
+    }
+
+    /// Subsidy of the block at height (without fees and before the superblock part is taken)
+    /// as GetBlockSubsidyHelper in Core: it depends on the difficulty of the previous block (`prev_bits`)
+    /// and is decreased every `subsidy_decrease_interval`
+    pub fn block_subsidy(&self, block_height: u32, prev_bits: u32) -> u64 {
+        let prev_height = block_height.saturating_sub(1);
+        let difficulty = self.subsidy_difficulty(prev_height, prev_bits);
+        // Core keeps the base subsidy in whole coins
+        let base = if prev_height < 5465 {
+            ((1111.0 / (difficulty + 1.0).powi(2)) as u64).clamp(1, 500)
+        } else if prev_height < 17000 || (difficulty <= 75.0 && prev_height < 24000) {
+            ((11111.0 / ((difficulty + 51.0) / 6.0).powi(2)) as u64).clamp(25, 500)
+        } else {
+            ((2222222.0 / ((difficulty + 2600.0) / 9.0).powi(2)) as u64).clamp(5, 25)
+        };
+        let decreases = prev_height / self.subsidy_decrease_interval();
+        (0..decreases).fold(base * DUFFS, |subsidy, _| subsidy - subsidy / 14)
+    }
+
+    /// Part of the subsidy reserved for the superblocks: 10% (20% since Core v20)
+    pub fn treasury_payment(&self, block_height: u32, prev_bits: u32) -> u64 {
+        if block_height.saturating_sub(1) <= self.budget_payments_start_block() {
+            return 0;
+        }
+        let subsidy = self.block_subsidy(block_height, prev_bits);
+        if self.core20_is_active_at(block_height) { subsidy / 5 } else { subsidy / 10 }
+    }
+
+    /// Value of the block shared between the miner and the masternode
+    pub fn block_value(&self, block_height: u32, prev_bits: u32, fees: u64) -> u64 {
+        self.block_subsidy(block_height, prev_bits) - self.treasury_payment(block_height, prev_bits) + fees
+    }
+
+    /// Masternode share of the block value: 60% (75% since Core v20, so it stays 60% of the subsidy)
+    pub fn masternode_payment(&self, block_height: u32, block_value: u64) -> u64 {
+        if self.core20_is_active_at(block_height) {
+            block_value * 3 / 4
+        } else {
+            block_value * 3 / 5
+        }
+    }
+
+    /// Part of the masternode payment moved to the credit pool since MN_RR:
+    /// 37.5% of the masternode share of the subsidy (fees aren't reallocated)
+    pub fn platform_share(&self, block_height: u32, prev_bits: u32) -> u64 {
+        if self.mn_rr_is_active_at(block_height) {
+            self.masternode_payment(block_height, self.block_value(block_height, prev_bits, 0)) * 375 / 1000
+        } else {
+            0
+        }
+    }
+
     pub fn header_max_amount(&self) -> u64 {
         2000
     }
@@ -474,6 +549,10 @@ impl ChainType {
         }
     }
 
+    pub fn core19_is_active_at(&self, height: u32) -> bool {
+        height >= self.core19_activation_height()
+    }
+
     pub fn core20_activation_height(&self) -> u32 {
         match self {
             ChainType::MainNet => 1987776,
@@ -485,4 +564,15 @@ impl ChainType {
         height >= self.core20_activation_height()
     }
 
+    pub fn mn_rr_activation_height(&self) -> u32 {
+        match self {
+            ChainType::MainNet => 2128896,
+            ChainType::TestNet => 1066900,
+            _ => u32::MAX
+        }
+    }
+    pub fn mn_rr_is_active_at(&self, height: u32) -> bool {
+        height >= self.mn_rr_activation_height()
+    }
+
 }
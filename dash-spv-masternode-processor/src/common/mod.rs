@@ -2,12 +2,15 @@ pub mod block;
 pub mod llmq_snapshot_skip_mode;
 pub mod llmq_version;
 pub mod merkle_tree;
+pub mod signature_validation_status;
 pub mod socket_address;
 pub mod masternode_type;
+pub mod target;
 
 pub use self::block::Block;
 pub use self::llmq_snapshot_skip_mode::LLMQSnapshotSkipMode;
 pub use self::llmq_version::LLMQVersion;
 pub use self::masternode_type::MasternodeType;
 pub use self::merkle_tree::MerkleTree;
+pub use self::signature_validation_status::SignatureValidationStatus;
 pub use self::socket_address::SocketAddress;
@@ -0,0 +1,18 @@
+/// Outcome of the verification of the message recovered-signed by the quorum
+#[warn(non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum SignatureValidationStatus {
+    Verified = 0,
+    /// Masternode list with the signing quorums isn't available
+    NoMasternodeList = 1,
+    /// There is no quorum of the required type responsible for the request
+    NoQuorum = 2,
+    InvalidSignature = 3,
+}
+
+impl SignatureValidationStatus {
+    pub fn is_verified(&self) -> bool {
+        *self == Self::Verified
+    }
+}
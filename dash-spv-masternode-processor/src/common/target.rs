@@ -0,0 +1,10 @@
+//! Log targets of the processing pipeline, so the host can filter them
+
+pub const LIST_DIFF: &str = "masternode_processor::mnlistdiff";
+pub const QR_INFO: &str = "masternode_processor::qrinfo";
+pub const QUORUM: &str = "masternode_processor::quorum";
+pub const ROTATION: &str = "masternode_processor::rotation";
+pub const CACHE: &str = "masternode_processor::cache";
+pub const LOCKS: &str = "masternode_processor::locks";
+pub const PAYMENTS: &str = "masternode_processor::payments";
+pub const FFI: &str = "masternode_processor::ffi";
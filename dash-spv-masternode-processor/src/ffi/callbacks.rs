@@ -1,9 +1,12 @@
 extern crate libc;
 use std::ffi::c_void;
+use std::ptr::null;
 use crate::{models, types};
 use crate::crypto::{byte_util::MutDecodable, UInt256, UInt768};
+use crate::ffi::boxer::boxed;
 use crate::ffi::from::FromFFI;
-use crate::processing::ProcessingError;
+use crate::ffi::to::ToFFI;
+use crate::processing::{CoreProvider, ProcessingError};
 
 pub type AddInsightBlockingLookup =
     unsafe extern "C" fn(block_hash: *mut [u8; 32], context: *const c_void);
@@ -146,3 +149,165 @@ pub fn lookup_cl_signature_by_block_hash<SL, SD>(
         None
     }
 }
+
+/// Set of callbacks registered across FFI which is used as a storage backend for the processor
+pub struct FFICoreProvider {
+    /// External Masternode Manager Diff Message Context
+    pub opaque_context: *const c_void,
+    pub get_block_height_by_hash: GetBlockHeightByHash,
+    pub get_merkle_root_by_hash: MerkleRootLookup,
+    get_block_hash_by_height: GetBlockHashByHeight,
+    get_llmq_snapshot_by_block_hash: GetLLMQSnapshotByBlockHash,
+    get_cl_signature_by_block_hash: GetCLSignatureByBlockHash,
+    save_llmq_snapshot: SaveLLMQSnapshot,
+    save_cl_signature: SaveCLSignature,
+    get_masternode_list_by_block_hash: MasternodeListLookup,
+    save_masternode_list: MasternodeListSave,
+    destroy_masternode_list: MasternodeListDestroy,
+    add_insight: AddInsightBlockingLookup,
+    destroy_hash: HashDestroy,
+    destroy_snapshot: LLMQSnapshotDestroy,
+    should_process_diff_with_range: ShouldProcessDiffWithRange,
+}
+
+impl std::fmt::Debug for FFICoreProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FFICoreProvider")
+            .field("context", &self.opaque_context)
+            .finish()
+    }
+}
+
+impl FFICoreProvider {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        get_merkle_root_by_hash: MerkleRootLookup,
+        get_block_height_by_hash: GetBlockHeightByHash,
+        get_block_hash_by_height: GetBlockHashByHeight,
+        get_llmq_snapshot_by_block_hash: GetLLMQSnapshotByBlockHash,
+        save_llmq_snapshot: SaveLLMQSnapshot,
+        get_cl_signature_by_block_hash: GetCLSignatureByBlockHash,
+        save_cl_signature: SaveCLSignature,
+        get_masternode_list_by_block_hash: MasternodeListLookup,
+        save_masternode_list: MasternodeListSave,
+        destroy_masternode_list: MasternodeListDestroy,
+        add_insight: AddInsightBlockingLookup,
+        destroy_hash: HashDestroy,
+        destroy_snapshot: LLMQSnapshotDestroy,
+        should_process_diff_with_range: ShouldProcessDiffWithRange,
+    ) -> Self {
+        Self {
+            get_merkle_root_by_hash,
+            get_block_height_by_hash,
+            get_block_hash_by_height,
+            get_llmq_snapshot_by_block_hash,
+            save_llmq_snapshot,
+            get_cl_signature_by_block_hash,
+            save_cl_signature,
+            get_masternode_list_by_block_hash,
+            save_masternode_list,
+            destroy_masternode_list,
+            add_insight,
+            destroy_hash,
+            destroy_snapshot,
+            should_process_diff_with_range,
+            opaque_context: null(),
+        }
+    }
+}
+
+impl CoreProvider for FFICoreProvider {
+    fn lookup_block_height_by_hash(&self, block_hash: UInt256) -> u32 {
+        unsafe { (self.get_block_height_by_hash)(boxed(block_hash.0), self.opaque_context) }
+    }
+
+    fn lookup_block_hash_by_height(&self, block_height: u32) -> Option<UInt256> {
+        lookup_block_hash_by_height(
+            block_height,
+            |h: u32| unsafe { (self.get_block_hash_by_height)(h, self.opaque_context) },
+            |hash: *mut u8| unsafe { (self.destroy_hash)(hash) },
+        )
+    }
+
+    fn lookup_merkle_root_by_hash(&self, block_hash: UInt256) -> Option<UInt256> {
+        lookup_merkle_root_by_hash(
+            block_hash,
+            |h: UInt256| unsafe { (self.get_merkle_root_by_hash)(boxed(h.0), self.opaque_context) },
+            |hash: *mut u8| unsafe { (self.destroy_hash)(hash) },
+        )
+    }
+
+    fn lookup_masternode_list(&self, block_hash: UInt256) -> Option<models::MasternodeList> {
+        lookup_masternode_list(
+            block_hash,
+            |h: UInt256| unsafe {
+                (self.get_masternode_list_by_block_hash)(boxed(h.0), self.opaque_context)
+            },
+            |list: *mut types::MasternodeList| unsafe { (self.destroy_masternode_list)(list) },
+        )
+    }
+
+    fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &models::MasternodeList) -> bool {
+        unsafe {
+            (self.save_masternode_list)(
+                boxed(block_hash.0),
+                boxed(masternode_list.encode()),
+                self.opaque_context,
+            )
+        }
+    }
+
+    fn lookup_snapshot_by_block_hash(&self, block_hash: UInt256) -> Option<models::LLMQSnapshot> {
+        lookup_snapshot_by_block_hash(
+            block_hash,
+            |h: UInt256| unsafe {
+                (self.get_llmq_snapshot_by_block_hash)(boxed(h.0), self.opaque_context)
+            },
+            |snapshot: *mut types::LLMQSnapshot| unsafe { (self.destroy_snapshot)(snapshot) },
+        )
+    }
+
+    fn save_snapshot(&self, block_hash: UInt256, snapshot: models::LLMQSnapshot) -> bool {
+        unsafe {
+            (self.save_llmq_snapshot)(
+                boxed(block_hash.0),
+                boxed(snapshot.encode()),
+                self.opaque_context,
+            )
+        }
+    }
+
+    fn lookup_cl_signature_by_block_hash(&self, block_hash: UInt256) -> Option<UInt768> {
+        lookup_cl_signature_by_block_hash(
+            block_hash,
+            |h: UInt256| unsafe {
+                (self.get_cl_signature_by_block_hash)(boxed(h.0), self.opaque_context)
+            },
+            |obj: *mut u8| unsafe { (self.destroy_hash)(obj) },
+        )
+    }
+
+    fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> bool {
+        unsafe {
+            (self.save_cl_signature)(
+                boxed(block_hash.0),
+                boxed(cl_signature.0),
+                self.opaque_context,
+            )
+        }
+    }
+
+    fn should_process_diff_with_range(&self, base_block_hash: UInt256, block_hash: UInt256) -> ProcessingError {
+        unsafe {
+            (self.should_process_diff_with_range)(
+                boxed(base_block_hash.0),
+                boxed(block_hash.0),
+                self.opaque_context,
+            )
+        }
+    }
+
+    fn add_insight(&self, block_hash: UInt256) {
+        unsafe { (self.add_insight)(boxed(block_hash.0), self.opaque_context) }
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::slice;
+use std::sync::Arc;
 use crate::{chain, common, models, tx, types};
 use crate::consensus::encode;
 use crate::crypto::{byte_util::Reversable, UInt128, UInt160, UInt256, UInt384, UInt768};
@@ -76,6 +77,7 @@ impl FromFFI for types::Transaction {
             },
             tx_type: self.tx_type,
             payload_offset: self.payload_offset,
+            extra_payload: None,
             block_height: self.block_height,
         }
     }
@@ -128,7 +130,7 @@ impl FromFFI for types::MasternodeList {
                 |mut acc, i| {
                     let value = (*(*self.masternodes.add(i))).decode();
                     let key = value.provider_registration_transaction_hash.reversed();
-                    acc.insert(key, value);
+                    acc.insert(key, Arc::new(value));
                     acc
                 },
             ),
@@ -284,11 +286,7 @@ impl FromFFI for types::LLMQSnapshot {
     unsafe fn decode(&self) -> Self::Item {
         let member_list = slice::from_raw_parts(self.member_list as *const u8, self.member_list_length).to_vec();
         let skip_list = slice::from_raw_parts(self.skip_list as *const i32, self.skip_list_length).to_vec();
-        Self::Item {
-            member_list,
-            skip_list,
-            skip_list_mode: self.skip_list_mode,
-        }
+        Self::Item::new(member_list, self.member_count, skip_list, self.skip_list_mode)
     }
 }
 
@@ -1,3 +1,4 @@
+use std::borrow::Borrow;
 use std::collections::BTreeMap;
 use std::ptr::null_mut;
 use crate::{chain, common, models, tx, types};
@@ -294,6 +295,7 @@ impl ToFFI for models::LLMQSnapshot {
         Self::Item {
             member_list_length: self.member_list.len(),
             member_list: boxed_vec(self.member_list.clone()),
+            member_count: self.member_count,
             skip_list_length: self.skip_list.len(),
             skip_list: boxed_vec(self.skip_list.clone()),
             skip_list_mode: self.skip_list_mode,
@@ -342,13 +344,13 @@ pub fn encode_quorums_map(
     )
 }
 
-pub fn encode_masternodes_map(
-    masternodes: &BTreeMap<UInt256, models::MasternodeEntry>,
+pub fn encode_masternodes_map<E: Borrow<models::MasternodeEntry>>(
+    masternodes: &BTreeMap<UInt256, E>,
 ) -> *mut *mut types::MasternodeEntry {
     boxed_vec(
         masternodes
-            .iter()
-            .map(|(_, entry)| boxed(entry.encode()))
+            .values()
+            .map(|entry| boxed(entry.borrow().encode()))
             .collect(),
     )
 }
@@ -34,6 +34,7 @@ pub mod hash_types;
 pub mod keys;
 pub mod models;
 pub mod network;
+pub mod storage;
 pub mod tx;
 pub mod types;
 pub mod util;
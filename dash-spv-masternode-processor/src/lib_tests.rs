@@ -8,6 +8,7 @@ pub mod tests {
     use serde::{Deserialize, Serialize};
     use std::io::Read;
     use std::ptr::null_mut;
+    use std::sync::Arc;
     use std::{env, fs, slice};
     use crate::bindings::common::{processor_create_cache, register_processor, register_rust_logger};
     use crate::bindings::masternode::{process_mnlistdiff_from_message, process_qrinfo_from_message};
@@ -165,7 +166,7 @@ pub mod tests {
             "process_mnlistdiff_from_message_internal.start: {:?}",
             std::time::Instant::now()
         );
-        processor.opaque_context = context;
+        processor.provider.opaque_context = context;
         processor.use_insight_as_backup = use_insight_as_backup;
         processor.chain_type = chain_type;
         let message: &[u8] = unsafe { slice::from_raw_parts(message_arr, message_length) };
@@ -195,7 +196,7 @@ pub mod tests {
         println!("process_qrinfo_from_message: {:?} {:?}", processor, cache);
         let message: &[u8] = unsafe { slice::from_raw_parts(message, message_length) };
         let processor = unsafe { &mut *processor };
-        processor.opaque_context = context;
+        processor.provider.opaque_context = context;
         processor.use_insight_as_backup = use_insight_as_backup;
         processor.chain_type = chain_type;
         let cache = unsafe { &mut *cache };
@@ -528,7 +529,7 @@ pub mod tests {
         let masternode_list = &*masternode_list;
         let masternode_list_decoded = masternode_list.decode();
         //println!("masternode_list_save_in_cache: {}", h);
-        data.cache.mn_lists.insert(h, masternode_list_decoded);
+        data.cache.mn_lists.insert(h, Arc::new(masternode_list_decoded));
         true
     }
 
@@ -0,0 +1,86 @@
+use std::io;
+use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType};
+use crate::common::SignatureValidationStatus;
+use crate::consensus::Encodable;
+use crate::crypto::{byte_util::BytesDecodable, UInt256, UInt768};
+use crate::models::{ChainLockValidationReport, LLMQEntry, MasternodeList};
+use crate::processing::{MessageError, MessageField, MessageSection, message_error::read_field};
+
+pub const CLSIG_REQUEST_ID_PREFIX: &str = "clsig";
+/// Quorums which sign the lock are taken from the block this number of blocks below the locked one
+pub const SIGN_HEIGHT_OFFSET: u32 = 8;
+
+/// ChainLock: 'clsig' message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainLock {
+    pub block_height: u32,
+    pub block_hash: UInt256,
+    pub signature: UInt768,
+}
+
+impl ChainLock {
+    pub fn new(block_height: u32, block_hash: UInt256, signature: UInt768) -> Self {
+        Self { block_height, block_hash, signature }
+    }
+
+    /// Read message received as 'clsig'
+    pub fn read(message: &[u8], offset: &mut usize) -> Result<Self, MessageError> {
+        let section = MessageSection::ChainLock;
+        let block_height = read_field(offset, section, MessageField::BlockHeight, None, |offset| i32::from_bytes(message, offset))?;
+        let block_hash = read_field(offset, section, MessageField::BlockHash, None, |offset| UInt256::from_bytes(message, offset))?;
+        let signature = read_field(offset, section, MessageField::Signature, Some(block_hash), |offset| UInt768::from_bytes(message, offset))?;
+        Ok(Self { block_height: block_height as u32, block_hash, signature })
+    }
+
+    pub fn llmq_type(chain_type: ChainType) -> LLMQType {
+        chain_type.chain_locks_type()
+    }
+
+    pub fn request_id(&self) -> UInt256 {
+        let mut buffer: Vec<u8> = Vec::new();
+        CLSIG_REQUEST_ID_PREFIX.to_string().enc(&mut buffer);
+        (self.block_height as i32).enc(&mut buffer);
+        UInt256::sha256d(buffer)
+    }
+
+    pub fn sign_hash(&self, quorum: &LLMQEntry) -> UInt256 {
+        quorum.sign_hash_for_request_id(self.request_id(), self.block_hash)
+    }
+
+    /// Height of the block which masternode list contains the quorums signing this lock
+    pub fn signing_block_height(&self) -> Option<u32> {
+        self.block_height.checked_sub(SIGN_HEIGHT_OFFSET)
+    }
+
+    /// Select the quorum responsible for the lock from the list of active quorums
+    pub fn quorum_entry<'a>(&self, masternode_list: &'a MasternodeList, chain_type: ChainType) -> Option<&'a LLMQEntry> {
+        masternode_list.quorum_entry_for_lock_request_id(self.request_id(), Self::llmq_type(chain_type))
+    }
+
+    /// Verify the threshold signature of the lock against the quorums of the masternode list
+    pub fn verify_with_masternode_list(&self, masternode_list: &MasternodeList, chain_type: ChainType) -> ChainLockValidationReport {
+        let mut report = ChainLockValidationReport::new(self, chain_type, SignatureValidationStatus::NoQuorum);
+        if let Some(quorum) = self.quorum_entry(masternode_list, chain_type) {
+            let sign_hash = self.sign_hash(quorum);
+            report.quorum_hash = Some(quorum.llmq_hash);
+            report.sign_hash = Some(sign_hash);
+            report.status = if quorum.verify_recovered_signature(sign_hash, self.signature) {
+                SignatureValidationStatus::Verified
+            } else {
+                SignatureValidationStatus::InvalidSignature
+            };
+        }
+        report
+    }
+}
+
+impl Encodable for ChainLock {
+    #[inline]
+    fn consensus_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, io::Error> {
+        let mut offset = 0;
+        offset += (self.block_height as i32).consensus_encode(&mut writer)?;
+        offset += self.block_hash.consensus_encode(&mut writer)?;
+        offset += self.signature.consensus_encode(&mut writer)?;
+        Ok(offset)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::chain::common::{ChainType, LLMQType};
+use crate::common::SignatureValidationStatus;
+use crate::crypto::UInt256;
+use crate::ffi::boxer::boxed;
+use crate::models::ChainLock;
+use crate::types;
+
+/// Outcome of the verification of the ChainLock ('clsig')
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainLockValidationReport {
+    pub llmq_type: LLMQType,
+    pub block_height: u32,
+    pub block_hash: UInt256,
+    pub request_id: UInt256,
+    /// Hash of the quorum which is responsible for signing the lock (if found)
+    pub quorum_hash: Option<UInt256>,
+    pub sign_hash: Option<UInt256>,
+    pub status: SignatureValidationStatus,
+}
+
+impl ChainLockValidationReport {
+    pub fn new(chain_lock: &ChainLock, chain_type: ChainType, status: SignatureValidationStatus) -> Self {
+        Self {
+            llmq_type: ChainLock::llmq_type(chain_type),
+            block_height: chain_lock.block_height,
+            block_hash: chain_lock.block_hash,
+            request_id: chain_lock.request_id(),
+            quorum_hash: None,
+            sign_hash: None,
+            status,
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.status.is_verified()
+    }
+
+    pub fn encode(&self) -> types::ChainLockValidationReport {
+        types::ChainLockValidationReport {
+            llmq_type: self.llmq_type,
+            block_height: self.block_height,
+            block_hash: boxed(self.block_hash.0),
+            request_id: boxed(self.request_id.0),
+            quorum_hash: self.quorum_hash.map_or(std::ptr::null_mut(), |hash| boxed(hash.0)),
+            sign_hash: self.sign_hash.map_or(std::ptr::null_mut(), |hash| boxed(hash.0)),
+            status: self.status,
+        }
+    }
+}
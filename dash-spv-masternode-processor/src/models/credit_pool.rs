@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use crate::tx::{AssetLockTransaction, AssetUnlockTransaction, CoinbaseTransaction};
+
+/// Change of the credit pool balance between two blocks
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CreditPoolBalanceChange {
+    pub base_block_height: u32,
+    pub block_height: u32,
+    pub base_balance: i64,
+    pub balance: i64,
+}
+
+impl CreditPoolBalanceChange {
+    pub fn diff(&self) -> i64 {
+        self.balance - self.base_balance
+    }
+}
+
+/// Credit pool balances reported by the coinbase transactions (since Core v20)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CreditPool {
+    pub balances: BTreeMap<u32, i64>,
+}
+
+impl CreditPool {
+    /// Remember the balance of the coinbase and return its change since the closest known block below
+    pub fn record_coinbase(&mut self, coinbase: &CoinbaseTransaction) -> Option<CreditPoolBalanceChange> {
+        let balance = coinbase.credit_pool_balance?;
+        self.balances.insert(coinbase.height, balance);
+        self.balances.range(..coinbase.height)
+            .next_back()
+            .map(|(&base_block_height, &base_balance)| CreditPoolBalanceChange {
+                base_block_height,
+                block_height: coinbase.height,
+                base_balance,
+                balance,
+            })
+    }
+
+    pub fn balance_at(&self, block_height: u32) -> Option<i64> {
+        self.balances.get(&block_height).copied()
+    }
+
+    pub fn balance_change(&self, base_block_height: u32, block_height: u32) -> Option<CreditPoolBalanceChange> {
+        Some(CreditPoolBalanceChange {
+            base_block_height,
+            block_height,
+            base_balance: self.balance_at(base_block_height)?,
+            balance: self.balance_at(block_height)?,
+        })
+    }
+
+    /// Balance expected in the coinbase of the block with these asset locks and unlocks,
+    /// `platform_reward` is the share of the block reward moved to the pool (zero before MN_RR)
+    pub fn expected_balance(base_balance: i64, asset_locks: &[AssetLockTransaction], asset_unlocks: &[AssetUnlockTransaction], platform_reward: u64) -> i64 {
+        let locked = asset_locks.iter().map(AssetLockTransaction::locked_amount).sum::<u64>();
+        let unlocked = asset_unlocks.iter().map(AssetUnlockTransaction::unlocked_amount).sum::<u64>();
+        base_balance + locked as i64 + platform_reward as i64 - unlocked as i64
+    }
+
+    pub fn clear(&mut self) {
+        self.balances.clear();
+    }
+}
@@ -0,0 +1,144 @@
+use std::io;
+use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType};
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::{byte_util::BytesDecodable, UInt256, UInt768};
+use crate::common::SignatureValidationStatus;
+use crate::models::{InstantLockValidationReport, LLMQEntry, MasternodeList};
+use crate::processing::{MessageError, MessageField, MessageSection, message_error::read_field};
+use crate::tx::TransactionOutPoint;
+
+pub const ISLOCK_REQUEST_ID_PREFIX: &str = "islock";
+
+/// InstantSend lock: legacy 'islock' or deterministic 'isdlock' (DIP-24) message
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstantLock {
+    pub version: u8,
+    pub inputs: Vec<TransactionOutPoint>,
+    pub transaction_hash: UInt256,
+    /// Hash of the block starting the cycle of the signing quorum (only for 'isdlock')
+    pub cycle_hash: Option<UInt256>,
+    pub signature: UInt768,
+}
+
+impl InstantLock {
+    /// Read 'islock' (is_deterministic = false) or 'isdlock' (is_deterministic = true) message
+    pub fn read(message: &[u8], offset: &mut usize, is_deterministic: bool) -> Result<Self, MessageError> {
+        let section = MessageSection::InstantLock;
+        let version = if is_deterministic {
+            read_field(offset, section, MessageField::Version, None, |offset| u8::from_bytes(message, offset))?
+        } else {
+            0
+        };
+        let inputs = read_field(offset, section, MessageField::Inputs, None, |offset| {
+            let count = VarInt::from_bytes(message, offset)?.0;
+            (0..count)
+                .map(|_| TransactionOutPoint::from_bytes(message, offset))
+                .collect::<Option<Vec<TransactionOutPoint>>>()
+        })?;
+        let transaction_hash = read_field(offset, section, MessageField::TransactionHash, None, |offset| UInt256::from_bytes(message, offset))?;
+        let cycle_hash = if is_deterministic {
+            Some(read_field(offset, section, MessageField::CycleHash, None, |offset| UInt256::from_bytes(message, offset))?)
+        } else {
+            None
+        };
+        let signature = read_field(offset, section, MessageField::Signature, None, |offset| UInt768::from_bytes(message, offset))?;
+        Ok(Self { version, inputs, transaction_hash, cycle_hash, signature })
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.cycle_hash.is_some()
+    }
+
+    /// Type of the quorum which signs this kind of lock on the chain
+    pub fn llmq_type(&self, chain_type: ChainType) -> LLMQType {
+        if self.is_deterministic() {
+            chain_type.isd_llmq_type()
+        } else {
+            chain_type.is_llmq_type()
+        }
+    }
+
+    /// Height of the block which masternode list contains the quorums signing 'isdlock':
+    /// the last block of the cycle started at `cycle_height` (as for the finished cycles in Core),
+    /// `None` for legacy locks or if `cycle_height` isn't the start of the cycle
+    pub fn signing_block_height(&self, cycle_height: u32, chain_type: ChainType) -> Option<u32> {
+        let interval = self.llmq_type(chain_type).params().dkg_params.interval;
+        (self.is_deterministic() && cycle_height.is_multiple_of(interval))
+            .then_some(cycle_height + interval - 1)
+    }
+
+    pub fn request_id(&self) -> UInt256 {
+        let mut buffer: Vec<u8> = Vec::new();
+        ISLOCK_REQUEST_ID_PREFIX.to_string().enc(&mut buffer);
+        VarInt(self.inputs.len() as u64).enc(&mut buffer);
+        self.inputs.iter().for_each(|input| { input.enc(&mut buffer); });
+        UInt256::sha256d(buffer)
+    }
+
+    pub fn sign_hash(&self, quorum: &LLMQEntry) -> UInt256 {
+        quorum.sign_hash_for_request_id(self.request_id(), self.transaction_hash)
+    }
+
+    /// Select the quorum responsible for the lock from the list of active quorums
+    pub fn quorum_entry<'a>(&self, masternode_list: &'a MasternodeList, chain_type: ChainType) -> Option<&'a LLMQEntry> {
+        let llmq_type = self.llmq_type(chain_type);
+        let request_id = self.request_id();
+        if self.is_deterministic() {
+            masternode_list.quorum_entry_for_rotated_request_id(request_id, llmq_type)
+        } else {
+            masternode_list.quorum_entry_for_lock_request_id(request_id, llmq_type)
+        }
+    }
+
+    /// Verify the threshold signature of the lock against the quorums of the masternode list
+    pub fn verify_with_masternode_list(&self, masternode_list: &MasternodeList, chain_type: ChainType) -> InstantLockValidationReport {
+        let llmq_type = self.llmq_type(chain_type);
+        let request_id = self.request_id();
+        match self.quorum_entry(masternode_list, chain_type) {
+            Some(quorum) => {
+                let sign_hash = self.sign_hash(quorum);
+                let status = if quorum.verify_recovered_signature(sign_hash, self.signature) {
+                    SignatureValidationStatus::Verified
+                } else {
+                    SignatureValidationStatus::InvalidSignature
+                };
+                InstantLockValidationReport {
+                    llmq_type,
+                    request_id,
+                    quorum_hash: Some(quorum.llmq_hash),
+                    quorum_index: quorum.index,
+                    sign_hash: Some(sign_hash),
+                    status,
+                }
+            },
+            None => InstantLockValidationReport {
+                llmq_type,
+                request_id,
+                quorum_hash: None,
+                quorum_index: None,
+                sign_hash: None,
+                status: SignatureValidationStatus::NoQuorum,
+            }
+        }
+    }
+}
+
+impl Encodable for InstantLock {
+    #[inline]
+    fn consensus_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, io::Error> {
+        let mut offset = 0;
+        if self.is_deterministic() {
+            offset += self.version.consensus_encode(&mut writer)?;
+        }
+        offset += VarInt(self.inputs.len() as u64).consensus_encode(&mut writer)?;
+        for input in &self.inputs {
+            offset += input.consensus_encode(&mut writer)?;
+        }
+        offset += self.transaction_hash.consensus_encode(&mut writer)?;
+        if let Some(cycle_hash) = self.cycle_hash {
+            offset += cycle_hash.consensus_encode(&mut writer)?;
+        }
+        offset += self.signature.consensus_encode(&mut writer)?;
+        Ok(offset)
+    }
+}
@@ -0,0 +1,34 @@
+use crate::chain::common::LLMQType;
+use crate::common::SignatureValidationStatus;
+use crate::crypto::UInt256;
+use crate::ffi::boxer::boxed;
+use crate::types;
+
+/// Outcome of the verification of the InstantSend lock ('islock' or 'isdlock')
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstantLockValidationReport {
+    pub llmq_type: LLMQType,
+    pub request_id: UInt256,
+    /// Hash of the quorum which is responsible for signing the lock (if found)
+    pub quorum_hash: Option<UInt256>,
+    pub quorum_index: Option<u16>,
+    pub sign_hash: Option<UInt256>,
+    pub status: SignatureValidationStatus,
+}
+
+impl InstantLockValidationReport {
+    pub fn is_verified(&self) -> bool {
+        self.status.is_verified()
+    }
+
+    pub fn encode(&self) -> types::InstantLockValidationReport {
+        types::InstantLockValidationReport {
+            llmq_type: self.llmq_type,
+            request_id: boxed(self.request_id.0),
+            quorum_hash: self.quorum_hash.map_or(std::ptr::null_mut(), |hash| boxed(hash.0)),
+            quorum_index: self.quorum_index.unwrap_or(0),
+            sign_hash: self.sign_hash.map_or(std::ptr::null_mut(), |hash| boxed(hash.0)),
+            status: self.status,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use byte::{BytesExt, ctx::{Bytes, Endian}, TryRead, LE};
 use hashes::hex::ToHex;
 use std::convert::Into;
+use std::io;
 #[cfg(feature = "generate-dashj-tests")]
 use serde::ser::SerializeStruct;
 #[cfg(feature = "generate-dashj-tests")]
@@ -11,6 +12,7 @@ use crate::consensus::{encode::VarInt, Encodable, WriteExt};
 use crate::crypto::{byte_util::AsBytes, data_ops::Data, UInt256, UInt384, UInt768};
 use crate::keys::BLSKey;
 use crate::models;
+use crate::common::target;
 use crate::processing::llmq_validation_status::{LLMQValidationStatus, LLMQPayloadValidationStatus};
 
 #[derive(PartialEq)]
@@ -170,6 +172,14 @@ impl<'a> TryRead<'a, Endian> for LLMQEntry {
     }
 }
 
+impl Encodable for LLMQEntry {
+    fn consensus_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, io::Error> {
+        let data = self.to_data();
+        writer.write_all(&data)?;
+        Ok(data.len())
+    }
+}
+
 impl LLMQEntry {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -304,6 +314,26 @@ impl LLMQEntry {
         UInt256::sha256d(buffer)
     }
 
+    /// Approximate number of bytes held by the entry
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() + self.signers_bitset.len() + self.valid_members_bitset.len()
+    }
+
+    /// Hash which is signed by the quorum for the request (as CLLMQUtils::BuildSignHash in Core)
+    pub fn sign_hash_for_request_id(&self, request_id: UInt256, message_hash: UInt256) -> UInt256 {
+        let mut buffer: Vec<u8> = Vec::with_capacity(97);
+        u8::from(self.llmq_type).enc(&mut buffer);
+        self.llmq_hash.enc(&mut buffer);
+        request_id.enc(&mut buffer);
+        message_hash.enc(&mut buffer);
+        UInt256::sha256d(buffer)
+    }
+
+    /// Verifies recovered threshold signature of the quorum
+    pub fn verify_recovered_signature(&self, sign_hash: UInt256, signature: UInt768) -> bool {
+        BLSKey::verify_with_public_key(sign_hash, signature, self.public_key, self.version.use_bls_legacy())
+    }
+
     pub fn generate_commitment_hash(&mut self) -> UInt256 {
         if self.commitment_hash.is_none() {
             self.commitment_hash = Some(UInt256::sha256d(self.commitment_data()));
@@ -363,7 +393,7 @@ impl LLMQEntry {
             self.valid_members_bitset.as_slice().true_bits_count();
         if valid_members_bitset_true_bits_count < quorum_threshold {
             warn!("Error: The number of set bits in the valid members bitvector {} must be >= quorumThreshold {}", valid_members_bitset_true_bits_count, quorum_threshold);
-            return LLMQPayloadValidationStatus::SignersBelowThreshold { actual: valid_members_bitset_true_bits_count, threshold: quorum_threshold };
+            return LLMQPayloadValidationStatus::MembersBelowThreshold { actual: valid_members_bitset_true_bits_count, threshold: quorum_threshold };
         }
         LLMQPayloadValidationStatus::Ok
     }
@@ -397,17 +427,17 @@ impl LLMQEntry {
             operator_keys,
             use_legacy);
         if !all_commitment_aggregated_signature_validated {
-            println!("••• INVALID AGGREGATED SIGNATURE {}: {:?} ({})", block_height, self.llmq_type, self.all_commitment_aggregated_signature);
+            warn!(target: target::QUORUM, "invalid aggregated signature llmq_type={:?} llmq_hash={} block_height={} signature={}", self.llmq_type, self.llmq_hash, block_height, self.all_commitment_aggregated_signature);
             return LLMQValidationStatus::InvalidAggregatedSignature;
         }
         // The sig must validate against the commitmentHash and all public keys determined by the signers bitvector.
         // This is an aggregated BLS signature verification.
         let quorum_signature_validated = BLSKey::verify_quorum_signature(commitment_hash.as_bytes(), self.threshold_signature.as_bytes(), self.public_key.as_bytes(), use_legacy);
         if !quorum_signature_validated {
-            println!("••• INVALID QUORUM SIGNATURE {}: {:?} ({})", block_height, self.llmq_type, self.threshold_signature);
+            warn!(target: target::QUORUM, "invalid quorum signature llmq_type={:?} llmq_hash={} block_height={} signature={}", self.llmq_type, self.llmq_hash, block_height, self.threshold_signature);
             return LLMQValidationStatus::InvalidQuorumSignature;
         }
-        println!("••• quorum {:?} validated at {}", self.llmq_type, block_height);
+        debug!(target: target::QUORUM, "quorum validated llmq_type={:?} llmq_hash={} block_height={}", self.llmq_type, self.llmq_hash, block_height);
         LLMQValidationStatus::Verified
     }
 }
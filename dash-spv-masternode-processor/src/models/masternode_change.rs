@@ -0,0 +1,33 @@
+use crate::common::{MasternodeType, SocketAddress};
+use crate::crypto::{UInt160, UInt256};
+use crate::models::OperatorPublicKey;
+
+/// What has changed in the masternode state
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MasternodeChangeKind {
+    Registered,
+    Removed,
+    OperatorKeyChanged { from: OperatorPublicKey, to: OperatorPublicKey },
+    VotingKeyChanged { from: UInt160, to: UInt160 },
+    /// Proof-of-Service ban
+    Banned,
+    /// Revival after the PoSe ban (ProUpServTx)
+    Revived,
+    ServiceChanged { from: SocketAddress, to: SocketAddress },
+    TypeChanged { from: MasternodeType, to: MasternodeType },
+}
+
+/// Single change of the masternode state in the timeline.
+/// Height is known with the precision of the masternode lists (diffs) it was observed in
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MasternodeChange {
+    pub block_height: u32,
+    pub provider_registration_transaction_hash: UInt256,
+    pub kind: MasternodeChangeKind,
+}
+
+impl MasternodeChange {
+    pub fn new(block_height: u32, provider_registration_transaction_hash: UInt256, kind: MasternodeChangeKind) -> Self {
+        Self { block_height, provider_registration_transaction_hash, kind }
+    }
+}
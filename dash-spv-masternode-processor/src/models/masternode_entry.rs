@@ -8,7 +8,7 @@ use crate::chain::constants::CORE_PROTO_19_2;
 use crate::common::{Block, MasternodeType, SocketAddress};
 use crate::consensus::Encodable;
 use crate::crypto::{UInt160, UInt256, byte_util::Zeroable};
-use crate::models::OperatorPublicKey;
+use crate::models::{MasternodeChange, MasternodeChangeKind, OperatorPublicKey};
 use crate::util::data_ops::short_hex_string_from;
 
 // (block height, list diff version (2: BLSBasic), protocol_version)
@@ -223,6 +223,44 @@ impl MasternodeEntry {
         UInt256::sha256d(writer)
     }
 
+    /// Serialized as the simplified masternode list entry of the diff
+    pub fn to_data(&self, protocol_version: u32) -> Vec<u8> {
+        let mut writer = Vec::<u8>::new();
+        let version = if protocol_version >= CORE_PROTO_19_2 {
+            self.operator_public_key.version.enc(&mut writer);
+            self.operator_public_key.version
+        } else {
+            1 // legacy
+        };
+        self.provider_registration_transaction_hash.enc(&mut writer);
+        self.confirmed_hash.enc(&mut writer);
+        self.socket_address.enc(&mut writer);
+        self.operator_public_key.enc(&mut writer);
+        self.key_id_voting.enc(&mut writer);
+        u8::from(self.is_valid).enc(&mut writer);
+        if version >= 2 {
+            u16::from(self.mn_type).enc(&mut writer);
+            if self.mn_type == MasternodeType::HighPerformance {
+                self.platform_http_port.swap_bytes().enc(&mut writer);
+                self.platform_node_id.enc(&mut writer);
+            }
+        }
+        writer
+    }
+
+    /// Whether both entries are serialized into the same simplified masternode list entry
+    pub fn is_same_simplified_entry(&self, other: &MasternodeEntry) -> bool {
+        self.provider_registration_transaction_hash == other.provider_registration_transaction_hash &&
+            self.confirmed_hash == other.confirmed_hash &&
+            self.socket_address == other.socket_address &&
+            self.operator_public_key == other.operator_public_key &&
+            self.key_id_voting == other.key_id_voting &&
+            self.is_valid == other.is_valid &&
+            self.mn_type == other.mn_type &&
+            self.platform_http_port == other.platform_http_port &&
+            self.platform_node_id == other.platform_node_id
+    }
+
     pub fn confirmed_hash_at(&self, block_height: u32) -> Option<UInt256> {
         self.known_confirmed_at_height
             .and_then(|h| (h <= block_height)
@@ -336,11 +374,83 @@ impl MasternodeEntry {
         used_hash
     }
 
+    /// State of the masternode as of the block height reconstructed from the tracked history
+    /// (validity, operator key, entry hash and confirmation)
+    pub fn state_at(&self, block_height: u32) -> MasternodeEntry {
+        let mut entry = self.clone();
+        entry.is_valid = self.is_valid_at(block_height);
+        entry.operator_public_key = self.operator_public_key_at(block_height);
+        entry.entry_hash = self.entry_hash_at(block_height);
+        if self.confirmed_hash_at(block_height).is_none() && self.known_confirmed_at_height.is_some() {
+            entry.confirmed_hash = UInt256::MIN;
+            entry.update_confirmed_hash_hashed_with_pro_reg_tx_hash();
+        }
+        entry.previous_validity.retain(|block, _| block.height <= block_height);
+        entry.previous_operator_public_keys.retain(|block, _| block.height <= block_height);
+        entry.previous_entry_hashes.retain(|block, _| block.height <= block_height);
+        entry
+    }
+
+    /// Changes of the masternode between the state of the base entry (at base_block_height)
+    /// and this one (at block_height). Operator key rotations and PoSe bans/revivals are placed
+    /// at the heights recorded in the history, others at block_height
+    pub fn changes_since(&self, base: &MasternodeEntry, base_block_height: u32, block_height: u32) -> Vec<MasternodeChange> {
+        let pro_reg_tx_hash = self.provider_registration_transaction_hash;
+        let mut changes = Vec::new();
+        let in_range = |height: u32| height > base_block_height && height <= block_height;
+        let mut is_valid = base.is_valid_at(base_block_height);
+        let mut validity_heights = self.previous_validity.keys()
+            .map(|block| block.height)
+            .filter(|height| in_range(*height))
+            .collect::<Vec<_>>();
+        validity_heights.push(block_height);
+        validity_heights.into_iter().for_each(|height| {
+            let is_valid_at = self.is_valid_at(height);
+            if is_valid_at != is_valid {
+                let kind = if is_valid_at { MasternodeChangeKind::Revived } else { MasternodeChangeKind::Banned };
+                changes.push(MasternodeChange::new(height, pro_reg_tx_hash, kind));
+                is_valid = is_valid_at;
+            }
+        });
+        let mut operator_public_key = base.operator_public_key_at(base_block_height);
+        let mut key_heights = self.previous_operator_public_keys.keys()
+            .map(|block| block.height)
+            .filter(|height| in_range(*height))
+            .collect::<Vec<_>>();
+        key_heights.push(block_height);
+        key_heights.into_iter().for_each(|height| {
+            let key = self.operator_public_key_at(height);
+            if key != operator_public_key {
+                changes.push(MasternodeChange::new(height, pro_reg_tx_hash, MasternodeChangeKind::OperatorKeyChanged { from: operator_public_key, to: key }));
+                operator_public_key = key;
+            }
+        });
+        if base.key_id_voting != self.key_id_voting {
+            changes.push(MasternodeChange::new(block_height, pro_reg_tx_hash, MasternodeChangeKind::VotingKeyChanged { from: base.key_id_voting, to: self.key_id_voting }));
+        }
+        if base.socket_address != self.socket_address {
+            changes.push(MasternodeChange::new(block_height, pro_reg_tx_hash, MasternodeChangeKind::ServiceChanged { from: base.socket_address, to: self.socket_address }));
+        }
+        if base.mn_type != self.mn_type {
+            changes.push(MasternodeChange::new(block_height, pro_reg_tx_hash, MasternodeChangeKind::TypeChanged { from: base.mn_type, to: self.mn_type }));
+        }
+        changes.sort_by_key(|change| change.block_height);
+        changes
+    }
+
+    /// Approximate number of bytes held by the entry including its history
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() +
+            self.previous_operator_public_keys.len() * std::mem::size_of::<(Block, OperatorPublicKey)>() +
+            self.previous_entry_hashes.len() * std::mem::size_of::<(Block, UInt256)>() +
+            self.previous_validity.len() * std::mem::size_of::<(Block, bool)>()
+    }
+
     pub fn unique_id(&self) -> String {
         short_hex_string_from(&self.provider_registration_transaction_hash.0)
     }
 
-    pub fn update_with_previous_entry(&mut self, entry: &mut MasternodeEntry, block_height: u32, block_hash: UInt256) {
+    pub fn update_with_previous_entry(&mut self, entry: &MasternodeEntry, block_height: u32, block_hash: UInt256) {
         let block = Block::new(block_height, block_hash);
         self.previous_validity = entry
             .previous_validity
@@ -1,10 +1,11 @@
 use std::cmp::min;
 use std::collections::BTreeMap;
+use std::sync::Arc;
 use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType};
-use crate::common::MasternodeType;
+use crate::common::{MasternodeType, SignatureValidationStatus};
 use crate::consensus::Encodable;
-use crate::crypto::{byte_util::{Reversable, Zeroable}, UInt256};
-use crate::models::{LLMQEntry, LLMQModifierType, MasternodeEntry};
+use crate::crypto::{byte_util::{Reversable, Zeroable}, UInt256, UInt768};
+use crate::models::{LLMQEntry, LLMQModifierType, MasternodeChange, MasternodeChangeKind, MasternodeEntry};
 use crate::tx::CoinbaseTransaction;
 use crate::util::data_ops::merkle_root_from_hashes;
 
@@ -14,7 +15,8 @@ pub struct MasternodeList {
     pub known_height: u32,
     pub masternode_merkle_root: Option<UInt256>,
     pub llmq_merkle_root: Option<UInt256>,
-    pub masternodes: BTreeMap<UInt256, MasternodeEntry>,
+    /// Entries are shared with the lists they weren't changed in
+    pub masternodes: BTreeMap<UInt256, Arc<MasternodeEntry>>,
     pub quorums: BTreeMap<LLMQType, BTreeMap<UInt256, LLMQEntry>>,
 }
 
@@ -46,7 +48,7 @@ impl<'a> std::fmt::Debug for MasternodeList {
 
 impl MasternodeList {
     pub fn new(
-        masternodes: BTreeMap<UInt256, MasternodeEntry>,
+        masternodes: BTreeMap<UInt256, Arc<MasternodeEntry>>,
         quorums: BTreeMap<LLMQType, BTreeMap<UInt256, LLMQEntry>>,
         block_hash: UInt256,
         block_height: u32,
@@ -101,7 +103,7 @@ impl MasternodeList {
     }
 
     pub fn masternode_for(&self, registration_hash: UInt256) -> Option<&MasternodeEntry> {
-        self.masternodes.get(&registration_hash)
+        self.masternodes.get(&registration_hash).map(Arc::as_ref)
     }
 
     pub fn has_valid_mn_list_root(&self, tx: &CoinbaseTransaction) -> bool {
@@ -161,6 +163,24 @@ impl MasternodeList {
             .find(|&entry| entry.llmq_hash == quorum_hash)
     }
 
+    /// Verifies threshold signature of the Platform quorum over the message (state transition result,
+    /// GroveDB proof root, block commit) with the scheme of the quorum version
+    pub fn verify_platform_signature(
+        &self,
+        chain_type: ChainType,
+        quorum_hash: UInt256,
+        request_id: UInt256,
+        message_hash: UInt256,
+        signature: UInt768,
+    ) -> SignatureValidationStatus {
+        match self.quorum_entry_for_platform_with_quorum_hash(quorum_hash, chain_type.platform_type()) {
+            Some(quorum) if quorum.verify_recovered_signature(quorum.sign_hash_for_request_id(request_id, message_hash), signature) =>
+                SignatureValidationStatus::Verified,
+            Some(_) => SignatureValidationStatus::InvalidSignature,
+            None => SignatureValidationStatus::NoQuorum,
+        }
+    }
+
     pub fn quorum_entry_for_lock_request_id(
         &self,
         request_id: UInt256,
@@ -179,6 +199,63 @@ impl MasternodeList {
         });
         first_quorum
     }
+    /// DIP-24: rotated quorum is selected by the index taken from the last bits of the request id
+    pub fn quorum_entry_for_rotated_request_id(
+        &self,
+        request_id: UInt256,
+        llmq_type: LLMQType,
+    ) -> Option<&LLMQEntry> {
+        let n = llmq_type.active_quorum_count().checked_ilog2()?;
+        let b = u64::from_le_bytes(request_id.0[24..].try_into().unwrap());
+        let signer = ((1u64 << n) - 1) & (b >> (64 - n - 1));
+        self.quorums
+            .get(&llmq_type)?
+            .values()
+            .find(|entry| entry.index.is_some_and(|index| u64::from(index) == signer))
+    }
+
+    /// State of the masternode from this list as of the block height (see `MasternodeEntry::state_at`)
+    pub fn masternode_state_at(&self, provider_registration_transaction_hash: UInt256, block_height: u32) -> Option<MasternodeEntry> {
+        self.masternodes
+            .get(&provider_registration_transaction_hash.reversed())
+            .map(|entry| entry.state_at(block_height))
+    }
+
+    /// Every change of the masternodes between the base list and this one,
+    /// ordered by height (registrations and removals are placed at the height of this list)
+    pub fn masternode_changes_since(&self, base: &MasternodeList) -> Vec<MasternodeChange> {
+        let mut changes = Vec::new();
+        base.masternodes.iter().for_each(|(hash, base_entry)| match self.masternodes.get(hash) {
+            Some(entry) =>
+                changes.extend(entry.changes_since(base_entry, base.known_height, self.known_height)),
+            None =>
+                changes.push(MasternodeChange::new(self.known_height, base_entry.provider_registration_transaction_hash, MasternodeChangeKind::Removed)),
+        });
+        self.masternodes.iter()
+            .filter(|(hash, _)| !base.masternodes.contains_key(hash))
+            .for_each(|(_, entry)| changes.push(MasternodeChange::new(self.known_height, entry.provider_registration_transaction_hash, MasternodeChangeKind::Registered)));
+        changes.sort_by_key(|change| change.block_height);
+        changes
+    }
+
+    /// Approximate number of bytes held by the list: its entries and quorums
+    pub fn memory_usage(&self) -> usize {
+        self.own_memory_usage() +
+            self.masternodes.values()
+                .map(|entry| entry.memory_usage())
+                .sum::<usize>()
+    }
+
+    /// Approximate number of bytes held by the list without the (shared) entries themselves
+    pub fn own_memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() +
+            self.masternodes.len() * (std::mem::size_of::<UInt256>() + std::mem::size_of::<Arc<MasternodeEntry>>()) +
+            self.quorums.values()
+                .flat_map(|map| map.values())
+                .map(|quorum| std::mem::size_of::<UInt256>() + quorum.memory_usage())
+                .sum::<usize>()
+    }
+
     pub fn reversed_pro_reg_tx_hashes(&self) -> Vec<&UInt256> {
         self.masternodes.keys().collect::<Vec<&UInt256>>()
     }
@@ -202,17 +279,17 @@ impl MasternodeList {
 
 impl MasternodeList {
     pub fn score_masternodes_map(
-        masternodes: BTreeMap<UInt256, MasternodeEntry>,
+        masternodes: &BTreeMap<UInt256, Arc<MasternodeEntry>>,
         quorum_modifier: UInt256,
         block_height: u32,
         hpmn_only: bool,
-    ) -> BTreeMap<UInt256, MasternodeEntry> {
+    ) -> BTreeMap<UInt256, Arc<MasternodeEntry>> {
         masternodes
-            .into_iter()
-            .filter_map(|(_, entry)|
+            .values()
+            .filter_map(|entry|
                 if !hpmn_only || entry.mn_type == MasternodeType::HighPerformance {
-                    Self::masternode_score(&entry, quorum_modifier, block_height)
-                        .map(|score| (score, entry))
+                    Self::masternode_score(entry, quorum_modifier, block_height)
+                        .map(|score| (score, Arc::clone(entry)))
                 } else {
                     None
                 }
@@ -220,21 +297,21 @@ impl MasternodeList {
             .collect()
     }
 
-    pub fn get_masternodes_for_quorum(quorum: &LLMQEntry, chain_type: ChainType, masternodes: BTreeMap<UInt256, MasternodeEntry>, block_height: u32, llmq_modifier: LLMQModifierType) -> Vec<MasternodeEntry> {
+    pub fn get_masternodes_for_quorum(quorum: &LLMQEntry, chain_type: ChainType, masternodes: &BTreeMap<UInt256, Arc<MasternodeEntry>>, block_height: u32, llmq_modifier: LLMQModifierType) -> Vec<MasternodeEntry> {
         let llmq_type = quorum.llmq_type;
         let hpmn_only = llmq_type == chain_type.platform_type() && !quorum.version.use_bls_legacy();
         let quorum_modifier = llmq_modifier.build_llmq_hash();
         let quorum_count = llmq_type.size();
         let masternodes_in_list_count = masternodes.len();
-        let mut score_dictionary = Self::score_masternodes_map(masternodes, quorum_modifier, block_height, hpmn_only);
-        let mut scores: Vec<UInt256> = score_dictionary.clone().into_keys().collect();
+        let score_dictionary = Self::score_masternodes_map(masternodes, quorum_modifier, block_height, hpmn_only);
+        let mut scores: Vec<UInt256> = score_dictionary.keys().copied().collect();
         scores.sort_by(|&s1, &s2| s2.reversed().cmp(&s1.reversed()));
         let mut valid_masternodes: Vec<MasternodeEntry> = Vec::new();
         let count = min(masternodes_in_list_count, scores.len());
         for score in scores.iter().take(count) {
-            if let Some(masternode) = score_dictionary.get_mut(score) {
-                if (*masternode).is_valid_at(block_height) {
-                    valid_masternodes.push((*masternode).clone());
+            if let Some(masternode) = score_dictionary.get(score) {
+                if masternode.is_valid_at(block_height) {
+                    valid_masternodes.push(masternode.as_ref().clone());
                 }
             }
             if valid_masternodes.len() == quorum_count as usize {
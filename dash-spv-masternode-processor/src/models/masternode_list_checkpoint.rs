@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use byte::{BytesExt, LE};
+use hashes::hex::FromHex;
+use crate::chain::common::ChainType;
+use crate::chain::constants::CORE_PROTO_19_2;
+use crate::common::{MasternodeType, SocketAddress};
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::{byte_util::Reversable, UInt128, UInt160, UInt256, UInt384, VarBytes};
+use crate::models::{LLMQSnapshot, MasternodeEntry, MNListDiff, OperatorPublicKey};
+
+const CHECKPOINT_DATA_MAGIC: [u8; 4] = *b"DMCP";
+/// Version 1: 'MNLISTDIFF' as is
+const CHECKPOINT_DATA_VERSION_RAW: u16 = 1;
+/// Current version of the checkpoint data format:
+/// 'MNLISTDIFF' without masternodes followed by the compact masternode entries
+pub const CHECKPOINT_DATA_VERSION: u16 = 2;
+
+const ENTRY_IS_VALID: u8 = 0x01;
+const ENTRY_IPV4: u8 = 0x02;
+const ENTRY_SAME_PORT: u8 = 0x04;
+const ENTRY_KNOWN_CONFIRMED_HASH: u8 = 0x08;
+const ENTRY_KNOWN_VOTING_KEY: u8 = 0x10;
+const ENTRY_BASIC_BLS: u8 = 0x20;
+const ENTRY_HIGH_PERFORMANCE: u8 = 0x40;
+const IPV4_MAPPED_PREFIX: [u8; 12] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff];
+
+/// Block with the known masternode list state: the list built from the checkpoint data
+/// must match merkle roots committed in the coinbase of this block
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MasternodeListCheckpoint {
+    pub block_height: u32,
+    pub block_hash: UInt256,
+    /// 'merkleRootMNList' of the coinbase transaction
+    pub masternode_merkle_root: UInt256,
+    /// 'merkleRootQuorums' of the coinbase transaction
+    pub llmq_merkle_root: Option<UInt256>,
+}
+
+fn hash_from_hex(hex: &str) -> UInt256 {
+    UInt256::from_hex(hex).unwrap().reverse()
+}
+
+impl MasternodeListCheckpoint {
+    pub fn new(block_height: u32, block_hash: UInt256, masternode_merkle_root: UInt256, llmq_merkle_root: Option<UInt256>) -> Self {
+        Self { block_height, block_hash, masternode_merkle_root, llmq_merkle_root }
+    }
+
+    /// Checkpoints known for the chain, ordered by height
+    pub fn checkpoints_for(chain_type: ChainType) -> Vec<Self> {
+        match chain_type {
+            ChainType::MainNet => vec![
+                Self::new(
+                    1720000,
+                    hash_from_hex("000000000000001ef1f8a3d33bbe304c1d12f59f2c8aa989099dc215fd10903e"),
+                    hash_from_hex("9c3bd13c029856ea3bc97fad08ba1f34cfb259ab3c142c848ad81a48c0ad6e40"),
+                    Some(hash_from_hex("fc0255dbf1a766335ab9dbc039bed424baa71ae544d56852c760e39e5f2033e2"))),
+                Self::new(
+                    1738648,
+                    hash_from_hex("000000000000000e1c7aa8d3a097c38e53cbed7fb5b117a4fe2ab356913a5b5e"),
+                    hash_from_hex("a8e6b0739db764d4953a61d7f6a0ac7adf9bce13458df0765367d705c90e1b9d"),
+                    Some(hash_from_hex("f68e0e6a80e761cb824af6edb7cfce421871099a6dd3e292029cde030d70bfb0"))),
+            ],
+            ChainType::TestNet => vec![
+                Self::new(
+                    530000,
+                    hash_from_hex("0000060db4b6bdb17f0617d15637bdf0f18ad738ccb438ee2cd000fef11c7130"),
+                    hash_from_hex("a2c7e33780082cd500f3199ea96a13c7d9771ac90d7bbf2a610f041621bc633c"),
+                    Some(hash_from_hex("b335cfc9dfc71af78aa11fa483cfc420e45f07038a9c76697d233157d8f92cfb"))),
+                Self::new(
+                    905176,
+                    hash_from_hex("00000034c5769fc33540641d2a9668b80a8ead30e3be5def7effbb9e37269d7f"),
+                    hash_from_hex("60b577bee763b038d0a7512eecf1fb9e6b8492809b9d491de70105a13d764aee"),
+                    Some(hash_from_hex("cedbb225730bccf880c34fb19a9413bc0a1f02e041a6b80b33a2e0f03cb184b2"))),
+            ],
+            ChainType::DevNet(..) => vec![],
+        }
+    }
+
+    /// The most recent checkpoint at or below the height
+    pub fn last_checkpoint_for(chain_type: ChainType, block_height: u32) -> Option<Self> {
+        Self::checkpoints_for(chain_type)
+            .into_iter()
+            .rev()
+            .find(|checkpoint| checkpoint.block_height <= block_height)
+    }
+
+    pub fn checkpoint_for_block_hash(chain_type: ChainType, block_hash: UInt256) -> Option<Self> {
+        Self::checkpoints_for(chain_type)
+            .into_iter()
+            .find(|checkpoint| checkpoint.block_hash == block_hash)
+    }
+
+    /// Checkpoint data embedded into the library (with 'embedded-checkpoints' feature),
+    /// only the most recent checkpoint of the chain is embedded
+    #[cfg(feature = "embedded-checkpoints")]
+    pub fn embedded_data(&self, chain_type: ChainType) -> Option<MasternodeListCheckpointData> {
+        let data: &'static [u8] = match (chain_type, self.block_height) {
+            (ChainType::MainNet, 1738648) => include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/files/checkpoints/mainnet_1738648.dat")),
+            (ChainType::TestNet, 905176) => include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/files/checkpoints/testnet_905176.dat")),
+            _ => return None,
+        };
+        MasternodeListCheckpointData::from_data(data).ok()
+    }
+}
+
+/// Masternode list at the checkpoint in the form of 'MNLISTDIFF' from the genesis
+/// along with quorum snapshots needed to rebuild members of the rotated quorums after the checkpoint
+#[derive(Clone, Debug, Default)]
+pub struct MasternodeListCheckpointData {
+    pub protocol_version: u32,
+    pub list_diff: Vec<u8>,
+    pub snapshots: BTreeMap<UInt256, LLMQSnapshot>,
+}
+
+impl MasternodeListCheckpointData {
+    pub fn new(list_diff: Vec<u8>, protocol_version: u32) -> Self {
+        Self { protocol_version, list_diff, snapshots: BTreeMap::new() }
+    }
+
+    pub fn with_snapshot(mut self, block_hash: UInt256, snapshot: LLMQSnapshot) -> Self {
+        self.snapshots.insert(block_hash, snapshot);
+        self
+    }
+
+    /// Serialized as the versioned checkpoint file
+    pub fn to_data(&self) -> byte::Result<Vec<u8>> {
+        let mut list_diff = MNListDiff::read(&self.list_diff, &mut 0, |_| u32::MAX, self.protocol_version)
+            .map_err(|_| byte::Error::BadInput { err: "Malformed list diff" })?;
+        let masternodes = std::mem::take(&mut list_diff.added_or_modified_masternodes);
+        let mut writer = Vec::<u8>::new();
+        writer.extend_from_slice(&CHECKPOINT_DATA_MAGIC);
+        CHECKPOINT_DATA_VERSION.enc(&mut writer);
+        self.protocol_version.enc(&mut writer);
+        list_diff.to_data(self.protocol_version).enc(&mut writer);
+        VarInt(masternodes.len() as u64).enc(&mut writer);
+        let mut entry_writer = CompactEntries::default();
+        for entry in masternodes.values() {
+            entry_writer.write(entry, self.protocol_version, &mut writer)?;
+        }
+        VarInt(self.snapshots.len() as u64).enc(&mut writer);
+        self.snapshots.iter().for_each(|(block_hash, snapshot)| {
+            block_hash.enc(&mut writer);
+            snapshot.enc(&mut writer);
+        });
+        Ok(writer)
+    }
+
+    /// Read the checkpoint file made by `to_data`
+    pub fn from_data(bytes: &[u8]) -> byte::Result<Self> {
+        let offset = &mut 0;
+        let magic: &[u8] = bytes.read_with(offset, byte::ctx::Bytes::Len(CHECKPOINT_DATA_MAGIC.len()))?;
+        if magic != CHECKPOINT_DATA_MAGIC {
+            return Err(byte::Error::BadInput { err: "Not a masternode list checkpoint" });
+        }
+        let version = bytes.read_with::<u16>(offset, LE)?;
+        let protocol_version = bytes.read_with::<u32>(offset, LE)?;
+        let list_diff = match version {
+            CHECKPOINT_DATA_VERSION_RAW => bytes.read_with::<VarBytes>(offset, LE)?.1.to_vec(),
+            CHECKPOINT_DATA_VERSION => {
+                let stripped = bytes.read_with::<VarBytes>(offset, LE)?.1;
+                let mut list_diff = MNListDiff::read(stripped, &mut 0, |_| u32::MAX, protocol_version)
+                    .map_err(|_| byte::Error::BadInput { err: "Malformed list diff" })?;
+                let count = bytes.read_with::<VarInt>(offset, LE)?.0;
+                let mut entry_reader = CompactEntries::default();
+                for _i in 0..count {
+                    let entry = entry_reader.read(bytes, offset, protocol_version)?;
+                    list_diff.added_or_modified_masternodes.insert(entry.provider_registration_transaction_hash.reversed(), entry);
+                }
+                list_diff.to_data(protocol_version)
+            },
+            _ => return Err(byte::Error::BadInput { err: "Unsupported checkpoint version" }),
+        };
+        let count = bytes.read_with::<VarInt>(offset, LE)?.0;
+        let mut snapshots = BTreeMap::new();
+        for _i in 0..count {
+            let block_hash = bytes.read_with::<UInt256>(offset, LE)?;
+            snapshots.insert(block_hash, bytes.read_with::<LLMQSnapshot>(offset, LE)?);
+        }
+        if *offset != bytes.len() {
+            return Err(byte::Error::BadInput { err: "Unexpected trailing bytes" });
+        }
+        Ok(Self { protocol_version, list_diff, snapshots })
+    }
+}
+
+/// Masternode entries in the checkpoint file take about 15% less than in 'MNLISTDIFF':
+/// flags replace validity and key scheme, IPv4 addresses are written without the IPv6 prefix,
+/// the port is omitted if it's the same as of the previous entry, while confirmed hashes
+/// (many masternodes are confirmed in the same block) and voting keys are written once and then referred by index
+#[derive(Default)]
+struct CompactEntries {
+    port: u16,
+    confirmed_hashes: ValueTable<UInt256>,
+    voting_keys: ValueTable<UInt160>,
+}
+
+impl CompactEntries {
+    fn write(&mut self, entry: &MasternodeEntry, protocol_version: u32, writer: &mut Vec<u8>) -> byte::Result<()> {
+        let version = entry.operator_public_key.version;
+        if !(1..=2).contains(&version) {
+            return Err(byte::Error::BadInput { err: "Unsupported operator key version" });
+        }
+        let ip_address = entry.socket_address.ip_address.0;
+        let is_ipv4 = ip_address[..12] == IPV4_MAPPED_PREFIX;
+        let confirmed_hash_index = self.confirmed_hashes.index_of(entry.confirmed_hash);
+        let voting_key_index = self.voting_keys.index_of(entry.key_id_voting);
+        let mut flags = 0;
+        if entry.is_valid { flags |= ENTRY_IS_VALID; }
+        if is_ipv4 { flags |= ENTRY_IPV4; }
+        if entry.socket_address.port == self.port { flags |= ENTRY_SAME_PORT; }
+        if confirmed_hash_index.is_some() { flags |= ENTRY_KNOWN_CONFIRMED_HASH; }
+        if voting_key_index.is_some() { flags |= ENTRY_KNOWN_VOTING_KEY; }
+        if version == 2 { flags |= ENTRY_BASIC_BLS; }
+        if entry.mn_type == MasternodeType::HighPerformance { flags |= ENTRY_HIGH_PERFORMANCE; }
+        flags.enc(&mut *writer);
+        entry.provider_registration_transaction_hash.enc(&mut *writer);
+        match confirmed_hash_index {
+            Some(index) => { VarInt(index).enc(&mut *writer); },
+            None => { entry.confirmed_hash.enc(&mut *writer); },
+        }
+        writer.extend_from_slice(if is_ipv4 { &ip_address[12..] } else { &ip_address });
+        if entry.socket_address.port != self.port {
+            self.port = entry.socket_address.port;
+            self.port.enc(&mut *writer);
+        }
+        entry.operator_public_key.data.enc(&mut *writer);
+        match voting_key_index {
+            Some(index) => { VarInt(index).enc(&mut *writer); },
+            None => { entry.key_id_voting.enc(&mut *writer); },
+        }
+        if entry.mn_type == MasternodeType::HighPerformance {
+            if protocol_version < CORE_PROTO_19_2 || version < 2 {
+                return Err(byte::Error::BadInput { err: "High performance masternode in the legacy entry" });
+            }
+            entry.platform_http_port.enc(&mut *writer);
+            entry.platform_node_id.enc(&mut *writer);
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, bytes: &[u8], offset: &mut usize, protocol_version: u32) -> byte::Result<MasternodeEntry> {
+        let flags = bytes.read_with::<u8>(offset, LE)?;
+        let provider_registration_transaction_hash = bytes.read_with::<UInt256>(offset, LE)?;
+        let confirmed_hash = if flags & ENTRY_KNOWN_CONFIRMED_HASH != 0 {
+            self.confirmed_hashes.value_at(bytes.read_with::<VarInt>(offset, LE)?.0)?
+        } else {
+            self.confirmed_hashes.insert(bytes.read_with::<UInt256>(offset, LE)?)
+        };
+        let mut ip_address = [0u8; 16];
+        if flags & ENTRY_IPV4 != 0 {
+            ip_address[..12].copy_from_slice(&IPV4_MAPPED_PREFIX);
+            ip_address[12..].copy_from_slice(bytes.read_with::<&[u8]>(offset, byte::ctx::Bytes::Len(4))?);
+        } else {
+            ip_address.copy_from_slice(bytes.read_with::<&[u8]>(offset, byte::ctx::Bytes::Len(16))?);
+        }
+        if flags & ENTRY_SAME_PORT == 0 {
+            self.port = bytes.read_with::<u16>(offset, LE)?;
+        }
+        let version = if flags & ENTRY_BASIC_BLS != 0 { 2 } else { 1 };
+        let operator_public_key = OperatorPublicKey { data: bytes.read_with::<UInt384>(offset, LE)?, version };
+        let key_id_voting = if flags & ENTRY_KNOWN_VOTING_KEY != 0 {
+            self.voting_keys.value_at(bytes.read_with::<VarInt>(offset, LE)?.0)?
+        } else {
+            self.voting_keys.insert(bytes.read_with::<UInt160>(offset, LE)?)
+        };
+        let (mn_type, platform_http_port, platform_node_id) = if flags & ENTRY_HIGH_PERFORMANCE != 0 {
+            (MasternodeType::HighPerformance, bytes.read_with::<u16>(offset, LE)?, bytes.read_with::<UInt160>(offset, LE)?)
+        } else {
+            (MasternodeType::Regular, 0, UInt160::MIN)
+        };
+        Ok(MasternodeEntry::new(
+            version,
+            provider_registration_transaction_hash,
+            confirmed_hash,
+            SocketAddress { ip_address: UInt128(ip_address), port: self.port },
+            key_id_voting,
+            operator_public_key,
+            flags & ENTRY_IS_VALID,
+            mn_type,
+            platform_http_port,
+            platform_node_id,
+            u32::MAX,
+            protocol_version))
+    }
+}
+
+/// Distinct values in the order of the first occurrence
+struct ValueTable<T> {
+    indexes: BTreeMap<T, u64>,
+    values: Vec<T>,
+}
+
+impl<T> Default for ValueTable<T> {
+    fn default() -> Self {
+        Self { indexes: BTreeMap::new(), values: vec![] }
+    }
+}
+
+impl<T: Copy + Ord> ValueTable<T> {
+    /// Index of the value if it's already written, otherwise the value is remembered
+    fn index_of(&mut self, value: T) -> Option<u64> {
+        let index = self.indexes.get(&value).copied();
+        if index.is_none() {
+            self.insert(value);
+        }
+        index
+    }
+
+    fn insert(&mut self, value: T) -> T {
+        self.indexes.insert(value, self.values.len() as u64);
+        self.values.push(value);
+        value
+    }
+
+    fn value_at(&self, index: u64) -> byte::Result<T> {
+        self.values.get(index as usize)
+            .copied()
+            .ok_or(byte::Error::BadInput { err: "Unknown value index" })
+    }
+}
@@ -0,0 +1,100 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use crate::chain::common::ChainType;
+use crate::common::MasternodeType;
+use crate::crypto::UInt256;
+use crate::models::{MasternodeEntry, MasternodeList};
+use crate::processing::MasternodePaymentValidationStatus;
+
+/// Number of blocks in a row EvoNode is paid for (its voting weight)
+pub const EVONODE_CONSECUTIVE_PAYMENTS: u32 = 4;
+/// Operator reward is given in 1/100 of percent
+pub const OPERATOR_REWARD_DENOMINATOR: u64 = 10000;
+
+/// Part of the deterministic masternode state which is needed to select the payee,
+/// but isn't presented in the simplified masternode list (it comes from ProRegTx/ProUpRegTx and the full node)
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MasternodePaymentState {
+    pub registered_height: u32,
+    /// 0 if the masternode has never been paid
+    pub last_paid_height: u32,
+    pub pose_revived_height: Option<u32>,
+    /// Number of blocks in a row EvoNode has been paid for at its last paid height
+    pub consecutive_payments: u32,
+    pub payout_script: Vec<u8>,
+    pub operator_payout_script: Option<Vec<u8>>,
+    /// Operator share of the masternode reward in 1/100 of percent
+    pub operator_reward: u16,
+}
+
+impl MasternodePaymentState {
+    /// Height used to order masternodes in the payment queue
+    pub fn payment_queue_height(&self) -> u32 {
+        match self.pose_revived_height {
+            Some(revived_height) if revived_height > self.last_paid_height => revived_height,
+            _ if self.last_paid_height == 0 => self.registered_height,
+            _ => self.last_paid_height,
+        }
+    }
+
+    /// Masternode and operator payments for the masternode reward of the block
+    pub fn payments(&self, masternode_reward: u64) -> Vec<MasternodePayment> {
+        let operator_reward = match &self.operator_payout_script {
+            Some(script) if self.operator_reward != 0 && !script.is_empty() =>
+                masternode_reward * self.operator_reward as u64 / OPERATOR_REWARD_DENOMINATOR,
+            _ => 0,
+        };
+        let mut payments = Vec::with_capacity(2);
+        if masternode_reward > operator_reward {
+            payments.push(MasternodePayment { script: self.payout_script.clone(), amount: masternode_reward - operator_reward });
+        }
+        if let (Some(script), true) = (&self.operator_payout_script, operator_reward > 0) {
+            payments.push(MasternodePayment { script: script.clone(), amount: operator_reward });
+        }
+        payments
+    }
+}
+
+/// Output of the coinbase transaction expected to pay the masternode
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MasternodePayment {
+    pub script: Vec<u8>,
+    pub amount: u64,
+}
+
+impl MasternodeList {
+    /// Masternode expected to be paid in the block following this list.
+    /// Valid masternodes are ordered by their last paid (or registration/revival) height and then by proTxHash,
+    /// EvoNode paid in the previous block keeps being paid until it gets `EVONODE_CONSECUTIVE_PAYMENTS` in a row (since Core v19)
+    pub fn masternode_payee<'a>(
+        &'a self,
+        chain_type: ChainType,
+        payment_states: &'a BTreeMap<UInt256, MasternodePaymentState>,
+    ) -> Result<(&'a MasternodeEntry, &'a MasternodePaymentState), MasternodePaymentValidationStatus> {
+        let mut candidates = Vec::with_capacity(self.masternodes.len());
+        for entry in self.masternodes.values().filter(|entry| entry.is_valid) {
+            let state = payment_states.get(&entry.provider_registration_transaction_hash)
+                .ok_or(MasternodePaymentValidationStatus::MissingPaymentState)?;
+            candidates.push((entry.as_ref(), state));
+        }
+        if chain_type.core19_is_active_at(self.known_height) {
+            if let Some(evonode) = candidates.iter().find(|(entry, state)|
+                state.last_paid_height == self.known_height &&
+                    entry.mn_type == MasternodeType::HighPerformance &&
+                    state.consecutive_payments < EVONODE_CONSECUTIVE_PAYMENTS) {
+                return Ok(*evonode);
+            }
+        }
+        candidates
+            .into_iter()
+            .min_by(|(entry1, state1), (entry2, state2)| Self::compare_by_last_paid(entry1, state1, entry2, state2))
+            .ok_or(MasternodePaymentValidationStatus::NoPayee)
+    }
+
+    /// Ties are resolved by proTxHash compared as uint256 in Core (memcmp of the internal byte order)
+    fn compare_by_last_paid(entry1: &MasternodeEntry, state1: &MasternodePaymentState, entry2: &MasternodeEntry, state2: &MasternodePaymentState) -> Ordering {
+        state1.payment_queue_height()
+            .cmp(&state2.payment_queue_height())
+            .then_with(|| entry1.provider_registration_transaction_hash.cmp(&entry2.provider_registration_transaction_hash))
+    }
+}
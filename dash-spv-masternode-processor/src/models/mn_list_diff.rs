@@ -2,13 +2,15 @@ use byte::BytesExt;
 use hashes::hex::ToHex;
 use std::collections::{BTreeMap, HashSet};
 use crate::chain::common::LLMQType;
+use crate::common::MerkleTree;
 use crate::chain::constants::{CORE_PROTO_20, CORE_PROTO_BLS_BASIC, CORE_PROTO_DIFF_VERSION_ORDER};
-use crate::consensus::encode::VarInt;
+use crate::consensus::{encode::VarInt, Encodable};
 use crate::crypto::byte_util::{BytesDecodable, Reversable};
 use crate::crypto::var_array::VarArray;
 use crate::crypto::{UInt256, UInt768};
-use crate::models::{LLMQEntry, MasternodeEntry};
+use crate::models::{LLMQEntry, MasternodeEntry, MasternodeList};
 use crate::models::masternode_entry::MasternodeReadContext;
+use crate::processing::{MessageError, MessageField, MessageSection, message_error::read_field};
 use crate::tx::CoinbaseTransaction;
 
 #[derive(Clone)]
@@ -67,75 +69,88 @@ impl MNListDiff {
         block_height_lookup: F,
         protocol_version: u32,
     ) -> Option<Self> {
+        Self::read(message, offset, block_height_lookup, protocol_version).ok()
+    }
+
+    /// Same as `new` but reports which part of the message can't be read
+    pub fn read<F: Fn(UInt256) -> u32>(
+        message: &[u8],
+        offset: &mut usize,
+        block_height_lookup: F,
+        protocol_version: u32,
+    ) -> Result<Self, MessageError> {
         let mut version = 1;
         if protocol_version >= CORE_PROTO_DIFF_VERSION_ORDER {
-            version = u16::from_bytes(message, offset)?
+            version = read_field(offset, MessageSection::MNListDiff, MessageField::Version, None, |offset| u16::from_bytes(message, offset))?;
         }
-        let base_block_hash = UInt256::from_bytes(message, offset)?;
-        let block_hash = UInt256::from_bytes(message, offset)?;
+        let base_block_hash = read_field(offset, MessageSection::MNListDiff, MessageField::BaseBlockHash, None, |offset| UInt256::from_bytes(message, offset))?;
+        let block_hash = read_field(offset, MessageSection::MNListDiff, MessageField::BlockHash, None, |offset| UInt256::from_bytes(message, offset))?;
+        let known_hash = Some(block_hash);
         let base_block_height = block_height_lookup(base_block_hash);
         let block_height = block_height_lookup(block_hash);
-        let total_transactions = u32::from_bytes(message, offset)?;
-        let merkle_hashes = VarArray::<UInt256>::from_bytes(message, offset)?;
-        let merkle_flags_count = VarInt::from_bytes(message, offset)?.0 as usize;
-        let merkle_flags: &[u8] = match message.read_with(offset, byte::ctx::Bytes::Len(merkle_flags_count)) {
-            Ok(data) => data,
-            Err(_err) => { return None; },
-        };
-        let coinbase_transaction = CoinbaseTransaction::from_bytes(message, offset)?;
+        let total_transactions = read_field(offset, MessageSection::MNListDiff, MessageField::TotalTransactions, known_hash, |offset| u32::from_bytes(message, offset))?;
+        let merkle_hashes = read_field(offset, MessageSection::MNListDiff, MessageField::MerkleHashes, known_hash, |offset| VarArray::<UInt256>::from_bytes(message, offset))?;
+        let merkle_flags: &[u8] = read_field(offset, MessageSection::MNListDiff, MessageField::MerkleFlags, known_hash, |offset| {
+            let merkle_flags_count = VarInt::from_bytes(message, offset)?.0 as usize;
+            message.read_with(offset, byte::ctx::Bytes::Len(merkle_flags_count)).ok()
+        })?;
+        let coinbase_transaction = read_field(offset, MessageSection::MNListDiff, MessageField::CoinbaseTransaction, known_hash, |offset| CoinbaseTransaction::from_bytes(message, offset))?;
         if protocol_version >= CORE_PROTO_BLS_BASIC && protocol_version < CORE_PROTO_DIFF_VERSION_ORDER {
             // BLS Basic
-            version = u16::from_bytes(message, offset)?
+            version = read_field(offset, MessageSection::MNListDiff, MessageField::Version, known_hash, |offset| u16::from_bytes(message, offset))?;
         }
         let masternode_read_ctx = MasternodeReadContext(block_height, version, protocol_version);
-        let deleted_masternode_count = VarInt::from_bytes(message, offset)?.0;
-        let mut deleted_masternode_hashes: Vec<UInt256> =
-            Vec::with_capacity(deleted_masternode_count as usize);
-        for _i in 0..deleted_masternode_count {
-            deleted_masternode_hashes.push(UInt256::from_bytes(message, offset)?);
+        let deleted_masternode_hashes = read_field(offset, MessageSection::MNListDiff, MessageField::DeletedMasternodes, known_hash, |offset| {
+            let deleted_masternode_count = VarInt::from_bytes(message, offset)?.0;
+            (0..deleted_masternode_count)
+                .map(|_| UInt256::from_bytes(message, offset))
+                .collect::<Option<Vec<UInt256>>>()
+        })?;
+        let added_masternode_count = read_field(offset, MessageSection::MNListDiff, MessageField::AddedMasternodes, known_hash, |offset| VarInt::from_bytes(message, offset))?.0;
+        let mut added_or_modified_masternodes: BTreeMap<UInt256, MasternodeEntry> = BTreeMap::new();
+        for i in 0..added_masternode_count as usize {
+            let entry = read_field(offset, MessageSection::MNListDiff, MessageField::AddedMasternode(i), known_hash, |offset| message.read_with::<MasternodeEntry>(offset, masternode_read_ctx).ok())?;
+            added_or_modified_masternodes.insert(entry.provider_registration_transaction_hash.reversed(), entry);
         }
-        let added_masternode_count = VarInt::from_bytes(message, offset)?.0;
-        let added_or_modified_masternodes: BTreeMap<UInt256, MasternodeEntry> = (0..added_masternode_count)
-            .fold(BTreeMap::new(), |mut acc, _| {
-                if let Ok(entry) = message.read_with::<MasternodeEntry>(offset, masternode_read_ctx) {
-                    acc.insert(entry.provider_registration_transaction_hash.reversed(), entry);
-                }
-                acc
-            });
-
         let mut deleted_quorums: BTreeMap<LLMQType, Vec<UInt256>> = BTreeMap::new();
         let mut added_quorums = Vec::<LLMQEntry>::new();
         let quorums_active = coinbase_transaction.coinbase_transaction_version >= 2;
         if quorums_active {
-            let deleted_quorums_count = VarInt::from_bytes(message, offset)?.0;
-            for _i in 0..deleted_quorums_count {
-                let llmq_type = LLMQType::from_bytes(message, offset)?;
-                let llmq_hash = UInt256::from_bytes(message, offset)?;
+            let deleted = read_field(offset, MessageSection::MNListDiff, MessageField::DeletedQuorums, known_hash, |offset| {
+                let deleted_quorums_count = VarInt::from_bytes(message, offset)?.0;
+                (0..deleted_quorums_count)
+                    .map(|_| Some((LLMQType::from_bytes(message, offset)?, UInt256::from_bytes(message, offset)?)))
+                    .collect::<Option<Vec<(LLMQType, UInt256)>>>()
+            })?;
+            deleted.into_iter().for_each(|(llmq_type, llmq_hash)| {
                 deleted_quorums
                     .entry(llmq_type)
                     .or_insert_with(Vec::new)
                     .push(llmq_hash);
-            }
-            let added_quorums_count = VarInt::from_bytes(message, offset)?.0;
-            for _i in 0..added_quorums_count {
-                added_quorums.push(LLMQEntry::from_bytes(message, offset)?);
+            });
+            let added_quorums_count = read_field(offset, MessageSection::MNListDiff, MessageField::AddedQuorums, known_hash, |offset| VarInt::from_bytes(message, offset))?.0;
+            for i in 0..added_quorums_count as usize {
+                added_quorums.push(read_field(offset, MessageSection::MNListDiff, MessageField::AddedQuorum(i), known_hash, |offset| LLMQEntry::from_bytes(message, offset))?);
             }
         }
         let mut quorums_cls_sigs = BTreeMap::new();
         if protocol_version >= CORE_PROTO_20 {
-            let quorums_cl_sigs_count = VarInt::from_bytes(message, offset)?.0;
-            for _i in 0..quorums_cl_sigs_count {
-                let signature = UInt768::from_bytes(message, offset)?;
-                let index_set_length = VarInt::from_bytes(message, offset)?.0 as usize;
-                let mut index_set = HashSet::with_capacity(index_set_length);
-                for _i in 0..index_set_length {
-                    index_set.insert(u16::from_bytes(message, offset)?);
+            read_field(offset, MessageSection::MNListDiff, MessageField::QuorumsCLSigs, known_hash, |offset| {
+                let quorums_cl_sigs_count = VarInt::from_bytes(message, offset)?.0;
+                for _i in 0..quorums_cl_sigs_count {
+                    let signature = UInt768::from_bytes(message, offset)?;
+                    let index_set_length = VarInt::from_bytes(message, offset)?.0 as usize;
+                    let mut index_set = HashSet::with_capacity(index_set_length);
+                    for _i in 0..index_set_length {
+                        index_set.insert(u16::from_bytes(message, offset)?);
+                    }
+                    quorums_cls_sigs.insert(signature, index_set);
                 }
-                quorums_cls_sigs.insert(signature, index_set);
-            }
+                Some(())
+            })?;
         }
 
-        Some(Self {
+        Ok(Self {
             base_block_hash,
             block_hash,
             total_transactions,
@@ -153,6 +168,124 @@ impl MNListDiff {
         })
     }
 
+    /// Builds the diff which turns `base` list into `target` list the same way Dash Core does
+    /// (quorums are ordered by type and quorum hash).
+    /// `cl_signature_lookup` provides the chain lock signature the added quorum relies on (protocol 70230+),
+    /// quorums without known signature are listed with the null signature
+    pub fn from_masternode_lists<F: Fn(&LLMQEntry) -> Option<UInt768>>(
+        base: &MasternodeList,
+        target: &MasternodeList,
+        coinbase_transaction: CoinbaseTransaction,
+        merkle_tree: MerkleTree,
+        protocol_version: u32,
+        cl_signature_lookup: F,
+    ) -> Self {
+        let deleted_masternode_hashes = base.masternodes
+            .iter()
+            .filter(|(hash, _)| !target.masternodes.contains_key(hash))
+            .map(|(_, entry)| entry.provider_registration_transaction_hash)
+            .collect();
+        let added_or_modified_masternodes = target.masternodes
+            .iter()
+            .filter(|(hash, entry)| !base.masternodes.get(hash)
+                .is_some_and(|base_entry| base_entry.is_same_simplified_entry(entry)))
+            .map(|(hash, entry)| (*hash, entry.as_ref().clone()))
+            .collect();
+        let mut deleted_quorums = BTreeMap::<LLMQType, Vec<UInt256>>::new();
+        let mut added_quorums = Vec::<LLMQEntry>::new();
+        if coinbase_transaction.coinbase_transaction_version >= 2 {
+            let has_quorum = |list: &MasternodeList, quorum: &LLMQEntry| list.quorums
+                .get(&quorum.llmq_type)
+                .is_some_and(|map| map.contains_key(&quorum.llmq_hash));
+            Self::sorted_quorums(base)
+                .filter(|quorum| !has_quorum(target, quorum))
+                .for_each(|quorum| deleted_quorums
+                    .entry(quorum.llmq_type)
+                    .or_insert_with(Vec::new)
+                    .push(quorum.llmq_hash));
+            added_quorums = Self::sorted_quorums(target)
+                .filter(|quorum| !has_quorum(base, quorum))
+                .cloned()
+                .collect();
+        }
+        let mut quorums_cls_sigs = BTreeMap::<UInt768, HashSet<u16>>::new();
+        if protocol_version >= CORE_PROTO_20 {
+            added_quorums.iter().enumerate().for_each(|(index, quorum)| {
+                quorums_cls_sigs.entry(cl_signature_lookup(quorum).unwrap_or(UInt768::MIN))
+                    .or_default()
+                    .insert(index as u16);
+            });
+        }
+        // Since 70229 BLS scheme is specified by the version of each entry
+        let version = if protocol_version >= CORE_PROTO_BLS_BASIC && protocol_version < CORE_PROTO_DIFF_VERSION_ORDER &&
+            target.masternodes.values().any(|m| m.operator_public_key.version == 2) { 2 } else { 1 };
+        Self {
+            base_block_hash: base.block_hash,
+            block_hash: target.block_hash,
+            total_transactions: merkle_tree.tree_element_count,
+            merkle_hashes: merkle_tree.hashes,
+            merkle_flags: merkle_tree.flags.to_vec(),
+            coinbase_transaction,
+            deleted_masternode_hashes,
+            added_or_modified_masternodes,
+            deleted_quorums,
+            added_quorums,
+            base_block_height: base.known_height,
+            block_height: target.known_height,
+            version,
+            quorums_cls_sigs,
+        }
+    }
+
+    /// Quorums in the order of Dash Core (by type and then by quorum hash)
+    fn sorted_quorums(list: &MasternodeList) -> impl Iterator<Item = &LLMQEntry> {
+        list.quorums
+            .values()
+            .flat_map(|map| map.values())
+    }
+
+    /// Serialized as the response for 'GETMNLISTDIFF' for the given protocol version
+    pub fn to_data(&self, protocol_version: u32) -> Vec<u8> {
+        let mut writer = Vec::<u8>::new();
+        if protocol_version >= CORE_PROTO_DIFF_VERSION_ORDER {
+            self.version.enc(&mut writer);
+        }
+        self.base_block_hash.enc(&mut writer);
+        self.block_hash.enc(&mut writer);
+        self.total_transactions.enc(&mut writer);
+        VarInt(self.merkle_hashes.len() as u64).enc(&mut writer);
+        self.merkle_hashes.iter().for_each(|hash| { hash.enc(&mut writer); });
+        self.merkle_flags.enc(&mut writer);
+        writer.extend(self.coinbase_transaction.to_data());
+        if protocol_version >= CORE_PROTO_BLS_BASIC && protocol_version < CORE_PROTO_DIFF_VERSION_ORDER {
+            self.version.enc(&mut writer);
+        }
+        VarInt(self.deleted_masternode_hashes.len() as u64).enc(&mut writer);
+        self.deleted_masternode_hashes.iter().for_each(|hash| { hash.enc(&mut writer); });
+        VarInt(self.added_or_modified_masternodes.len() as u64).enc(&mut writer);
+        self.added_or_modified_masternodes.values().for_each(|entry| writer.extend(entry.to_data(protocol_version)));
+        if self.coinbase_transaction.coinbase_transaction_version >= 2 {
+            VarInt(self.deleted_quorums.values().map(Vec::len).sum::<usize>() as u64).enc(&mut writer);
+            self.deleted_quorums.iter().for_each(|(llmq_type, hashes)| hashes.iter().for_each(|hash| {
+                u8::from(*llmq_type).enc(&mut writer);
+                hash.enc(&mut writer);
+            }));
+            VarInt(self.added_quorums.len() as u64).enc(&mut writer);
+            self.added_quorums.iter().for_each(|quorum| { quorum.enc(&mut writer); });
+        }
+        if protocol_version >= CORE_PROTO_20 {
+            VarInt(self.quorums_cls_sigs.len() as u64).enc(&mut writer);
+            self.quorums_cls_sigs.iter().for_each(|(signature, index_set)| {
+                signature.enc(&mut writer);
+                let mut indexes = index_set.iter().collect::<Vec<_>>();
+                indexes.sort();
+                VarInt(indexes.len() as u64).enc(&mut writer);
+                indexes.into_iter().for_each(|index| { index.enc(&mut writer); });
+            });
+        }
+        writer
+    }
+
     pub fn has_basic_scheme_keys(&self) -> bool {
         self.added_or_modified_masternodes.values().any(|m| m.operator_public_key.version == 2)
     }
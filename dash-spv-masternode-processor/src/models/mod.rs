@@ -2,21 +2,39 @@ use byte::{BytesExt, LE};
 use crate::crypto::byte_util::BytesDecodable;
 use crate::impl_bytes_decodable;
 
+pub mod chain_lock;
+pub mod chain_lock_validation_report;
+pub mod credit_pool;
+pub mod instant_lock;
+pub mod instant_lock_validation_report;
 pub mod llmq_entry;
 pub mod llmq_typed_hash;
+pub mod masternode_change;
 pub mod masternode_entry;
 pub mod masternode_list;
+pub mod masternode_list_checkpoint;
+pub mod masternode_payment;
 pub mod mn_list_diff;
 pub mod operator_public_key;
+pub mod rotation_info;
 pub mod snapshot;
 
+pub use self::chain_lock::ChainLock;
+pub use self::chain_lock_validation_report::ChainLockValidationReport;
+pub use self::credit_pool::{CreditPool, CreditPoolBalanceChange};
+pub use self::instant_lock::InstantLock;
+pub use self::instant_lock_validation_report::InstantLockValidationReport;
 pub use self::llmq_entry::{LLMQEntry, LLMQModifierType, LLMQVerificationContext};
 pub use self::llmq_typed_hash::LLMQIndexedHash;
 pub use self::llmq_typed_hash::LLMQTypedHash;
+pub use self::masternode_change::{MasternodeChange, MasternodeChangeKind};
 pub use self::masternode_entry::MasternodeEntry;
 pub use self::masternode_list::MasternodeList;
+pub use self::masternode_list_checkpoint::{MasternodeListCheckpoint, MasternodeListCheckpointData};
+pub use self::masternode_payment::{MasternodePayment, MasternodePaymentState};
 pub use self::mn_list_diff::MNListDiff;
 pub use self::operator_public_key::OperatorPublicKey;
+pub use self::rotation_info::LLMQRotationInfo;
 pub use self::snapshot::LLMQSnapshot;
 
 impl_bytes_decodable!(LLMQEntry);
@@ -1,4 +1,8 @@
+use byte::BytesExt;
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::{byte_util::BytesDecodable, UInt256};
 use crate::models::{LLMQEntry, LLMQSnapshot, MNListDiff};
+use crate::processing::{MessageError, MessageField, MessageSection, message_error::read_field};
 
 #[derive(Debug)]
 pub struct LLMQRotationInfo {
@@ -17,3 +21,99 @@ pub struct LLMQRotationInfo {
     pub quorum_snapshot_list: Vec<LLMQSnapshot>,
     pub mn_list_diff_list: Vec<MNListDiff>,
 }
+
+impl LLMQRotationInfo {
+    /// Read message received as a response for 'GETQRINFO' call
+    pub fn read<F: Fn(UInt256) -> u32>(
+        message: &[u8],
+        offset: &mut usize,
+        block_height_lookup: F,
+        protocol_version: u32,
+    ) -> Result<Self, MessageError> {
+        let read_list_diff = |offset: &mut usize, section: MessageSection|
+            MNListDiff::read(message, offset, &block_height_lookup, protocol_version)
+                .map_err(|err| err.in_section(section));
+        let read_snapshot = |offset: &mut usize, section: MessageSection|
+            read_field(offset, section, MessageField::Snapshot, None, |offset| LLMQSnapshot::from_bytes(message, offset));
+        let read_count = |offset: &mut usize, section: MessageSection|
+            read_field(offset, section, MessageField::Count, None, |offset| VarInt::from_bytes(message, offset))
+                .map(|count| count.0 as usize);
+        let snapshot_at_h_c = read_snapshot(offset, MessageSection::SnapshotAtHC)?;
+        let snapshot_at_h_2c = read_snapshot(offset, MessageSection::SnapshotAtH2C)?;
+        let snapshot_at_h_3c = read_snapshot(offset, MessageSection::SnapshotAtH3C)?;
+        let mn_list_diff_tip = read_list_diff(offset, MessageSection::DiffAtTip)?;
+        let mn_list_diff_at_h = read_list_diff(offset, MessageSection::DiffAtH)?;
+        let mn_list_diff_at_h_c = read_list_diff(offset, MessageSection::DiffAtHC)?;
+        let mn_list_diff_at_h_2c = read_list_diff(offset, MessageSection::DiffAtH2C)?;
+        let mn_list_diff_at_h_3c = read_list_diff(offset, MessageSection::DiffAtH3C)?;
+        let extra_share = read_field(offset, MessageSection::ExtraShare, MessageField::Flag, None, |offset| message.read_with::<bool>(offset, ()).ok())?;
+        let (snapshot_at_h_4c, mn_list_diff_at_h_4c) = if extra_share {
+            (Some(read_snapshot(offset, MessageSection::SnapshotAtH4C)?), Some(read_list_diff(offset, MessageSection::DiffAtH4C)?))
+        } else {
+            (None, None)
+        };
+        let last_quorum_per_index_count = read_count(offset, MessageSection::LastQuorumPerIndex(0))?;
+        let mut last_quorum_per_index = Vec::<LLMQEntry>::with_capacity(last_quorum_per_index_count);
+        for i in 0..last_quorum_per_index_count {
+            let section = MessageSection::LastQuorumPerIndex(i);
+            last_quorum_per_index.push(read_field(offset, section, MessageField::QuorumEntry, None, |offset| LLMQEntry::from_bytes(message, offset))?);
+        }
+        let quorum_snapshot_list_count = read_count(offset, MessageSection::QuorumSnapshotList(0))?;
+        let mut quorum_snapshot_list = Vec::<LLMQSnapshot>::with_capacity(quorum_snapshot_list_count);
+        for i in 0..quorum_snapshot_list_count {
+            quorum_snapshot_list.push(read_snapshot(offset, MessageSection::QuorumSnapshotList(i))?);
+        }
+        let mn_list_diff_list_count = read_count(offset, MessageSection::MNListDiffList(0))?;
+        if quorum_snapshot_list_count != mn_list_diff_list_count {
+            return Err(MessageError::SnapshotCountMismatch { snapshots: quorum_snapshot_list_count, diffs: mn_list_diff_list_count });
+        }
+        let mut mn_list_diff_list = Vec::<MNListDiff>::with_capacity(mn_list_diff_list_count);
+        for i in 0..mn_list_diff_list_count {
+            mn_list_diff_list.push(read_list_diff(offset, MessageSection::MNListDiffList(i))?);
+        }
+        Ok(Self {
+            snapshot_at_h_c,
+            snapshot_at_h_2c,
+            snapshot_at_h_3c,
+            snapshot_at_h_4c,
+            mn_list_diff_tip,
+            mn_list_diff_at_h,
+            mn_list_diff_at_h_c,
+            mn_list_diff_at_h_2c,
+            mn_list_diff_at_h_3c,
+            mn_list_diff_at_h_4c,
+            extra_share,
+            last_quorum_per_index,
+            quorum_snapshot_list,
+            mn_list_diff_list,
+        })
+    }
+
+    /// Serialized as the response for 'GETQRINFO' for the given protocol version
+    pub fn to_data(&self, protocol_version: u32) -> Vec<u8> {
+        let mut writer = Vec::<u8>::new();
+        self.snapshot_at_h_c.enc(&mut writer);
+        self.snapshot_at_h_2c.enc(&mut writer);
+        self.snapshot_at_h_3c.enc(&mut writer);
+        writer.extend(self.mn_list_diff_tip.to_data(protocol_version));
+        writer.extend(self.mn_list_diff_at_h.to_data(protocol_version));
+        writer.extend(self.mn_list_diff_at_h_c.to_data(protocol_version));
+        writer.extend(self.mn_list_diff_at_h_2c.to_data(protocol_version));
+        writer.extend(self.mn_list_diff_at_h_3c.to_data(protocol_version));
+        // The flag tells whether h-4c data follows, so it can't be set without the data
+        if let (Some(snapshot), Some(list_diff)) = (&self.snapshot_at_h_4c, &self.mn_list_diff_at_h_4c) {
+            true.enc(&mut writer);
+            snapshot.enc(&mut writer);
+            writer.extend(list_diff.to_data(protocol_version));
+        } else {
+            false.enc(&mut writer);
+        }
+        VarInt(self.last_quorum_per_index.len() as u64).enc(&mut writer);
+        self.last_quorum_per_index.iter().for_each(|quorum| { quorum.enc(&mut writer); });
+        VarInt(self.quorum_snapshot_list.len() as u64).enc(&mut writer);
+        self.quorum_snapshot_list.iter().for_each(|snapshot| { snapshot.enc(&mut writer); });
+        VarInt(self.mn_list_diff_list.len() as u64).enc(&mut writer);
+        self.mn_list_diff_list.iter().for_each(|list_diff| writer.extend(list_diff.to_data(protocol_version)));
+        writer
+    }
+}
@@ -5,17 +5,19 @@ use hashes::hex::ToHex;
 use serde::{Serialize, Serializer};
 #[cfg(feature = "generate-dashj-tests")]
 use serde::ser::SerializeStruct;
+use std::io;
 use crate::common::LLMQSnapshotSkipMode;
-use crate::consensus::encode::VarInt;
+use crate::consensus::{Encodable, encode::VarInt};
 use crate::crypto::{byte_util::BytesDecodable, data_ops::Data};
 use crate::impl_bytes_decodable;
-use crate::models::MasternodeEntry;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Eq)]
 pub struct LLMQSnapshot {
     // The bitset of nodes already in quarters at the start of cycle at height n
     // (masternodeListSize + 7)/8
     pub member_list: Vec<u8>,
+    // Number of bits in the member list (masternodeListSize)
+    pub member_count: usize,
     // Skiplist at height n
     pub skip_list: Vec<i32>,
     //  Mode of the skip list
@@ -25,6 +27,7 @@ impl Default for LLMQSnapshot {
     fn default() -> Self {
         Self {
             member_list: vec![],
+            member_count: 0,
             skip_list: vec![],
             skip_list_mode: LLMQSnapshotSkipMode::NoSkipping,
         }
@@ -68,6 +71,7 @@ impl<'a> TryRead<'a, Endian> for LLMQSnapshot {
         }
         let snapshot = Self {
             member_list: member_list.to_vec(),
+            member_count: member_list_length,
             skip_list,
             skip_list_mode,
         };
@@ -75,11 +79,26 @@ impl<'a> TryRead<'a, Endian> for LLMQSnapshot {
     }
 }
 
+impl Encodable for LLMQSnapshot {
+    fn consensus_encode<W: io::Write>(&self, mut writer: W) -> Result<usize, io::Error> {
+        let mut offset = u32::from(self.skip_list_mode).consensus_encode(&mut writer)?;
+        offset += VarInt(self.member_count as u64).consensus_encode(&mut writer)?;
+        writer.write_all(&self.member_list)?;
+        offset += self.member_list.len();
+        offset += VarInt(self.skip_list.len() as u64).consensus_encode(&mut writer)?;
+        for skip in &self.skip_list {
+            offset += skip.consensus_encode(&mut writer)?;
+        }
+        Ok(offset)
+    }
+}
+
 impl LLMQSnapshot {
 
-    pub fn new(member_list: Vec<u8>, skip_list: Vec<i32>, skip_list_mode: LLMQSnapshotSkipMode) -> Self {
+    pub fn new(member_list: Vec<u8>, member_count: usize, skip_list: Vec<i32>, skip_list_mode: LLMQSnapshotSkipMode) -> Self {
         LLMQSnapshot {
             member_list,
+            member_count,
             skip_list,
             skip_list_mode
         }
@@ -89,22 +108,31 @@ impl LLMQSnapshot {
         self.member_list.len() + 1 + 2 + self.skip_list.len() * 2
     }
 
+    /// Approximate number of bytes held by the snapshot
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of::<Self>() + self.member_list.len() + self.skip_list.len() * std::mem::size_of::<i32>()
+    }
+
     pub fn member_is_true_at_index(&self, i: u32) -> bool {
         self.member_list.as_slice().bit_is_true_at_le_index(i)
     }
 
-    pub fn apply_skip_strategy(
+    pub fn apply_skip_strategy<T: Clone>(
         &self,
-        sorted_combined_mns_list: Vec<MasternodeEntry>,
+        sorted_combined_mns_list: Vec<T>,
         quorum_count: usize,
         quarter_size: usize,
-    ) -> Vec<Vec<MasternodeEntry>> {
-        let mut quarter_quorum_members = vec![Vec::<MasternodeEntry>::new(); quorum_count];
+    ) -> Vec<Vec<T>> {
+        let mut quarter_quorum_members = vec![Vec::<T>::new(); quorum_count];
+        if sorted_combined_mns_list.is_empty() {
+            // Nothing to pick from (would loop forever)
+            return quarter_quorum_members;
+        }
         match self.skip_list_mode {
             LLMQSnapshotSkipMode::NoSkipping => {
                 let mut iter = sorted_combined_mns_list.iter();
                 (0..quorum_count).for_each(|i| {
-                    let mut quarter = Vec::<MasternodeEntry>::new();
+                    let mut quarter = Vec::<T>::new();
                     while quarter.len() < quarter_size {
                         if let Some(node) = iter.next() {
                             quarter.push(node.clone());
@@ -144,7 +172,7 @@ impl LLMQSnapshot {
             }
             LLMQSnapshotSkipMode::SkipExcept => {
                 (0..quorum_count).for_each(|i| {
-                    let mut quarter = Vec::<MasternodeEntry>::new();
+                    let mut quarter = Vec::<T>::new();
                     self.skip_list.iter().for_each(|unskipped| {
                         if let Some(node) = sorted_combined_mns_list.get(*unskipped as usize) {
                             if quarter.len() < quarter_size {
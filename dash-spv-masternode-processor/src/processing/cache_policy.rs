@@ -0,0 +1,45 @@
+use crate::chain::common::{ChainType, IHaveChainSettings};
+
+/// Number of cycles below the rotated quorum whose work block lists are needed to rebuild its members (h-c .. h-4c)
+pub const ROTATION_CYCLES_DEPTH: u32 = 4;
+/// Work block of the quorum is 8 blocks below its base block
+pub const ROTATION_WORK_BLOCK_OFFSET: u32 = 8;
+
+/// Limits applied to the processor cache, unbounded by default
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CachePolicy {
+    /// Maximum number of cached masternode lists, least recently used lists are evicted first
+    pub max_masternode_lists: Option<usize>,
+    /// Number of the most recent (by height) masternode lists which are never evicted
+    pub keep_last_masternode_lists: usize,
+    /// Lists within this number of blocks below the most recent list are needed by active rotation cycles
+    /// and are never evicted
+    pub rotation_window: u32,
+    /// Maximum number of cached quorum snapshots
+    pub max_snapshots: Option<usize>,
+    /// Maximum number of cached chain lock signatures
+    pub max_cl_signatures: Option<usize>,
+    /// Soft limit for the estimated memory usage of the cache in bytes
+    pub max_memory_usage: Option<usize>,
+}
+
+impl CachePolicy {
+    /// Policy keeping at most `max_masternode_lists` lists
+    /// while preserving the ones needed to rebuild members of the rotated quorums of the chain
+    pub fn for_chain(chain_type: ChainType, max_masternode_lists: usize) -> Self {
+        let cycle_length = chain_type.isd_llmq_type().params().dkg_params.interval;
+        Self {
+            max_masternode_lists: Some(max_masternode_lists),
+            keep_last_masternode_lists: 1,
+            rotation_window: (ROTATION_CYCLES_DEPTH + 1) * cycle_length + ROTATION_WORK_BLOCK_OFFSET,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.max_masternode_lists.is_some() ||
+            self.max_snapshots.is_some() ||
+            self.max_cl_signatures.is_some() ||
+            self.max_memory_usage.is_some()
+    }
+}
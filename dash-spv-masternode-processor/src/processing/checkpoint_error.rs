@@ -0,0 +1,43 @@
+use crate::crypto::UInt256;
+use crate::processing::MessageError;
+
+/// Reason why the masternode list checkpoint can't be applied
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CheckpointError {
+    /// Checkpoint data has no list for this chain and height
+    NoData,
+    /// Checkpoint data is malformed
+    Malformed(MessageError),
+    /// List in the data was built for another block
+    BlockHashMismatch {
+        expected: UInt256,
+        actual: UInt256,
+    },
+    /// List in the data isn't a diff from the genesis
+    UnexpectedBaseBlockHash(UInt256),
+    /// Coinbase of the checkpoint block commits to another masternode list
+    MasternodeMerkleRootMismatch,
+    /// Coinbase of the checkpoint block commits to another set of quorums
+    LLMQMerkleRootMismatch,
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::NoData =>
+                write!(f, "No checkpoint data"),
+            CheckpointError::Malformed(err) =>
+                write!(f, "Malformed checkpoint data: {}", err),
+            CheckpointError::BlockHashMismatch { expected, actual } =>
+                write!(f, "Checkpoint list is built for block {} instead of {}", actual, expected),
+            CheckpointError::UnexpectedBaseBlockHash(block_hash) =>
+                write!(f, "Checkpoint list is built from block {} instead of genesis", block_hash),
+            CheckpointError::MasternodeMerkleRootMismatch =>
+                write!(f, "Masternode list merkle root doesn't match the checkpoint"),
+            CheckpointError::LLMQMerkleRootMismatch =>
+                write!(f, "Quorum list merkle root doesn't match the checkpoint"),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
@@ -0,0 +1,29 @@
+use crate::crypto::{UInt256, UInt768};
+use crate::models;
+use crate::processing::ProcessingError;
+
+/// Storage backend used by `MasternodeProcessor` to reach the chain data it doesn't own:
+/// block index, masternode lists, quorum snapshots and ChainLock signatures.
+/// The set of FFI callbacks (`ffi::callbacks::FFICoreProvider`) is just one implementation of it,
+/// so Rust clients can feed the processor directly without going through the C ABI.
+pub trait CoreProvider: std::fmt::Debug {
+    /// Block index lookup: returns `u32::MAX` if the block is unknown
+    fn lookup_block_height_by_hash(&self, block_hash: UInt256) -> u32;
+    fn lookup_block_hash_by_height(&self, block_height: u32) -> Option<UInt256>;
+    fn lookup_merkle_root_by_hash(&self, block_hash: UInt256) -> Option<UInt256>;
+    /// Masternode list store
+    fn lookup_masternode_list(&self, block_hash: UInt256) -> Option<models::MasternodeList>;
+    fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &models::MasternodeList) -> bool;
+    /// Quorum snapshot store
+    fn lookup_snapshot_by_block_hash(&self, block_hash: UInt256) -> Option<models::LLMQSnapshot>;
+    fn save_snapshot(&self, block_hash: UInt256, snapshot: models::LLMQSnapshot) -> bool;
+    /// ChainLock signature store
+    fn lookup_cl_signature_by_block_hash(&self, block_hash: UInt256) -> Option<UInt768>;
+    fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> bool;
+    /// Ask the client whether diff between these blocks should be processed
+    fn should_process_diff_with_range(&self, _base_block_hash: UInt256, _block_hash: UInt256) -> ProcessingError {
+        ProcessingError::None
+    }
+    /// Ask the client to fetch block info from the backup source (insight)
+    fn add_insight(&self, _block_hash: UInt256) {}
+}
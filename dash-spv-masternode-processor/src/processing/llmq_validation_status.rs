@@ -1,16 +1,20 @@
+use crate::processing::RotationError;
+
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
 pub enum LLMQValidationStatus {
     Verified,
     InvalidPayload(LLMQPayloadValidationStatus),
     InvalidAggregatedSignature,
     InvalidQuorumSignature,
-    NoMasternodeList
+    NoMasternodeList,
+    /// Members of the rotated quorum can't be determined until the host provides missing data
+    MissingRotationData(RotationError),
 }
 
 impl LLMQValidationStatus {
     pub fn is_not_critical(&self) -> bool {
         match self {
-            Self::Verified | Self::NoMasternodeList => true,
+            Self::Verified | Self::NoMasternodeList | Self::MissingRotationData(..) => true,
             _ => false
         }
     }
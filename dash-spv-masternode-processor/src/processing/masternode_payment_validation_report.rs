@@ -0,0 +1,39 @@
+use crate::crypto::UInt256;
+use crate::models::MasternodePayment;
+
+#[warn(non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum MasternodePaymentValidationStatus {
+    Verified = 0,
+    /// Masternode list at the previous block isn't available
+    NoMasternodeList = 1,
+    /// There are no valid masternodes in the list
+    NoPayee = 2,
+    /// Payment state of some valid masternode is unknown, so the payee can't be determined
+    MissingPaymentState = 3,
+    /// Coinbase doesn't pay the expected script
+    MissingPayment = 4,
+    /// Coinbase pays the expected script with the unexpected amount
+    InvalidAmount = 5,
+}
+
+/// Outcome of the verification of masternode payments in the coinbase transaction
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MasternodePaymentValidationReport {
+    pub block_height: u32,
+    /// ProRegTx hash of the expected payee (if it's determined)
+    pub payee: Option<UInt256>,
+    pub expected_payments: Vec<MasternodePayment>,
+    pub status: MasternodePaymentValidationStatus,
+}
+
+impl MasternodePaymentValidationReport {
+    pub fn new(block_height: u32, status: MasternodePaymentValidationStatus) -> Self {
+        Self { block_height, payee: None, expected_payments: vec![], status }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.status == MasternodePaymentValidationStatus::Verified
+    }
+}
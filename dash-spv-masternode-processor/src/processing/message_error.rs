@@ -0,0 +1,244 @@
+use std::ptr::null_mut;
+use crate::crypto::UInt256;
+use crate::ffi::boxer::boxed;
+use crate::processing::ProcessingError;
+use crate::types;
+use crate::types::message_error::MessageErrorKind;
+
+/// Part of the 'MNLISTDIFF', 'QRINFO', 'ISLOCK' / 'ISDLOCK' or 'CLSIG' message
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageSection {
+    MNListDiff,
+    SnapshotAtHC,
+    SnapshotAtH2C,
+    SnapshotAtH3C,
+    SnapshotAtH4C,
+    DiffAtTip,
+    DiffAtH,
+    DiffAtHC,
+    DiffAtH2C,
+    DiffAtH3C,
+    DiffAtH4C,
+    ExtraShare,
+    LastQuorumPerIndex(usize),
+    QuorumSnapshotList(usize),
+    MNListDiffList(usize),
+    InstantLock,
+    ChainLock,
+}
+
+/// Field which is being read when error occurred
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageField {
+    Version,
+    BaseBlockHash,
+    BlockHash,
+    BlockHeight,
+    TotalTransactions,
+    MerkleHashes,
+    MerkleFlags,
+    CoinbaseTransaction,
+    DeletedMasternodes,
+    AddedMasternodes,
+    AddedMasternode(usize),
+    DeletedQuorums,
+    AddedQuorums,
+    AddedQuorum(usize),
+    QuorumsCLSigs,
+    Snapshot,
+    QuorumEntry,
+    Count,
+    Flag,
+    Inputs,
+    TransactionHash,
+    CycleHash,
+    Signature,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MessageError {
+    /// Message is truncated or malformed
+    Parse {
+        section: MessageSection,
+        field: MessageField,
+        offset: usize,
+        block_hash: Option<UInt256>,
+    },
+    /// Number of snapshots in 'QRINFO' doesn't match number of diffs
+    SnapshotCountMismatch {
+        snapshots: usize,
+        diffs: usize,
+    },
+    /// Diff is refused by the provider (i.e. unknown or locally stored blocks)
+    Rejected {
+        section: MessageSection,
+        base_block_hash: UInt256,
+        block_hash: UInt256,
+        error: ProcessingError,
+    },
+}
+
+impl MessageSection {
+    pub fn encode(self) -> (types::message_error::MessageSection, usize) {
+        match self {
+            MessageSection::MNListDiff => (types::message_error::MessageSection::MNListDiff, 0),
+            MessageSection::SnapshotAtHC => (types::message_error::MessageSection::SnapshotAtHC, 0),
+            MessageSection::SnapshotAtH2C => (types::message_error::MessageSection::SnapshotAtH2C, 0),
+            MessageSection::SnapshotAtH3C => (types::message_error::MessageSection::SnapshotAtH3C, 0),
+            MessageSection::SnapshotAtH4C => (types::message_error::MessageSection::SnapshotAtH4C, 0),
+            MessageSection::DiffAtTip => (types::message_error::MessageSection::DiffAtTip, 0),
+            MessageSection::DiffAtH => (types::message_error::MessageSection::DiffAtH, 0),
+            MessageSection::DiffAtHC => (types::message_error::MessageSection::DiffAtHC, 0),
+            MessageSection::DiffAtH2C => (types::message_error::MessageSection::DiffAtH2C, 0),
+            MessageSection::DiffAtH3C => (types::message_error::MessageSection::DiffAtH3C, 0),
+            MessageSection::DiffAtH4C => (types::message_error::MessageSection::DiffAtH4C, 0),
+            MessageSection::ExtraShare => (types::message_error::MessageSection::ExtraShare, 0),
+            MessageSection::LastQuorumPerIndex(index) => (types::message_error::MessageSection::LastQuorumPerIndex, index),
+            MessageSection::QuorumSnapshotList(index) => (types::message_error::MessageSection::QuorumSnapshotList, index),
+            MessageSection::MNListDiffList(index) => (types::message_error::MessageSection::MNListDiffList, index),
+            MessageSection::InstantLock => (types::message_error::MessageSection::InstantLock, 0),
+            MessageSection::ChainLock => (types::message_error::MessageSection::ChainLock, 0),
+        }
+    }
+}
+
+impl MessageField {
+    pub fn encode(self) -> (types::message_error::MessageField, usize) {
+        match self {
+            MessageField::Version => (types::message_error::MessageField::Version, 0),
+            MessageField::BaseBlockHash => (types::message_error::MessageField::BaseBlockHash, 0),
+            MessageField::BlockHash => (types::message_error::MessageField::BlockHash, 0),
+            MessageField::BlockHeight => (types::message_error::MessageField::BlockHeight, 0),
+            MessageField::TotalTransactions => (types::message_error::MessageField::TotalTransactions, 0),
+            MessageField::MerkleHashes => (types::message_error::MessageField::MerkleHashes, 0),
+            MessageField::MerkleFlags => (types::message_error::MessageField::MerkleFlags, 0),
+            MessageField::CoinbaseTransaction => (types::message_error::MessageField::CoinbaseTransaction, 0),
+            MessageField::DeletedMasternodes => (types::message_error::MessageField::DeletedMasternodes, 0),
+            MessageField::AddedMasternodes => (types::message_error::MessageField::AddedMasternodes, 0),
+            MessageField::AddedMasternode(index) => (types::message_error::MessageField::AddedMasternode, index),
+            MessageField::DeletedQuorums => (types::message_error::MessageField::DeletedQuorums, 0),
+            MessageField::AddedQuorums => (types::message_error::MessageField::AddedQuorums, 0),
+            MessageField::AddedQuorum(index) => (types::message_error::MessageField::AddedQuorum, index),
+            MessageField::QuorumsCLSigs => (types::message_error::MessageField::QuorumsCLSigs, 0),
+            MessageField::Snapshot => (types::message_error::MessageField::Snapshot, 0),
+            MessageField::QuorumEntry => (types::message_error::MessageField::QuorumEntry, 0),
+            MessageField::Count => (types::message_error::MessageField::Count, 0),
+            MessageField::Flag => (types::message_error::MessageField::Flag, 0),
+            MessageField::Inputs => (types::message_error::MessageField::Inputs, 0),
+            MessageField::TransactionHash => (types::message_error::MessageField::TransactionHash, 0),
+            MessageField::CycleHash => (types::message_error::MessageField::CycleHash, 0),
+            MessageField::Signature => (types::message_error::MessageField::Signature, 0),
+        }
+    }
+}
+
+impl MessageError {
+    pub fn parse(section: MessageSection, field: MessageField, offset: usize, block_hash: Option<UInt256>) -> Self {
+        MessageError::Parse { section, field, offset, block_hash }
+    }
+
+    /// Relocates error of the nested diff into the section of the enclosing message
+    pub fn in_section(self, section: MessageSection) -> Self {
+        match self {
+            MessageError::Parse { field, offset, block_hash, .. } =>
+                MessageError::Parse { section, field, offset, block_hash },
+            MessageError::Rejected { base_block_hash, block_hash, error, .. } =>
+                MessageError::Rejected { section, base_block_hash, block_hash, error },
+            error => error,
+        }
+    }
+
+    pub fn section(&self) -> Option<MessageSection> {
+        match self {
+            MessageError::Parse { section, .. } |
+            MessageError::Rejected { section, .. } => Some(*section),
+            MessageError::SnapshotCountMismatch { .. } => None,
+        }
+    }
+
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            MessageError::Parse { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self) -> types::MessageError {
+        let (section, section_index) = self.section().map_or((types::message_error::MessageSection::None, 0), MessageSection::encode);
+        let mut error = types::MessageError {
+            kind: MessageErrorKind::Parse,
+            section,
+            section_index,
+            field: types::message_error::MessageField::None,
+            field_index: 0,
+            offset: self.offset().unwrap_or(0),
+            block_hash: self.block_hash().map_or(null_mut(), |hash| boxed(hash.0)),
+            base_block_hash: null_mut(),
+            processing_error: ProcessingError::from(self),
+            snapshots_count: 0,
+            diffs_count: 0,
+        };
+        match self {
+            MessageError::Parse { field, .. } => {
+                (error.field, error.field_index) = field.encode();
+            },
+            MessageError::SnapshotCountMismatch { snapshots, diffs } => {
+                error.kind = MessageErrorKind::SnapshotCountMismatch;
+                error.snapshots_count = *snapshots;
+                error.diffs_count = *diffs;
+            },
+            MessageError::Rejected { base_block_hash, .. } => {
+                error.kind = MessageErrorKind::Rejected;
+                error.base_block_hash = boxed(base_block_hash.0);
+            },
+        }
+        error
+    }
+
+    pub fn block_hash(&self) -> Option<UInt256> {
+        match self {
+            MessageError::Parse { block_hash, .. } => *block_hash,
+            MessageError::Rejected { block_hash, .. } => Some(*block_hash),
+            MessageError::SnapshotCountMismatch { .. } => None,
+        }
+    }
+}
+
+/// Reads the field and reports its position in the message on failure
+pub(crate) fn read_field<T>(
+    offset: &mut usize,
+    section: MessageSection,
+    field: MessageField,
+    block_hash: Option<UInt256>,
+    read: impl FnOnce(&mut usize) -> Option<T>,
+) -> Result<T, MessageError> {
+    let start = *offset;
+    read(offset).ok_or(MessageError::parse(section, field, start, block_hash))
+}
+
+impl std::fmt::Display for MessageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageError::Parse { section, field, offset, block_hash: Some(block_hash) } =>
+                write!(f, "Can't read {:?} of {:?} for block {} at offset {}", field, section, block_hash, offset),
+            MessageError::Parse { section, field, offset, block_hash: None } =>
+                write!(f, "Can't read {:?} of {:?} at offset {}", field, section, offset),
+            MessageError::SnapshotCountMismatch { snapshots, diffs } =>
+                write!(f, "Number of snapshots ({}) should be equal to number of diffs ({})", snapshots, diffs),
+            MessageError::Rejected { section, base_block_hash, block_hash, error } =>
+                write!(f, "{:?} {}..{} is rejected: {:?}", section, base_block_hash, block_hash, error),
+        }
+    }
+}
+
+impl std::error::Error for MessageError {}
+
+impl From<&MessageError> for ProcessingError {
+    fn from(error: &MessageError) -> Self {
+        match error {
+            MessageError::Parse { .. } |
+            MessageError::SnapshotCountMismatch { .. } => ProcessingError::ParseError,
+            MessageError::Rejected { error, .. } => *error,
+        }
+    }
+}
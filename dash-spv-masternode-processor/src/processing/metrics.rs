@@ -0,0 +1,211 @@
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use crate::processing::LLMQValidationStatus;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum ValidationFailureReason {
+    InvalidPayload = 0,
+    InvalidAggregatedSignature = 1,
+    InvalidQuorumSignature = 2,
+    NoMasternodeList = 3,
+    MissingRotationData = 4,
+}
+
+impl ValidationFailureReason {
+    pub fn from_status(status: &LLMQValidationStatus) -> Option<Self> {
+        match status {
+            LLMQValidationStatus::Verified => None,
+            LLMQValidationStatus::InvalidPayload(..) => Some(Self::InvalidPayload),
+            LLMQValidationStatus::InvalidAggregatedSignature => Some(Self::InvalidAggregatedSignature),
+            LLMQValidationStatus::InvalidQuorumSignature => Some(Self::InvalidQuorumSignature),
+            LLMQValidationStatus::NoMasternodeList => Some(Self::NoMasternodeList),
+            LLMQValidationStatus::MissingRotationData(..) => Some(Self::MissingRotationData),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum CacheKind {
+    MasternodeList = 0,
+    Snapshot = 1,
+    CLSignature = 2,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Metric {
+    ListDiffProcessed,
+    QRInfoProcessed,
+    QuorumValidated,
+    QuorumValidationFailed(ValidationFailureReason),
+    /// Time spent to compute members of the rotated quorum
+    RotationTime(Duration),
+    /// Time spent to verify signatures of the quorums of the message
+    BLSVerificationTime(Duration),
+    /// Lookup in the processor cache (miss means the data was requested from the provider)
+    CacheLookup {
+        kind: CacheKind,
+        hit: bool,
+    },
+}
+
+/// Kind of the metric for FFI callback, the meaning of the value depends on the kind
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum MetricKind {
+    ListDiffProcessed = 0,
+    QRInfoProcessed = 1,
+    QuorumValidated = 2,
+    /// Value is the ValidationFailureReason
+    QuorumValidationFailed = 3,
+    /// Value is in microseconds
+    RotationTime = 4,
+    /// Value is in microseconds
+    BLSVerificationTime = 5,
+    /// Value is the CacheKind
+    CacheHit = 6,
+    /// Value is the CacheKind
+    CacheMiss = 7,
+}
+
+impl Metric {
+    pub fn kind_and_value(&self) -> (MetricKind, u64) {
+        match *self {
+            Metric::ListDiffProcessed => (MetricKind::ListDiffProcessed, 1),
+            Metric::QRInfoProcessed => (MetricKind::QRInfoProcessed, 1),
+            Metric::QuorumValidated => (MetricKind::QuorumValidated, 1),
+            Metric::QuorumValidationFailed(reason) => (MetricKind::QuorumValidationFailed, reason as u64),
+            Metric::RotationTime(duration) => (MetricKind::RotationTime, duration.as_micros() as u64),
+            Metric::BLSVerificationTime(duration) => (MetricKind::BLSVerificationTime, duration.as_micros() as u64),
+            Metric::CacheLookup { kind, hit: true } => (MetricKind::CacheHit, kind as u64),
+            Metric::CacheLookup { kind, hit: false } => (MetricKind::CacheMiss, kind as u64),
+        }
+    }
+}
+
+/// Receiver of the processing metrics
+pub trait MetricsSink: Send + Sync {
+    fn record(&self, metric: Metric);
+    /// Accumulated values if the sink keeps them
+    fn snapshot(&self) -> Option<MetricsSnapshot> {
+        None
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MetricsSnapshot {
+    pub list_diffs_processed: u64,
+    pub qr_infos_processed: u64,
+    pub quorums_validated: u64,
+    pub failures_invalid_payload: u64,
+    pub failures_invalid_aggregated_signature: u64,
+    pub failures_invalid_quorum_signature: u64,
+    pub failures_no_masternode_list: u64,
+    pub failures_missing_rotation_data: u64,
+    pub rotation_time_us: u64,
+    pub bls_verification_time_us: u64,
+    pub masternode_list_cache_hits: u64,
+    pub masternode_list_cache_misses: u64,
+    pub snapshot_cache_hits: u64,
+    pub snapshot_cache_misses: u64,
+    pub cl_signature_cache_hits: u64,
+    pub cl_signature_cache_misses: u64,
+}
+
+fn hit_ratio(hits: u64, misses: u64) -> f64 {
+    if hits + misses == 0 {
+        0.0
+    } else {
+        hits as f64 / (hits + misses) as f64
+    }
+}
+
+impl MetricsSnapshot {
+    pub fn masternode_list_cache_hit_ratio(&self) -> f64 {
+        hit_ratio(self.masternode_list_cache_hits, self.masternode_list_cache_misses)
+    }
+    pub fn snapshot_cache_hit_ratio(&self) -> f64 {
+        hit_ratio(self.snapshot_cache_hits, self.snapshot_cache_misses)
+    }
+    pub fn cl_signature_cache_hit_ratio(&self) -> f64 {
+        hit_ratio(self.cl_signature_cache_hits, self.cl_signature_cache_misses)
+    }
+}
+
+/// Sink accumulating metrics in counters
+#[derive(Debug, Default)]
+pub struct ProcessorMetrics {
+    list_diffs_processed: AtomicU64,
+    qr_infos_processed: AtomicU64,
+    quorums_validated: AtomicU64,
+    failures: [AtomicU64; 5],
+    rotation_time_us: AtomicU64,
+    bls_verification_time_us: AtomicU64,
+    cache_hits: [AtomicU64; 3],
+    cache_misses: [AtomicU64; 3],
+}
+
+impl MetricsSink for ProcessorMetrics {
+    fn record(&self, metric: Metric) {
+        let counter = match metric {
+            Metric::ListDiffProcessed => &self.list_diffs_processed,
+            Metric::QRInfoProcessed => &self.qr_infos_processed,
+            Metric::QuorumValidated => &self.quorums_validated,
+            Metric::QuorumValidationFailed(reason) => &self.failures[reason as usize],
+            Metric::RotationTime(..) => &self.rotation_time_us,
+            Metric::BLSVerificationTime(..) => &self.bls_verification_time_us,
+            Metric::CacheLookup { kind, hit: true } => &self.cache_hits[kind as usize],
+            Metric::CacheLookup { kind, hit: false } => &self.cache_misses[kind as usize],
+        };
+        let value = match metric {
+            Metric::RotationTime(duration) | Metric::BLSVerificationTime(duration) => duration.as_micros() as u64,
+            _ => 1,
+        };
+        counter.fetch_add(value, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Option<MetricsSnapshot> {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Some(MetricsSnapshot {
+            list_diffs_processed: load(&self.list_diffs_processed),
+            qr_infos_processed: load(&self.qr_infos_processed),
+            quorums_validated: load(&self.quorums_validated),
+            failures_invalid_payload: load(&self.failures[ValidationFailureReason::InvalidPayload as usize]),
+            failures_invalid_aggregated_signature: load(&self.failures[ValidationFailureReason::InvalidAggregatedSignature as usize]),
+            failures_invalid_quorum_signature: load(&self.failures[ValidationFailureReason::InvalidQuorumSignature as usize]),
+            failures_no_masternode_list: load(&self.failures[ValidationFailureReason::NoMasternodeList as usize]),
+            failures_missing_rotation_data: load(&self.failures[ValidationFailureReason::MissingRotationData as usize]),
+            rotation_time_us: load(&self.rotation_time_us),
+            bls_verification_time_us: load(&self.bls_verification_time_us),
+            masternode_list_cache_hits: load(&self.cache_hits[CacheKind::MasternodeList as usize]),
+            masternode_list_cache_misses: load(&self.cache_misses[CacheKind::MasternodeList as usize]),
+            snapshot_cache_hits: load(&self.cache_hits[CacheKind::Snapshot as usize]),
+            snapshot_cache_misses: load(&self.cache_misses[CacheKind::Snapshot as usize]),
+            cl_signature_cache_hits: load(&self.cache_hits[CacheKind::CLSignature as usize]),
+            cl_signature_cache_misses: load(&self.cache_misses[CacheKind::CLSignature as usize]),
+        })
+    }
+}
+
+pub type MetricCallback = unsafe extern "C" fn(kind: MetricKind, value: u64, context: *const c_void);
+
+/// Sink forwarding metrics to the FFI host
+#[derive(Debug)]
+pub struct FFIMetricsSink {
+    pub callback: MetricCallback,
+    pub context: *const c_void,
+}
+
+// The host is responsible for the callback to be callable from any thread along with its context
+unsafe impl Send for FFIMetricsSink {}
+unsafe impl Sync for FFIMetricsSink {}
+
+impl MetricsSink for FFIMetricsSink {
+    fn record(&self, metric: Metric) {
+        let (kind, value) = metric.kind_and_value();
+        unsafe { (self.callback)(kind, value, self.context) }
+    }
+}
@@ -1,9 +1,11 @@
+use std::ptr::null_mut;
 use std::collections::BTreeMap;
 use crate::{models, types};
 use crate::crypto::{UInt256, UInt768};
 use crate::ffi::boxer::{boxed, boxed_vec};
 use crate::ffi::to::{encode_masternodes_map, ToFFI};
-use crate::processing::ProcessingError;
+use crate::processing::{ProcessingError, QuorumCommitmentCheck, QuorumValidationReport};
+use crate::tx::QuorumCommitmentTransaction;
 
 pub struct MNListDiffResult {
     pub error_status: ProcessingError,
@@ -19,7 +21,10 @@ pub struct MNListDiffResult {
     pub modified_masternodes: BTreeMap<UInt256, models::MasternodeEntry>,
     pub added_quorums: Vec<models::LLMQEntry>,
     pub needed_masternode_lists: Vec<UInt256>,
+    pub needed_block_heights: Vec<u32>,
+    pub needed_snapshots: Vec<UInt256>,
     pub cl_signatures: BTreeMap<UInt256, UInt768>,
+    pub quorum_validation_reports: Vec<QuorumValidationReport>,
 }
 
 impl std::fmt::Debug for MNListDiffResult {
@@ -44,6 +49,9 @@ impl std::fmt::Debug for MNListDiffResult {
             .field("modified_masternodes", &self.modified_masternodes)
             .field("added_quorums", &self.added_quorums)
             .field("needed_masternode_lists", &self.needed_masternode_lists)
+            .field("needed_block_heights", &self.needed_block_heights)
+            .field("needed_snapshots", &self.needed_snapshots)
+            .field("quorum_validation_reports", &self.quorum_validation_reports)
             .finish()
     }
 }
@@ -64,7 +72,10 @@ impl Default for MNListDiffResult {
             modified_masternodes: Default::default(),
             added_quorums: vec![],
             needed_masternode_lists: vec![],
-            cl_signatures: Default::default()
+            needed_block_heights: vec![],
+            needed_snapshots: vec![],
+            cl_signatures: Default::default(),
+            quorum_validation_reports: vec![],
         }
     }
 }
@@ -73,12 +84,52 @@ impl MNListDiffResult {
     pub fn default_with_error(error: ProcessingError) -> Self {
         Self {error_status: error, ..Default::default()}
     }
+
+    /// Reports of the quorums which failed validation
+    pub fn invalid_quorums(&self) -> impl Iterator<Item = &QuorumValidationReport> {
+        self.quorum_validation_reports.iter().filter(|report| !report.is_not_critical())
+    }
+
+    /// Cross-check the commitments mined in the block against the quorums added by the diff,
+    /// null commitments are skipped as they don't add quorums.
+    /// Quorums added by the diff without a mined commitment are reported after the commitments,
+    /// but only when the diff covers exactly the block: wider diffs add the quorums mined in the other blocks too
+    pub fn cross_check_quorum_commitments(&self, commitments: &[QuorumCommitmentTransaction], base_block_height: u32, block_height: u32) -> Vec<QuorumCommitmentCheck> {
+        let is_same_quorum = |entry: &models::LLMQEntry, mined: &models::LLMQEntry|
+            entry.llmq_type == mined.llmq_type && entry.llmq_hash == mined.llmq_hash && entry.index == mined.index;
+        let mined_commitments = commitments.iter()
+            .filter(|commitment| !commitment.is_null())
+            .collect::<Vec<_>>();
+        let mut checks = mined_commitments.iter()
+            .map(|commitment| {
+                let diff_entry_hash = self.added_quorums.iter()
+                    .find(|entry| is_same_quorum(entry, &commitment.quorum_entry))
+                    .map(|entry| entry.entry_hash);
+                QuorumCommitmentCheck::new(commitment, diff_entry_hash)
+            })
+            .collect::<Vec<_>>();
+        if base_block_height.checked_add(1) != Some(block_height) {
+            return checks;
+        }
+        checks.extend(self.added_quorums.iter()
+            .filter(|entry| !mined_commitments.iter().any(|commitment| is_same_quorum(entry, &commitment.quorum_entry)))
+            .map(QuorumCommitmentCheck::not_mined));
+        checks
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.has_found_coinbase
+            && self.has_valid_quorums
+            && self.has_valid_mn_list_root
+            && self.has_valid_llmq_list_root
+    }
 }
 
 impl MNListDiffResult {
     pub fn encode(&self) -> types::MNListDiffResult {
         types::MNListDiffResult {
             error_status: self.error_status.into(),
+            message_error: null_mut(),
             base_block_hash: boxed(self.base_block_hash.0),
             block_hash: boxed(self.block_hash.0),
             has_found_coinbase: self.has_found_coinbase,
@@ -103,6 +154,15 @@ impl MNListDiffResult {
                     .collect(),
             ),
             needed_masternode_lists_count: self.needed_masternode_lists.len(),
+            needed_block_heights: boxed_vec(self.needed_block_heights.clone()),
+            needed_block_heights_count: self.needed_block_heights.len(),
+            needed_snapshots: boxed_vec(
+                self.needed_snapshots
+                    .iter()
+                    .map(|h| boxed(h.0))
+                    .collect(),
+            ),
+            needed_snapshots_count: self.needed_snapshots.len(),
             quorums_cl_sigs_count: self.cl_signatures.len(),
             quorums_cl_signatures_hashes: boxed_vec(
                 self.cl_signatures
@@ -113,7 +173,13 @@ impl MNListDiffResult {
                 self.cl_signatures
                     .values()
                     .map(|h| boxed(h.0))
-                    .collect())
+                    .collect()),
+            quorum_validation_reports: boxed_vec(
+                self.quorum_validation_reports
+                    .iter()
+                    .map(|report| boxed(report.encode()))
+                    .collect()),
+            quorum_validation_reports_count: self.quorum_validation_reports.len(),
         }
     }
 }
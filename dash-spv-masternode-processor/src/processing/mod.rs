@@ -1,14 +1,36 @@
+pub mod cache_policy;
+pub mod checkpoint_error;
+pub mod core_provider;
+pub mod masternode_payment_validation_report;
+pub mod message_error;
+pub mod metrics;
 pub mod mn_listdiff_result;
 pub mod processing_error;
 pub mod processor;
 pub mod processor_cache;
 pub mod qr_info_result;
+pub mod quorum_commitment_check;
+pub mod quorum_membership;
+pub mod quorum_validation_report;
+pub mod rotation_diagnostics;
+pub mod rotation_error;
 pub mod keys_cache;
 pub mod llmq_validation_status;
 
+pub use self::cache_policy::CachePolicy;
+pub use self::checkpoint_error::CheckpointError;
+pub use self::core_provider::CoreProvider;
 pub use self::llmq_validation_status::LLMQValidationStatus;
+pub use self::masternode_payment_validation_report::{MasternodePaymentValidationReport, MasternodePaymentValidationStatus};
+pub use self::message_error::{MessageError, MessageField, MessageSection};
+pub use self::metrics::{Metric, MetricsSink, MetricsSnapshot, ProcessorMetrics};
 pub use self::mn_listdiff_result::MNListDiffResult;
 pub use self::processing_error::ProcessingError;
 pub use self::processor::MasternodeProcessor;
 pub use self::processor_cache::MasternodeProcessorCache;
 pub use self::qr_info_result::QRInfoResult;
+pub use self::quorum_commitment_check::{QuorumCommitmentCheck, QuorumCommitmentCheckStatus};
+pub use self::quorum_membership::QuorumMembership;
+pub use self::quorum_validation_report::QuorumValidationReport;
+pub use self::rotation_diagnostics::{QuarterDiagnostics, RotationDiagnostics};
+pub use self::rotation_error::RotationError;
@@ -1,90 +1,82 @@
-use std::collections::{BTreeMap, HashSet};
-use std::ptr::null;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Instant;
 use crate::{common, models, types};
 use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType, LLMQParams};
+use crate::common::{target, SignatureValidationStatus};
 use crate::crypto::{byte_util::{Reversable, Zeroable}, UInt256, UInt768};
-use crate::ffi::boxer::boxed;
-use crate::ffi::callbacks;
-use crate::ffi::callbacks::{AddInsightBlockingLookup, GetBlockHashByHeight, GetBlockHeightByHash, GetCLSignatureByBlockHash, GetLLMQSnapshotByBlockHash, HashDestroy, LLMQSnapshotDestroy, MasternodeListDestroy, MasternodeListLookup, MasternodeListSave, MerkleRootLookup, SaveCLSignature, SaveLLMQSnapshot, ShouldProcessDiffWithRange};
-use crate::ffi::to::ToFFI;
-use crate::models::{LLMQModifierType, LLMQVerificationContext};
-use crate::processing::{LLMQValidationStatus, MasternodeProcessorCache, MNListDiffResult, ProcessingError};
-
-// https://github.com/rust-lang/rfcs/issues/2770
-#[repr(C)]
-pub struct MasternodeProcessor {
-    /// External Masternode Manager Diff Message Context
-    pub opaque_context: *const std::ffi::c_void,
+use crate::ffi::callbacks::FFICoreProvider;
+use crate::models::{ChainLockValidationReport, InstantLockValidationReport, LLMQModifierType, LLMQVerificationContext};
+use crate::tx::{AssetUnlockTransaction, CoinbaseTransaction};
+use crate::processing::rotation_diagnostics::{QuarterDiagnostics, RotationDiagnostics};
+use crate::processing::metrics::{CacheKind, Metric, MetricsSink, ValidationFailureReason};
+use crate::processing::{CheckpointError, CoreProvider, LLMQValidationStatus, MasternodePaymentValidationReport, MasternodePaymentValidationStatus, MasternodeProcessorCache, MessageError, MessageSection, MNListDiffResult, ProcessingError, QRInfoResult, QuorumMembership, QuorumValidationReport, RotationError};
+
+/// Height of the quorum block and valid members of the quorum
+/// (or the status of the quorum if its members can't be determined yet)
+type QuorumMembers = Result<(u32, Vec<models::MasternodeEntry>), LLMQValidationStatus>;
+
+pub struct MasternodeProcessor<T: CoreProvider = FFICoreProvider> {
+    /// Storage backend which provides block index, masternode lists, snapshots and CL signatures
+    pub provider: T,
     pub chain_type: ChainType,
     pub use_insight_as_backup: bool,
-    pub get_block_height_by_hash: GetBlockHeightByHash,
-    pub get_merkle_root_by_hash: MerkleRootLookup,
-    get_block_hash_by_height: GetBlockHashByHeight,
-    get_llmq_snapshot_by_block_hash: GetLLMQSnapshotByBlockHash,
-    get_cl_signature_by_block_hash: GetCLSignatureByBlockHash,
-    save_llmq_snapshot: SaveLLMQSnapshot,
-    save_cl_signature: SaveCLSignature,
-    get_masternode_list_by_block_hash: MasternodeListLookup,
-    save_masternode_list: MasternodeListSave,
-    destroy_masternode_list: MasternodeListDestroy,
-    add_insight: AddInsightBlockingLookup,
-    destroy_hash: HashDestroy,
-    destroy_snapshot: LLMQSnapshotDestroy,
-    should_process_diff_with_range: ShouldProcessDiffWithRange,
+    /// Number of threads used to verify signatures of the added quorums (1 means sequential verification)
+    pub quorum_verification_threads: usize,
+    /// Optional receiver of the processing metrics
+    pub metrics: Option<Arc<dyn MetricsSink>>,
 }
-impl std::fmt::Debug for MasternodeProcessor {
+impl<T: CoreProvider> std::fmt::Debug for MasternodeProcessor<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("MasternodeProcessor")
-            .field("context", &self.opaque_context)
+            .field("provider", &self.provider)
+            .field("chain_type", &self.chain_type)
+            .field("quorum_verification_threads", &self.quorum_verification_threads)
+            .field("metrics", &self.metrics.is_some())
             .finish()
     }
 }
 
-impl MasternodeProcessor {
-    #[allow(clippy::too_many_arguments)]
-    pub fn new(
-        get_merkle_root_by_hash: MerkleRootLookup,
-        get_block_height_by_hash: GetBlockHeightByHash,
-        get_block_hash_by_height: GetBlockHashByHeight,
-        get_llmq_snapshot_by_block_hash: GetLLMQSnapshotByBlockHash,
-        save_llmq_snapshot: SaveLLMQSnapshot,
-        get_cl_signature_by_block_hash: GetCLSignatureByBlockHash,
-        save_cl_signature: SaveCLSignature,
-        get_masternode_list_by_block_hash: MasternodeListLookup,
-        save_masternode_list: MasternodeListSave,
-        destroy_masternode_list: MasternodeListDestroy,
-        add_insight: AddInsightBlockingLookup,
-        destroy_hash: HashDestroy,
-        destroy_snapshot: LLMQSnapshotDestroy,
-        should_process_diff_with_range: ShouldProcessDiffWithRange,
-    ) -> Self {
+impl<T: CoreProvider> MasternodeProcessor<T> {
+    pub fn new(provider: T) -> Self {
         Self {
-            get_merkle_root_by_hash,
-            get_block_height_by_hash,
-            get_block_hash_by_height,
-            get_llmq_snapshot_by_block_hash,
-            save_llmq_snapshot,
-            get_cl_signature_by_block_hash,
-            save_cl_signature,
-            get_masternode_list_by_block_hash,
-            save_masternode_list,
-            destroy_masternode_list,
-            add_insight,
-            destroy_hash,
-            destroy_snapshot,
-            should_process_diff_with_range,
-            opaque_context: null(),
+            provider,
             chain_type: ChainType::MainNet,
             use_insight_as_backup: false,
+            quorum_verification_threads: 1,
+            metrics: None,
+        }
+    }
+
+    pub(crate) fn record_metric(&self, metric: Metric) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record(metric);
+        }
+    }
+
+    fn record_cache_lookup(&self, kind: CacheKind, hit: bool) {
+        self.record_metric(Metric::CacheLookup { kind, hit });
+    }
+
+    fn record_validation_report(&self, report: &QuorumValidationReport) {
+        match ValidationFailureReason::from_status(&report.status) {
+            None => self.record_metric(Metric::QuorumValidated),
+            Some(reason) => {
+                debug!(target: target::QUORUM, "quorum validation failed llmq_type={:?} llmq_hash={} reason={:?}", report.llmq_type, report.llmq_hash, reason);
+                self.record_metric(Metric::QuorumValidationFailed(reason));
+            }
         }
     }
 
+    /// Cached lists are shared: callers don't pay for the copy of the list
     pub(crate) fn find_masternode_list(
         &self,
         block_hash: UInt256,
-        cached_lists: &BTreeMap<UInt256, models::MasternodeList>,
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
         unknown_lists: &mut Vec<UInt256>,
-    ) -> Option<models::MasternodeList> {
+    ) -> Option<Arc<models::MasternodeList>> {
         let genesis_hash = self.chain_type.genesis_hash();
         if block_hash.is_zero() {
             // If it's a zero block we don't expect models list here
@@ -93,16 +85,17 @@ impl MasternodeProcessor {
         } else if block_hash.eq(&genesis_hash) {
             // If it's a genesis block we don't expect models list here
             // println!("find {}: {} It's a genesis -> Some(EMPTY MNL)", self.lookup_block_height_by_hash(block_hash), block_hash);
-            Some(models::MasternodeList::new(BTreeMap::default(), BTreeMap::default(), block_hash, self.lookup_block_height_by_hash(block_hash), false))
+            Some(Arc::new(models::MasternodeList::new(BTreeMap::default(), BTreeMap::default(), block_hash, self.lookup_block_height_by_hash(block_hash), false)))
             // None
         } else if let Some(cached) = cached_lists.get(&block_hash) {
             // Getting it from local cache stored as opaque in FFI context
             // println!("find_masternode_list (cache) {}: {} -> Some({:?})", self.lookup_block_height_by_hash(block_hash), block_hash, cached);
-            Some(cached.clone())
-        } else if let Some(looked) = self.lookup_masternode_list(block_hash) {
+            self.record_cache_lookup(CacheKind::MasternodeList, true);
+            Some(Arc::clone(cached))
+        } else if let Some(looked) = self.record_cache_miss(CacheKind::MasternodeList, || self.lookup_masternode_list(block_hash)) {
             // Getting it from FFI directly
             // println!("find_masternode_list {}: {} (ffi) -> Some({:?})", self.lookup_block_height_by_hash(block_hash), block_hash, looked);
-            Some(looked)
+            Some(Arc::new(looked))
         } else {
             // println!("find {}: {} Unknown -> None", self.lookup_block_height_by_hash(block_hash), block_hash);
             if self.lookup_block_height_by_hash(block_hash) != u32::MAX {
@@ -123,9 +116,10 @@ impl MasternodeProcessor {
     ) -> Option<models::LLMQSnapshot> {
         if let Some(cached) = cached_snapshots.get(&block_hash) {
             // Getting it from local cache stored as opaque in FFI context
+            self.record_cache_lookup(CacheKind::Snapshot, true);
             Some(cached.clone())
         } else {
-            self.lookup_snapshot_by_block_hash(block_hash)
+            self.record_cache_miss(CacheKind::Snapshot, || self.lookup_snapshot_by_block_hash(block_hash))
         }
     }
 
@@ -135,10 +129,10 @@ impl MasternodeProcessor {
                 if let Some(best_cl_signature) = self.find_cl_signature(work_block_hash, cached_cl_signatures) {
                     return LLMQModifierType::CoreV20(llmq_type, work_block_height, best_cl_signature);
                 } else {
-                    println!("llmq_modifier_type: clsig not found for block hash: {} ({})", work_block_hash, work_block_hash.reversed());
+                    warn!(target: target::QUORUM, "llmq modifier: clsig not found block_hash={} block_hash_reversed={}", work_block_hash, work_block_hash.reversed());
                 }
             } else {
-                println!("llmq_modifier_type: block not found for height: {}", work_block_height);
+                warn!(target: target::QUORUM, "llmq modifier: block not found block_height={}", work_block_height);
             }
         }
         LLMQModifierType::PreCoreV20(llmq_type, work_block_hash)
@@ -151,12 +145,19 @@ impl MasternodeProcessor {
     ) -> Option<UInt768> {
         if let Some(cached) = cached_cl_signatures.get(&block_hash) {
             // Getting it from local cache stored as opaque in FFI context
+            self.record_cache_lookup(CacheKind::CLSignature, true);
             Some(cached.clone())
         } else {
-            self.lookup_cl_signature_by_block_hash(block_hash)
+            self.record_cache_miss(CacheKind::CLSignature, || self.lookup_cl_signature_by_block_hash(block_hash))
         }
     }
 
+    /// Data missing in the cache is requested from the provider
+    fn record_cache_miss<R, F: FnOnce() -> Option<R>>(&self, kind: CacheKind, lookup: F) -> Option<R> {
+        self.record_cache_lookup(kind, false);
+        lookup()
+    }
+
 
     pub(crate) fn get_list_diff_result_with_base_lookup(
         &self,
@@ -171,6 +172,7 @@ impl MasternodeProcessor {
             &cache.mn_lists,
             &mut cache.needed_masternode_lists,
         );
+        cache.touch(&base_block_hash);
         self.get_list_diff_result(base_list, list_diff, verification_context, cache)
     }
 
@@ -186,12 +188,13 @@ impl MasternodeProcessor {
             &cache.mn_lists,
             &mut cache.needed_masternode_lists,
         );
+        cache.touch(&list_diff.base_block_hash);
         self.get_list_diff_result_internal(base_list, list_diff, verification_context, cache)
     }
 
     pub(crate) fn get_list_diff_result(
         &self,
-        base_list: Option<models::MasternodeList>,
+        base_list: Option<Arc<models::MasternodeList>>,
         list_diff: models::MNListDiff,
         verification_context: LLMQVerificationContext,
         cache: &mut MasternodeProcessorCache,
@@ -218,7 +221,7 @@ impl MasternodeProcessor {
 
     pub(crate) fn get_list_diff_result_internal(
         &self,
-        base_list: Option<models::MasternodeList>,
+        base_list: Option<Arc<models::MasternodeList>>,
         list_diff: models::MNListDiff,
         verification_context: LLMQVerificationContext,
         cache: &mut MasternodeProcessorCache,
@@ -228,7 +231,8 @@ impl MasternodeProcessor {
         let block_hash = list_diff.block_hash;
         let block_height = list_diff.block_height;
         let quorums_cl_sigs = list_diff.quorums_cls_sigs;
-        let (base_masternodes, base_quorums) = match base_list {
+        // The entries of the base list are shared, only the maps are copied if the list is cached
+        let (base_masternodes, base_quorums) = match base_list.map(Arc::unwrap_or_clone) {
             Some(list) => (list.masternodes, list.quorums),
             None => (BTreeMap::new(), BTreeMap::new()),
         };
@@ -245,7 +249,7 @@ impl MasternodeProcessor {
         let (added_quorums,
             quorums,
             cl_signatures,
-            has_valid_quorums) = self.classify_quorums(
+            quorum_validation_reports) = self.classify_quorums(
             base_quorums,
             list_diff.added_quorums,
             list_diff.deleted_quorums,
@@ -267,13 +271,18 @@ impl MasternodeProcessor {
             flags: list_diff.merkle_flags.as_slice(),
         };
         self.cache_masternode_list(block_hash, masternode_list.clone(), cache);
-        let needed_masternode_lists = cache.needed_masternode_lists.clone();
-        cache.needed_masternode_lists.clear();
+        let needed_masternode_lists = std::mem::take(&mut cache.needed_masternode_lists);
+        let needed_block_heights = std::mem::take(&mut cache.needed_block_heights);
+        let needed_snapshots = std::mem::take(&mut cache.needed_snapshots);
         let has_found_coinbase = coinbase_transaction.has_found_coinbase(&merkle_tree.hashes);
         let desired_merkle_root = self.lookup_merkle_root_by_hash(block_hash).unwrap_or(UInt256::MIN);
         let has_valid_coinbase = merkle_tree.has_root(desired_merkle_root);
         let has_valid_mn_list_root = masternode_list.has_valid_mn_list_root(&coinbase_transaction);
         let has_valid_llmq_list_root = !quorums_active || masternode_list.has_valid_llmq_list_root(&coinbase_transaction);
+        let has_valid_quorums = quorum_validation_reports.iter().all(QuorumValidationReport::is_not_critical);
+        if let Some(change) = cache.credit_pool.record_coinbase(&coinbase_transaction) {
+            debug!(target: target::LIST_DIFF, "credit pool balance={} diff={} since block_height={}", change.balance, change.diff(), change.base_block_height);
+        }
         let result = MNListDiffResult {
             error_status: ProcessingError::None,
             base_block_hash,
@@ -288,14 +297,282 @@ impl MasternodeProcessor {
             modified_masternodes,
             added_quorums,
             needed_masternode_lists,
+            needed_block_heights,
+            needed_snapshots,
             cl_signatures,
+            quorum_validation_reports,
         };
+        debug!(target: target::LIST_DIFF, "processed base_block_hash={} block_hash={} block_height={} added_masternodes={} added_quorums={} has_valid_mn_list_root={} has_valid_llmq_list_root={} has_valid_quorums={}",
+            base_block_hash, block_hash, block_height, result.added_masternodes.len(), result.added_quorums.len(), has_valid_mn_list_root, has_valid_llmq_list_root, has_valid_quorums);
+        self.record_metric(Metric::ListDiffProcessed);
         result
     }
 
+    /// Verifies InstantSend lock against the quorums active in the masternode list it was signed with.
+    /// For 'isdlock' it's the list at the last block of the cycle started at `cycle_hash`
+    /// (or the latest list if the cycle isn't finished yet), legacy 'islock' is verified against the latest list
+    pub fn verify_instant_lock(
+        &self,
+        instant_lock: &models::InstantLock,
+        cache: &MasternodeProcessorCache,
+    ) -> InstantLockValidationReport {
+        let masternode_list = match instant_lock.cycle_hash {
+            Some(cycle_hash) => match self.lookup_block_height_by_hash(cycle_hash) {
+                u32::MAX => None,
+                cycle_height => instant_lock.signing_block_height(cycle_height, self.chain_type)
+                    .and_then(|block_height| match self.lookup_block_hash_by_height(block_height) {
+                        Some(block_hash) => self.find_masternode_list(block_hash, &cache.mn_lists, &mut vec![]),
+                        None => self.latest_masternode_list(cache)
+                            .filter(|list| list.known_height >= cycle_height)
+                            .cloned(),
+                    })
+            },
+            None => self.latest_masternode_list(cache).cloned(),
+        };
+        match masternode_list {
+            Some(masternode_list) =>
+                instant_lock.verify_with_masternode_list(&masternode_list, self.chain_type),
+            None => InstantLockValidationReport {
+                llmq_type: instant_lock.llmq_type(self.chain_type),
+                request_id: instant_lock.request_id(),
+                quorum_hash: None,
+                quorum_index: None,
+                sign_hash: None,
+                status: SignatureValidationStatus::NoMasternodeList,
+            }
+        }
+    }
+
+    /// The most recent of the cached lists (the tip as far as the processor knows)
+    fn latest_masternode_list<'a>(&self, cache: &'a MasternodeProcessorCache) -> Option<&'a Arc<models::MasternodeList>> {
+        cache.mn_lists.values().max_by_key(|list| list.known_height)
+    }
+
+    /// Verifies ChainLock against the quorums active in the masternode list
+    /// for the block 8 blocks below the locked one
+    pub fn verify_chain_lock(
+        &self,
+        chain_lock: &models::ChainLock,
+        cache: &MasternodeProcessorCache,
+    ) -> ChainLockValidationReport {
+        chain_lock.signing_block_height()
+            .and_then(|block_height| self.lookup_block_hash_by_height(block_height))
+            .and_then(|block_hash| self.find_masternode_list(block_hash, &cache.mn_lists, &mut vec![]))
+            .map_or_else(
+                || ChainLockValidationReport::new(chain_lock, self.chain_type, SignatureValidationStatus::NoMasternodeList),
+                |masternode_list| chain_lock.verify_with_masternode_list(&masternode_list, self.chain_type))
+    }
+
+    /// Verifies Platform-signed payload against the Platform quorum
+    /// from the masternode list at the core chain-locked height
+    pub fn verify_platform_signature(
+        &self,
+        quorum_hash: UInt256,
+        core_chain_locked_height: u32,
+        request_id: UInt256,
+        message_hash: UInt256,
+        signature: UInt768,
+        cache: &MasternodeProcessorCache,
+    ) -> SignatureValidationStatus {
+        self.lookup_block_hash_by_height(core_chain_locked_height)
+            .and_then(|block_hash| self.find_masternode_list(block_hash, &cache.mn_lists, &mut vec![]))
+            .map_or(SignatureValidationStatus::NoMasternodeList, |masternode_list|
+                masternode_list.verify_platform_signature(self.chain_type, quorum_hash, request_id, message_hash, signature))
+    }
+
+    /// Verifies the quorum signature of the asset unlock against the Platform quorum
+    /// from the masternode list at the height of the block which includes the transaction
+    pub fn verify_asset_unlock(
+        &self,
+        asset_unlock: &AssetUnlockTransaction,
+        block_height: u32,
+        cache: &MasternodeProcessorCache,
+    ) -> SignatureValidationStatus {
+        self.lookup_block_hash_by_height(block_height)
+            .and_then(|block_hash| self.find_masternode_list(block_hash, &cache.mn_lists, &mut vec![]))
+            .map_or(SignatureValidationStatus::NoMasternodeList, |masternode_list|
+                asset_unlock.verify_with_masternode_list(&masternode_list, self.chain_type))
+    }
+
+    /// Predicts the masternode payee of the block at height and its payments
+    /// from the masternode list at the previous block, `prev_bits` is the compact target of the previous block
+    /// (the subsidy depends on its difficulty), `fees` are the fees of the transactions in the block
+    pub fn predict_masternode_payments(
+        &self,
+        block_height: u32,
+        prev_bits: u32,
+        fees: u64,
+        payment_states: &BTreeMap<UInt256, models::MasternodePaymentState>,
+        cache: &MasternodeProcessorCache,
+    ) -> Result<(UInt256, Vec<models::MasternodePayment>), MasternodePaymentValidationStatus> {
+        let masternode_list = block_height.checked_sub(1)
+            .and_then(|height| self.lookup_block_hash_by_height(height))
+            .and_then(|block_hash| self.find_masternode_list(block_hash, &cache.mn_lists, &mut vec![]))
+            .ok_or(MasternodePaymentValidationStatus::NoMasternodeList)?;
+        let (payee, state) = masternode_list.masternode_payee(self.chain_type, payment_states)?;
+        let block_value = self.chain_type.block_value(block_height, prev_bits, fees);
+        let masternode_reward = self.chain_type.masternode_payment(block_height, block_value)
+            - self.chain_type.platform_share(block_height, prev_bits);
+        Ok((payee.provider_registration_transaction_hash, state.payments(masternode_reward)))
+    }
+
+    /// Verifies that the coinbase transaction pays the expected masternode payee (and its operator)
+    pub fn verify_masternode_payment(
+        &self,
+        coinbase: &CoinbaseTransaction,
+        prev_bits: u32,
+        fees: u64,
+        payment_states: &BTreeMap<UInt256, models::MasternodePaymentState>,
+        cache: &MasternodeProcessorCache,
+    ) -> MasternodePaymentValidationReport {
+        let block_height = coinbase.height;
+        let (payee, expected_payments) = match self.predict_masternode_payments(block_height, prev_bits, fees, payment_states, cache) {
+            Ok(prediction) => prediction,
+            Err(status) => return MasternodePaymentValidationReport::new(block_height, status),
+        };
+        let outputs = &coinbase.base.outputs;
+        let status = expected_payments.iter()
+            .find_map(|payment| {
+                let mut paying_script = outputs.iter()
+                    .filter(|output| output.script.as_ref() == Some(&payment.script))
+                    .peekable();
+                if paying_script.peek().is_none() {
+                    Some(MasternodePaymentValidationStatus::MissingPayment)
+                } else if !paying_script.any(|output| output.amount == payment.amount) {
+                    Some(MasternodePaymentValidationStatus::InvalidAmount)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(MasternodePaymentValidationStatus::Verified);
+        if status != MasternodePaymentValidationStatus::Verified {
+            warn!(target: target::PAYMENTS, "masternode payment not verified block_height={} payee={} status={:?}", block_height, payee, status);
+        }
+        MasternodePaymentValidationReport { block_height, payee: Some(payee), expected_payments, status }
+    }
+
+    /// Quorums formed within the range of heights the masternode was selected into (ordered by height),
+    /// members are taken from the cache or computed from the cached masternode lists and snapshots.
+    /// The lookup is bounded by the cache: only the quorums of the cached masternode lists are scanned,
+    /// so the quorums of the lists pruned by the cache policy (or never processed) aren't reported,
+    /// as well as the quorums whose members can't be determined yet
+    pub fn quorum_memberships(
+        &self,
+        pro_reg_tx_hash: UInt256,
+        heights: RangeInclusive<u32>,
+        skip_removed_masternodes: bool,
+        cache: &mut MasternodeProcessorCache,
+    ) -> Vec<QuorumMembership> {
+        let mut known = BTreeSet::<(LLMQType, UInt256)>::new();
+        let mut quorums = BTreeMap::<(u32, LLMQType, UInt256), models::LLMQEntry>::new();
+        cache.mn_lists.values()
+            .flat_map(|list| list.quorums.values().flat_map(|quorums_of_type| quorums_of_type.values()))
+            .for_each(|quorum| {
+                if known.insert((quorum.llmq_type, quorum.llmq_hash)) {
+                    let block_height = self.lookup_block_height_by_hash(quorum.llmq_hash);
+                    if heights.contains(&block_height) {
+                        quorums.insert((block_height, quorum.llmq_type, quorum.llmq_hash), quorum.clone());
+                    }
+                }
+            });
+        // The lookup shouldn't request missing data along with the next processed message
+        let needed = (cache.needed_masternode_lists.len(), cache.needed_block_heights.len(), cache.needed_snapshots.len());
+        let memberships = quorums
+            .into_iter()
+            .filter_map(|((block_height, ..), quorum)| match self.quorum_members(&quorum, skip_removed_masternodes, cache) {
+                Ok((_, members)) => members.iter()
+                    .position(|member| member.provider_registration_transaction_hash == pro_reg_tx_hash)
+                    .map(|member_index| QuorumMembership::new(&quorum, block_height, member_index as u32)),
+                Err(status) => {
+                    debug!(target: target::QUORUM, "quorum members unavailable llmq_type={:?} llmq_hash={} status={:?}", quorum.llmq_type, quorum.llmq_hash, status);
+                    None
+                }
+            })
+            .collect();
+        cache.needed_masternode_lists.truncate(needed.0);
+        cache.needed_block_heights.truncate(needed.1);
+        cache.needed_snapshots.truncate(needed.2);
+        memberships
+    }
+
+    /// Cached masternode lists with known heights, ordered by height
+    fn cached_lists_by_height(cache: &MasternodeProcessorCache) -> Vec<&models::MasternodeList> {
+        let mut lists = cache.mn_lists.values()
+            .filter(|list| list.known_height != u32::MAX)
+            .map(Arc::as_ref)
+            .collect::<Vec<_>>();
+        lists.sort_by_key(|list| list.known_height);
+        lists
+    }
+
+    /// Reconstructs the state of the masternode at the block height from the cached masternode lists:
+    /// the nearest list at or below the height proves the masternode was registered by then,
+    /// and the nearest list at or above the height is preferred as it keeps the history of the entry
+    pub fn masternode_state_at(
+        &self,
+        provider_registration_transaction_hash: UInt256,
+        block_height: u32,
+        cache: &MasternodeProcessorCache,
+    ) -> Option<models::MasternodeEntry> {
+        let lists = Self::cached_lists_by_height(cache);
+        // Without the list below there is no way to tell whether it was registered at that height,
+        // and if the list below has no entry it wasn't registered (or was already removed)
+        let state_below = lists.iter()
+            .rev()
+            .find(|list| list.known_height <= block_height)?
+            .masternode_state_at(provider_registration_transaction_hash, block_height)?;
+        let state_above = lists.iter()
+            .find(|list| list.known_height >= block_height)
+            .and_then(|list| list.masternode_state_at(provider_registration_transaction_hash, block_height));
+        Some(state_above.unwrap_or(state_below))
+    }
+
+    /// Every change of the masternodes between two heights observed across the cached masternode lists
+    pub fn masternode_changes_between(
+        &self,
+        base_block_height: u32,
+        block_height: u32,
+        cache: &MasternodeProcessorCache,
+    ) -> Vec<models::MasternodeChange> {
+        let lists = Self::cached_lists_by_height(cache);
+        let base_index = lists.iter().rposition(|list| list.known_height <= base_block_height).unwrap_or(0);
+        let lists = lists.into_iter()
+            .skip(base_index)
+            .take_while(|list| list.known_height <= block_height)
+            .collect::<Vec<_>>();
+        lists.windows(2)
+            .flat_map(|pair| pair[1].masternode_changes_since(pair[0]))
+            .filter(|change| change.block_height > base_block_height && change.block_height <= block_height)
+            .collect()
+    }
+
+    /// Builds the diff between two lists (to be served to other clients).
+    /// Quorum chain lock signatures are taken from the cache or the provider
+    pub fn list_diff_between(
+        &self,
+        base_list: &models::MasternodeList,
+        masternode_list: &models::MasternodeList,
+        coinbase_transaction: CoinbaseTransaction,
+        merkle_tree: common::MerkleTree,
+        protocol_version: u32,
+        cache: &MasternodeProcessorCache,
+    ) -> models::MNListDiff {
+        models::MNListDiff::from_masternode_lists(base_list, masternode_list, coinbase_transaction, merkle_tree, protocol_version, |quorum| {
+            // The same block the signature is expected for in `classify_quorums`
+            let llmq_height = self.lookup_block_height_by_hash(quorum.llmq_hash);
+            if llmq_height == u32::MAX {
+                return None;
+            }
+            llmq_height.checked_sub(8)
+                .and_then(|height| self.lookup_block_hash_by_height(height))
+                .and_then(|block_hash| self.find_cl_signature(block_hash, &cache.cl_signatures))
+        })
+    }
+
+    #[allow(clippy::type_complexity)]
     pub fn classify_masternodes(
         &self,
-        base_masternodes: BTreeMap<UInt256, models::MasternodeEntry>,
+        base_masternodes: BTreeMap<UInt256, Arc<models::MasternodeEntry>>,
         added_or_modified_masternodes: BTreeMap<UInt256, models::MasternodeEntry>,
         deleted_masternode_hashes: Vec<UInt256>,
         block_height: u32,
@@ -303,7 +580,7 @@ impl MasternodeProcessor {
     ) -> (
         BTreeMap<UInt256, models::MasternodeEntry>,
         BTreeMap<UInt256, models::MasternodeEntry>,
-        BTreeMap<UInt256, models::MasternodeEntry>,
+        BTreeMap<UInt256, Arc<models::MasternodeEntry>>,
     ) {
         let added_masternodes = added_or_modified_masternodes
             .iter()
@@ -317,16 +594,11 @@ impl MasternodeProcessor {
             .map(|(k, v)| (*k, v.clone()))
             .collect::<BTreeMap<_, _>>();
 
-        let mut masternodes = if !base_masternodes.is_empty() {
-            let mut old_masternodes = base_masternodes;
-            for hash in deleted_masternode_hashes {
-                old_masternodes.remove(&hash.reversed());
-            }
-            old_masternodes.extend(added_masternodes.clone());
-            old_masternodes
-        } else {
-            added_masternodes.clone()
-        };
+        let mut masternodes = base_masternodes;
+        for hash in deleted_masternode_hashes {
+            masternodes.remove(&hash.reversed());
+        }
+        masternodes.extend(added_masternodes.iter().map(|(hash, entry)| (*hash, Arc::new(entry.clone()))));
 
         for (hash, modified) in &mut modified_masternodes {
             if let Some(old) = masternodes.get_mut(hash) {
@@ -335,10 +607,10 @@ impl MasternodeProcessor {
                     if !old.confirmed_hash.is_zero() &&
                         old.known_confirmed_at_height.is_some() &&
                         old.known_confirmed_at_height.unwrap() > block_height {
-                        old.known_confirmed_at_height = Some(block_height);
+                        Arc::make_mut(old).known_confirmed_at_height = Some(block_height);
                     }
                 }
-                masternodes.insert(*hash, modified.clone());
+                masternodes.insert(*hash, Arc::new(modified.clone()));
             }
         }
         (added_masternodes, modified_masternodes, masternodes)
@@ -358,11 +630,14 @@ impl MasternodeProcessor {
         Vec<models::LLMQEntry>,
         BTreeMap<LLMQType, BTreeMap<UInt256, models::LLMQEntry>>,
         BTreeMap<UInt256, UInt768>,
-        bool,
+        Vec<QuorumValidationReport>,
     ) {
-        let mut has_valid_quorums = true;
+        let mut reports = Vec::<QuorumValidationReport>::new();
         let mut signatures = BTreeMap::<UInt256, UInt768>::new();
         if verification_context.should_validate_quorums() {
+            // Members are selected sequentially since it depends on the cache,
+            // while the signatures of the quorums can be verified independently
+            let mut members = Vec::<(usize, QuorumMembers)>::new();
             added_quorums
                 .iter_mut()
                 .enumerate()
@@ -370,22 +645,27 @@ impl MasternodeProcessor {
                     if let Some(signature) = cl_signatures.iter().find_map(|(signature, index_set)|
                         if index_set.iter().any(|i| *i == index as u16) { Some(signature) } else { None }) {
                         let llmq_height = self.lookup_block_height_by_hash(quorum.llmq_hash);
-                        if llmq_height != u32::MAX {
-                            if let Some(llmq_hash_minus_8) = self.lookup_block_hash_by_height(llmq_height - 8) {
+                        if llmq_height == u32::MAX {
+                            warn!(target: target::QUORUM, "clsig: unknown block height llmq_hash={}", quorum.llmq_hash);
+                        } else if let Some(block_height) = llmq_height.checked_sub(8) {
+                            if let Some(llmq_hash_minus_8) = self.lookup_block_hash_by_height(block_height) {
                                 signatures.insert(llmq_hash_minus_8, signature.clone());
                                 cache.cl_signatures.insert(llmq_hash_minus_8, signature.clone());
                             } else {
-                                println!("WARN: unknown hash for {}", llmq_height - 8);
+                                warn!(target: target::QUORUM, "clsig: unknown block hash block_height={}", block_height);
                             }
                         } else {
-                            println!("WARN: unknown height for {}", quorum.llmq_hash);
+                            warn!(target: target::QUORUM, "clsig: quorum block is below the chain lock offset llmq_hash={} llmq_height={}", quorum.llmq_hash, llmq_height);
                         }
                     }
                     if verification_context.should_validate_quorum_of_type(quorum.llmq_type, self.chain_type) {
-                        let status = self.validate_quorum(quorum, skip_removed_masternodes, cache);
-                        has_valid_quorums &= status.is_not_critical();
+                        members.push((index, self.quorum_members(quorum, skip_removed_masternodes, cache)));
                     }
-            })
+            });
+            let started = Instant::now();
+            reports = self.verify_quorums(&mut added_quorums, members);
+            self.record_metric(Metric::BLSVerificationTime(started.elapsed()));
+            reports.iter().for_each(|report| self.record_validation_report(report));
         }
         for (llmq_type, keys_to_delete) in &deleted_quorums {
             if let Some(llmq_map) = base_quorums.get_mut(llmq_type) {
@@ -399,16 +679,92 @@ impl MasternodeProcessor {
                 .or_insert_with(BTreeMap::new)
                 .insert(llmq_entry.llmq_hash, llmq_entry.clone());
         });
-        (added_quorums, base_quorums, signatures, has_valid_quorums)
+        (added_quorums, base_quorums, signatures, reports)
     }
 
     pub fn validate_quorum(&self, quorum: &mut models::LLMQEntry, skip_removed_masternodes: bool, cache: &mut MasternodeProcessorCache) -> LLMQValidationStatus {
+        self.validate_quorum_with_report(quorum, skip_removed_masternodes, cache).status
+    }
+
+    /// Validates quorum and reports the status along with the members used for validation
+    pub fn validate_quorum_with_report(&self, quorum: &mut models::LLMQEntry, skip_removed_masternodes: bool, cache: &mut MasternodeProcessorCache) -> QuorumValidationReport {
+        let members = self.quorum_members(quorum, skip_removed_masternodes, cache);
+        let started = Instant::now();
+        let report = Self::verify_quorum(quorum, members);
+        self.record_metric(Metric::BLSVerificationTime(started.elapsed()));
+        self.record_validation_report(&report);
+        report
+    }
+
+    fn quorum_members(&self, quorum: &models::LLMQEntry, skip_removed_masternodes: bool, cache: &mut MasternodeProcessorCache) -> QuorumMembers {
         let llmq_block_hash = quorum.llmq_hash;
-        self.find_masternode_list(llmq_block_hash, &cache.mn_lists, &mut cache.needed_masternode_lists)
-            .map_or(
-                LLMQValidationStatus::NoMasternodeList,
-                |models::MasternodeList { masternodes, .. }|
-                    self.validate_quorum_with_masternodes(quorum, skip_removed_masternodes, llmq_block_hash, masternodes, cache))
+        let masternode_list = self.find_masternode_list(llmq_block_hash, &cache.mn_lists, &mut cache.needed_masternode_lists)
+            .ok_or(LLMQValidationStatus::NoMasternodeList)?;
+        cache.touch(&llmq_block_hash);
+        let block_height = self.lookup_block_height_by_hash(llmq_block_hash);
+        self.quorum_members_with_masternodes(quorum, skip_removed_masternodes, llmq_block_hash, block_height, &masternode_list.masternodes, cache)
+            .map(|members| (block_height, members))
+            .map_err(|err| {
+                cache.add_missing_rotation_data(&err);
+                LLMQValidationStatus::MissingRotationData(err)
+            })
+    }
+
+    fn verify_quorum(quorum: &mut models::LLMQEntry, members: QuorumMembers) -> QuorumValidationReport {
+        match members {
+            Ok((block_height, valid_masternodes)) => {
+                let members = valid_masternodes
+                    .iter()
+                    .map(|node| node.provider_registration_transaction_hash)
+                    .collect();
+                let status = quorum.verify(valid_masternodes, block_height);
+                QuorumValidationReport::new(quorum, status, members)
+            },
+            Err(status) => QuorumValidationReport::new(quorum, status, vec![])
+        }
+    }
+
+    /// Verifies quorums (given by index) across `quorum_verification_threads` threads.
+    /// Reports are returned in the same order as the quorums, so the result doesn't depend on the number of threads
+    fn verify_quorums(
+        &self,
+        quorums: &mut [models::LLMQEntry],
+        members: Vec<(usize, QuorumMembers)>,
+    ) -> Vec<QuorumValidationReport> {
+        let mut members = members.into_iter().peekable();
+        let mut jobs = Vec::with_capacity(members.len());
+        for (index, quorum) in quorums.iter_mut().enumerate() {
+            if let Some((_, quorum_members)) = members.next_if(|(i, _)| *i == index) {
+                jobs.push((quorum, quorum_members));
+            }
+        }
+        let threads = self.quorum_verification_threads.min(jobs.len());
+        if threads <= 1 {
+            return jobs.into_iter()
+                .map(|(quorum, members)| Self::verify_quorum(quorum, members))
+                .collect();
+        }
+        let chunk_size = jobs.len().div_ceil(threads);
+        let mut chunks = Vec::with_capacity(threads);
+        while jobs.len() > chunk_size {
+            let rest = jobs.split_off(chunk_size);
+            chunks.push(jobs);
+            jobs = rest;
+        }
+        chunks.push(jobs);
+        std::thread::scope(|scope| {
+            let handles = chunks
+                .into_iter()
+                .map(|chunk| scope.spawn(|| chunk
+                    .into_iter()
+                    .map(|(quorum, members)| Self::verify_quorum(quorum, members))
+                    .collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_else(|err| std::panic::resume_unwind(err)))
+                .collect()
+        })
     }
 
     pub fn validate_quorum_with_masternodes(
@@ -416,13 +772,33 @@ impl MasternodeProcessor {
         quorum: &mut models::LLMQEntry,
         skip_removed_masternodes: bool,
         block_hash: UInt256,
-        masternodes: BTreeMap<UInt256, models::MasternodeEntry>,
+        masternodes: &BTreeMap<UInt256, Arc<models::MasternodeEntry>>,
         cache: &mut MasternodeProcessorCache,
     ) -> LLMQValidationStatus {
         let block_height = self.lookup_block_height_by_hash(block_hash);
+        match self.quorum_members_with_masternodes(quorum, skip_removed_masternodes, block_hash, block_height, masternodes, cache) {
+            //crate::util::java::generate_final_commitment_test_file(self.chain_type, block_height, &quorum, &valid_masternodes);
+            Ok(valid_masternodes) => quorum.verify(valid_masternodes, block_height),
+            Err(err) => {
+                cache.add_missing_rotation_data(&err);
+                LLMQValidationStatus::MissingRotationData(err)
+            }
+        }
+    }
+
+    fn quorum_members_with_masternodes(
+        &self,
+        quorum: &models::LLMQEntry,
+        skip_removed_masternodes: bool,
+        block_hash: UInt256,
+        block_height: u32,
+        masternodes: &BTreeMap<UInt256, Arc<models::MasternodeEntry>>,
+        cache: &mut MasternodeProcessorCache,
+    ) -> Result<Vec<models::MasternodeEntry>, RotationError> {
         let llmq_type = quorum.llmq_type;
-        let valid_masternodes = if quorum.index.is_some() {
-            self.get_rotated_masternodes_for_quorum(
+        if quorum.index.is_some() {
+            let started = Instant::now();
+            let members = self.get_rotated_masternodes_for_quorum(
                 llmq_type,
                 block_hash,
                 block_height,
@@ -433,115 +809,137 @@ impl MasternodeProcessor {
                 &cache.cl_signatures,
                 &mut cache.needed_masternode_lists,
                 skip_removed_masternodes,
-            )
+            );
+            self.record_metric(Metric::RotationTime(started.elapsed()));
+            if let Err(err) = &members {
+                debug!(target: target::ROTATION, "rotated quorum members unavailable llmq_type={:?} block_height={} error={}", llmq_type, block_height, err);
+            }
+            members
         } else {
-            models::MasternodeList::get_masternodes_for_quorum(
+            Ok(models::MasternodeList::get_masternodes_for_quorum(
                 quorum,
                 self.chain_type,
                 masternodes,
                 block_height,
-                self.llmq_modifier_type_for(llmq_type, quorum.llmq_hash, block_height - 8, &cache.cl_signatures)
-            )
-        };
-        //crate::util::java::generate_final_commitment_test_file(self.chain_type, block_height, &quorum, &valid_masternodes);
-        quorum.verify(valid_masternodes, block_height)
+                self.llmq_modifier_type_for(llmq_type, quorum.llmq_hash, Self::quorum_work_block_height(block_height)?, &cache.cl_signatures)
+            ))
+        }
     }
 
-    fn sort_scored_masternodes(scored_masternodes: BTreeMap<UInt256, models::MasternodeEntry>) -> Vec<models::MasternodeEntry> {
+    fn sort_scored_masternodes<M>(scored_masternodes: BTreeMap<UInt256, M>) -> Vec<M> {
         let mut v = Vec::from_iter(scored_masternodes);
         v.sort_by(|(s1, _), (s2, _)| s2.reversed().cmp(&s1.reversed()));
         v.into_iter().map(|(s, node)| node).collect()
     }
 
-    pub fn valid_masternodes_for_rotated_quorum_map(
-        masternodes: Vec<models::MasternodeEntry>,
+    pub fn valid_masternodes_for_rotated_quorum_map<M: Borrow<models::MasternodeEntry>>(
+        masternodes: Vec<M>,
         quorum_modifier: UInt256,
         block_height: u32,
-    ) -> Vec<models::MasternodeEntry> {
+    ) -> Vec<M> {
         let scored_masternodes = masternodes
             .into_iter()
-            .filter_map(|entry| models::MasternodeList::masternode_score(&entry, quorum_modifier, block_height)
+            .filter_map(|entry| models::MasternodeList::masternode_score(entry.borrow(), quorum_modifier, block_height)
                 .map(|score| (score, entry)))
             .collect::<BTreeMap<_, _>>();
         Self::sort_scored_masternodes(scored_masternodes)
     }
 
+    /// Height of the block (8 blocks before the quorum base block) used to calculate quorum modifier
+    fn quorum_work_block_height(quorum_base_block_height: u32) -> Result<u32, RotationError> {
+        quorum_base_block_height
+            .checked_sub(8)
+            .ok_or(RotationError::InvalidBlockHeight(quorum_base_block_height))
+    }
+
+    /// Masternode list at the work block of the quorum
+    fn rotation_work_masternode_list(
+        &self,
+        work_block_height: u32,
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
+        unknown_lists: &mut Vec<UInt256>,
+    ) -> Result<(UInt256, Arc<models::MasternodeList>), RotationError> {
+        let work_block_hash = self.lookup_block_hash_by_height(work_block_height)
+            .ok_or(RotationError::MissingBlockHash(work_block_height))?;
+        self.find_masternode_list(work_block_hash, cached_lists, unknown_lists)
+            .map(|masternode_list| (work_block_hash, masternode_list))
+            .ok_or(RotationError::MissingMasternodeList { block_height: work_block_height, block_hash: work_block_hash })
+    }
+
     // Reconstruct quorum members at index from snapshot
     pub fn quorum_quarter_members_by_snapshot(
         &self,
         llmq_params: LLMQParams,
         quorum_base_block_height: u32,
-        cached_lists: &BTreeMap<UInt256, models::MasternodeList>,
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
+        cached_snapshots: &BTreeMap<UInt256, models::LLMQSnapshot>,
+        cached_cl_signatures: &BTreeMap<UInt256, UInt768>,
+        unknown_lists: &mut Vec<UInt256>,
+    ) -> Result<Vec<Vec<models::MasternodeEntry>>, RotationError> {
+        self.quarter_members_by_snapshot(llmq_params, quorum_base_block_height, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists, None)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn quarter_members_by_snapshot(
+        &self,
+        llmq_params: LLMQParams,
+        quorum_base_block_height: u32,
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
         cached_snapshots: &BTreeMap<UInt256, models::LLMQSnapshot>,
         cached_cl_signatures: &BTreeMap<UInt256, UInt768>,
         unknown_lists: &mut Vec<UInt256>,
-    ) -> Vec<Vec<models::MasternodeEntry>> {
-        let work_block_height = quorum_base_block_height - 8;
+        mut diagnostics: Option<&mut QuarterDiagnostics>,
+    ) -> Result<Vec<Vec<models::MasternodeEntry>>, RotationError> {
+        let work_block_height = Self::quorum_work_block_height(quorum_base_block_height)?;
         let llmq_type = llmq_params.r#type;
         let quorum_count = llmq_params.signing_active_quorum_count as usize;
         let quorum_size = llmq_params.size;
         let quarter_size = (quorum_size / 4) as usize;
         // Quorum members dichotomy in snapshot
-        match self.lookup_block_hash_by_height(work_block_height) {
-            None => panic!("MISSING: block for height: {}", work_block_height),
-            Some(work_block_hash) => {
-                if let Some(masternode_list) = self.find_masternode_list(work_block_hash, cached_lists, unknown_lists) {
-                    if let Some(snapshot) = self.find_snapshot(work_block_hash, cached_snapshots) {
-                        let mut i: u32 = 0;
-                        // println!("•••• quorum_quarter_members_by_snapshot: {:?}: {:?}: {}: {}", llmq_type, snapshot.skip_list_mode, work_block_height, work_block_hash.reversed());
-                        // println!("{:#?}", masternode_list);
-                        // println!("••••");
-                        // java::generate_snapshot(&snapshot, work_block_height);
-                        // java::generate_llmq_hash(llmq_type, work_block_hash.reversed());
-                        // java::generate_masternode_list_from_map(&masternode_list.masternodes);
-                        let quorum_modifier_type =  self.llmq_modifier_type_for(llmq_type, work_block_hash, work_block_height, cached_cl_signatures);
-                        let quorum_modifier = quorum_modifier_type.build_llmq_hash();
-                        // println!("quorum_modifier: {}", quorum_modifier);
-                        // println!("snapshot: {:?}", snapshot);
-                        let scored_masternodes = models::MasternodeList::score_masternodes_map(masternode_list.masternodes, quorum_modifier, work_block_height, false);
-                        // java::generate_masternode_list_from_map(&scored_masternodes);
-                        let sorted_scored_masternodes = Self::sort_scored_masternodes(scored_masternodes);
-                        // println!("//////////////////sorted_scored_masternodes////////////////////");
-                        // println!("{:#?}", sorted_scored_masternodes.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>());
-                        let (used_at_h, unused_at_h) = sorted_scored_masternodes
-                            .into_iter()
-                            .partition(|_| {
-                                let is_true = snapshot.member_is_true_at_index(i);
-                                i += 1;
-                                is_true
-                            });
-                        // java::generate_masternode_list(&used_at_h);
-                        // java::generate_masternode_list(&unused_at_h);
-                        // println!("//////////////////////////////////////");
-                        let sorted_used_at_h = Self::valid_masternodes_for_rotated_quorum_map(
-                            used_at_h,
-                            quorum_modifier,
-                            work_block_height,
-                        );
-                        // println!("////////////sorted_used_at_h////////////////");
-                        // println!("{:#?}", sorted_used_at_h.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>());
-                        let sorted_unused_at_h = Self::valid_masternodes_for_rotated_quorum_map(
-                            unused_at_h,
-                            quorum_modifier,
-                            work_block_height,
-                        );
-                        // println!("////////////sorted_unused_at_h////////////////");
-                        // println!("{:#?}", sorted_unused_at_h.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>());
-                        let mut sorted_combined_mns_list = sorted_unused_at_h;
-                        sorted_combined_mns_list.extend(sorted_used_at_h);
-                        // println!("////////////sorted_combined_mns_list////////////////");
-                        // println!("{:#?}", sorted_combined_mns_list.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>());
-                        snapshot.apply_skip_strategy(sorted_combined_mns_list, quorum_count, quarter_size)
-                    } else {
-                        info!("MISSING: snapshot for block at height: {}: {}", work_block_height, work_block_hash);
-                        vec![]
-                    }
-                } else {
-                    info!("MISSING: masternode_list for block at height: {}: {}", work_block_height, work_block_hash);
-                    vec![]
-                }
-            }
+        let (work_block_hash, masternode_list) = self.rotation_work_masternode_list(work_block_height, cached_lists, unknown_lists)?;
+        let snapshot = self.find_snapshot(work_block_hash, cached_snapshots)
+            .ok_or(RotationError::MissingSnapshot { block_height: work_block_height, block_hash: work_block_hash })?;
+        let mut i: u32 = 0;
+        // println!("•••• quorum_quarter_members_by_snapshot: {:?}: {:?}: {}: {}", llmq_type, snapshot.skip_list_mode, work_block_height, work_block_hash.reversed());
+        // java::generate_snapshot(&snapshot, work_block_height);
+        // java::generate_llmq_hash(llmq_type, work_block_hash.reversed());
+        // java::generate_masternode_list_from_map(&masternode_list.masternodes);
+        let quorum_modifier_type =  self.llmq_modifier_type_for(llmq_type, work_block_hash, work_block_height, cached_cl_signatures);
+        let quorum_modifier = quorum_modifier_type.build_llmq_hash();
+        let scored_masternodes = models::MasternodeList::score_masternodes_map(&masternode_list.masternodes, quorum_modifier, work_block_height, false);
+        let sorted_scored_masternodes = Self::sort_scored_masternodes(scored_masternodes);
+        let (used_at_h, unused_at_h) = sorted_scored_masternodes
+            .into_iter()
+            .partition(|_| {
+                let is_true = snapshot.member_is_true_at_index(i);
+                i += 1;
+                is_true
+            });
+        let sorted_used_at_h = Self::valid_masternodes_for_rotated_quorum_map(
+            used_at_h,
+            quorum_modifier,
+            work_block_height,
+        );
+        let sorted_unused_at_h = Self::valid_masternodes_for_rotated_quorum_map(
+            unused_at_h,
+            quorum_modifier,
+            work_block_height,
+        );
+        let mut sorted_combined_mns_list = sorted_unused_at_h;
+        sorted_combined_mns_list.extend(sorted_used_at_h);
+        if let Some(diagnostics) = diagnostics.as_deref_mut() {
+            *diagnostics = QuarterDiagnostics::new(quorum_base_block_height, work_block_height, work_block_hash);
+            diagnostics.record_snapshot(&snapshot);
+            diagnostics.record_sorted_combined_masternodes(&sorted_combined_mns_list, quorum_modifier);
+        }
+        let quarter_members = snapshot.apply_skip_strategy(sorted_combined_mns_list, quorum_count, quarter_size)
+            .into_iter()
+            .map(|quarter| quarter.into_iter().map(Arc::unwrap_or_clone).collect())
+            .collect::<Vec<_>>();
+        if let Some(diagnostics) = diagnostics {
+            diagnostics.record_members(&quarter_members);
         }
+        Ok(quarter_members)
     }
 
     // fn log_masternodes(vec: &Vec<models::MasternodeEntry>, prefix: String) {
@@ -550,123 +948,130 @@ impl MasternodeProcessor {
     // }
 
     // Determine quorum members at new index
+    #[allow(clippy::too_many_arguments)]
     pub fn new_quorum_quarter_members(
         &self,
         params: LLMQParams,
         quorum_base_block_height: u32,
         previous_quarters: [&Vec<Vec<models::MasternodeEntry>>; 3],
-        cached_lists: &BTreeMap<UInt256, models::MasternodeList>,
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
         cached_cl_signatures: &BTreeMap<UInt256, UInt768>,
         unknown_lists: &mut Vec<UInt256>,
         skip_removed_masternodes: bool,
-    ) -> Vec<Vec<models::MasternodeEntry>> {
+    ) -> Result<Vec<Vec<models::MasternodeEntry>>, RotationError> {
+        self.new_quarter_members(params, quorum_base_block_height, previous_quarters, cached_lists, cached_cl_signatures, unknown_lists, skip_removed_masternodes, None)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_quarter_members(
+        &self,
+        params: LLMQParams,
+        quorum_base_block_height: u32,
+        previous_quarters: [&Vec<Vec<models::MasternodeEntry>>; 3],
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
+        cached_cl_signatures: &BTreeMap<UInt256, UInt768>,
+        unknown_lists: &mut Vec<UInt256>,
+        skip_removed_masternodes: bool,
+        mut diagnostics: Option<&mut QuarterDiagnostics>,
+    ) -> Result<Vec<Vec<models::MasternodeEntry>>, RotationError> {
         let quorum_count = params.signing_active_quorum_count as usize;
         let mut quarter_quorum_members = vec![Vec::<models::MasternodeEntry>::new(); quorum_count];
         let quorum_size = params.size as usize;
         let quarter_size = quorum_size / 4;
-        let work_block_height = quorum_base_block_height - 8;
-        match self.lookup_block_hash_by_height(work_block_height) {
-            None => panic!("missing block for height: {}", work_block_height),
-            Some(work_block_hash) => {
-                if let Some(masternode_list) = self.find_masternode_list(work_block_hash, cached_lists, unknown_lists) {
-                    //java::generate_masternode_list_from_map(&masternode_list.masternodes);
-                    // println!("•••• new_quorum_quarter_members: {:?}: (skip_removed: {}) {}: {}", params.r#type, skip_removed_masternodes, work_block_height, work_block_hash.reversed());
-                    // println!("{:#?}", masternode_list);
-                    // println!("••••");
-                    if masternode_list.masternodes.len() < quarter_size {
-                        println!("models list at {}: {} has less masternodes ({}) then required for quarter size: ({})", work_block_height, work_block_hash, masternode_list.masternodes.len(), quarter_size);
-                        quarter_quorum_members
-                    } else {
-                        let mut used_at_h_masternodes = Vec::<models::MasternodeEntry>::new();
-                        let mut unused_at_h_masternodes = Vec::<models::MasternodeEntry>::new();
-                        let mut used_at_h_indexed_masternodes = vec![Vec::<models::MasternodeEntry>::new(); quorum_count];
-                        for i in 0..quorum_count {
-                            // for quarters h - c, h -2c, h -3c
-                            for quarter in &previous_quarters {
-                                if let Some(quarter_nodes) = quarter.get(i) {
-                                    //Self::log_masternodes(quarter_nodes, format!("••••• PREV QUARTER {} ••••••• ", i));
-                                    for node in quarter_nodes {
-                                        let hash = node.provider_registration_transaction_hash;
-                                        if (!skip_removed_masternodes || masternode_list.has_masternode(hash)) &&
-                                            masternode_list.has_valid_masternode(hash) {
-                                            if !used_at_h_masternodes.iter().any(|m| m.provider_registration_transaction_hash == hash) {
-                                                used_at_h_masternodes.push(node.clone());
-                                            }
-                                            if !used_at_h_indexed_masternodes[i].iter().any(|m| m.provider_registration_transaction_hash == hash) {
-                                                used_at_h_indexed_masternodes[i].push(node.clone());
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        masternode_list.masternodes.values().for_each(|mn| {
-                            if mn.is_valid && !used_at_h_masternodes.iter().any(|node| mn.provider_registration_transaction_hash == node.provider_registration_transaction_hash) {
-                                unused_at_h_masternodes.push(mn.clone());
+        let work_block_height = Self::quorum_work_block_height(quorum_base_block_height)?;
+        let (work_block_hash, masternode_list) = self.rotation_work_masternode_list(work_block_height, cached_lists, unknown_lists)?;
+        if let Some(diagnostics) = diagnostics.as_deref_mut() {
+            *diagnostics = QuarterDiagnostics::new(quorum_base_block_height, work_block_height, work_block_hash);
+        }
+        //java::generate_masternode_list_from_map(&masternode_list.masternodes);
+        // println!("•••• new_quorum_quarter_members: {:?}: (skip_removed: {}) {}: {}", params.r#type, skip_removed_masternodes, work_block_height, work_block_hash.reversed());
+        if masternode_list.masternodes.len() < quarter_size {
+            warn!(target: target::ROTATION, "not enough masternodes in the work block list block_height={} block_hash={} masternodes={} quarter_size={}", work_block_height, work_block_hash, masternode_list.masternodes.len(), quarter_size);
+            return Ok(quarter_quorum_members);
+        }
+        let mut used_at_h_masternodes = Vec::<models::MasternodeEntry>::new();
+        let mut unused_at_h_masternodes = Vec::<models::MasternodeEntry>::new();
+        let mut used_at_h_indexed_masternodes = vec![Vec::<models::MasternodeEntry>::new(); quorum_count];
+        for (i, used_at_h_indexed_at_i) in used_at_h_indexed_masternodes.iter_mut().enumerate() {
+            // for quarters h - c, h -2c, h -3c
+            for quarter in &previous_quarters {
+                if let Some(quarter_nodes) = quarter.get(i) {
+                    //Self::log_masternodes(quarter_nodes, format!("••••• PREV QUARTER {} ••••••• ", i));
+                    for node in quarter_nodes {
+                        let hash = node.provider_registration_transaction_hash;
+                        if (!skip_removed_masternodes || masternode_list.has_masternode(hash)) &&
+                            masternode_list.has_valid_masternode(hash) {
+                            if !used_at_h_masternodes.iter().any(|m| m.provider_registration_transaction_hash == hash) {
+                                used_at_h_masternodes.push(node.clone());
                             }
-                        });
-                        //Self::log_masternodes(&used_at_h_masternodes, format!("••••• USED AT H {} ••••••• ", work_block_height));
-                        //Self::log_masternodes(&unused_at_h_masternodes, format!("••••• UNUSED AT H {} •••••••", work_block_height));
-                        let quorum_modifier_type = self.llmq_modifier_type_for(params.r#type, work_block_hash, work_block_height, cached_cl_signatures);
-                        let quorum_modifier = quorum_modifier_type.build_llmq_hash();
-                        let sorted_used_mns_list = Self::valid_masternodes_for_rotated_quorum_map(used_at_h_masternodes, quorum_modifier, work_block_height);
-                        let sorted_unused_mns_list = Self::valid_masternodes_for_rotated_quorum_map(unused_at_h_masternodes, quorum_modifier, work_block_height);
-                        //Self::log_masternodes(&sorted_unused_mns_list, format!("••••• SORTED UNUSED AT H {} ••••••• ", work_block_height));
-                        //Self::log_masternodes(&sorted_used_mns_list, format!("••••• SORTED USED AT H {} ••••••• ", work_block_height));
-                        let mut sorted_combined_mns_list = sorted_unused_mns_list;
-                        sorted_combined_mns_list.extend(sorted_used_mns_list);
-                        // println!("••••• SORTED COMBINED AT H {} •••••••", work_block_height);
-                        // println!("{:#?}", sorted_combined_mns_list.iter().map(|m|m.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>());
-
-                        let mut skip_list = Vec::<i32>::new();
-                        let mut first_skipped_index = 0i32;
-                        let mut idx = 0i32;
-                        for i in 0..quorum_count {
-                            let masternodes_used_at_h_indexed_at_i = used_at_h_indexed_masternodes.get_mut(i).unwrap();
-                            let used_mns_count = masternodes_used_at_h_indexed_at_i.len();
-                            let sorted_combined_mns_list_len = sorted_combined_mns_list.len();
-                            let mut updated = false;
-                            let initial_loop_idx = idx;
-                            while quarter_quorum_members[i].len() < quarter_size && used_mns_count + quarter_quorum_members[i].len() < sorted_combined_mns_list_len {
-                                let mn = sorted_combined_mns_list.get(idx as usize).unwrap();
-                                // TODO: replace masternodes with smart pointers to avoid cloning
-                                if masternodes_used_at_h_indexed_at_i.iter().any(|node| mn.provider_registration_transaction_hash == node.provider_registration_transaction_hash) {
-                                    let skip_index = idx - first_skipped_index;
-                                    if first_skipped_index == 0 {
-                                        first_skipped_index = idx;
-                                    }
-                                    skip_list.push(idx);
-                                } else {
-                                    masternodes_used_at_h_indexed_at_i.push(mn.clone());
-                                    quarter_quorum_members[i].push(mn.clone());
-                                    updated = true;
-                                }
-                                idx += 1;
-                                if idx == sorted_combined_mns_list_len as i32 {
-                                    idx = 0;
-                                }
-                                if idx == initial_loop_idx {
-                                    if !updated {
-                                        println!("there are not enough MNs {}: {} then required for quarter size: ({})", work_block_height, work_block_hash, quarter_size);
-                                        return vec![Vec::<models::MasternodeEntry>::new(); quorum_count];
-                                    }
-                                    updated = false;
-                                }
+                            if !used_at_h_indexed_at_i.iter().any(|m| m.provider_registration_transaction_hash == hash) {
+                                used_at_h_indexed_at_i.push(node.clone());
                             }
                         }
-                        // println!("••••• QUARTER MEMBERS •••••••");
-                        // quarter_quorum_members.iter().enumerate().for_each(|(index, members)| {
-                        //     Self::log_masternodes(&members, format!("••••• INDEX {} ••••••• ", index));
-                        // });
-                        // println!("•••••");
-                        quarter_quorum_members
                     }
+                }
+            }
+        }
+        masternode_list.masternodes.values().for_each(|mn| {
+            if mn.is_valid && !used_at_h_masternodes.iter().any(|node| mn.provider_registration_transaction_hash == node.provider_registration_transaction_hash) {
+                unused_at_h_masternodes.push(mn.as_ref().clone());
+            }
+        });
+        //Self::log_masternodes(&used_at_h_masternodes, format!("••••• USED AT H {} ••••••• ", work_block_height));
+        //Self::log_masternodes(&unused_at_h_masternodes, format!("••••• UNUSED AT H {} •••••••", work_block_height));
+        let quorum_modifier_type = self.llmq_modifier_type_for(params.r#type, work_block_hash, work_block_height, cached_cl_signatures);
+        let quorum_modifier = quorum_modifier_type.build_llmq_hash();
+        let sorted_used_mns_list = Self::valid_masternodes_for_rotated_quorum_map(used_at_h_masternodes, quorum_modifier, work_block_height);
+        let sorted_unused_mns_list = Self::valid_masternodes_for_rotated_quorum_map(unused_at_h_masternodes, quorum_modifier, work_block_height);
+        let mut sorted_combined_mns_list = sorted_unused_mns_list;
+        sorted_combined_mns_list.extend(sorted_used_mns_list);
+        // println!("••••• SORTED COMBINED AT H {} •••••••", work_block_height);
+        // println!("{:#?}", sorted_combined_mns_list.iter().map(|m|m.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>());
+        if let Some(diagnostics) = diagnostics.as_deref_mut() {
+            diagnostics.record_sorted_combined_masternodes(&sorted_combined_mns_list, quorum_modifier);
+        }
+        let mut skip_list = Vec::<i32>::new();
+        let mut first_skipped_index = 0i32;
+        let mut idx = 0i32;
+        let sorted_combined_mns_list_len = sorted_combined_mns_list.len();
+        for (quarter, masternodes_used_at_h_indexed_at_i) in quarter_quorum_members.iter_mut().zip(used_at_h_indexed_masternodes.iter_mut()) {
+            let used_mns_count = masternodes_used_at_h_indexed_at_i.len();
+            let mut updated = false;
+            let initial_loop_idx = idx;
+            while quarter.len() < quarter_size && used_mns_count + quarter.len() < sorted_combined_mns_list_len {
+                let mn = match sorted_combined_mns_list.get(idx as usize) {
+                    Some(mn) => mn,
+                    None => break
+                };
+                // TODO: replace masternodes with smart pointers to avoid cloning
+                if masternodes_used_at_h_indexed_at_i.iter().any(|node| mn.provider_registration_transaction_hash == node.provider_registration_transaction_hash) {
+                    if first_skipped_index == 0 {
+                        first_skipped_index = idx;
+                    }
+                    skip_list.push(idx);
                 } else {
-                    println!("missing models list for height: {}: {}", work_block_height, work_block_hash);
-                    quarter_quorum_members
+                    masternodes_used_at_h_indexed_at_i.push(mn.clone());
+                    quarter.push(mn.clone());
+                    updated = true;
+                }
+                idx += 1;
+                if idx == sorted_combined_mns_list_len as i32 {
+                    idx = 0;
+                }
+                if idx == initial_loop_idx {
+                    if !updated {
+                        warn!(target: target::ROTATION, "not enough masternodes to fill the quarter block_height={} block_hash={} quarter_size={}", work_block_height, work_block_hash, quarter_size);
+                        return Ok(vec![Vec::<models::MasternodeEntry>::new(); quorum_count]);
+                    }
+                    updated = false;
                 }
             }
         }
+        if let Some(diagnostics) = diagnostics {
+            diagnostics.record_new_quarter_skip_list(skip_list);
+            diagnostics.record_members(&quarter_quorum_members);
+        }
+        Ok(quarter_quorum_members)
     }
 
     fn add_quorum_members_from_quarter(
@@ -680,35 +1085,29 @@ impl MasternodeProcessor {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn rotate_members(
         &self,
         cycle_quorum_base_block_height: u32,
         llmq_params: LLMQParams,
-        cached_lists: &BTreeMap<UInt256, models::MasternodeList>,
+        cached_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
         cached_snapshots: &BTreeMap<UInt256, models::LLMQSnapshot>,
         cached_cl_signatures: &BTreeMap<UInt256, UInt768>,
         unknown_lists: &mut Vec<UInt256>,
         skip_removed_masternodes: bool,
-    ) -> Vec<Vec<models::MasternodeEntry>> {
+        mut diagnostics: Option<&mut RotationDiagnostics>,
+    ) -> Result<Vec<Vec<models::MasternodeEntry>>, RotationError> {
         let num_quorums = llmq_params.signing_active_quorum_count as usize;
         let cycle_length = llmq_params.dkg_params.interval;
-        // println!("/////////////////////// rotate_members {}: {} /////////", cycle_quorum_base_block_height, cycle_length);
-        let quorum_base_block_height = cycle_quorum_base_block_height - cycle_length;
-        let prev_q_h_m_c = self.quorum_quarter_members_by_snapshot(llmq_params, quorum_base_block_height, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists);
-        // println!("/////////////////////// prev_q_h_m_c : {} /////////", quorum_base_block_height);
-        // println!("{:#?}", prev_q_h_m_c.iter().map(|p| p.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>()).collect::<Vec<_>>());
-        let quorum_base_block_height = cycle_quorum_base_block_height - 2 * cycle_length;
-        let prev_q_h_m_2c = self.quorum_quarter_members_by_snapshot(llmq_params, quorum_base_block_height, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists);
-        // println!("/////////////////////// prev_q_h_m_2c : {} /////////", quorum_base_block_height);
-        // println!("{:#?}", prev_q_h_m_2c.iter().map(|p| p.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>()).collect::<Vec<_>>());
-        let quorum_base_block_height = cycle_quorum_base_block_height - 3 * cycle_length;
-        let prev_q_h_m_3c = self.quorum_quarter_members_by_snapshot(llmq_params, quorum_base_block_height, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists);
-        // println!("/////////////////////// prev_q_h_m_3c : {} /////////", quorum_base_block_height);
-        // println!("{:#?}", prev_q_h_m_3c.iter().map(|p| p.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>()).collect::<Vec<_>>());
-
+        let quorum_base_block_height_at = |cycles: u32| cycle_quorum_base_block_height
+            .checked_sub(cycles * cycle_length)
+            .ok_or(RotationError::InvalidBlockHeight(cycle_quorum_base_block_height));
+        let prev_q_h_m_c = self.quarter_members_by_snapshot(llmq_params, quorum_base_block_height_at(1)?, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists, diagnostics.as_deref_mut().map(|d| &mut d.quarter_at_h_c))?;
+        let prev_q_h_m_2c = self.quarter_members_by_snapshot(llmq_params, quorum_base_block_height_at(2)?, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists, diagnostics.as_deref_mut().map(|d| &mut d.quarter_at_h_2c))?;
+        let prev_q_h_m_3c = self.quarter_members_by_snapshot(llmq_params, quorum_base_block_height_at(3)?, cached_lists, cached_snapshots, cached_cl_signatures, unknown_lists, diagnostics.as_deref_mut().map(|d| &mut d.quarter_at_h_3c))?;
         let mut rotated_members =
             Vec::<Vec<models::MasternodeEntry>>::with_capacity(num_quorums);
-        let new_quarter_members = self.new_quorum_quarter_members(
+        let new_quarter_members = self.new_quarter_members(
             llmq_params,
             cycle_quorum_base_block_height,
             [
@@ -720,20 +1119,48 @@ impl MasternodeProcessor {
             cached_cl_signatures,
             unknown_lists,
             skip_removed_masternodes,
-        );
+            diagnostics.as_deref_mut().map(|d| &mut d.new_quarter),
+        )?;
         // println!("/////////////////////// new_quarter_members : {} /////////", cycle_quorum_base_block_height);
         // println!("{:#?}", new_quarter_members.iter().map(|p| p.iter().map(|n| n.provider_registration_transaction_hash.reversed()).collect::<Vec<_>>()).collect::<Vec<_>>());
-
         (0..num_quorums).for_each(|i| {
             Self::add_quorum_members_from_quarter(&mut rotated_members, &prev_q_h_m_3c, i);
             Self::add_quorum_members_from_quarter(&mut rotated_members, &prev_q_h_m_2c, i);
             Self::add_quorum_members_from_quarter(&mut rotated_members, &prev_q_h_m_c, i);
             Self::add_quorum_members_from_quarter(&mut rotated_members, &new_quarter_members, i);
         });
-        rotated_members
+        if let Some(diagnostics) = diagnostics {
+            diagnostics.record_quorum_members(&rotated_members);
+        }
+        Ok(rotated_members)
+    }
+
+    /// Recomputes members of the rotated quorums of the cycle recording the intermediate data
+    /// (snapshots used, sorted masternodes with scores, quarters), so it can be compared with Dash Core.
+    /// Cached members aren't used nor updated
+    pub fn rotation_diagnostics(
+        &self,
+        llmq_type: LLMQType,
+        cycle_base_block_height: u32,
+        skip_removed_masternodes: bool,
+        cache: &MasternodeProcessorCache,
+    ) -> Result<RotationDiagnostics, RotationError> {
+        let mut diagnostics = RotationDiagnostics::new(llmq_type, cycle_base_block_height, skip_removed_masternodes);
+        self.rotate_members(
+            cycle_base_block_height,
+            llmq_type.params(),
+            &cache.mn_lists,
+            &cache.llmq_snapshots,
+            &cache.cl_signatures,
+            &mut vec![],
+            skip_removed_masternodes,
+            Some(&mut diagnostics),
+        )?;
+        Ok(diagnostics)
     }
 
-    /// Determine masternodes which is responsible for signing at this quorum index
+    /// Determine masternodes which is responsible for signing at this quorum index.
+    /// Members are cached only if all the required data (blocks, lists and snapshots) is known
     #[allow(clippy::too_many_arguments)]
     pub fn get_rotated_masternodes_for_quorum(
         &self,
@@ -742,176 +1169,105 @@ impl MasternodeProcessor {
         block_height: u32,
         cached_llmq_members: &mut BTreeMap<LLMQType, BTreeMap<UInt256, Vec<models::MasternodeEntry>>>,
         cached_llmq_indexed_members: &mut BTreeMap<LLMQType, BTreeMap<models::LLMQIndexedHash, Vec<models::MasternodeEntry>>>,
-        cached_mn_lists: &BTreeMap<UInt256, models::MasternodeList>,
+        cached_mn_lists: &BTreeMap<UInt256, Arc<models::MasternodeList>>,
         cached_llmq_snapshots: &BTreeMap<UInt256, models::LLMQSnapshot>,
         cached_cl_signatures: &BTreeMap<UInt256, UInt768>,
         cached_needed_masternode_lists: &mut Vec<UInt256>,
         skip_removed_masternodes: bool,
-    ) -> Vec<models::MasternodeEntry> {
-        let map_by_type_opt = cached_llmq_members.get_mut(&llmq_type);
-        if map_by_type_opt.is_some() {
-            if let Some(members) = map_by_type_opt.as_ref().unwrap().get(&block_hash) {
-                return members.clone();
-            }
-        } else {
-            cached_llmq_members.insert(llmq_type, BTreeMap::new());
+    ) -> Result<Vec<models::MasternodeEntry>, RotationError> {
+        let map_by_type = cached_llmq_members.entry(llmq_type).or_default();
+        if let Some(members) = map_by_type.get(&block_hash) {
+            return Ok(members.clone());
         }
-        let map_by_type = cached_llmq_members.get_mut(&llmq_type).unwrap();
         let llmq_params = llmq_type.params();
         let quorum_index = block_height % llmq_params.dkg_params.interval;
         let cycle_base_height = block_height - quorum_index;
-        // println!("/////////////////////get_rotated_masternodes_for_quorum {} {} {} {}", block_height, llmq_params.dkg_params.interval, quorum_index, cycle_base_height);
-        match self.lookup_block_hash_by_height(cycle_base_height) {
-            None => panic!("missing hash for block at height: {}", cycle_base_height),
-            Some(cycle_base_hash) => {
-                let map_by_type_indexed_opt = cached_llmq_indexed_members.get_mut(&llmq_type);
-                if map_by_type_indexed_opt.is_some() {
-                    if let Some(members) = map_by_type_indexed_opt
-                        .as_ref()
-                        .unwrap()
-                        .get(&(cycle_base_hash, quorum_index).into())
-                    {
-                        map_by_type.insert(block_hash, members.clone());
-                        return members.clone();
-                    }
-                } else {
-                    cached_llmq_indexed_members.insert(llmq_type, BTreeMap::new());
-                }
-                let rotated_members = self.rotate_members(
-                    cycle_base_height,
-                    llmq_params,
-                    cached_mn_lists,
-                    cached_llmq_snapshots,
-                    cached_cl_signatures,
-                    cached_needed_masternode_lists,
-                    skip_removed_masternodes,
-                );
-                let map_indexed_quorum_members_of_type =
-                    cached_llmq_indexed_members.get_mut(&llmq_type).unwrap();
-                rotated_members.iter().enumerate().for_each(|(i, members)| {
-                    map_indexed_quorum_members_of_type.insert((cycle_base_hash, i).into(), members.clone());
-                });
-                if let Some(members) = rotated_members.get(quorum_index as usize) {
-                    map_by_type.insert(block_hash, members.clone());
-                    return members.clone();
-                }
-                vec![]
-            }
+        let cycle_base_hash = self.lookup_block_hash_by_height(cycle_base_height)
+            .ok_or(RotationError::MissingBlockHash(cycle_base_height))?;
+        let map_indexed_quorum_members_of_type = cached_llmq_indexed_members.entry(llmq_type).or_default();
+        if let Some(members) = map_indexed_quorum_members_of_type.get(&(cycle_base_hash, quorum_index).into()) {
+            map_by_type.insert(block_hash, members.clone());
+            return Ok(members.clone());
         }
+        let rotated_members = self.rotate_members(
+            cycle_base_height,
+            llmq_params,
+            cached_mn_lists,
+            cached_llmq_snapshots,
+            cached_cl_signatures,
+            cached_needed_masternode_lists,
+            skip_removed_masternodes,
+            None,
+        )?;
+        rotated_members.iter().enumerate().for_each(|(i, members)| {
+            map_indexed_quorum_members_of_type.insert((cycle_base_hash, i).into(), members.clone());
+        });
+        if let Some(members) = rotated_members.get(quorum_index as usize) {
+            map_by_type.insert(block_hash, members.clone());
+            return Ok(members.clone());
+        }
+        Ok(vec![])
     }
 
     ///////////////////////////////////////////////////////////////////////////////////////////
-    /// FFI-callbacks
+    /// Storage backend
     ///////////////////////////////////////////////////////////////////////////////////////////
 
-    pub fn lookup_masternode_list(
-        &self,
-        block_hash: UInt256,
-    ) -> Option<models::MasternodeList> {
-        // First look at the local cache
-        callbacks::lookup_masternode_list(
-            block_hash,
-            |h: UInt256| unsafe {
-                (self.get_masternode_list_by_block_hash)(boxed(h.0), self.opaque_context)
-            },
-            |list: *mut types::MasternodeList| unsafe { (self.destroy_masternode_list)(list) },
-        )
+    pub fn lookup_masternode_list(&self, block_hash: UInt256) -> Option<models::MasternodeList> {
+        self.provider.lookup_masternode_list(block_hash)
     }
 
-    pub fn save_masternode_list(
-        &self,
-        block_hash: UInt256,
-        masternode_list: &models::MasternodeList,
-    ) -> bool {
-        unsafe {
-            (self.save_masternode_list)(
-                boxed(block_hash.0),
-                boxed(masternode_list.encode()),
-                self.opaque_context,
-            )
+    pub fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &models::MasternodeList) -> bool {
+        self.provider.save_masternode_list(block_hash, masternode_list)
+    }
+
+    /// Stores verified masternode list and CL signatures from the diff result in the storage backend
+    pub fn save_list_diff_result(&self, result: &MNListDiffResult) -> bool {
+        if !result.is_valid() {
+            return false;
         }
+        result.cl_signatures.iter().all(|(block_hash, signature)| self.save_cl_signature(*block_hash, *signature))
+            && self.save_masternode_list(result.block_hash, &result.masternode_list)
     }
 
     pub fn lookup_block_hash_by_height(&self, block_height: u32) -> Option<UInt256> {
-        callbacks::lookup_block_hash_by_height(
-            block_height,
-            |h: u32| unsafe { (self.get_block_hash_by_height)(h, self.opaque_context) },
-            |hash: *mut u8| unsafe { (self.destroy_hash)(hash) },
-        )
+        self.provider.lookup_block_hash_by_height(block_height)
     }
 
     pub fn lookup_block_height_by_hash(&self, block_hash: UInt256) -> u32 {
-        unsafe { (self.get_block_height_by_hash)(boxed(block_hash.0), self.opaque_context) }
+        self.provider.lookup_block_height_by_hash(block_hash)
     }
 
     pub fn lookup_snapshot_by_block_hash(&self, block_hash: UInt256) -> Option<models::LLMQSnapshot> {
-        callbacks::lookup_snapshot_by_block_hash(
-            block_hash,
-            |h: UInt256| unsafe {
-                (self.get_llmq_snapshot_by_block_hash)(boxed(h.0), self.opaque_context)
-            },
-            |snapshot: *mut types::LLMQSnapshot| unsafe { (self.destroy_snapshot)(snapshot) },
-        )
+        self.provider.lookup_snapshot_by_block_hash(block_hash)
     }
 
     pub fn save_snapshot(&self, block_hash: UInt256, snapshot: models::LLMQSnapshot) -> bool {
         #[cfg(feature = "generate-dashj-tests")]
         crate::util::java::save_snapshot_to_json(&snapshot, self.lookup_block_height_by_hash(block_hash));
-        unsafe {
-            (self.save_llmq_snapshot)(
-                boxed(block_hash.0),
-                boxed(snapshot.encode()),
-                self.opaque_context,
-            )
-        }
+        self.provider.save_snapshot(block_hash, snapshot)
     }
 
     pub fn lookup_cl_signature_by_block_hash(&self, block_hash: UInt256) -> Option<UInt768> {
-        callbacks::lookup_cl_signature_by_block_hash(
-            block_hash,
-            |h: UInt256| unsafe {
-                (self.get_cl_signature_by_block_hash)(boxed(h.0), self.opaque_context)
-            },
-            |obj: *mut u8| unsafe { (self.destroy_hash)(obj) },
-        )
+        self.provider.lookup_cl_signature_by_block_hash(block_hash)
     }
 
     pub fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> bool {
         #[cfg(feature = "generate-dashj-tests")]
         crate::util::java::save_cl_signature_to_json(&cl_signature, self.lookup_block_height_by_hash(block_hash));
-        unsafe {
-            (self.save_cl_signature)(
-                boxed(block_hash.0),
-                boxed(cl_signature.0),
-                self.opaque_context,
-            )
-        }
+        self.provider.save_cl_signature(block_hash, cl_signature)
     }
 
     pub fn lookup_merkle_root_by_hash(&self, block_hash: UInt256) -> Option<UInt256> {
-        callbacks::lookup_merkle_root_by_hash(
-            block_hash,
-            |h: UInt256| unsafe { (self.get_merkle_root_by_hash)(boxed(h.0), self.opaque_context) },
-            |hash: *mut u8| unsafe { (self.destroy_hash)(hash) },
-        )
+        self.provider.lookup_merkle_root_by_hash(block_hash)
     }
 
-    pub fn should_process_diff_with_range(
-        &self,
-        base_block_hash: UInt256,
-        block_hash: UInt256,
-    ) -> ProcessingError {
-        unsafe {
-            (self.should_process_diff_with_range)(
-                boxed(base_block_hash.0),
-                boxed(block_hash.0),
-                self.opaque_context,
-            )
-        }
+    pub fn should_process_diff_with_range(&self, base_block_hash: UInt256, block_hash: UInt256) -> ProcessingError {
+        self.provider.should_process_diff_with_range(base_block_hash, block_hash)
     }
 
     pub fn add_insight(&self, block_hash: UInt256) {
-        unsafe { (self.add_insight)(boxed(block_hash.0), self.opaque_context) }
+        self.provider.add_insight(block_hash)
     }
 
     pub fn read_list_diff_from_message<'a>(
@@ -922,4 +1278,171 @@ impl MasternodeProcessor {
     ) -> Option<models::MNListDiff> {
         models::MNListDiff::new(message, offset, |block_hash| self.lookup_block_height_by_hash(block_hash), protocol_version)
     }
+
+    /// Same as `read_list_diff_from_message` but reports which part of the message is malformed
+    pub fn try_read_list_diff_from_message(
+        &self,
+        message: &[u8],
+        offset: &mut usize,
+        protocol_version: u32
+    ) -> Result<models::MNListDiff, MessageError> {
+        models::MNListDiff::read(message, offset, |block_hash| self.lookup_block_height_by_hash(block_hash), protocol_version)
+    }
+
+    pub fn try_read_rotation_info_from_message(
+        &self,
+        message: &[u8],
+        offset: &mut usize,
+        protocol_version: u32
+    ) -> Result<models::LLMQRotationInfo, MessageError> {
+        models::LLMQRotationInfo::read(message, offset, |block_hash| self.lookup_block_height_by_hash(block_hash), protocol_version)
+    }
+
+    fn check_diff_range(&self, section: MessageSection, list_diff: &models::MNListDiff) -> Result<(), MessageError> {
+        match self.should_process_diff_with_range(list_diff.base_block_hash, list_diff.block_hash) {
+            ProcessingError::None => Ok(()),
+            error => Err(MessageError::Rejected {
+                section,
+                base_block_hash: list_diff.base_block_hash,
+                block_hash: list_diff.block_hash,
+                error
+            })
+        }
+    }
+
+    /// Read and process message received as a response for 'GETMNLISTDIFF' call
+    pub fn mn_list_diff_result_from_message(
+        &self,
+        message: &[u8],
+        is_from_snapshot: bool,
+        protocol_version: u32,
+        cache: &mut MasternodeProcessorCache,
+    ) -> Result<MNListDiffResult, MessageError> {
+        let list_diff = self.try_read_list_diff_from_message(message, &mut 0, protocol_version)?;
+        if !is_from_snapshot {
+            self.check_diff_range(MessageSection::MNListDiff, &list_diff)?;
+        }
+        Ok(self.get_list_diff_result_internal_with_base_lookup(list_diff, LLMQVerificationContext::MNListDiff, cache))
+    }
+
+    /// Start from the masternode list at the checkpoint instead of syncing from the genesis:
+    /// the list is built from the checkpoint data, verified against the merkle roots of the checkpoint,
+    /// then cached and saved, so the following diffs can use the checkpoint block as their base
+    pub fn apply_masternode_list_checkpoint(
+        &self,
+        checkpoint: &models::MasternodeListCheckpoint,
+        data: &models::MasternodeListCheckpointData,
+        cache: &mut MasternodeProcessorCache,
+    ) -> Result<models::MasternodeList, CheckpointError> {
+        // The host may not have the header of the checkpoint block yet
+        let block_height_lookup = |block_hash: UInt256| if block_hash == checkpoint.block_hash {
+            checkpoint.block_height
+        } else {
+            self.lookup_block_height_by_hash(block_hash)
+        };
+        let list_diff = models::MNListDiff::read(&data.list_diff, &mut 0, block_height_lookup, data.protocol_version)
+            .map_err(CheckpointError::Malformed)?;
+        if list_diff.block_hash != checkpoint.block_hash {
+            return Err(CheckpointError::BlockHashMismatch { expected: checkpoint.block_hash, actual: list_diff.block_hash });
+        }
+        if !list_diff.base_block_hash.is_zero() && list_diff.base_block_hash != self.chain_type.genesis_hash() {
+            return Err(CheckpointError::UnexpectedBaseBlockHash(list_diff.base_block_hash));
+        }
+        let coinbase_transaction = &list_diff.coinbase_transaction;
+        if coinbase_transaction.merkle_root_mn_list != checkpoint.masternode_merkle_root {
+            return Err(CheckpointError::MasternodeMerkleRootMismatch);
+        }
+        if checkpoint.llmq_merkle_root.is_some() && coinbase_transaction.merkle_root_llmq_list != checkpoint.llmq_merkle_root {
+            return Err(CheckpointError::LLMQMerkleRootMismatch);
+        }
+        let block_hash = list_diff.block_hash;
+        let result = self.get_list_diff_result_internal(None, list_diff, LLMQVerificationContext::None, cache);
+        if !result.has_valid_mn_list_root || !result.has_valid_llmq_list_root {
+            cache.remove_masternode_list(&block_hash);
+            return Err(if result.has_valid_mn_list_root {
+                CheckpointError::LLMQMerkleRootMismatch
+            } else {
+                CheckpointError::MasternodeMerkleRootMismatch
+            });
+        }
+        data.snapshots.iter().for_each(|(block_hash, snapshot)| {
+            self.save_snapshot(*block_hash, snapshot.clone());
+            cache.add_snapshot(*block_hash, snapshot.clone());
+        });
+        self.save_masternode_list(block_hash, &result.masternode_list);
+        Ok(result.masternode_list)
+    }
+
+    /// Read and process message received as a response for 'GETQRINFO' call
+    pub fn qr_info_result_from_message(
+        &self,
+        message: &[u8],
+        is_from_snapshot: bool,
+        is_rotated_quorums_presented: bool,
+        protocol_version: u32,
+        cache: &mut MasternodeProcessorCache,
+    ) -> Result<QRInfoResult, MessageError> {
+        let models::LLMQRotationInfo {
+            snapshot_at_h_c,
+            snapshot_at_h_2c,
+            snapshot_at_h_3c,
+            snapshot_at_h_4c,
+            mn_list_diff_tip: diff_tip,
+            mn_list_diff_at_h: diff_h,
+            mn_list_diff_at_h_c: diff_h_c,
+            mn_list_diff_at_h_2c: diff_h_2c,
+            mn_list_diff_at_h_3c: diff_h_3c,
+            mn_list_diff_at_h_4c: diff_h_4c,
+            extra_share,
+            last_quorum_per_index,
+            quorum_snapshot_list,
+            mn_list_diff_list,
+        } = self.try_read_rotation_info_from_message(message, &mut 0, protocol_version)?;
+        if !is_from_snapshot {
+            self.check_diff_range(MessageSection::DiffAtTip, &diff_tip)?;
+        }
+        self.save_snapshot(diff_h_c.block_hash, snapshot_at_h_c.clone());
+        self.save_snapshot(diff_h_2c.block_hash, snapshot_at_h_2c.clone());
+        self.save_snapshot(diff_h_3c.block_hash, snapshot_at_h_3c.clone());
+        if let (Some(diff_h_4c), Some(snapshot_at_h_4c)) = (&diff_h_4c, &snapshot_at_h_4c) {
+            self.save_snapshot(diff_h_4c.block_hash, snapshot_at_h_4c.clone());
+        }
+        let mn_list_diff_list = mn_list_diff_list
+            .into_iter()
+            .zip(quorum_snapshot_list.iter())
+            .map(|(list_diff, snapshot)| {
+                let block_hash = list_diff.block_hash;
+                let result = self.get_list_diff_result_internal_with_base_lookup(list_diff, LLMQVerificationContext::None, cache);
+                self.save_snapshot(block_hash, snapshot.clone());
+                result
+            })
+            .collect();
+        // The order is important since the each new one dependent on previous
+        let result_at_h_4c = diff_h_4c
+            .map(|diff| self.get_list_diff_result_internal_with_base_lookup(diff, LLMQVerificationContext::None, cache));
+        let result_at_h_3c = self.get_list_diff_result_internal_with_base_lookup(diff_h_3c, LLMQVerificationContext::None, cache);
+        let result_at_h_2c = self.get_list_diff_result_internal_with_base_lookup(diff_h_2c, LLMQVerificationContext::None, cache);
+        let result_at_h_c = self.get_list_diff_result_internal_with_base_lookup(diff_h_c, LLMQVerificationContext::None, cache);
+        let result_at_h = self.get_list_diff_result_internal_with_base_lookup(diff_h, LLMQVerificationContext::QRInfo(is_rotated_quorums_presented), cache);
+        let result_at_tip = self.get_list_diff_result_internal_with_base_lookup(diff_tip, LLMQVerificationContext::None, cache);
+        debug!(target: target::QR_INFO, "processed block_hash_at_h={} block_hash_at_tip={} extra_share={}", result_at_h.block_hash, result_at_tip.block_hash, extra_share);
+        self.record_metric(Metric::QRInfoProcessed);
+        Ok(QRInfoResult {
+            error_status: ProcessingError::None,
+            result_at_tip,
+            result_at_h,
+            result_at_h_c,
+            result_at_h_2c,
+            result_at_h_3c,
+            result_at_h_4c,
+            snapshot_at_h_c,
+            snapshot_at_h_2c,
+            snapshot_at_h_3c,
+            snapshot_at_h_4c,
+            extra_share,
+            last_quorum_per_index,
+            quorum_snapshot_list,
+            mn_list_diff_list,
+        })
+    }
 }
@@ -1,16 +1,29 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
 use crate::chain::common;
 use crate::crypto::{UInt256, UInt768};
 use crate::models;
+use crate::processing::{CachePolicy, RotationError};
 
 #[derive(Clone, Default)]
 pub struct MasternodeProcessorCache {
     pub llmq_members: BTreeMap<common::LLMQType, BTreeMap<UInt256, Vec<models::MasternodeEntry>>>,
     pub llmq_indexed_members: BTreeMap<common::LLMQType, BTreeMap<models::LLMQIndexedHash, Vec<models::MasternodeEntry>>>,
-    pub mn_lists: BTreeMap<UInt256, models::MasternodeList>,
+    pub mn_lists: BTreeMap<UInt256, Arc<models::MasternodeList>>,
     pub llmq_snapshots: BTreeMap<UInt256, models::LLMQSnapshot>,
     pub cl_signatures: BTreeMap<UInt256, UInt768>,
     pub needed_masternode_lists: Vec<UInt256>,
+    /// Heights of the blocks whose hashes are required to compute members of rotated quorums
+    pub needed_block_heights: Vec<u32>,
+    /// Hashes of the blocks whose snapshots are required to compute members of rotated quorums
+    pub needed_snapshots: Vec<UInt256>,
+    /// Credit pool balances of the processed coinbase transactions (not stored in the cache snapshot)
+    pub credit_pool: models::CreditPool,
+    /// Limits used to evict entries when the cache grows
+    pub policy: CachePolicy,
+    pub(crate) access_clock: u64,
+    /// Last access stamps of the lists, snapshots and signatures by block hash
+    pub(crate) access_stamps: BTreeMap<UInt256, u64>,
 }
 
 impl std::fmt::Debug for MasternodeProcessorCache {
@@ -22,6 +35,10 @@ impl std::fmt::Debug for MasternodeProcessorCache {
             .field("mn_lists", &self.mn_lists)
             .field("cl_signatures", &self.cl_signatures)
             .field("needed_masternode_lists", &self.needed_masternode_lists)
+            .field("needed_block_heights", &self.needed_block_heights)
+            .field("needed_snapshots", &self.needed_snapshots)
+            .field("credit_pool", &self.credit_pool)
+            .field("policy", &self.policy)
             .finish()
     }
 }
@@ -33,25 +50,223 @@ impl MasternodeProcessorCache {
         self.mn_lists.clear();
         self.llmq_snapshots.clear();
         self.needed_masternode_lists.clear();
+        self.needed_block_heights.clear();
+        self.needed_snapshots.clear();
         self.cl_signatures.clear();
+        self.credit_pool.clear();
+        self.access_stamps.clear();
+    }
+    pub fn with_policy(policy: CachePolicy) -> Self {
+        Self { policy, ..Default::default() }
+    }
+    /// Replace the cached data with the restored one, keeping the policy of this cache
+    pub fn restore_from(&mut self, restored: MasternodeProcessorCache) {
+        let policy = self.policy;
+        *self = MasternodeProcessorCache { policy, ..restored };
+        self.enforce_policy();
+    }
+    /// Mark the data stored for the block as recently used (nothing is recorded if there is no such data)
+    pub fn touch(&mut self, block_hash: &UInt256) {
+        if self.has_data_for(block_hash) {
+            self.access_clock += 1;
+            self.access_stamps.insert(*block_hash, self.access_clock);
+        }
+    }
+    fn access_stamp(&self, block_hash: &UInt256) -> u64 {
+        self.access_stamps.get(block_hash).copied().unwrap_or_default()
+    }
+    fn has_data_for(&self, block_hash: &UInt256) -> bool {
+        self.mn_lists.contains_key(block_hash) ||
+            self.llmq_snapshots.contains_key(block_hash) ||
+            self.cl_signatures.contains_key(block_hash)
+    }
+    fn forget_if_unused(&mut self, block_hash: &UInt256) {
+        if !self.has_data_for(block_hash) {
+            self.access_stamps.remove(block_hash);
+        }
+    }
+    /// Remember the data which should be fetched by the host before the quorum can be validated
+    pub fn add_missing_rotation_data(&mut self, error: &RotationError) {
+        match *error {
+            RotationError::MissingBlockHash(block_height) if !self.needed_block_heights.contains(&block_height) =>
+                self.needed_block_heights.push(block_height),
+            RotationError::MissingMasternodeList { block_hash, .. } if !self.needed_masternode_lists.contains(&block_hash) =>
+                self.needed_masternode_lists.push(block_hash),
+            RotationError::MissingSnapshot { block_hash, .. } if !self.needed_snapshots.contains(&block_hash) =>
+                self.needed_snapshots.push(block_hash),
+            _ => {}
+        }
     }
     pub fn add_masternode_list(&mut self, block_hash: UInt256, list: models::MasternodeList) {
-        self.mn_lists.insert(block_hash, list);
+        self.mn_lists.insert(block_hash, Arc::new(list));
+        self.touch(&block_hash);
+        self.enforce_policy();
     }
     pub fn remove_masternode_list(&mut self, block_hash: &UInt256) {
         self.mn_lists.remove(block_hash);
+        self.forget_if_unused(block_hash);
     }
     pub fn add_snapshot(&mut self, block_hash: UInt256, snapshot: models::LLMQSnapshot) {
         self.llmq_snapshots.insert(block_hash, snapshot);
+        self.touch(&block_hash);
+        self.enforce_policy();
     }
     pub fn remove_snapshot(&mut self, block_hash: &UInt256) {
         self.llmq_snapshots.remove(block_hash);
+        self.forget_if_unused(block_hash);
     }
     pub fn add_cl_signature(&mut self, block_hash: UInt256, cl_signature: UInt768) {
         self.cl_signatures.insert(block_hash, cl_signature);
+        self.touch(&block_hash);
+        self.enforce_policy();
     }
     pub fn remove_cl_signature(&mut self, block_hash: &UInt256) {
         self.cl_signatures.remove(block_hash);
+        self.forget_if_unused(block_hash);
+    }
+
+    /// Hashes of the lists which are kept regardless of the access order:
+    /// the most recent ones and the ones within the window of active rotation cycles
+    pub fn pinned_masternode_lists(&self) -> BTreeSet<UInt256> {
+        let mut lists = self.mn_lists.iter()
+            .filter(|(_, list)| list.known_height != u32::MAX)
+            .map(|(block_hash, list)| (list.known_height, *block_hash))
+            .collect::<Vec<_>>();
+        lists.sort_unstable_by(|a, b| b.cmp(a));
+        let Some(&(tip_height, _)) = lists.first() else {
+            return BTreeSet::new();
+        };
+        let window_start = tip_height.saturating_sub(self.policy.rotation_window);
+        lists.iter()
+            .enumerate()
+            .filter(|&(i, &(height, _))| i < self.policy.keep_last_masternode_lists || (self.policy.rotation_window > 0 && height >= window_start))
+            .map(|(_, &(_, block_hash))| block_hash)
+            .collect()
+    }
+
+    /// Least recently used first
+    fn eviction_candidates<V>(&self, map: &BTreeMap<UInt256, V>, pinned: &BTreeSet<UInt256>) -> Vec<UInt256> {
+        let mut candidates = map.keys()
+            .filter(|block_hash| !pinned.contains(block_hash))
+            .map(|block_hash| (self.access_stamp(block_hash), *block_hash))
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        candidates.into_iter().map(|(_, block_hash)| block_hash).collect()
+    }
+
+    /// Returns the estimated number of bytes released, `entry_lists` counts the cached lists sharing each entry
+    fn evict_masternode_list(&mut self, block_hash: &UInt256, entry_lists: &mut HashMap<*const models::MasternodeEntry, usize>) -> usize {
+        let key_size = std::mem::size_of::<UInt256>();
+        let stamps = self.access_stamps.len();
+        let mut released = 0;
+        if let Some(list) = self.mn_lists.remove(block_hash) {
+            released += key_size + list.own_memory_usage();
+            for entry in list.masternodes.values() {
+                if let Some(count) = entry_lists.get_mut(&Arc::as_ptr(entry)) {
+                    *count -= 1;
+                    if *count == 0 {
+                        released += entry.memory_usage();
+                    }
+                }
+            }
+        }
+        self.forget_if_unused(block_hash);
+        // Members are derived from the list at the quorum block
+        released += self.remove_quorum_members(block_hash);
+        released + (stamps - self.access_stamps.len()) * Self::ACCESS_STAMP_SIZE
+    }
+
+    /// Number of the cached lists sharing each entry
+    fn masternode_entry_lists(&self) -> HashMap<*const models::MasternodeEntry, usize> {
+        let mut entry_lists = HashMap::new();
+        self.mn_lists.values()
+            .flat_map(|list| list.masternodes.values())
+            .for_each(|entry| *entry_lists.entry(Arc::as_ptr(entry)).or_default() += 1);
+        entry_lists
+    }
+
+    /// Evict entries exceeding the limits of the policy.
+    /// Returns hashes of the evicted masternode lists
+    pub fn enforce_policy(&mut self) -> Vec<UInt256> {
+        let mut evicted = Vec::new();
+        if !self.policy.is_bounded() {
+            return evicted;
+        }
+        let pinned = self.pinned_masternode_lists();
+        let mut list_candidates = self.eviction_candidates(&self.mn_lists, &pinned).into_iter();
+        // Computed only if some list is evicted
+        let mut entry_lists = None;
+        if let Some(max) = self.policy.max_masternode_lists {
+            while self.mn_lists.len() > max {
+                match list_candidates.next() {
+                    Some(block_hash) => {
+                        self.evict_masternode_list(&block_hash, entry_lists.get_or_insert_with(|| self.masternode_entry_lists()));
+                        evicted.push(block_hash);
+                    },
+                    None => break
+                }
+            }
+        }
+        if let Some(max) = self.policy.max_snapshots {
+            let excess = self.llmq_snapshots.len().saturating_sub(max);
+            for block_hash in self.eviction_candidates(&self.llmq_snapshots, &pinned).into_iter().take(excess) {
+                self.remove_snapshot(&block_hash);
+            }
+        }
+        if let Some(max) = self.policy.max_cl_signatures {
+            let excess = self.cl_signatures.len().saturating_sub(max);
+            for block_hash in self.eviction_candidates(&self.cl_signatures, &pinned).into_iter().take(excess) {
+                self.remove_cl_signature(&block_hash);
+            }
+        }
+        if let Some(max) = self.policy.max_memory_usage {
+            let mut usage = self.memory_usage();
+            while usage > max {
+                match list_candidates.next() {
+                    Some(block_hash) => {
+                        let entry_lists = entry_lists.get_or_insert_with(|| self.masternode_entry_lists());
+                        usage = usage.saturating_sub(self.evict_masternode_list(&block_hash, entry_lists));
+                        evicted.push(block_hash);
+                    },
+                    None => break
+                }
+            }
+        }
+        evicted
+    }
+
+    const ACCESS_STAMP_SIZE: usize = std::mem::size_of::<UInt256>() + std::mem::size_of::<u64>();
+
+    fn members_memory_usage(members: &[models::MasternodeEntry]) -> usize {
+        members.iter().map(models::MasternodeEntry::memory_usage).sum()
+    }
+
+    /// Estimated number of bytes held by the cached data (entries shared by the lists are counted once)
+    pub fn memory_usage(&self) -> usize {
+        let key_size = std::mem::size_of::<UInt256>();
+        let mut counted_entries = HashSet::new();
+        let lists = self.mn_lists.values()
+            .map(|list| key_size + list.own_memory_usage() + list.masternodes.values()
+                .filter(|entry| counted_entries.insert(Arc::as_ptr(entry)))
+                .map(|entry| entry.memory_usage())
+                .sum::<usize>())
+            .sum::<usize>();
+        let snapshots = self.llmq_snapshots.values()
+            .map(|snapshot| key_size + snapshot.memory_usage())
+            .sum::<usize>();
+        let cl_signatures = self.cl_signatures.len() * (key_size + std::mem::size_of::<UInt768>());
+        let members = self.llmq_members.values()
+            .flat_map(|map| map.values())
+            .map(|members| key_size + Self::members_memory_usage(members))
+            .sum::<usize>();
+        let indexed_members = self.llmq_indexed_members.values()
+            .flat_map(|map| map.values())
+            .map(|members| std::mem::size_of::<models::LLMQIndexedHash>() + Self::members_memory_usage(members))
+            .sum::<usize>();
+        let needed = (self.needed_masternode_lists.len() + self.needed_snapshots.len()) * key_size +
+            self.needed_block_heights.len() * std::mem::size_of::<u32>();
+        let access_stamps = self.access_stamps.len() * Self::ACCESS_STAMP_SIZE;
+        lists + snapshots + cl_signatures + members + indexed_members + needed + access_stamps
     }
     pub fn get_quorum_members_of_type(
         &mut self,
@@ -81,9 +296,13 @@ impl MasternodeProcessorCache {
         None
     }
 
-    pub fn remove_quorum_members(&mut self, block_hash: &UInt256) {
+    /// Returns the estimated number of bytes released
+    pub fn remove_quorum_members(&mut self, block_hash: &UInt256) -> usize {
+        let mut released = 0;
         self.llmq_members.iter_mut().for_each(|(llmq_type, map)| {
-            map.remove(block_hash);
+            if let Some(members) = map.remove(block_hash) {
+                released += std::mem::size_of::<UInt256>() + Self::members_memory_usage(&members);
+            }
         });
         self.llmq_indexed_members.iter_mut().for_each(|(llmq_type, map)| {
             let empties = map
@@ -92,8 +311,11 @@ impl MasternodeProcessorCache {
                 .map(|(k, _)| *k)
                 .collect::<Vec<_>>();
             empties.iter().for_each(|h| {
-                map.remove(h);
+                if let Some(members) = map.remove(h) {
+                    released += std::mem::size_of::<models::LLMQIndexedHash>() + Self::members_memory_usage(&members);
+                }
             });
         });
+        released
     }
 }
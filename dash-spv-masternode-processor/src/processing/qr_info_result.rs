@@ -1,4 +1,7 @@
-use crate::models;
+use std::ptr::null_mut;
+use crate::{models, types};
+use crate::ffi::boxer::{boxed, boxed_vec};
+use crate::ffi::to::ToFFI;
 use crate::processing::{MNListDiffResult, ProcessingError};
 
 #[derive(Debug)]
@@ -47,4 +50,41 @@ impl QRInfoResult {
     pub fn default_with_error(error: ProcessingError) -> Self {
         Self { error_status: error, ..Self::default() }
     }
+
+    pub fn encode(&self) -> types::QRInfoResult {
+        types::QRInfoResult {
+            error_status: self.error_status,
+            message_error: null_mut(),
+            result_at_tip: boxed(self.result_at_tip.encode()),
+            result_at_h: boxed(self.result_at_h.encode()),
+            result_at_h_c: boxed(self.result_at_h_c.encode()),
+            result_at_h_2c: boxed(self.result_at_h_2c.encode()),
+            result_at_h_3c: boxed(self.result_at_h_3c.encode()),
+            result_at_h_4c: self.result_at_h_4c
+                .as_ref()
+                .map_or(null_mut(), |result| boxed(result.encode())),
+            snapshot_at_h_c: boxed(self.snapshot_at_h_c.encode()),
+            snapshot_at_h_2c: boxed(self.snapshot_at_h_2c.encode()),
+            snapshot_at_h_3c: boxed(self.snapshot_at_h_3c.encode()),
+            snapshot_at_h_4c: self.snapshot_at_h_4c
+                .as_ref()
+                .map_or(null_mut(), |snapshot| boxed(snapshot.encode())),
+            extra_share: self.extra_share,
+            last_quorum_per_index: boxed_vec(self.last_quorum_per_index
+                .iter()
+                .map(|quorum| boxed(quorum.encode()))
+                .collect()),
+            last_quorum_per_index_count: self.last_quorum_per_index.len(),
+            quorum_snapshot_list: boxed_vec(self.quorum_snapshot_list
+                .iter()
+                .map(|snapshot| boxed(snapshot.encode()))
+                .collect()),
+            quorum_snapshot_list_count: self.quorum_snapshot_list.len(),
+            mn_list_diff_list: boxed_vec(self.mn_list_diff_list
+                .iter()
+                .map(|result| boxed(result.encode()))
+                .collect()),
+            mn_list_diff_list_count: self.mn_list_diff_list.len(),
+        }
+    }
 }
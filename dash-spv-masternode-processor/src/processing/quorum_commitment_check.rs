@@ -0,0 +1,70 @@
+use crate::chain::common::LLMQType;
+use crate::crypto::UInt256;
+use crate::models::LLMQEntry;
+use crate::tx::QuorumCommitmentTransaction;
+
+#[warn(non_camel_case_types)]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum QuorumCommitmentCheckStatus {
+    /// Quorum added by the diff is the same as the mined one
+    Matched = 0,
+    /// Mined quorum isn't added by the diff
+    NotInDiff = 1,
+    /// Quorum added by the diff differs from the mined one
+    Mismatch = 2,
+    /// Quorum added by the single block diff has no commitment mined in the block
+    NotMined = 3,
+}
+
+/// Outcome of the cross-check of the commitment mined in the block against the diff
+/// (or of the quorum added by the diff against the mined commitments)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuorumCommitmentCheck {
+    pub llmq_type: LLMQType,
+    pub llmq_hash: UInt256,
+    pub index: Option<u16>,
+    /// Height of the mined commitment (if any)
+    pub commitment_height: Option<u32>,
+    /// Entry hash of the mined commitment (if any)
+    pub mined_entry_hash: Option<UInt256>,
+    /// Entry hash of the quorum added by the diff (if any)
+    pub diff_entry_hash: Option<UInt256>,
+    pub status: QuorumCommitmentCheckStatus,
+}
+
+impl QuorumCommitmentCheck {
+    pub fn new(commitment: &QuorumCommitmentTransaction, diff_entry_hash: Option<UInt256>) -> Self {
+        let entry = &commitment.quorum_entry;
+        let status = match diff_entry_hash {
+            Some(hash) if hash == entry.entry_hash => QuorumCommitmentCheckStatus::Matched,
+            Some(_) => QuorumCommitmentCheckStatus::Mismatch,
+            None => QuorumCommitmentCheckStatus::NotInDiff,
+        };
+        Self {
+            llmq_type: entry.llmq_type,
+            llmq_hash: entry.llmq_hash,
+            index: entry.index,
+            commitment_height: Some(commitment.quorum_commitment_height),
+            mined_entry_hash: Some(entry.entry_hash),
+            diff_entry_hash,
+            status,
+        }
+    }
+
+    pub fn not_mined(entry: &LLMQEntry) -> Self {
+        Self {
+            llmq_type: entry.llmq_type,
+            llmq_hash: entry.llmq_hash,
+            index: entry.index,
+            commitment_height: None,
+            mined_entry_hash: None,
+            diff_entry_hash: Some(entry.entry_hash),
+            status: QuorumCommitmentCheckStatus::NotMined,
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.status == QuorumCommitmentCheckStatus::Matched
+    }
+}
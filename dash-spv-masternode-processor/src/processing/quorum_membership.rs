@@ -0,0 +1,33 @@
+use crate::chain::common::LLMQType;
+use crate::crypto::{data_ops::Data, UInt256};
+use crate::models;
+
+/// Quorum the masternode was selected into
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuorumMembership {
+    pub llmq_type: LLMQType,
+    pub llmq_hash: UInt256,
+    /// Index of the rotated quorum within its cycle
+    pub index: Option<u16>,
+    pub block_height: u32,
+    /// Position of the masternode in the quorum members (and in the bitsets of the commitment)
+    pub member_index: u32,
+    /// The masternode signed the final commitment of the quorum
+    pub signed: bool,
+    /// The masternode is the valid member of the quorum according to the commitment
+    pub valid_member: bool,
+}
+
+impl QuorumMembership {
+    pub fn new(quorum: &models::LLMQEntry, block_height: u32, member_index: u32) -> Self {
+        Self {
+            llmq_type: quorum.llmq_type,
+            llmq_hash: quorum.llmq_hash,
+            index: quorum.index,
+            block_height,
+            member_index,
+            signed: quorum.signers_bitset.as_slice().bit_is_true_at_le_index(member_index),
+            valid_member: quorum.valid_members_bitset.as_slice().bit_is_true_at_le_index(member_index),
+        }
+    }
+}
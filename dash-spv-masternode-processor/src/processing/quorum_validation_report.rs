@@ -0,0 +1,73 @@
+use hashes::hex::FromHex;
+use crate::chain::common::LLMQType;
+use crate::crypto::UInt256;
+use crate::ffi::boxer::{boxed, boxed_vec};
+use crate::{models, types};
+use crate::processing::{LLMQValidationStatus, llmq_validation_status::LLMQPayloadValidationStatus};
+use crate::types::quorum_validation_report::QuorumValidationStatus;
+
+/// Outcome of the validation of the single quorum from the diff
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuorumValidationReport {
+    pub llmq_type: LLMQType,
+    pub llmq_hash: UInt256,
+    pub index: Option<u16>,
+    pub status: LLMQValidationStatus,
+    /// ProRegTx hashes of the masternodes used as quorum members (in the order of the signers bitset)
+    pub members: Vec<UInt256>,
+}
+
+impl QuorumValidationReport {
+    pub fn new(quorum: &models::LLMQEntry, status: LLMQValidationStatus, members: Vec<UInt256>) -> Self {
+        Self {
+            llmq_type: quorum.llmq_type,
+            llmq_hash: quorum.llmq_hash,
+            index: quorum.index,
+            status,
+            members,
+        }
+    }
+
+    pub fn is_not_critical(&self) -> bool {
+        self.status.is_not_critical()
+    }
+
+    pub fn encode(&self) -> types::QuorumValidationReport {
+        let (status, invalid_bitset, actual_count, threshold) = match &self.status {
+            LLMQValidationStatus::Verified |
+            LLMQValidationStatus::InvalidPayload(LLMQPayloadValidationStatus::Ok) =>
+                (QuorumValidationStatus::Verified, vec![], 0, 0),
+            LLMQValidationStatus::InvalidPayload(LLMQPayloadValidationStatus::InvalidSigners(bitset)) =>
+                (QuorumValidationStatus::InvalidSigners, Vec::from_hex(bitset).unwrap_or_default(), 0, 0),
+            LLMQValidationStatus::InvalidPayload(LLMQPayloadValidationStatus::InvalidMembers(bitset)) =>
+                (QuorumValidationStatus::InvalidMembers, Vec::from_hex(bitset).unwrap_or_default(), 0, 0),
+            LLMQValidationStatus::InvalidPayload(LLMQPayloadValidationStatus::SignersBelowThreshold { actual, threshold }) =>
+                (QuorumValidationStatus::SignersBelowThreshold, vec![], *actual, *threshold),
+            LLMQValidationStatus::InvalidPayload(LLMQPayloadValidationStatus::MembersBelowThreshold { actual, threshold }) =>
+                (QuorumValidationStatus::MembersBelowThreshold, vec![], *actual, *threshold),
+            LLMQValidationStatus::InvalidAggregatedSignature =>
+                (QuorumValidationStatus::InvalidAggregatedSignature, vec![], 0, 0),
+            LLMQValidationStatus::InvalidQuorumSignature =>
+                (QuorumValidationStatus::InvalidQuorumSignature, vec![], 0, 0),
+            LLMQValidationStatus::NoMasternodeList =>
+                (QuorumValidationStatus::NoMasternodeList, vec![], 0, 0),
+            LLMQValidationStatus::MissingRotationData(_) =>
+                (QuorumValidationStatus::MissingRotationData, vec![], 0, 0),
+        };
+        types::QuorumValidationReport {
+            llmq_type: self.llmq_type,
+            llmq_hash: boxed(self.llmq_hash.0),
+            index: self.index.unwrap_or(0),
+            status,
+            invalid_bitset_length: invalid_bitset.len(),
+            invalid_bitset: boxed_vec(invalid_bitset),
+            actual_count,
+            threshold,
+            members: boxed_vec(self.members
+                .iter()
+                .map(|hash| boxed(hash.0))
+                .collect()),
+            members_count: self.members.len(),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::borrow::Borrow;
+use hashes::hex::ToHex;
+use serde::{Serialize, Serializer};
+use crate::chain::common::LLMQType;
+use crate::common::LLMQSnapshotSkipMode;
+use crate::crypto::{byte_util::Reversable, UInt256};
+use crate::models;
+
+/// Hashes are serialized in the display (reversed) form used by Dash Core RPC
+fn serialize_hash<S: Serializer>(hash: &UInt256, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hash.reversed().0.to_hex())
+}
+
+fn serialize_quarters<S: Serializer>(quarters: &[Vec<UInt256>], serializer: S) -> Result<S::Ok, S::Error> {
+    quarters.iter()
+        .map(|members| members.iter().map(|hash| hash.reversed().0.to_hex()).collect::<Vec<_>>())
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+fn serialize_llmq_type<S: Serializer>(llmq_type: &LLMQType, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u8(u8::from(*llmq_type))
+}
+
+fn pro_reg_tx_hashes(members: &[Vec<models::MasternodeEntry>]) -> Vec<Vec<UInt256>> {
+    members.iter()
+        .map(|quarter| quarter.iter().map(|node| node.provider_registration_transaction_hash).collect())
+        .collect()
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ScoredMasternode {
+    #[serde(serialize_with = "serialize_hash")]
+    pub pro_reg_tx_hash: UInt256,
+    /// None if the masternode has no score at the work block (not valid or not confirmed)
+    #[serde(serialize_with = "serialize_optional_hash")]
+    pub score: Option<UInt256>,
+}
+
+fn serialize_optional_hash<S: Serializer>(hash: &Option<UInt256>, serializer: S) -> Result<S::Ok, S::Error> {
+    match hash {
+        Some(hash) => serialize_hash(hash, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Intermediate data of the quarter members calculation at the work block of the quorum base block.
+/// Snapshot fields are named as in the quorum snapshots of Dash Core RPC
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct QuarterDiagnostics {
+    pub quorum_base_block_height: u32,
+    pub work_block_height: u32,
+    #[serde(serialize_with = "serialize_hash")]
+    pub work_block_hash: UInt256,
+    /// Member bits of the snapshot (empty for the new quarter which isn't taken from the snapshot)
+    #[serde(rename = "activeQuorumMembers")]
+    pub active_quorum_members: Vec<bool>,
+    /// Skip mode of the snapshot or the one of the skip list produced while building the new quarter
+    #[serde(rename = "mnSkipListMode")]
+    pub skip_list_mode: u32,
+    /// Skip list of the snapshot or the one produced while building the new quarter
+    #[serde(rename = "mnSkipList")]
+    pub skip_list: Vec<i32>,
+    /// Unused masternodes followed by the used ones, each part is sorted by score
+    pub sorted_combined_masternodes: Vec<ScoredMasternode>,
+    /// Quarter members per quorum index
+    #[serde(serialize_with = "serialize_quarters")]
+    pub members: Vec<Vec<UInt256>>,
+}
+
+impl QuarterDiagnostics {
+    pub(crate) fn new(quorum_base_block_height: u32, work_block_height: u32, work_block_hash: UInt256) -> Self {
+        Self { quorum_base_block_height, work_block_height, work_block_hash, ..Default::default() }
+    }
+
+    pub(crate) fn record_snapshot(&mut self, snapshot: &models::LLMQSnapshot) {
+        self.active_quorum_members = (0..snapshot.member_count as u32)
+            .map(|i| snapshot.member_is_true_at_index(i))
+            .collect();
+        self.skip_list_mode = u32::from(snapshot.skip_list_mode);
+        self.skip_list = snapshot.skip_list.clone();
+    }
+
+    pub(crate) fn record_new_quarter_skip_list(&mut self, skip_list: Vec<i32>) {
+        self.skip_list_mode = u32::from(if skip_list.is_empty() {
+            LLMQSnapshotSkipMode::NoSkipping
+        } else {
+            LLMQSnapshotSkipMode::SkipFirst
+        });
+        self.skip_list = skip_list;
+    }
+
+    pub(crate) fn record_sorted_combined_masternodes<T: Borrow<models::MasternodeEntry>>(&mut self, masternodes: &[T], quorum_modifier: UInt256) {
+        self.sorted_combined_masternodes = masternodes.iter()
+            .map(Borrow::borrow)
+            .map(|node: &models::MasternodeEntry| ScoredMasternode {
+                pro_reg_tx_hash: node.provider_registration_transaction_hash,
+                score: models::MasternodeList::masternode_score(node, quorum_modifier, self.work_block_height),
+            })
+            .collect();
+    }
+
+    pub(crate) fn record_members(&mut self, members: &[Vec<models::MasternodeEntry>]) {
+        self.members = pro_reg_tx_hashes(members);
+    }
+}
+
+/// DIP-24 rotation cycle reconstructed step by step: quarters at h-c, h-2c, h-3c (from the snapshots),
+/// the new quarter at h and the resulting members of each quorum index
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct RotationDiagnostics {
+    #[serde(serialize_with = "serialize_llmq_type")]
+    pub llmq_type: LLMQType,
+    pub cycle_base_block_height: u32,
+    pub skip_removed_masternodes: bool,
+    pub quarter_at_h_c: QuarterDiagnostics,
+    pub quarter_at_h_2c: QuarterDiagnostics,
+    pub quarter_at_h_3c: QuarterDiagnostics,
+    pub new_quarter: QuarterDiagnostics,
+    /// Members per quorum index
+    #[serde(serialize_with = "serialize_quarters")]
+    pub quorum_members: Vec<Vec<UInt256>>,
+}
+
+impl RotationDiagnostics {
+    pub(crate) fn new(llmq_type: LLMQType, cycle_base_block_height: u32, skip_removed_masternodes: bool) -> Self {
+        Self {
+            llmq_type,
+            cycle_base_block_height,
+            skip_removed_masternodes,
+            quarter_at_h_c: Default::default(),
+            quarter_at_h_2c: Default::default(),
+            quarter_at_h_3c: Default::default(),
+            new_quarter: Default::default(),
+            quorum_members: vec![],
+        }
+    }
+
+    pub(crate) fn record_quorum_members(&mut self, members: &[Vec<models::MasternodeEntry>]) {
+        self.quorum_members = pro_reg_tx_hashes(members);
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::crypto::UInt256;
+
+/// Data required to compute members of the rotated quorum which isn't available (yet)
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum RotationError {
+    /// Hash of the block at height is unknown
+    MissingBlockHash(u32),
+    MissingMasternodeList {
+        block_height: u32,
+        block_hash: UInt256,
+    },
+    MissingSnapshot {
+        block_height: u32,
+        block_hash: UInt256,
+    },
+    /// Quorum cycle starts before the genesis
+    InvalidBlockHeight(u32),
+}
+
+impl std::fmt::Display for RotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RotationError::MissingBlockHash(block_height) =>
+                write!(f, "Missing hash for block at height {}", block_height),
+            RotationError::MissingMasternodeList { block_height, block_hash } =>
+                write!(f, "Missing masternode list for block at height {}: {}", block_height, block_hash),
+            RotationError::MissingSnapshot { block_height, block_hash } =>
+                write!(f, "Missing snapshot for block at height {}: {}", block_height, block_hash),
+            RotationError::InvalidBlockHeight(block_height) =>
+                write!(f, "Invalid quorum cycle height {}", block_height),
+        }
+    }
+}
+
+impl std::error::Error for RotationError {}
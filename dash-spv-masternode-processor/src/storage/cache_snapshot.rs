@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Write};
+use std::path::Path;
+use std::sync::Arc;
+use byte::{BytesExt, LE};
+use crate::chain::common::LLMQType;
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::{UInt256, UInt768};
+use crate::models::{LLMQIndexedHash, LLMQSnapshot, MasternodeEntry, MasternodeList};
+use crate::processing::MasternodeProcessorCache;
+use crate::storage::Persistable;
+
+const CACHE_SNAPSHOT_MAGIC: [u8; 4] = *b"DMPC";
+/// Current version of the cache snapshot format
+pub const CACHE_SNAPSHOT_VERSION: u16 = 1;
+const CACHE_SNAPSHOT_CHECKSUM_SIZE: usize = 4;
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn checksum(data: &[u8]) -> [u8; CACHE_SNAPSHOT_CHECKSUM_SIZE] {
+    let hash = UInt256::sha256d(data);
+    [hash.0[0], hash.0[1], hash.0[2], hash.0[3]]
+}
+
+fn persist_members(members: &[MasternodeEntry], writer: &mut Vec<u8>) {
+    VarInt(members.len() as u64).enc(&mut *writer);
+    members.iter().for_each(|entry| entry.persist(writer));
+}
+
+fn restore_members(bytes: &[u8], offset: &mut usize) -> byte::Result<Vec<MasternodeEntry>> {
+    let count = bytes.read_with::<VarInt>(offset, LE)?.0;
+    (0..count)
+        .map(|_| MasternodeEntry::restore(bytes, offset))
+        .collect()
+}
+
+/// Entries are stored per list, so the ones which didn't change since the previous list are shared again
+fn share_masternode_entries(mut lists: BTreeMap<UInt256, MasternodeList>) -> BTreeMap<UInt256, Arc<MasternodeList>> {
+    let mut block_hashes = lists.iter()
+        .map(|(block_hash, list)| (list.known_height, *block_hash))
+        .collect::<Vec<_>>();
+    block_hashes.sort_unstable();
+    let mut previous = BTreeMap::<UInt256, Arc<MasternodeEntry>>::new();
+    for (_, block_hash) in block_hashes {
+        if let Some(list) = lists.get_mut(&block_hash) {
+            for (hash, entry) in list.masternodes.iter_mut() {
+                match previous.get(hash) {
+                    Some(shared) if shared == entry => *entry = Arc::clone(shared),
+                    _ => { previous.insert(*hash, Arc::clone(entry)); }
+                }
+            }
+        }
+    }
+    lists.into_iter()
+        .map(|(block_hash, list)| (block_hash, Arc::new(list)))
+        .collect()
+}
+
+fn persist_map<K, V, FK: Fn(&K, &mut Vec<u8>), FV: Fn(&V, &mut Vec<u8>)>(map: &BTreeMap<K, V>, writer: &mut Vec<u8>, persist_key: FK, persist_value: FV) {
+    VarInt(map.len() as u64).enc(&mut *writer);
+    map.iter().for_each(|(key, value)| {
+        persist_key(key, writer);
+        persist_value(value, writer);
+    });
+}
+
+fn restore_map<K: Ord, V, FK: Fn(&[u8], &mut usize) -> byte::Result<K>, FV: Fn(&[u8], &mut usize) -> byte::Result<V>>(bytes: &[u8], offset: &mut usize, restore_key: FK, restore_value: FV) -> byte::Result<BTreeMap<K, V>> {
+    let count = bytes.read_with::<VarInt>(offset, LE)?.0;
+    let mut map = BTreeMap::new();
+    for _i in 0..count {
+        let key = restore_key(bytes, offset)?;
+        map.insert(key, restore_value(bytes, offset)?);
+    }
+    Ok(map)
+}
+
+fn persist_block_hash(block_hash: &UInt256, writer: &mut Vec<u8>) {
+    block_hash.enc(writer);
+}
+
+fn restore_block_hash(bytes: &[u8], offset: &mut usize) -> byte::Result<UInt256> {
+    bytes.read_with::<UInt256>(offset, LE)
+}
+
+fn persist_llmq_type(llmq_type: &LLMQType, writer: &mut Vec<u8>) {
+    u8::from(*llmq_type).enc(writer);
+}
+
+fn restore_llmq_type(bytes: &[u8], offset: &mut usize) -> byte::Result<LLMQType> {
+    bytes.read_with::<u8>(offset, LE).map(LLMQType::from)
+}
+
+/// Lists, snapshots, CL signatures and computed quorum members of the cache.
+/// Cache policy and the data requested from the host (needed lists, snapshots and block heights) are not stored
+impl Persistable for MasternodeProcessorCache {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        persist_map(&self.mn_lists, writer, persist_block_hash, |list, writer| list.persist(writer));
+        persist_map(&self.llmq_snapshots, writer, persist_block_hash, LLMQSnapshot::persist);
+        persist_map(&self.cl_signatures, writer, persist_block_hash, UInt768::persist);
+        persist_map(&self.llmq_members, writer, persist_llmq_type, |map, writer|
+            persist_map(map, writer, persist_block_hash, |members, writer| persist_members(members, writer)));
+        persist_map(&self.llmq_indexed_members, writer, persist_llmq_type, |map, writer|
+            persist_map(map, writer, |indexed_hash: &LLMQIndexedHash, writer| {
+                indexed_hash.index.enc(&mut *writer);
+                indexed_hash.hash.enc(writer);
+            }, |members, writer| persist_members(members, writer)));
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        let mn_lists = share_masternode_entries(restore_map(bytes, offset, restore_block_hash, MasternodeList::restore)?);
+        let llmq_snapshots = restore_map(bytes, offset, restore_block_hash, LLMQSnapshot::restore)?;
+        let cl_signatures = restore_map(bytes, offset, restore_block_hash, UInt768::restore)?;
+        let llmq_members = restore_map(bytes, offset, restore_llmq_type, |bytes, offset|
+            restore_map(bytes, offset, restore_block_hash, restore_members))?;
+        let llmq_indexed_members = restore_map(bytes, offset, restore_llmq_type, |bytes, offset|
+            restore_map(bytes, offset, |bytes, offset| {
+                let index = bytes.read_with::<u32>(offset, LE)?;
+                let hash = restore_block_hash(bytes, offset)?;
+                Ok(LLMQIndexedHash::new(hash, index))
+            }, restore_members))?;
+        let mut cache = MasternodeProcessorCache {
+            llmq_members,
+            llmq_indexed_members,
+            mn_lists,
+            llmq_snapshots,
+            cl_signatures,
+            ..Default::default()
+        };
+        let block_hashes = cache.mn_lists.keys()
+            .chain(cache.llmq_snapshots.keys())
+            .chain(cache.cl_signatures.keys())
+            .copied()
+            .collect::<Vec<_>>();
+        block_hashes.iter().for_each(|block_hash| cache.touch(block_hash));
+        Ok(cache)
+    }
+}
+
+/// Versioned and checksummed binary snapshot of the cache
+pub fn cache_snapshot_data(cache: &MasternodeProcessorCache) -> Vec<u8> {
+    let mut writer = Vec::<u8>::new();
+    writer.extend_from_slice(&CACHE_SNAPSHOT_MAGIC);
+    CACHE_SNAPSHOT_VERSION.enc(&mut writer);
+    cache.persist(&mut writer);
+    let checksum = checksum(&writer);
+    writer.extend_from_slice(&checksum);
+    writer
+}
+
+/// Restore the cache from the snapshot made by `cache_snapshot_data`
+pub fn cache_from_snapshot_data(bytes: &[u8]) -> io::Result<MasternodeProcessorCache> {
+    let header_size = CACHE_SNAPSHOT_MAGIC.len() + 2;
+    if bytes.len() < header_size + CACHE_SNAPSHOT_CHECKSUM_SIZE {
+        return Err(invalid_data("Cache snapshot is too short"));
+    }
+    if bytes[..CACHE_SNAPSHOT_MAGIC.len()] != CACHE_SNAPSHOT_MAGIC {
+        return Err(invalid_data("Not a cache snapshot"));
+    }
+    let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if version != CACHE_SNAPSHOT_VERSION {
+        return Err(invalid_data("Unsupported cache snapshot version"));
+    }
+    let (data, stored_checksum) = bytes.split_at(bytes.len() - CACHE_SNAPSHOT_CHECKSUM_SIZE);
+    if checksum(data) != stored_checksum {
+        return Err(invalid_data("Cache snapshot checksum mismatch"));
+    }
+    MasternodeProcessorCache::from_persisted_data(&data[header_size..])
+        .map_err(|_| invalid_data("Corrupted cache snapshot"))
+}
+
+/// Write the snapshot into a temporary file first, so the existing snapshot is replaced only when the new one is complete
+pub fn save_cache_snapshot<P: AsRef<Path>>(cache: &MasternodeProcessorCache, path: P) -> io::Result<()> {
+    let path = path.as_ref();
+    let tmp_path = path.with_extension("tmp");
+    let mut tmp = File::create(&tmp_path)?;
+    tmp.write_all(&cache_snapshot_data(cache))?;
+    tmp.sync_all()?;
+    drop(tmp);
+    fs::rename(&tmp_path, path)
+}
+
+pub fn load_cache_snapshot<P: AsRef<Path>>(path: P) -> io::Result<MasternodeProcessorCache> {
+    cache_from_snapshot_data(&fs::read(path)?)
+}
@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use crate::consensus::Encodable;
+use crate::crypto::{UInt256, UInt768};
+use crate::models::{LLMQSnapshot, MasternodeList};
+use crate::storage::Persistable;
+
+const STORE_MAGIC: [u8; 4] = *b"DMNS";
+/// Current version of the store file format
+pub const STORE_VERSION: u16 = 2;
+const STORE_HEADER_SIZE: u64 = 6;
+// kind (1) + block hash (32) + block height (4) + payload length (4)
+const RECORD_HEADER_SIZE: usize = 41;
+const RECORD_CHECKSUM_SIZE: usize = 4;
+// Compaction is started automatically once superseded records take more than this and more than live records
+const AUTO_COMPACTION_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RecordKind {
+    MasternodeList = 1,
+    LLMQSnapshot = 2,
+    CLSignature = 3,
+}
+
+impl RecordKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(RecordKind::MasternodeList),
+            2 => Some(RecordKind::LLMQSnapshot),
+            3 => Some(RecordKind::CLSignature),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct RecordLocation {
+    offset: u64,
+    size: usize,
+    height: u32,
+}
+
+struct FileStoreInner {
+    file: File,
+    len: u64,
+    index: BTreeMap<(RecordKind, UInt256), RecordLocation>,
+    dead_bytes: u64,
+}
+
+/// Append-only file-backed storage for masternode lists, quorum snapshots and ChainLock signatures keyed by block hash.
+/// Each record is checksummed; on opening, a torn or corrupted tail (i.e. after a crash in the middle of write)
+/// is cut off so the store always resumes from the last completely written record.
+/// Superseded and removed records are dropped by compaction, which rewrites live records
+/// into a temporary file and atomically replaces the store with it.
+pub struct FileStore {
+    path: PathBuf,
+    inner: Mutex<FileStoreInner>,
+}
+
+impl std::fmt::Debug for FileStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileStore")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+fn checksum(data: &[u8]) -> [u8; RECORD_CHECKSUM_SIZE] {
+    let hash = UInt256::sha256d(data);
+    [hash.0[0], hash.0[1], hash.0[2], hash.0[3]]
+}
+
+fn encode_record(kind: RecordKind, block_hash: UInt256, height: u32, payload: &[u8]) -> Vec<u8> {
+    let mut writer = Vec::<u8>::with_capacity(RECORD_HEADER_SIZE + payload.len() + RECORD_CHECKSUM_SIZE);
+    (kind as u8).enc(&mut writer);
+    block_hash.enc(&mut writer);
+    height.enc(&mut writer);
+    (payload.len() as u32).enc(&mut writer);
+    writer.extend_from_slice(payload);
+    let checksum = checksum(&writer);
+    writer.extend_from_slice(&checksum);
+    writer
+}
+
+fn read_record_header(header: &[u8; RECORD_HEADER_SIZE]) -> (u8, UInt256, u32, usize) {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&header[1..33]);
+    let height = u32::from_le_bytes([header[33], header[34], header[35], header[36]]);
+    let len = u32::from_le_bytes([header[37], header[38], header[39], header[40]]);
+    (header[0], UInt256(hash), height, len as usize)
+}
+
+fn write_header(file: &mut File) -> io::Result<()> {
+    file.write_all(&STORE_MAGIC)?;
+    file.write_all(&STORE_VERSION.to_le_bytes())
+}
+
+fn sync_dir(path: &Path) {
+    // Directory sync makes rename durable, but it isn't supported on every platform
+    if let Some(dir) = path.parent() {
+        if let Ok(dir) = File::open(dir) {
+            let _ = dir.sync_all();
+        }
+    }
+}
+
+impl FileStoreInner {
+
+    fn load(mut file: File) -> io::Result<Self> {
+        let file_len = file.metadata()?.len();
+        if file_len < STORE_HEADER_SIZE {
+            // New store or the header itself was torn
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            write_header(&mut file)?;
+            file.sync_all()?;
+            return Ok(Self { file, len: STORE_HEADER_SIZE, index: BTreeMap::new(), dead_bytes: 0 });
+        }
+        let mut index = BTreeMap::<(RecordKind, UInt256), RecordLocation>::new();
+        let mut dead_bytes = 0u64;
+        let mut valid_len = STORE_HEADER_SIZE;
+        {
+            file.seek(SeekFrom::Start(0))?;
+            let mut reader = BufReader::new(&mut file);
+            let mut header = [0u8; STORE_HEADER_SIZE as usize];
+            reader.read_exact(&mut header)?;
+            if header[..4] != STORE_MAGIC {
+                return Err(invalid_data("Not a masternode store file"));
+            }
+            let version = u16::from_le_bytes([header[4], header[5]]);
+            if version != STORE_VERSION {
+                return Err(invalid_data("Unsupported masternode store version"));
+            }
+            let mut record_header = [0u8; RECORD_HEADER_SIZE];
+            let mut record = Vec::<u8>::new();
+            loop {
+                if reader.read_exact(&mut record_header).is_err() {
+                    break;
+                }
+                let (kind, block_hash, height, len) = read_record_header(&record_header);
+                let size = RECORD_HEADER_SIZE + len + RECORD_CHECKSUM_SIZE;
+                if valid_len + size as u64 > file_len {
+                    break;
+                }
+                record.clear();
+                record.extend_from_slice(&record_header);
+                record.resize(size, 0);
+                if reader.read_exact(&mut record[RECORD_HEADER_SIZE..]).is_err() {
+                    break;
+                }
+                let (data, sum) = record.split_at(size - RECORD_CHECKSUM_SIZE);
+                if checksum(data) != sum {
+                    warn!("FileStore: checksum mismatch at offset {}, dropping the tail", valid_len);
+                    break;
+                }
+                let kind = match RecordKind::from_u8(kind) {
+                    Some(kind) => kind,
+                    None => {
+                        warn!("FileStore: unknown record kind {} at offset {}, dropping the tail", kind, valid_len);
+                        break;
+                    }
+                };
+                let location = RecordLocation { offset: valid_len, size, height };
+                // Empty payload is a tombstone
+                let replaced = if len == 0 {
+                    dead_bytes += size as u64;
+                    index.remove(&(kind, block_hash))
+                } else {
+                    index.insert((kind, block_hash), location)
+                };
+                if let Some(replaced) = replaced {
+                    dead_bytes += replaced.size as u64;
+                }
+                valid_len += size as u64;
+            }
+        }
+        if valid_len < file_len {
+            warn!("FileStore: truncating {} bytes of incomplete records", file_len - valid_len);
+            file.set_len(valid_len)?;
+            file.sync_all()?;
+        }
+        Ok(Self { file, len: valid_len, index, dead_bytes })
+    }
+
+    fn append(&mut self, kind: RecordKind, block_hash: UInt256, height: u32, payload: &[u8]) -> io::Result<()> {
+        let record = encode_record(kind, block_hash, height, payload);
+        let offset = self.len;
+        self.file.seek(SeekFrom::Start(offset))?;
+        if let Err(err) = self.file.write_all(&record).and_then(|_| self.file.sync_data()) {
+            // Don't leave a partial record behind to keep the file appendable
+            let _ = self.file.set_len(offset);
+            return Err(err);
+        }
+        self.len += record.len() as u64;
+        let replaced = if payload.is_empty() {
+            self.dead_bytes += record.len() as u64;
+            self.index.remove(&(kind, block_hash))
+        } else {
+            self.index.insert((kind, block_hash), RecordLocation { offset, size: record.len(), height })
+        };
+        if let Some(replaced) = replaced {
+            self.dead_bytes += replaced.size as u64;
+        }
+        Ok(())
+    }
+
+    fn read_record(&mut self, location: RecordLocation) -> io::Result<Vec<u8>> {
+        let mut record = vec![0u8; location.size];
+        self.file.seek(SeekFrom::Start(location.offset))?;
+        self.file.read_exact(&mut record)?;
+        let (data, sum) = record.split_at(location.size - RECORD_CHECKSUM_SIZE);
+        if checksum(data) != sum {
+            return Err(invalid_data("Masternode store record is corrupted"));
+        }
+        Ok(record)
+    }
+
+    fn read_payload(&mut self, location: RecordLocation) -> io::Result<Vec<u8>> {
+        let mut record = self.read_record(location)?;
+        record.truncate(location.size - RECORD_CHECKSUM_SIZE);
+        Ok(record.split_off(RECORD_HEADER_SIZE))
+    }
+
+    fn live_bytes(&self) -> u64 {
+        self.len - STORE_HEADER_SIZE - self.dead_bytes
+    }
+}
+
+impl FileStore {
+    /// Opens the store at the path or creates a new one
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() && !dir.exists() {
+                fs::create_dir_all(dir)?;
+            }
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let inner = FileStoreInner::load(file)?;
+        Ok(Self { path, inner: Mutex::new(inner) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FileStoreInner> {
+        // Records are written before the index is updated so the state is consistent even after a panic
+        self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn put(&self, kind: RecordKind, block_hash: UInt256, height: u32, payload: &[u8]) -> io::Result<()> {
+        let mut inner = self.lock();
+        inner.append(kind, block_hash, height, payload)?;
+        if inner.dead_bytes > AUTO_COMPACTION_THRESHOLD && inner.dead_bytes > inner.live_bytes() {
+            self.compact_locked(&mut inner)?;
+        }
+        Ok(())
+    }
+
+    fn get<T: Persistable>(&self, kind: RecordKind, block_hash: UInt256) -> io::Result<Option<T>> {
+        let mut inner = self.lock();
+        match inner.index.get(&(kind, block_hash)).copied() {
+            Some(location) => {
+                let payload = inner.read_payload(location)?;
+                T::from_persisted_data(&payload)
+                    .map(Some)
+                    .map_err(|_err| invalid_data("Can't restore record from masternode store"))
+            },
+            None => Ok(None)
+        }
+    }
+
+    pub fn contains(&self, kind: RecordKind, block_hash: UInt256) -> bool {
+        self.lock().index.contains_key(&(kind, block_hash))
+    }
+
+    /// Removes the record by appending a tombstone
+    pub fn remove(&self, kind: RecordKind, block_hash: UInt256) -> io::Result<()> {
+        if self.contains(kind, block_hash) {
+            self.put(kind, block_hash, u32::MAX, &[])
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &MasternodeList) -> io::Result<()> {
+        self.put(RecordKind::MasternodeList, block_hash, masternode_list.known_height, &masternode_list.to_persisted_data())
+    }
+
+    pub fn masternode_list(&self, block_hash: UInt256) -> io::Result<Option<MasternodeList>> {
+        self.get(RecordKind::MasternodeList, block_hash)
+    }
+
+    /// Masternode list with the highest known height, i.e. the one to resume syncing from
+    pub fn latest_masternode_list(&self) -> io::Result<Option<MasternodeList>> {
+        let latest = self.lock().index
+            .iter()
+            .filter(|((kind, _), location)| *kind == RecordKind::MasternodeList && location.height != u32::MAX)
+            .max_by_key(|(_, location)| location.height)
+            .map(|((_, block_hash), _)| *block_hash);
+        match latest {
+            Some(block_hash) => self.masternode_list(block_hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Block hashes of the stored masternode lists ordered by height
+    pub fn masternode_list_block_hashes(&self) -> Vec<(u32, UInt256)> {
+        let mut hashes = self.lock().index
+            .iter()
+            .filter(|((kind, _), _)| *kind == RecordKind::MasternodeList)
+            .map(|((_, block_hash), location)| (location.height, *block_hash))
+            .collect::<Vec<_>>();
+        hashes.sort();
+        hashes
+    }
+
+    pub fn save_snapshot(&self, block_hash: UInt256, snapshot: &LLMQSnapshot) -> io::Result<()> {
+        self.put(RecordKind::LLMQSnapshot, block_hash, u32::MAX, &snapshot.to_persisted_data())
+    }
+
+    pub fn snapshot(&self, block_hash: UInt256) -> io::Result<Option<LLMQSnapshot>> {
+        self.get(RecordKind::LLMQSnapshot, block_hash)
+    }
+
+    pub fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> io::Result<()> {
+        self.put(RecordKind::CLSignature, block_hash, u32::MAX, &cl_signature.to_persisted_data())
+    }
+
+    pub fn cl_signature(&self, block_hash: UInt256) -> io::Result<Option<UInt768>> {
+        self.get(RecordKind::CLSignature, block_hash)
+    }
+
+    /// Rewrites the store leaving only live records
+    pub fn compact(&self) -> io::Result<()> {
+        let mut inner = self.lock();
+        self.compact_locked(&mut inner)
+    }
+
+    fn compact_locked(&self, inner: &mut FileStoreInner) -> io::Result<()> {
+        let tmp_path = self.path.with_extension("compact");
+        let mut tmp = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        write_header(&mut tmp)?;
+        let mut index = BTreeMap::new();
+        let mut len = STORE_HEADER_SIZE;
+        // Keep the original order of records
+        let mut locations = inner.index.iter().map(|(key, location)| (*key, *location)).collect::<Vec<_>>();
+        locations.sort_by_key(|(_, location)| location.offset);
+        for (key, location) in locations {
+            let record = inner.read_record(location)?;
+            tmp.write_all(&record)?;
+            index.insert(key, RecordLocation { offset: len, ..location });
+            len += location.size as u64;
+        }
+        tmp.sync_all()?;
+        drop(tmp);
+        fs::rename(&tmp_path, &self.path)?;
+        sync_dir(&self.path);
+        inner.file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        inner.len = len;
+        inner.index = index;
+        inner.dead_bytes = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+pub mod cache_snapshot;
+pub mod file_store;
+pub mod persistable;
+pub mod persistent_provider;
+
+pub use self::cache_snapshot::{cache_from_snapshot_data, cache_snapshot_data, load_cache_snapshot, save_cache_snapshot};
+pub use self::file_store::FileStore;
+pub use self::file_store::RecordKind;
+pub use self::persistable::Persistable;
+pub use self::persistent_provider::PersistentProvider;
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use byte::{BytesExt, LE, TryRead};
+use byte::ctx::Endian;
+use crate::chain::common::LLMQType;
+use crate::common::{Block, MasternodeType, SocketAddress};
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::{UInt128, UInt160, UInt256, UInt384, UInt768, VarBytes};
+use crate::models::{LLMQEntry, LLMQSnapshot, MasternodeEntry, MasternodeList, OperatorPublicKey};
+
+/// Storage representation of the models.
+/// Unlike the wire format it keeps the whole state of the model
+/// (i.e. history of operator keys and validity for masternode entries, verification flags for quorums)
+pub trait Persistable: Sized {
+    fn persist(&self, writer: &mut Vec<u8>);
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self>;
+
+    fn to_persisted_data(&self) -> Vec<u8> {
+        let mut writer = Vec::<u8>::new();
+        self.persist(&mut writer);
+        writer
+    }
+
+    fn from_persisted_data(bytes: &[u8]) -> byte::Result<Self> {
+        let offset = &mut 0;
+        let value = Self::restore(bytes, offset)?;
+        if *offset != bytes.len() {
+            return Err(byte::Error::BadInput { err: "Unexpected trailing bytes" });
+        }
+        Ok(value)
+    }
+}
+
+fn read<'a, T: TryRead<'a, Endian>>(bytes: &'a [u8], offset: &mut usize) -> byte::Result<T> {
+    bytes.read_with::<T>(offset, LE)
+}
+
+fn persist_option<T: Encodable>(value: &Option<T>, writer: &mut Vec<u8>) {
+    match value {
+        Some(value) => {
+            true.enc(&mut *writer);
+            value.enc(writer);
+        },
+        None => {
+            false.enc(writer);
+        }
+    }
+}
+
+fn restore_option<'a, T: TryRead<'a, Endian>>(bytes: &'a [u8], offset: &mut usize) -> byte::Result<Option<T>> {
+    if bytes.read_with::<bool>(offset, ())? {
+        read::<T>(bytes, offset).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+fn persist_block_map<V, F: Fn(&V, &mut Vec<u8>)>(map: &BTreeMap<Block, V>, writer: &mut Vec<u8>, persist_value: F) {
+    VarInt(map.len() as u64).enc(&mut *writer);
+    map.iter().for_each(|(block, value)| {
+        block.height.enc(&mut *writer);
+        block.hash.enc(&mut *writer);
+        persist_value(value, writer);
+    });
+}
+
+fn restore_block_map<V, F: Fn(&[u8], &mut usize) -> byte::Result<V>>(bytes: &[u8], offset: &mut usize, restore_value: F) -> byte::Result<BTreeMap<Block, V>> {
+    let count = read::<VarInt>(bytes, offset)?.0;
+    let mut map = BTreeMap::new();
+    for _i in 0..count {
+        let height = read::<u32>(bytes, offset)?;
+        let hash = read::<UInt256>(bytes, offset)?;
+        map.insert(Block::new(height, hash), restore_value(bytes, offset)?);
+    }
+    Ok(map)
+}
+
+impl Persistable for UInt768 {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        self.enc(writer);
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        read::<UInt768>(bytes, offset)
+    }
+}
+
+impl Persistable for OperatorPublicKey {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        self.data.enc(&mut *writer);
+        self.version.enc(writer);
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        let data = read::<UInt384>(bytes, offset)?;
+        let version = read::<u16>(bytes, offset)?;
+        Ok(OperatorPublicKey { data, version })
+    }
+}
+
+impl Persistable for MasternodeEntry {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        self.provider_registration_transaction_hash.enc(&mut *writer);
+        self.confirmed_hash.enc(&mut *writer);
+        persist_option(&self.confirmed_hash_hashed_with_provider_registration_transaction_hash, writer);
+        self.socket_address.ip_address.enc(&mut *writer);
+        self.socket_address.port.enc(&mut *writer);
+        self.operator_public_key.persist(writer);
+        persist_block_map(&self.previous_operator_public_keys, writer, |key, writer| key.persist(writer));
+        persist_block_map(&self.previous_entry_hashes, writer, |hash, writer| { hash.enc(writer); });
+        persist_block_map(&self.previous_validity, writer, |is_valid, writer| { is_valid.enc(writer); });
+        persist_option(&self.known_confirmed_at_height, writer);
+        self.update_height.enc(&mut *writer);
+        self.key_id_voting.enc(&mut *writer);
+        self.is_valid.enc(&mut *writer);
+        u16::from(self.mn_type).enc(&mut *writer);
+        self.platform_http_port.enc(&mut *writer);
+        self.platform_node_id.enc(&mut *writer);
+        self.entry_hash.enc(writer);
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        let provider_registration_transaction_hash = read::<UInt256>(bytes, offset)?;
+        let confirmed_hash = read::<UInt256>(bytes, offset)?;
+        let confirmed_hash_hashed_with_provider_registration_transaction_hash = restore_option::<UInt256>(bytes, offset)?;
+        let ip_address = read::<UInt128>(bytes, offset)?;
+        let port = read::<u16>(bytes, offset)?;
+        let operator_public_key = OperatorPublicKey::restore(bytes, offset)?;
+        let previous_operator_public_keys = restore_block_map(bytes, offset, OperatorPublicKey::restore)?;
+        let previous_entry_hashes = restore_block_map(bytes, offset, |bytes, offset| read::<UInt256>(bytes, offset))?;
+        let previous_validity = restore_block_map(bytes, offset, |bytes, offset| bytes.read_with::<bool>(offset, ()))?;
+        let known_confirmed_at_height = restore_option::<u32>(bytes, offset)?;
+        let update_height = read::<u32>(bytes, offset)?;
+        let key_id_voting = read::<UInt160>(bytes, offset)?;
+        let is_valid = bytes.read_with::<bool>(offset, ())?;
+        let mn_type = match read::<u16>(bytes, offset)? {
+            0 => MasternodeType::Regular,
+            1 => MasternodeType::HighPerformance,
+            _ => return Err(byte::Error::BadInput { err: "Unknown masternode type" }),
+        };
+        let platform_http_port = read::<u16>(bytes, offset)?;
+        let platform_node_id = read::<UInt160>(bytes, offset)?;
+        let entry_hash = read::<UInt256>(bytes, offset)?;
+        Ok(MasternodeEntry {
+            provider_registration_transaction_hash,
+            confirmed_hash,
+            confirmed_hash_hashed_with_provider_registration_transaction_hash,
+            socket_address: SocketAddress { ip_address, port },
+            operator_public_key,
+            previous_operator_public_keys,
+            previous_entry_hashes,
+            previous_validity,
+            known_confirmed_at_height,
+            update_height,
+            key_id_voting,
+            is_valid,
+            mn_type,
+            platform_http_port,
+            platform_node_id,
+            entry_hash,
+        })
+    }
+}
+
+impl Persistable for LLMQEntry {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        self.to_data().enc(&mut *writer);
+        self.verified.enc(&mut *writer);
+        self.saved.enc(writer);
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        let data = read::<VarBytes>(bytes, offset)?.1;
+        let mut entry = read::<LLMQEntry>(data, &mut 0)?;
+        entry.verified = bytes.read_with::<bool>(offset, ())?;
+        entry.saved = bytes.read_with::<bool>(offset, ())?;
+        Ok(entry)
+    }
+}
+
+impl Persistable for MasternodeList {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        self.block_hash.enc(&mut *writer);
+        self.known_height.enc(&mut *writer);
+        persist_option(&self.masternode_merkle_root, writer);
+        persist_option(&self.llmq_merkle_root, writer);
+        VarInt(self.masternodes.len() as u64).enc(&mut *writer);
+        self.masternodes.iter().for_each(|(hash, entry)| {
+            hash.enc(&mut *writer);
+            entry.persist(writer);
+        });
+        VarInt(self.quorums.len() as u64).enc(&mut *writer);
+        self.quorums.iter().for_each(|(llmq_type, map)| {
+            u8::from(*llmq_type).enc(&mut *writer);
+            VarInt(map.len() as u64).enc(&mut *writer);
+            map.values().for_each(|entry| entry.persist(writer));
+        });
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        let block_hash = read::<UInt256>(bytes, offset)?;
+        let known_height = read::<u32>(bytes, offset)?;
+        let masternode_merkle_root = restore_option::<UInt256>(bytes, offset)?;
+        let llmq_merkle_root = restore_option::<UInt256>(bytes, offset)?;
+        let masternodes_count = read::<VarInt>(bytes, offset)?.0;
+        let mut masternodes = BTreeMap::new();
+        for _i in 0..masternodes_count {
+            let hash = read::<UInt256>(bytes, offset)?;
+            masternodes.insert(hash, Arc::new(MasternodeEntry::restore(bytes, offset)?));
+        }
+        let types_count = read::<VarInt>(bytes, offset)?.0;
+        let mut quorums = BTreeMap::new();
+        for _i in 0..types_count {
+            let llmq_type = LLMQType::from(read::<u8>(bytes, offset)?);
+            let count = read::<VarInt>(bytes, offset)?.0;
+            let mut map = BTreeMap::new();
+            for _j in 0..count {
+                let entry = LLMQEntry::restore(bytes, offset)?;
+                map.insert(entry.llmq_hash, entry);
+            }
+            quorums.insert(llmq_type, map);
+        }
+        Ok(MasternodeList {
+            block_hash,
+            known_height,
+            masternode_merkle_root,
+            llmq_merkle_root,
+            masternodes,
+            quorums,
+        })
+    }
+}
+
+impl Persistable for LLMQSnapshot {
+    fn persist(&self, writer: &mut Vec<u8>) {
+        // Snapshot has no state besides the wire data
+        self.enc(writer);
+    }
+
+    fn restore(bytes: &[u8], offset: &mut usize) -> byte::Result<Self> {
+        read::<LLMQSnapshot>(bytes, offset)
+    }
+}
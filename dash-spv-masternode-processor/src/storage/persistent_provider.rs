@@ -0,0 +1,91 @@
+use crate::crypto::{UInt256, UInt768};
+use crate::models;
+use crate::processing::{CoreProvider, ProcessingError};
+use crate::storage::FileStore;
+
+/// Storage backend which keeps masternode lists, snapshots and CL signatures in the `FileStore`
+/// and takes block index (and everything not found in the store) from the wrapped provider
+#[derive(Debug)]
+pub struct PersistentProvider<T: CoreProvider> {
+    pub provider: T,
+    pub store: FileStore,
+}
+
+impl<T: CoreProvider> PersistentProvider<T> {
+    pub fn new(provider: T, store: FileStore) -> Self {
+        Self { provider, store }
+    }
+}
+
+impl<T: CoreProvider> CoreProvider for PersistentProvider<T> {
+    fn lookup_block_height_by_hash(&self, block_hash: UInt256) -> u32 {
+        self.provider.lookup_block_height_by_hash(block_hash)
+    }
+
+    fn lookup_block_hash_by_height(&self, block_height: u32) -> Option<UInt256> {
+        self.provider.lookup_block_hash_by_height(block_height)
+    }
+
+    fn lookup_merkle_root_by_hash(&self, block_hash: UInt256) -> Option<UInt256> {
+        self.provider.lookup_merkle_root_by_hash(block_hash)
+    }
+
+    fn lookup_masternode_list(&self, block_hash: UInt256) -> Option<models::MasternodeList> {
+        match self.store.masternode_list(block_hash) {
+            Ok(Some(list)) => Some(list),
+            Ok(None) => self.provider.lookup_masternode_list(block_hash),
+            Err(err) => {
+                warn!("PersistentProvider: can't read masternode list {}: {}", block_hash, err);
+                self.provider.lookup_masternode_list(block_hash)
+            }
+        }
+    }
+
+    fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &models::MasternodeList) -> bool {
+        self.store.save_masternode_list(block_hash, masternode_list)
+            .map_err(|err| warn!("PersistentProvider: can't save masternode list {}: {}", block_hash, err))
+            .is_ok()
+    }
+
+    fn lookup_snapshot_by_block_hash(&self, block_hash: UInt256) -> Option<models::LLMQSnapshot> {
+        match self.store.snapshot(block_hash) {
+            Ok(Some(snapshot)) => Some(snapshot),
+            Ok(None) => self.provider.lookup_snapshot_by_block_hash(block_hash),
+            Err(err) => {
+                warn!("PersistentProvider: can't read snapshot {}: {}", block_hash, err);
+                self.provider.lookup_snapshot_by_block_hash(block_hash)
+            }
+        }
+    }
+
+    fn save_snapshot(&self, block_hash: UInt256, snapshot: models::LLMQSnapshot) -> bool {
+        self.store.save_snapshot(block_hash, &snapshot)
+            .map_err(|err| warn!("PersistentProvider: can't save snapshot {}: {}", block_hash, err))
+            .is_ok()
+    }
+
+    fn lookup_cl_signature_by_block_hash(&self, block_hash: UInt256) -> Option<UInt768> {
+        match self.store.cl_signature(block_hash) {
+            Ok(Some(signature)) => Some(signature),
+            Ok(None) => self.provider.lookup_cl_signature_by_block_hash(block_hash),
+            Err(err) => {
+                warn!("PersistentProvider: can't read cl signature {}: {}", block_hash, err);
+                self.provider.lookup_cl_signature_by_block_hash(block_hash)
+            }
+        }
+    }
+
+    fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> bool {
+        self.store.save_cl_signature(block_hash, cl_signature)
+            .map_err(|err| warn!("PersistentProvider: can't save cl signature {}: {}", block_hash, err))
+            .is_ok()
+    }
+
+    fn should_process_diff_with_range(&self, base_block_hash: UInt256, block_hash: UInt256) -> ProcessingError {
+        self.provider.should_process_diff_with_range(base_block_hash, block_hash)
+    }
+
+    fn add_insight(&self, block_hash: UInt256) {
+        self.provider.add_insight(block_hash)
+    }
+}
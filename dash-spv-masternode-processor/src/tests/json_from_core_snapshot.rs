@@ -1,5 +1,6 @@
 use std::collections::BTreeMap;
 use std::num::ParseIntError;
+use std::sync::Arc;
 use byte::BytesExt;
 use byte::ctx::Bytes;
 use serde::{Deserialize, Serialize};
@@ -187,7 +188,7 @@ pub fn list_to_list(value: MNList) -> models::MasternodeList {
     let known_height = value.known_height;
     let masternode_merkle_root = Some(block_hash_to_block_hash(value.masternode_merkle_root));
     let llmq_merkle_root = Some(block_hash_to_block_hash(value.quorum_merkle_root));
-    let masternodes = nodes_to_masternodes(value.mn_list);
+    let masternodes = shared_entries(nodes_to_masternodes(value.mn_list));
     let quorums = quorums_to_quorums_map(value.new_quorums);
     models::MasternodeList {
         block_hash,
@@ -204,23 +205,25 @@ pub fn block_hash_to_block_hash(block_hash: String) -> UInt256 {
 }
 
 pub fn snapshot_to_snapshot(snapshot: Snapshot) -> models::LLMQSnapshot {
+    let member_count = snapshot.active_quorum_members.len();
     let member_list = bools_to_bytes(snapshot.active_quorum_members);
     let skip_list = snapshot.mn_skip_list;
     let skip_list_mode = LLMQSnapshotSkipMode::from(snapshot.mn_skip_list_mode as u32);
-    models::LLMQSnapshot::new(member_list, skip_list, skip_list_mode)
+    models::LLMQSnapshot::new(member_list, member_count, skip_list, skip_list_mode)
 }
 
 pub fn value_to_snapshot(value: &serde_json::Value) -> models::LLMQSnapshot {
     let snapshot: Snapshot = serde_json::from_value(value.clone()).unwrap();
+    let member_count = snapshot.active_quorum_members.len();
     let member_list = bools_to_bytes(snapshot.active_quorum_members);
     let skip_list = snapshot.mn_skip_list;
     let skip_list_mode = LLMQSnapshotSkipMode::from(snapshot.mn_skip_list_mode as u32);
-    models::LLMQSnapshot::new(member_list, skip_list, skip_list_mode)
+    models::LLMQSnapshot::new(member_list, member_count, skip_list, skip_list_mode)
 }
 
 pub fn value_to_masternode_list(value: &serde_json::Value) -> models::MasternodeList {
     let nodes: Vec<Node> = serde_json::from_value(value.clone()).unwrap();
-    let masternodes = nodes_to_masternodes(nodes);
+    let masternodes = shared_entries(nodes_to_masternodes(nodes));
     models::MasternodeList {
         block_hash: Default::default(),
         known_height: 0,
@@ -292,6 +295,10 @@ pub fn quorums_to_quorums_map(value: Vec<Llmq>) -> BTreeMap<LLMQType, BTreeMap<U
 //     map
 // }
 
+pub fn shared_entries(masternodes: BTreeMap<UInt256, models::MasternodeEntry>) -> BTreeMap<UInt256, Arc<models::MasternodeEntry>> {
+    masternodes.into_iter().map(|(hash, entry)| (hash, Arc::new(entry))).collect()
+}
+
 pub fn nodes_to_masternodes(value: Vec<Node>) -> BTreeMap<UInt256, models::MasternodeEntry> {
     let map: BTreeMap<UInt256, models::MasternodeEntry> = value
         .into_iter()
@@ -0,0 +1,114 @@
+use std::collections::BTreeMap;
+use crate::chain::common::ChainType;
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::message_from_file;
+use crate::models::{LLMQSnapshot, MasternodeList};
+use crate::processing::{CachePolicy, MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+fn block_hash(height: u32) -> UInt256 {
+    UInt256::sha256d(height.to_le_bytes())
+}
+
+fn add_list(cache: &mut MasternodeProcessorCache, height: u32) {
+    let hash = block_hash(height);
+    cache.add_masternode_list(hash, MasternodeList::new(BTreeMap::new(), BTreeMap::new(), hash, height, false));
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used_lists() {
+    let cache = &mut MasternodeProcessorCache::with_policy(CachePolicy {
+        max_masternode_lists: Some(3),
+        keep_last_masternode_lists: 1,
+        ..Default::default()
+    });
+    (1..=3).for_each(|height| add_list(cache, height * 100));
+    // The oldest list is used again, so the list at 200 is the least recently used one
+    cache.touch(&block_hash(100));
+    add_list(cache, 400);
+    assert_eq!(cache.mn_lists.len(), 3);
+    assert!(!cache.mn_lists.contains_key(&block_hash(200)));
+    assert!(cache.mn_lists.contains_key(&block_hash(100)));
+    // The most recent list is kept even if it's the least recently used one
+    cache.touch(&block_hash(100));
+    cache.touch(&block_hash(300));
+    add_list(cache, 50);
+    assert!(cache.mn_lists.contains_key(&block_hash(400)));
+    assert!(cache.mn_lists.contains_key(&block_hash(50)));
+    assert!(!cache.mn_lists.contains_key(&block_hash(100)));
+}
+
+#[test]
+fn test_cache_keeps_lists_of_active_rotation_cycles() {
+    let policy = CachePolicy::for_chain(ChainType::TestNet, 2);
+    // 5 cycles of 288 blocks for llmq_60_75 + work block offset
+    assert_eq!(policy.rotation_window, 1448);
+    let cycle_height = |cycle: u32| 10000 + 288 * cycle;
+    let cache = &mut MasternodeProcessorCache::with_policy(policy);
+    (0..=6).for_each(|cycle| add_list(cache, cycle_height(cycle)));
+    // Only the list below the window is evicted, the window keeps the rest
+    assert!(!cache.mn_lists.contains_key(&block_hash(cycle_height(0))));
+    assert_eq!(cache.mn_lists.len(), 6);
+    assert_eq!(cache.pinned_masternode_lists().len(), 6);
+    add_list(cache, cycle_height(7));
+    assert!(!cache.mn_lists.contains_key(&block_hash(cycle_height(1))));
+    assert!(cache.mn_lists.contains_key(&block_hash(cycle_height(2))));
+    assert_eq!(cache.mn_lists.len(), 6);
+}
+
+#[test]
+fn test_cache_limits_snapshots_and_signatures() {
+    let cache = &mut MasternodeProcessorCache::with_policy(CachePolicy {
+        max_snapshots: Some(2),
+        max_cl_signatures: Some(1),
+        ..Default::default()
+    });
+    (1..=3).for_each(|height| cache.add_snapshot(block_hash(height), LLMQSnapshot::default()));
+    (1..=3).for_each(|height| cache.add_cl_signature(block_hash(height), UInt768::MIN));
+    assert_eq!(cache.llmq_snapshots.len(), 2);
+    assert!(!cache.llmq_snapshots.contains_key(&block_hash(1)));
+    assert_eq!(cache.cl_signatures.keys().collect::<Vec<_>>(), vec![&block_hash(3)]);
+}
+
+#[test]
+fn test_cache_memory_usage_limit() {
+    let provider = InMemoryProvider { blocks: init_testnet_store(), ..Default::default() };
+    let mut processor = MasternodeProcessor::new(provider);
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("MNL_0_122928.dat"), true, 70221, cache).unwrap();
+    let usage_of_one_list = cache.memory_usage();
+    let list = cache.mn_lists.values().next().unwrap();
+    assert!(usage_of_one_list >= list.masternodes.len() * std::mem::size_of::<crate::models::MasternodeEntry>());
+    processor.mn_list_diff_result_from_message(&message_from_file("MNL_122928_123000.dat"), true, 70221, cache).unwrap();
+    assert_eq!(cache.mn_lists.len(), 2);
+    let usage_of_two_lists = cache.memory_usage();
+    assert!(usage_of_two_lists > usage_of_one_list);
+    // Entries which weren't changed by the diff are shared by both lists and counted once
+    let lists_usage = cache.mn_lists.values().map(|list| list.memory_usage()).sum::<usize>();
+    assert!(usage_of_two_lists < lists_usage);
+    cache.policy = CachePolicy {
+        max_memory_usage: Some(usage_of_two_lists - 1),
+        keep_last_masternode_lists: 1,
+        ..Default::default()
+    };
+    cache.enforce_policy();
+    assert_eq!(cache.mn_lists.len(), 1);
+    assert_eq!(cache.mn_lists.values().next().unwrap().known_height, 123000);
+    assert!(cache.memory_usage() < usage_of_two_lists);
+}
+
+#[test]
+fn test_cache_touch_ignores_unknown_hashes() {
+    let cache = &mut MasternodeProcessorCache::default();
+    let usage = cache.memory_usage();
+    (1..=100).for_each(|height| cache.touch(&block_hash(height)));
+    assert!(cache.access_stamps.is_empty());
+    assert_eq!(cache.memory_usage(), usage);
+    add_list(cache, 100);
+    cache.touch(&block_hash(100));
+    assert_eq!(cache.access_stamps.len(), 1);
+    cache.remove_masternode_list(&block_hash(100));
+    assert!(cache.access_stamps.is_empty());
+}
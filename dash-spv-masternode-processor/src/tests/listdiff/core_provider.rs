@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use crate::chain::common::ChainType;
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::{message_from_file, MerkleBlock};
+use crate::models;
+use crate::models::LLMQVerificationContext;
+use crate::processing::{CoreProvider, LLMQValidationStatus, MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::{init_mainnet_store, init_testnet_store};
+
+#[derive(Debug, Default)]
+pub struct InMemoryProvider {
+    pub blocks: Vec<MerkleBlock>,
+    pub masternode_lists: RefCell<BTreeMap<UInt256, models::MasternodeList>>,
+    pub snapshots: RefCell<BTreeMap<UInt256, models::LLMQSnapshot>>,
+    pub cl_signatures: RefCell<BTreeMap<UInt256, UInt768>>,
+}
+
+impl CoreProvider for InMemoryProvider {
+    fn lookup_block_height_by_hash(&self, block_hash: UInt256) -> u32 {
+        self.blocks.iter().find(|block| block.hash == block_hash).map_or(u32::MAX, |block| block.height)
+    }
+    fn lookup_block_hash_by_height(&self, block_height: u32) -> Option<UInt256> {
+        self.blocks.iter().find(|block| block.height == block_height).map(|block| block.hash)
+    }
+    fn lookup_merkle_root_by_hash(&self, block_hash: UInt256) -> Option<UInt256> {
+        self.blocks.iter().find(|block| block.hash == block_hash).map(|block| block.merkleroot)
+    }
+    fn lookup_masternode_list(&self, block_hash: UInt256) -> Option<models::MasternodeList> {
+        self.masternode_lists.borrow().get(&block_hash).cloned()
+    }
+    fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &models::MasternodeList) -> bool {
+        self.masternode_lists.borrow_mut().insert(block_hash, masternode_list.clone());
+        true
+    }
+    fn lookup_snapshot_by_block_hash(&self, block_hash: UInt256) -> Option<models::LLMQSnapshot> {
+        self.snapshots.borrow().get(&block_hash).cloned()
+    }
+    fn save_snapshot(&self, block_hash: UInt256, snapshot: models::LLMQSnapshot) -> bool {
+        self.snapshots.borrow_mut().insert(block_hash, snapshot);
+        true
+    }
+    fn lookup_cl_signature_by_block_hash(&self, block_hash: UInt256) -> Option<UInt768> {
+        self.cl_signatures.borrow().get(&block_hash).cloned()
+    }
+    fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> bool {
+        self.cl_signatures.borrow_mut().insert(block_hash, cl_signature);
+        true
+    }
+}
+
+#[test]
+fn test_processor_with_rust_provider() {
+    let provider = InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() };
+    let mut processor = MasternodeProcessor::new(provider);
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let bytes = message_from_file("ML1720000.dat");
+    let list_diff = processor.read_list_diff_from_message(&bytes, &mut 0, 70221)
+        .expect("Diff must be parsed");
+    let block_hash = list_diff.block_hash;
+    let result = processor.get_list_diff_result_internal_with_base_lookup(list_diff, LLMQVerificationContext::MNListDiff, cache);
+    assert!(result.has_found_coinbase, "has no coinbase");
+    assert!(result.has_valid_mn_list_root, "invalid mnl root");
+    assert!(result.has_valid_llmq_list_root, "invalid llmq root");
+    assert!(processor.save_masternode_list(block_hash, &result.masternode_list));
+    let stored = processor.lookup_masternode_list(block_hash).expect("List must be stored");
+    assert_eq!(stored.masternode_merkle_root, result.masternode_list.masternode_merkle_root);
+}
+
+#[test]
+fn test_quorum_validation_reports() {
+    let provider = InMemoryProvider { blocks: init_testnet_store(), ..Default::default() };
+    let mut processor = MasternodeProcessor::new(provider);
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("MNL_0_122928.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("MNL_122928_123000.dat"), true, 70221, cache).unwrap();
+    assert_eq!(result.quorum_validation_reports.len(), result.added_quorums.len());
+    assert_eq!(result.has_valid_quorums, result.invalid_quorums().next().is_none());
+    // Quorum based on the list at 122928 is validated against its members
+    let report = result.quorum_validation_reports
+        .iter()
+        .find(|report| report.status != LLMQValidationStatus::NoMasternodeList)
+        .expect("Quorum at 122928 must be validated");
+    assert_eq!(report.members.len() as u32, report.llmq_type.size());
+    let list = &cache.mn_lists[&report.llmq_hash];
+    assert!(report.members.iter().all(|hash| list.masternodes.values().any(|node| node.provider_registration_transaction_hash == *hash)));
+    // Lists of other quorums are unknown
+    result.quorum_validation_reports
+        .iter()
+        .filter(|report| report.status == LLMQValidationStatus::NoMasternodeList)
+        .for_each(|report| {
+            assert!(report.members.is_empty());
+            assert!(result.needed_masternode_lists.contains(&report.llmq_hash));
+        });
+    let encoded = report.encode();
+    assert_eq!(encoded.llmq_type, report.llmq_type);
+    assert_eq!(unsafe { *encoded.llmq_hash }, report.llmq_hash.0);
+    assert_eq!(encoded.members_count, report.members.len());
+    assert_eq!(unsafe { **encoded.members }, report.members[0].0);
+}
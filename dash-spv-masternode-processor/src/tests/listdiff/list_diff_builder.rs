@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use crate::chain::common::ChainType;
+use crate::common::MerkleTree;
+use crate::lib_tests::tests::message_from_file;
+use crate::models;
+use crate::models::LLMQVerificationContext;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::{init_mainnet_store, init_testnet_store};
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+/// Processes the diffs and checks that the diff built from the resulting lists is the same as received one
+fn assert_built_diffs_match(processor: MasternodeProcessor<InMemoryProvider>, diffs: &[(&str, u32)]) {
+    let cache = &mut MasternodeProcessorCache::default();
+    let mut base_list = models::MasternodeList::default();
+    for (i, (name, protocol_version)) in diffs.iter().enumerate() {
+        let bytes = message_from_file(name);
+        let list_diff = processor.read_list_diff_from_message(&bytes, &mut 0, *protocol_version).unwrap();
+        let received_diff = list_diff.clone();
+        let result = processor.get_list_diff_result_internal_with_base_lookup(list_diff, LLMQVerificationContext::MNListDiff, cache);
+        let masternode_list = result.masternode_list;
+        if i > 0 {
+            let merkle_tree = MerkleTree {
+                tree_element_count: received_diff.total_transactions,
+                hashes: received_diff.merkle_hashes.clone(),
+                flags: received_diff.merkle_flags.as_slice(),
+            };
+            // Not every chain lock signature can be found with the blocks we have in the store, so take them from the received diff
+            let received_cl_signatures = received_diff.quorums_cls_sigs
+                .iter()
+                .flat_map(|(signature, indexes)| indexes.iter().map(|index| (received_diff.added_quorums[*index as usize].entry_hash, *signature)))
+                .collect::<BTreeMap<_, _>>();
+            let built_diff = models::MNListDiff::from_masternode_lists(
+                &base_list,
+                &masternode_list,
+                received_diff.coinbase_transaction.clone(),
+                merkle_tree,
+                *protocol_version,
+                |quorum| received_cl_signatures.get(&quorum.entry_hash).copied());
+            assert_eq!(built_diff.base_block_hash, received_diff.base_block_hash);
+            assert_eq!(built_diff.block_hash, received_diff.block_hash);
+            assert_eq!(built_diff.version, received_diff.version);
+            assert_eq!(
+                built_diff.deleted_masternode_hashes.iter().collect::<BTreeSet<_>>(),
+                received_diff.deleted_masternode_hashes.iter().collect::<BTreeSet<_>>());
+            assert_eq!(
+                built_diff.added_or_modified_masternodes.keys().collect::<Vec<_>>(),
+                received_diff.added_or_modified_masternodes.keys().collect::<Vec<_>>());
+            assert_eq!(built_diff.deleted_quorums, received_diff.deleted_quorums);
+            assert_eq!(
+                built_diff.added_quorums.iter().map(|quorum| quorum.entry_hash).collect::<Vec<_>>(),
+                received_diff.added_quorums.iter().map(|quorum| quorum.entry_hash).collect::<Vec<_>>());
+            assert_eq!(built_diff.quorums_cls_sigs, received_diff.quorums_cls_sigs);
+            // Applying the built diff to the base list gives the same list
+            let rebuilt = processor.get_list_diff_result_internal(Some(Arc::new(base_list.clone())), built_diff, LLMQVerificationContext::None, cache);
+            assert_eq!(rebuilt.masternode_list.masternode_merkle_root, masternode_list.masternode_merkle_root);
+            assert_eq!(rebuilt.masternode_list.llmq_merkle_root, masternode_list.llmq_merkle_root);
+            assert!(rebuilt.has_valid_mn_list_root && rebuilt.has_valid_llmq_list_root);
+        }
+        base_list = masternode_list;
+    }
+}
+
+#[test]
+fn test_build_list_diff_mainnet() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    assert_built_diffs_match(processor, &[
+        ("mainnet/MNL_0_1870848.dat", 70227),
+        ("mainnet/MNL_1870848_1871136.dat", 70227),
+        ("mainnet/MNL_1871136_1871184.dat", 70227),
+    ]);
+}
+
+#[test]
+fn test_build_list_diff_with_processor() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let base_list = processor.mn_list_diff_result_from_message(&message_from_file("mainnet/MNL_0_1870848.dat"), true, 70227, cache).unwrap().masternode_list;
+    let bytes = message_from_file("mainnet/MNL_1870848_1871136.dat");
+    let received_diff = processor.read_list_diff_from_message(&bytes, &mut 0, 70227).unwrap();
+    let masternode_list = processor.mn_list_diff_result_from_message(&bytes, true, 70227, cache).unwrap().masternode_list;
+    let merkle_tree = MerkleTree {
+        tree_element_count: received_diff.total_transactions,
+        hashes: received_diff.merkle_hashes.clone(),
+        flags: received_diff.merkle_flags.as_slice(),
+    };
+    let built_diff = processor.list_diff_between(&base_list, &masternode_list, received_diff.coinbase_transaction.clone(), merkle_tree, 70227, cache);
+    assert_eq!(built_diff.added_or_modified_masternodes.len(), received_diff.added_or_modified_masternodes.len());
+    assert_eq!(built_diff.added_quorums.len(), received_diff.added_quorums.len());
+    // No chain lock signatures before protocol 70230
+    assert!(built_diff.quorums_cls_sigs.is_empty());
+}
+
+#[test]
+fn test_build_list_diff_with_quorums_cl_sigs() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    assert_built_diffs_match(processor, &[
+        ("testnet/MNL_0_530000_70228.dat", 70228),
+        ("testnet/MNL_530000_905465__70230.dat", 70230),
+    ]);
+}
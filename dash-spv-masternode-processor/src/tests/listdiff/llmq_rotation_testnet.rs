@@ -7,7 +7,7 @@ use crate::hashes::hex::FromHex;
 use crate::lib_tests::tests::{add_insight_lookup_default, FFIContext, get_block_hash_by_height_from_context, get_block_height_by_hash_from_context, get_cl_signature_by_block_hash_from_context, get_llmq_snapshot_by_block_hash_from_context, get_masternode_list_by_block_hash_from_cache, get_merkle_root_by_hash_default, hash_destroy_default, masternode_list_destroy_default, masternode_list_save_in_cache, message_from_file, process_qrinfo_from_message_internal, register_logger, save_cl_signature_in_cache, save_llmq_snapshot_in_cache, should_process_diff_with_range_default, snapshot_destroy_default};
 use crate::models;
 use crate::tests::block_store::init_mainnet_store;
-use crate::tests::json_from_core_snapshot::{block_hash_to_block_hash, ListDiff, masternode_list_from_genesis_diff, QRInfo, snapshot_to_snapshot};
+use crate::tests::json_from_core_snapshot::{block_hash_to_block_hash, ListDiff, masternode_list_from_genesis_diff, QRInfo, shared_entries, snapshot_to_snapshot};
 
 #[test]
 fn testnet_quorum_quarters() {
@@ -53,9 +53,9 @@ fn testnet_quorum_quarters() {
                 .insert(entry.llmq_hash, entry.clone());
             acc
     });
-    let masternode_list_8792 = models::MasternodeList::new(list_diff_8792.added_or_modified_masternodes, added_quorums_8792, block_hash_8792, block_height_8792, true);
-    let masternode_list_8840 = models::MasternodeList::new(list_diff_8840.added_or_modified_masternodes, added_quorums_8840, block_hash_8840, block_height_8840, true);
-    let masternode_list_8888 = models::MasternodeList::new(list_diff_8888.added_or_modified_masternodes, added_quorums_8888, block_hash_8888, block_height_8888, true);
+    let masternode_list_8792 = models::MasternodeList::new(shared_entries(list_diff_8792.added_or_modified_masternodes), added_quorums_8792, block_hash_8792, block_height_8792, true);
+    let masternode_list_8840 = models::MasternodeList::new(shared_entries(list_diff_8840.added_or_modified_masternodes), added_quorums_8840, block_hash_8840, block_height_8840, true);
+    let masternode_list_8888 = models::MasternodeList::new(shared_entries(list_diff_8888.added_or_modified_masternodes), added_quorums_8888, block_hash_8888, block_height_8888, true);
 
     let processor = unsafe {
         &mut *register_processor(
@@ -83,7 +83,7 @@ fn testnet_quorum_quarters() {
     let bytes = message_from_file("QRINFO_0_1739226.dat");
     let old_bytes = message_from_file("QRINFO_0_1740902.dat");
     let old_bytes2 = message_from_file("QRINFO_0_1740910.dat");
-    processor.opaque_context = context as *mut _ as *mut std::ffi::c_void;
+    processor.provider.opaque_context = context as *mut _ as *mut std::ffi::c_void;
     processor.use_insight_as_backup = true;
     processor.chain_type = chain;
 
@@ -170,7 +170,7 @@ fn testnet_quorum_quarters() {
         &mut context.cache.cl_signatures,
         &mut context.cache.needed_masternode_lists,
         false
-    );
+    ).unwrap();
     let node_hashes = nodes.iter().map(|m| m.provider_registration_transaction_hash.clone()).collect::<Vec<UInt256>>();
     let needed_hashes = [
         UInt256::from_hex("d2426992844e311149b2962f4ea991656ea5d1b36541e314e251915a6ffa1316").unwrap().reverse(),
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
 use crate::bindings::common::{processor_create_cache, register_processor};
 use crate::bindings::masternode::process_mnlistdiff_from_message;
 use crate::ffi::from::FromFFI;
@@ -58,7 +59,7 @@ pub fn load_masternode_lists_for_files(
     files: Vec<String>,
     assert_validity: bool,
     context: &mut FFIContext,
-) -> (bool, BTreeMap<UInt256, models::MasternodeList>) {
+) -> (bool, BTreeMap<UInt256, Arc<models::MasternodeList>>) {
     let cache = unsafe { &mut *processor_create_cache() };
     let processor = unsafe {
         register_processor(
@@ -36,7 +36,7 @@ fn mainnet_test_invalid_mn_list_root() {
             should_process_diff_with_range_default,
         )
     };
-    processor.opaque_context = context as *mut _ as *mut std::ffi::c_void;
+    processor.provider.opaque_context = context as *mut _ as *mut std::ffi::c_void;
     processor.use_insight_as_backup = true;
     processor.chain_type = context.chain;
 
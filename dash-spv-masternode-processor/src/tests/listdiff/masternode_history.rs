@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use crate::chain::common::ChainType;
+use crate::common::{Block, SocketAddress};
+use crate::crypto::{byte_util::Reversable, UInt128, UInt256};
+use crate::lib_tests::tests::message_from_file;
+use crate::models::{MasternodeChangeKind, OperatorPublicKey};
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+#[test]
+fn test_masternode_state_at_height() {
+    let provider = InMemoryProvider { blocks: init_testnet_store(), ..Default::default() };
+    let mut processor = MasternodeProcessor::new(provider);
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("MNL_0_122928.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("MNL_122928_123000.dat"), true, 70221, cache).unwrap();
+    let (_, entry) = result.masternode_list.masternodes.iter().next().unwrap();
+    let pro_reg_tx_hash = entry.provider_registration_transaction_hash;
+    let mut entry = entry.as_ref().clone();
+    // Pretend the operator key was rotated and node was banned in between
+    let old_key = OperatorPublicKey { data: Default::default(), version: entry.operator_public_key.version };
+    entry.previous_operator_public_keys.insert(Block::new(122950, UInt256::MIN), old_key);
+    entry.previous_validity.insert(Block::new(122960, UInt256::MIN), !entry.is_valid);
+    assert_eq!(entry.state_at(122940).operator_public_key, old_key);
+    assert_eq!(entry.state_at(122940).is_valid, !entry.is_valid);
+    assert_eq!(entry.state_at(122955).operator_public_key, entry.operator_public_key);
+    assert_eq!(entry.state_at(122955).is_valid, !entry.is_valid);
+    assert_eq!(entry.state_at(122970).is_valid, entry.is_valid);
+    assert!(processor.masternode_state_at(pro_reg_tx_hash, 123000, cache).is_some());
+    assert!(processor.masternode_state_at(UInt256::sha256d([1u8]), 123000, cache).is_none());
+    // There is no list below the height, so it can't be told whether the masternode was registered
+    let first_height = cache.mn_lists.values().map(|list| list.known_height).min().unwrap();
+    assert!(processor.masternode_state_at(pro_reg_tx_hash, first_height - 1, cache).is_none());
+    // Masternode registered after the list below isn't there yet
+    let mut added = entry.clone();
+    added.provider_registration_transaction_hash = UInt256::sha256d([2u8]);
+    Arc::make_mut(cache.mn_lists.get_mut(&result.masternode_list.block_hash).unwrap())
+        .masternodes.insert(added.provider_registration_transaction_hash.reversed(), Arc::new(added.clone()));
+    assert!(processor.masternode_state_at(added.provider_registration_transaction_hash, 122950, cache).is_none());
+    assert!(processor.masternode_state_at(added.provider_registration_transaction_hash, 123000, cache).is_some());
+}
+
+#[test]
+fn test_masternode_changes_between_heights() {
+    let provider = InMemoryProvider { blocks: init_testnet_store(), ..Default::default() };
+    let mut processor = MasternodeProcessor::new(provider);
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let base_result = processor.mn_list_diff_result_from_message(&message_from_file("MNL_0_122928.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("MNL_122928_123000.dat"), true, 70221, cache).unwrap();
+    let base_height = base_result.masternode_list.known_height;
+    let height = result.masternode_list.known_height;
+    let changes = processor.masternode_changes_between(base_height, height, cache);
+    assert!(changes.iter().all(|change| change.block_height > base_height && change.block_height <= height));
+    let registered = changes.iter().filter(|change| change.kind == MasternodeChangeKind::Registered).count();
+    assert_eq!(registered, result.added_masternodes.len());
+    let removed = changes.iter()
+        .filter(|change| change.kind == MasternodeChangeKind::Removed)
+        .map(|change| change.provider_registration_transaction_hash.reversed())
+        .collect::<Vec<_>>();
+    assert!(removed.iter().all(|hash| !result.masternode_list.masternodes.contains_key(hash)));
+    // Changes of the modified nodes are attributed to the modified entries only
+    changes.iter()
+        .filter(|change| !matches!(change.kind, MasternodeChangeKind::Registered | MasternodeChangeKind::Removed))
+        .for_each(|change| assert!(result.modified_masternodes.contains_key(&change.provider_registration_transaction_hash.reversed())));
+    assert!(processor.masternode_changes_between(height, height, cache).is_empty());
+
+    let mut list = result.masternode_list.clone();
+    let (hash, entry) = list.masternodes.iter_mut().next().unwrap();
+    let hash = *hash;
+    let entry = Arc::make_mut(entry);
+    let service = SocketAddress { ip_address: UInt128::MAX, port: 1 };
+    let previous_service = entry.socket_address;
+    entry.socket_address = service;
+    list.known_height = height + 10;
+    let changes = list.masternode_changes_since(&result.masternode_list);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].provider_registration_transaction_hash.reversed(), hash);
+    assert_eq!(changes[0].kind, MasternodeChangeKind::ServiceChanged { from: previous_service, to: service });
+}
@@ -0,0 +1,114 @@
+use crate::chain::common::ChainType;
+use crate::common::LLMQSnapshotSkipMode;
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::UInt256;
+use crate::lib_tests::tests::message_from_file;
+use crate::models::{LLMQSnapshot, MasternodeListCheckpoint, MasternodeListCheckpointData, MNListDiff};
+use crate::processing::{CheckpointError, MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::{init_mainnet_store, init_testnet_store};
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+#[test]
+fn test_checkpoint_selection() {
+    assert_eq!(MasternodeListCheckpoint::last_checkpoint_for(ChainType::MainNet, 1719999), None);
+    assert_eq!(MasternodeListCheckpoint::last_checkpoint_for(ChainType::MainNet, 1738647).unwrap().block_height, 1720000);
+    assert_eq!(MasternodeListCheckpoint::last_checkpoint_for(ChainType::MainNet, 1800000).unwrap().block_height, 1738648);
+    let checkpoint = MasternodeListCheckpoint::last_checkpoint_for(ChainType::TestNet, 530000).unwrap();
+    assert_eq!(MasternodeListCheckpoint::checkpoint_for_block_hash(ChainType::TestNet, checkpoint.block_hash), Some(checkpoint));
+    assert!(MasternodeListCheckpoint::checkpoints_for(ChainType::DevNet(Default::default())).is_empty());
+}
+
+#[test]
+fn test_checkpoint_data_round_trip() {
+    let data = MasternodeListCheckpointData::new(message_from_file("testnet/MNT530000.dat"), 70221)
+        .with_snapshot(UInt256::MIN, LLMQSnapshot::new(vec![0xff, 0x01], 9, vec![1, 5, -1], LLMQSnapshotSkipMode::SkipFirst));
+    let bytes = data.to_data().unwrap();
+    // Masternode entries are written in the compact form
+    assert!(bytes.len() < data.list_diff.len() * 9 / 10);
+    let restored = MasternodeListCheckpointData::from_data(&bytes).unwrap();
+    assert_eq!(restored.protocol_version, 70221);
+    // The same diff, with entries in the order of `MNListDiff::to_data`
+    let list_diff = MNListDiff::read(&data.list_diff, &mut 0, |_| u32::MAX, 70221).unwrap();
+    assert_eq!(restored.list_diff, list_diff.to_data(70221));
+    assert_eq!(restored.snapshots[&UInt256::MIN].skip_list, vec![1, 5, -1]);
+    assert_eq!(restored.to_data().unwrap(), bytes);
+    assert!(MasternodeListCheckpointData::from_data(&bytes[..bytes.len() - 1]).is_err());
+    assert!(MasternodeListCheckpointData::from_data(&data.list_diff).is_err());
+    assert!(MasternodeListCheckpointData::new(data.list_diff[..100].to_vec(), 70221).to_data().is_err());
+    // Version 1 keeps 'MNLISTDIFF' as is
+    let mut legacy = b"DMCP".to_vec();
+    1u16.enc(&mut legacy);
+    70221u32.enc(&mut legacy);
+    data.list_diff.enc(&mut legacy);
+    VarInt(0).enc(&mut legacy);
+    let restored = MasternodeListCheckpointData::from_data(&legacy).unwrap();
+    assert_eq!(restored.list_diff, data.list_diff);
+    assert!(restored.snapshots.is_empty());
+}
+
+#[test]
+fn test_apply_checkpoint_file_with_snapshots() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let checkpoint = MasternodeListCheckpoint::last_checkpoint_for(ChainType::TestNet, u32::MAX).unwrap();
+    let data = MasternodeListCheckpointData::from_data(&message_from_file("checkpoints/testnet_905176.dat")).unwrap();
+    assert_eq!(data.protocol_version, 70230);
+    assert_eq!(data.snapshots.len(), 4);
+    let list = processor.apply_masternode_list_checkpoint(&checkpoint, &data, cache).unwrap();
+    assert_eq!(list.known_height, 905176);
+    assert_eq!(list.llmq_merkle_root, checkpoint.llmq_merkle_root);
+    // Snapshots for members of the rotated quorums of the checkpoint cycle
+    assert!(data.snapshots.keys().all(|block_hash| cache.llmq_snapshots.contains_key(block_hash)));
+    assert!(data.snapshots.keys().all(|block_hash| processor.provider.snapshots.borrow().contains_key(block_hash)));
+}
+
+#[test]
+fn test_apply_checkpoint_and_continue_from_it() {
+    // Provider knows nothing about the checkpoint block at the start
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let checkpoint = MasternodeListCheckpoint::last_checkpoint_for(ChainType::TestNet, 530000).unwrap();
+    let data = MasternodeListCheckpointData::new(message_from_file("testnet/MNT530000.dat"), 70221);
+    let list = processor.apply_masternode_list_checkpoint(&checkpoint, &data, cache).unwrap();
+    assert_eq!(list.known_height, 530000);
+    assert_eq!(list.masternode_merkle_root, Some(checkpoint.masternode_merkle_root));
+    assert!(cache.mn_lists.contains_key(&checkpoint.block_hash));
+    assert!(processor.provider.masternode_lists.borrow().contains_key(&checkpoint.block_hash));
+    // The next diff is based on the checkpoint block
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_530000_852596.dat"), true, 70228, cache).unwrap();
+    assert_eq!(result.base_block_hash, checkpoint.block_hash);
+    assert!(result.has_valid_mn_list_root);
+    assert!(result.has_valid_llmq_list_root);
+}
+
+#[test]
+fn test_apply_checkpoint_rejects_mismatched_data() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let checkpoint = MasternodeListCheckpoint::last_checkpoint_for(ChainType::MainNet, 1720000).unwrap();
+    let data = MasternodeListCheckpointData::new(message_from_file("ML1720000.dat"), 70221);
+    let forged = MasternodeListCheckpoint { masternode_merkle_root: UInt256::MIN, ..checkpoint };
+    assert_eq!(processor.apply_masternode_list_checkpoint(&forged, &data, cache), Err(CheckpointError::MasternodeMerkleRootMismatch));
+    let forged = MasternodeListCheckpoint { llmq_merkle_root: Some(UInt256::MIN), ..checkpoint };
+    assert_eq!(processor.apply_masternode_list_checkpoint(&forged, &data, cache), Err(CheckpointError::LLMQMerkleRootMismatch));
+    let testnet_data = MasternodeListCheckpointData::new(message_from_file("testnet/MNT530000.dat"), 70221);
+    assert!(matches!(processor.apply_masternode_list_checkpoint(&checkpoint, &testnet_data, cache), Err(CheckpointError::BlockHashMismatch { .. })));
+    assert!(matches!(processor.apply_masternode_list_checkpoint(&checkpoint, &MasternodeListCheckpointData::default(), cache), Err(CheckpointError::Malformed(..))));
+    assert!(cache.mn_lists.is_empty());
+    assert!(processor.apply_masternode_list_checkpoint(&checkpoint, &data, cache).is_ok());
+}
+
+#[cfg(feature = "embedded-checkpoints")]
+#[test]
+fn test_embedded_checkpoints() {
+    for chain_type in [ChainType::MainNet, ChainType::TestNet] {
+        let checkpoint = *MasternodeListCheckpoint::checkpoints_for(chain_type).last().unwrap();
+        let data = checkpoint.embedded_data(chain_type).expect("Checkpoint data must be embedded");
+        let mut processor = MasternodeProcessor::new(InMemoryProvider::default());
+        processor.chain_type = chain_type;
+        assert!(processor.apply_masternode_list_checkpoint(&checkpoint, &data, &mut MasternodeProcessorCache::default()).is_ok());
+    }
+}
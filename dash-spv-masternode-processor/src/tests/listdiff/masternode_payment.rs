@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use crate::chain::common::ChainType;
+use crate::chain::params::DUFFS;
+use crate::common::MasternodeType;
+use crate::crypto::{byte_util::Reversable, UInt256};
+use crate::lib_tests::tests::message_from_file;
+use crate::models::{MasternodeList, MasternodePayment, MasternodePaymentState};
+use crate::processing::{MasternodePaymentValidationStatus, MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::tx::{CoinbaseTransaction, TransactionOutput};
+
+fn payout_script(index: usize) -> Vec<u8> {
+    let mut script = vec![0x76, 0xa9, 0x14];
+    script.extend_from_slice(&[index as u8; 20]);
+    script.extend_from_slice(&[0x88, 0xac]);
+    script
+}
+
+/// Every valid masternode is registered at 1000 and paid in the order of the list
+fn payment_states(list: &MasternodeList) -> BTreeMap<UInt256, MasternodePaymentState> {
+    list.masternodes.values()
+        .filter(|entry| entry.is_valid)
+        .enumerate()
+        .map(|(index, entry)| (entry.provider_registration_transaction_hash, MasternodePaymentState {
+            registered_height: 1000,
+            last_paid_height: 500000 + index as u32,
+            payout_script: payout_script(index),
+            ..Default::default()
+        }))
+        .collect()
+}
+
+fn processed_list() -> (MasternodeProcessor<InMemoryProvider>, MasternodeProcessorCache, MasternodeList, CoinbaseTransaction) {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let mut cache = MasternodeProcessorCache::default();
+    let bytes = message_from_file("testnet/MNT530000.dat");
+    let coinbase = processor.read_list_diff_from_message(&bytes, &mut 0, 70221).unwrap().coinbase_transaction;
+    let result = processor.mn_list_diff_result_from_message(&bytes, true, 70221, &mut cache).unwrap();
+    (processor, cache, result.masternode_list, coinbase)
+}
+
+/// Compact target of the previous block with the difficulty high enough for the minimal subsidy
+const HIGH_DIFFICULTY_BITS: u32 = 0x1b00ffff;
+
+#[test]
+fn test_block_subsidy_schedule() {
+    let chain_type = ChainType::MainNet;
+    // The first mainnet blocks were mined at the minimal difficulty and got 500 DASH
+    assert_eq!(chain_type.block_subsidy(1, 0x1e0ffff0), 500 * DUFFS);
+    assert_eq!(chain_type.treasury_payment(1, 0x1e0ffff0), 0);
+    // The subsidy of the GPU/ASIC mining era is 2222222/(((difficulty+2600)/9)^2) clamped to 5..25 DASH
+    assert_eq!(chain_type.block_subsidy(100000, 0x1d00ffff), 25 * DUFFS);
+    assert_eq!(chain_type.block_subsidy(100000, 0x1c00ffff), 22 * DUFFS);
+    assert_eq!(chain_type.block_subsidy(100000, HIGH_DIFFICULTY_BITS), 5 * DUFFS);
+    assert_eq!(chain_type.block_subsidy(210240, HIGH_DIFFICULTY_BITS), 5 * DUFFS);
+    assert_eq!(chain_type.block_subsidy(210241, HIGH_DIFFICULTY_BITS), 5 * DUFFS - 5 * DUFFS / 14);
+    // Core v20: treasury takes 20%, masternode gets 75% of the rest
+    let height = chain_type.core20_activation_height();
+    let subsidy = chain_type.block_subsidy(height, HIGH_DIFFICULTY_BITS);
+    assert_eq!(chain_type.treasury_payment(height, HIGH_DIFFICULTY_BITS), subsidy / 5);
+    assert_eq!(chain_type.treasury_payment(height - 1, HIGH_DIFFICULTY_BITS), subsidy / 10);
+    assert_eq!(chain_type.block_value(height, HIGH_DIFFICULTY_BITS, 1000), subsidy - subsidy / 5 + 1000);
+    assert_eq!(chain_type.masternode_payment(height, 800), 600);
+    assert_eq!(chain_type.masternode_payment(height - 1, 900), 540);
+}
+
+#[test]
+fn test_block_reward_of_testnet_coinbases() {
+    let processor = MasternodeProcessor::new(InMemoryProvider::default());
+    let coinbase = |file: &str| processor.read_list_diff_from_message(&message_from_file(file), &mut 0, 70230).unwrap().coinbase_transaction;
+    // The block has no transactions other than coinbase, miner and masternode outputs take the whole block value
+    let coinbase_v2 = coinbase("testnet/MNL_530000_900096__70230.dat");
+    let block_value = ChainType::TestNet.block_value(coinbase_v2.height, 0x1e0fffff, 0);
+    assert_eq!(coinbase_v2.base.outputs[0].amount + coinbase_v2.base.outputs[1].amount, block_value);
+    assert_eq!(coinbase_v2.base.outputs[1].amount, ChainType::TestNet.masternode_payment(coinbase_v2.height, block_value));
+    // The block after Core v20 activation is mined at the difficulty high enough for the minimal subsidy
+    let coinbase_v3 = coinbase("testnet/MNL_530000_905465__70230.dat");
+    let fees = 29925;
+    let block_value = ChainType::TestNet.block_value(coinbase_v3.height, HIGH_DIFFICULTY_BITS, fees);
+    assert_eq!(coinbase_v3.base.outputs[0].amount + coinbase_v3.base.outputs[1].amount, block_value);
+    assert_eq!(coinbase_v3.base.outputs[1].amount, ChainType::TestNet.masternode_payment(coinbase_v3.height, block_value));
+    assert_eq!(ChainType::TestNet.platform_share(coinbase_v3.height, HIGH_DIFFICULTY_BITS), 0);
+}
+
+#[test]
+fn test_platform_share_since_mn_rr() {
+    let chain_type = ChainType::MainNet;
+    let height = chain_type.mn_rr_activation_height();
+    assert_eq!(chain_type.platform_share(height - 1, HIGH_DIFFICULTY_BITS), 0);
+    // 37.5% of the masternode share of the subsidy, fees stay with the masternode
+    let subsidy_reward = chain_type.masternode_payment(height, chain_type.block_value(height, HIGH_DIFFICULTY_BITS, 0));
+    assert_eq!(chain_type.platform_share(height, HIGH_DIFFICULTY_BITS), subsidy_reward * 375 / 1000);
+}
+
+#[test]
+fn test_masternode_payee_selection() {
+    let (_, _, list, _) = processed_list();
+    let mut states = payment_states(&list);
+    let valid = list.masternodes.values().filter(|entry| entry.is_valid).collect::<Vec<_>>();
+    let (payee, _) = list.masternode_payee(ChainType::TestNet, &states).unwrap();
+    assert_eq!(payee.provider_registration_transaction_hash, valid[0].provider_registration_transaction_hash);
+    // Never paid masternode is queued by the registration height
+    states.get_mut(&valid[5].provider_registration_transaction_hash).unwrap().last_paid_height = 0;
+    let (payee, _) = list.masternode_payee(ChainType::TestNet, &states).unwrap();
+    assert_eq!(payee.provider_registration_transaction_hash, valid[5].provider_registration_transaction_hash);
+    // Revived masternode is queued by the revival height
+    states.get_mut(&valid[5].provider_registration_transaction_hash).unwrap().pose_revived_height = Some(600000);
+    let (payee, _) = list.masternode_payee(ChainType::TestNet, &states).unwrap();
+    assert_eq!(payee.provider_registration_transaction_hash, valid[0].provider_registration_transaction_hash);
+    // Ties are resolved by proTxHash compared byte-wise in the internal order (uint256 operator< in Core),
+    // take the masternode whose hash is smaller that way, but bigger when compared as a reversed (displayed) hash
+    let hash = |index: usize| valid[index].provider_registration_transaction_hash;
+    let tied = (1..valid.len())
+        .find(|&index| hash(index) < hash(0) && hash(index).reversed() > hash(0).reversed())
+        .unwrap();
+    states.get_mut(&hash(tied)).unwrap().last_paid_height = 500000;
+    let (payee, _) = list.masternode_payee(ChainType::TestNet, &states).unwrap();
+    assert_eq!(payee.provider_registration_transaction_hash, hash(tied));
+    states.remove(&valid[7].provider_registration_transaction_hash);
+    assert_eq!(list.masternode_payee(ChainType::TestNet, &states).err(), Some(MasternodePaymentValidationStatus::MissingPaymentState));
+    assert_eq!(list.masternode_payee(ChainType::TestNet, &BTreeMap::new()).err(), Some(MasternodePaymentValidationStatus::MissingPaymentState));
+}
+
+#[test]
+fn test_evonode_consecutive_payments() {
+    let (_, _, mut list, _) = processed_list();
+    list.known_height = ChainType::TestNet.core19_activation_height() + 100;
+    let mut states = payment_states(&list);
+    let evonode_hash = {
+        let evonode = Arc::make_mut(list.masternodes.values_mut().filter(|entry| entry.is_valid).nth(10).unwrap());
+        evonode.mn_type = MasternodeType::HighPerformance;
+        evonode.provider_registration_transaction_hash
+    };
+    let state = states.get_mut(&evonode_hash).unwrap();
+    state.last_paid_height = list.known_height;
+    state.consecutive_payments = 2;
+    let (payee, _) = list.masternode_payee(ChainType::TestNet, &states).unwrap();
+    assert_eq!(payee.provider_registration_transaction_hash, evonode_hash);
+    states.get_mut(&evonode_hash).unwrap().consecutive_payments = 4;
+    let (payee, _) = list.masternode_payee(ChainType::TestNet, &states).unwrap();
+    assert_ne!(payee.provider_registration_transaction_hash, evonode_hash);
+}
+
+#[test]
+fn test_operator_reward_split() {
+    let state = MasternodePaymentState {
+        payout_script: payout_script(1),
+        operator_payout_script: Some(payout_script(2)),
+        operator_reward: 1250,
+        ..Default::default()
+    };
+    assert_eq!(state.payments(1000000), vec![
+        MasternodePayment { script: payout_script(1), amount: 875000 },
+        MasternodePayment { script: payout_script(2), amount: 125000 },
+    ]);
+    // Operator reward without the operator payout script stays with the owner
+    let state = MasternodePaymentState { operator_payout_script: None, ..state };
+    assert_eq!(state.payments(1000000), vec![MasternodePayment { script: payout_script(1), amount: 1000000 }]);
+}
+
+#[test]
+fn test_verify_coinbase_masternode_payment() {
+    let (processor, cache, list, mut coinbase) = processed_list();
+    let mut states = payment_states(&list);
+    let payee_hash = list.masternode_payee(ChainType::TestNet, &states).unwrap().0.provider_registration_transaction_hash;
+    let payee_state = states.get_mut(&payee_hash).unwrap();
+    payee_state.operator_payout_script = Some(payout_script(200));
+    payee_state.operator_reward = 500;
+    let fees = 12345;
+    coinbase.height = 530001;
+    let bits = HIGH_DIFFICULTY_BITS;
+    let (predicted_payee, payments) = processor.predict_masternode_payments(530001, bits, fees, &states, &cache).unwrap();
+    assert_eq!(predicted_payee, payee_hash);
+    let reward = ChainType::TestNet.masternode_payment(530001, ChainType::TestNet.block_value(530001, bits, fees));
+    assert_eq!(payments.iter().map(|payment| payment.amount).sum::<u64>(), reward);
+    let miner_output = TransactionOutput { amount: 1000, script: Some(payout_script(255)), address: None };
+    coinbase.base.outputs = vec![miner_output.clone()];
+    coinbase.base.outputs.extend(payments.iter().map(|payment| TransactionOutput { amount: payment.amount, script: Some(payment.script.clone()), address: None }));
+    let report = processor.verify_masternode_payment(&coinbase, bits, fees, &states, &cache);
+    assert!(report.is_verified());
+    assert_eq!(report.payee, Some(payee_hash));
+    assert_eq!(report.expected_payments, payments);
+    coinbase.base.outputs[2].amount -= 1;
+    assert_eq!(processor.verify_masternode_payment(&coinbase, bits, fees, &states, &cache).status, MasternodePaymentValidationStatus::InvalidAmount);
+    coinbase.base.outputs.truncate(2);
+    assert_eq!(processor.verify_masternode_payment(&coinbase, bits, fees, &states, &cache).status, MasternodePaymentValidationStatus::MissingPayment);
+    // The list at the previous block is unknown
+    coinbase.height = 530100;
+    assert_eq!(processor.verify_masternode_payment(&coinbase, bits, fees, &states, &cache).status, MasternodePaymentValidationStatus::NoMasternodeList);
+}
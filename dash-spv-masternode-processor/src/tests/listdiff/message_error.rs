@@ -0,0 +1,155 @@
+use crate::chain::common::ChainType;
+use crate::crypto::{byte_util::BytesDecodable, UInt256, UInt768};
+use crate::lib_tests::tests::message_from_file;
+use crate::{models, types};
+use crate::processing::{CoreProvider, MasternodeProcessor, MasternodeProcessorCache, MessageError, MessageField, MessageSection, ProcessingError};
+use crate::tests::block_store::init_mainnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::types::message_error::MessageErrorKind;
+
+fn mainnet_processor() -> MasternodeProcessor<InMemoryProvider> {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    processor
+}
+
+#[test]
+fn test_mnlistdiff_truncated_message() {
+    let processor = mainnet_processor();
+    let bytes = message_from_file("ML1720000.dat");
+    let list_diff = processor.try_read_list_diff_from_message(&bytes, &mut 0, 70221).unwrap();
+    // Cut the message in the middle of the block hash
+    let err = processor.try_read_list_diff_from_message(&bytes[..40], &mut 0, 70221).unwrap_err();
+    assert_eq!(err, MessageError::parse(MessageSection::MNListDiff, MessageField::BlockHash, 32, None));
+    // Cut the message in the middle of the masternode entries
+    let err = processor.try_read_list_diff_from_message(&bytes[..bytes.len() / 2], &mut 0, 70221).unwrap_err();
+    match err {
+        MessageError::Parse { section: MessageSection::MNListDiff, field: MessageField::AddedMasternode(index), offset, block_hash } => {
+            assert!(index > 0 && index < list_diff.added_or_modified_masternodes.len());
+            assert!(offset <= bytes.len() / 2);
+            assert_eq!(block_hash, Some(list_diff.block_hash));
+        },
+        err => panic!("Unexpected error: {:?}", err)
+    }
+    assert_eq!(ProcessingError::from(&err), ProcessingError::ParseError);
+    let cache = &mut MasternodeProcessorCache::default();
+    let result = processor.mn_list_diff_result_from_message(&bytes[..bytes.len() / 2], false, 70221, cache);
+    assert_eq!(result.unwrap_err(), err);
+}
+
+#[derive(Debug)]
+struct RejectingProvider(InMemoryProvider);
+
+impl CoreProvider for RejectingProvider {
+    fn lookup_block_height_by_hash(&self, block_hash: UInt256) -> u32 {
+        self.0.lookup_block_height_by_hash(block_hash)
+    }
+    fn lookup_block_hash_by_height(&self, block_height: u32) -> Option<UInt256> {
+        self.0.lookup_block_hash_by_height(block_height)
+    }
+    fn lookup_merkle_root_by_hash(&self, block_hash: UInt256) -> Option<UInt256> {
+        self.0.lookup_merkle_root_by_hash(block_hash)
+    }
+    fn lookup_masternode_list(&self, block_hash: UInt256) -> Option<models::MasternodeList> {
+        self.0.lookup_masternode_list(block_hash)
+    }
+    fn save_masternode_list(&self, block_hash: UInt256, masternode_list: &models::MasternodeList) -> bool {
+        self.0.save_masternode_list(block_hash, masternode_list)
+    }
+    fn lookup_snapshot_by_block_hash(&self, block_hash: UInt256) -> Option<models::LLMQSnapshot> {
+        self.0.lookup_snapshot_by_block_hash(block_hash)
+    }
+    fn save_snapshot(&self, block_hash: UInt256, snapshot: models::LLMQSnapshot) -> bool {
+        self.0.save_snapshot(block_hash, snapshot)
+    }
+    fn lookup_cl_signature_by_block_hash(&self, block_hash: UInt256) -> Option<UInt768> {
+        self.0.lookup_cl_signature_by_block_hash(block_hash)
+    }
+    fn save_cl_signature(&self, block_hash: UInt256, cl_signature: UInt768) -> bool {
+        self.0.save_cl_signature(block_hash, cl_signature)
+    }
+    fn should_process_diff_with_range(&self, _base_block_hash: UInt256, _block_hash: UInt256) -> ProcessingError {
+        ProcessingError::LocallyStored
+    }
+}
+
+#[test]
+fn test_mnlistdiff_rejected_by_provider() {
+    let mut processor = MasternodeProcessor::new(RejectingProvider(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() }));
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let bytes = message_from_file("ML1720000.dat");
+    let list_diff = processor.try_read_list_diff_from_message(&bytes, &mut 0, 70221).unwrap();
+    let err = processor.mn_list_diff_result_from_message(&bytes, false, 70221, cache).unwrap_err();
+    assert_eq!(err.section(), Some(MessageSection::MNListDiff));
+    assert_eq!(err.block_hash(), Some(list_diff.block_hash));
+    assert_eq!(ProcessingError::from(&err), ProcessingError::LocallyStored);
+    // Diffs from snapshot are processed without asking the provider
+    assert!(processor.mn_list_diff_result_from_message(&bytes, true, 70221, cache).is_ok());
+}
+
+#[test]
+fn test_qrinfo_truncated_message() {
+    let processor = mainnet_processor();
+    let bytes = message_from_file("QRINFO_0_1739226.dat");
+    // Find where the diff at h starts
+    let offset = &mut 0;
+    for _ in 0..3 {
+        models::LLMQSnapshot::from_bytes(&bytes, offset).unwrap();
+    }
+    processor.try_read_list_diff_from_message(&bytes, offset, 70221).unwrap();
+    let diff_h_offset = *offset;
+    let diff_h = processor.try_read_list_diff_from_message(&bytes, offset, 70221).unwrap();
+    let cache = &mut MasternodeProcessorCache::default();
+    let err = processor.qr_info_result_from_message(&bytes[..diff_h_offset + 50], true, true, 70221, cache).unwrap_err();
+    assert_eq!(err.section(), Some(MessageSection::DiffAtH));
+    assert_eq!(err.offset(), Some(diff_h_offset + 32));
+    assert_eq!(err.block_hash(), None);
+    let err = processor.qr_info_result_from_message(&bytes[..*offset - 1], true, true, 70221, cache).unwrap_err();
+    assert_eq!(err.section(), Some(MessageSection::DiffAtH));
+    assert_eq!(err.block_hash(), Some(diff_h.block_hash));
+    assert_eq!(ProcessingError::from(&err), ProcessingError::ParseError);
+    assert!(processor.qr_info_result_from_message(&bytes, true, true, 70221, cache).is_ok());
+}
+
+#[test]
+fn test_message_error_ffi_result() {
+    let processor = mainnet_processor();
+    let bytes = message_from_file("ML1720000.dat");
+    let list_diff = processor.try_read_list_diff_from_message(&bytes, &mut 0, 70221).unwrap();
+    let err = processor.try_read_list_diff_from_message(&bytes[..bytes.len() / 2], &mut 0, 70221).unwrap_err();
+    let result = types::MNListDiffResult::default_with_message_error(&err);
+    assert_eq!(result.error_status, ProcessingError::ParseError);
+    let ffi_error = unsafe { &*result.message_error };
+    assert_eq!(ffi_error.kind, MessageErrorKind::Parse);
+    assert_eq!(ffi_error.section, types::message_error::MessageSection::MNListDiff);
+    assert_eq!(ffi_error.field, types::message_error::MessageField::AddedMasternode);
+    assert_eq!(Some(ffi_error.offset), err.offset());
+    assert!(ffi_error.field_index > 0);
+    assert_eq!(unsafe { *ffi_error.block_hash }, list_diff.block_hash.0);
+    assert!(ffi_error.base_block_hash.is_null());
+
+    let err = MessageError::SnapshotCountMismatch { snapshots: 2, diffs: 3 };
+    let result = types::QRInfoResult::default_with_message_error(&err);
+    let ffi_error = unsafe { &*result.message_error };
+    assert_eq!(ffi_error.kind, MessageErrorKind::SnapshotCountMismatch);
+    assert_eq!(ffi_error.section, types::message_error::MessageSection::None);
+    assert_eq!((ffi_error.snapshots_count, ffi_error.diffs_count), (2, 3));
+    assert!(ffi_error.block_hash.is_null());
+
+    let err = MessageError::Rejected {
+        section: MessageSection::MNListDiffList(4),
+        base_block_hash: UInt256([1u8; 32]),
+        block_hash: UInt256([2u8; 32]),
+        error: ProcessingError::LocallyStored,
+    };
+    let result = types::QRInfoResult::default_with_message_error(&err);
+    assert_eq!(result.error_status, ProcessingError::LocallyStored);
+    let ffi_error = unsafe { &*result.message_error };
+    assert_eq!(ffi_error.kind, MessageErrorKind::Rejected);
+    assert_eq!((ffi_error.section, ffi_error.section_index), (types::message_error::MessageSection::MNListDiffList, 4));
+    assert_eq!(ffi_error.processing_error, ProcessingError::LocallyStored);
+    assert_eq!(unsafe { *ffi_error.base_block_hash }, [1u8; 32]);
+    assert_eq!(unsafe { *ffi_error.block_hash }, [2u8; 32]);
+    assert!(types::MNListDiffResult::default().message_error.is_null());
+}
@@ -16,4 +16,17 @@ pub mod testnet_core19;
 pub mod migration_core19;
 pub mod mainnet_core19;
 pub mod absinthe_core20;
+pub mod core_provider;
+pub mod message_error;
 pub mod core20_testnet;
+pub mod quorum_verification;
+pub mod rotation_error;
+pub mod list_diff_builder;
+pub mod wire_encoding;
+pub mod masternode_history;
+pub mod cache_eviction;
+pub mod masternode_list_checkpoint;
+pub mod processor_metrics;
+pub mod masternode_payment;
+pub mod quorum_membership;
+pub mod rotation_diagnostics;
@@ -0,0 +1,70 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::chain::common::ChainType;
+use crate::lib_tests::tests::message_from_file;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, Metric, MetricsSink, MetricsSnapshot, ProcessorMetrics};
+use crate::processing::metrics::{CacheKind, MetricKind, ValidationFailureReason};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+#[derive(Default)]
+struct CollectingSink {
+    metrics: Mutex<Vec<Metric>>,
+}
+
+impl MetricsSink for CollectingSink {
+    fn record(&self, metric: Metric) {
+        self.metrics.lock().unwrap().push(metric);
+    }
+}
+
+#[test]
+fn test_processor_metrics_counters() {
+    let metrics = ProcessorMetrics::default();
+    metrics.record(Metric::ListDiffProcessed);
+    metrics.record(Metric::QuorumValidated);
+    metrics.record(Metric::QuorumValidationFailed(ValidationFailureReason::InvalidQuorumSignature));
+    metrics.record(Metric::RotationTime(Duration::from_micros(150)));
+    metrics.record(Metric::RotationTime(Duration::from_micros(50)));
+    metrics.record(Metric::CacheLookup { kind: CacheKind::Snapshot, hit: true });
+    metrics.record(Metric::CacheLookup { kind: CacheKind::Snapshot, hit: true });
+    metrics.record(Metric::CacheLookup { kind: CacheKind::Snapshot, hit: true });
+    metrics.record(Metric::CacheLookup { kind: CacheKind::Snapshot, hit: false });
+    let snapshot = metrics.snapshot().unwrap();
+    assert_eq!(snapshot, MetricsSnapshot {
+        list_diffs_processed: 1,
+        quorums_validated: 1,
+        failures_invalid_quorum_signature: 1,
+        rotation_time_us: 200,
+        snapshot_cache_hits: 3,
+        snapshot_cache_misses: 1,
+        ..Default::default()
+    });
+    assert_eq!(snapshot.snapshot_cache_hit_ratio(), 0.75);
+    assert_eq!(snapshot.masternode_list_cache_hit_ratio(), 0.0);
+    assert_eq!(Metric::BLSVerificationTime(Duration::from_millis(2)).kind_and_value(), (MetricKind::BLSVerificationTime, 2000));
+    assert_eq!(Metric::CacheLookup { kind: CacheKind::CLSignature, hit: false }.kind_and_value(), (MetricKind::CacheMiss, 2));
+}
+
+#[test]
+fn test_processor_records_metrics() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let sink = Arc::new(CollectingSink::default());
+    processor.metrics = Some(sink.clone());
+    let cache = &mut MasternodeProcessorCache::default();
+    let base_result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNT530000.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_530000_852596.dat"), true, 70228, cache).unwrap();
+    let recorded = sink.metrics.lock().unwrap();
+    let count = |f: fn(&Metric) -> bool| recorded.iter().filter(|metric| f(metric)).count();
+    assert_eq!(count(|m| matches!(m, Metric::ListDiffProcessed)), 2);
+    // The second diff is based on the list cached after the first one
+    assert!(count(|m| matches!(m, Metric::CacheLookup { kind: CacheKind::MasternodeList, hit: true })) >= 1);
+    let validated = count(|m| matches!(m, Metric::QuorumValidated | Metric::QuorumValidationFailed(..)));
+    assert_eq!(validated, base_result.quorum_validation_reports.len() + result.quorum_validation_reports.len());
+    // Processor with the default sink accumulates the same
+    processor.metrics = Some(Arc::new(ProcessorMetrics::default()));
+    processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNT530000.dat"), true, 70221, &mut MasternodeProcessorCache::default()).unwrap();
+    let snapshot = processor.metrics.as_ref().and_then(|metrics| metrics.snapshot()).unwrap();
+    assert_eq!(snapshot.list_diffs_processed, 1);
+}
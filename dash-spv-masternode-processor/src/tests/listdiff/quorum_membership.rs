@@ -0,0 +1,36 @@
+use hashes::hex::FromHex;
+use crate::chain::common::{ChainType, LLMQType};
+use crate::crypto::UInt256;
+use crate::lib_tests::tests::message_from_file;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+#[test]
+fn test_quorum_memberships_of_masternode() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("MNL_0_122928.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("MNL_122928_123000.dat"), true, 70221, cache).unwrap();
+    // LLMQ_50_60 at 122928 signed by all of its 50 members
+    let llmq_hash = UInt256::from_hex("23b8cd5303c892c0b140152f256c7ff27645890e04f93dc7df75d90100000000").unwrap();
+    let signer = UInt256::from_hex("baa3334baf40368d6bffae98557902fcd7041b78ee1b910cd6606549298e3203").unwrap();
+    let non_member = UInt256::from_hex("0022afbe93054ca11ce9b67892661af4558597bacff0ab82bff05a2b4a89ca2d").unwrap();
+    let memberships = processor.quorum_memberships(signer, 0..=123000, false, cache);
+    let membership = memberships.iter()
+        .find(|membership| membership.llmq_hash == llmq_hash && membership.llmq_type == LLMQType::Llmqtype50_60)
+        .expect("Masternode must be found among members of the quorum");
+    assert_eq!(membership.block_height, 122928);
+    assert_eq!(membership.member_index, 1);
+    assert!(membership.signed);
+    assert!(membership.valid_member);
+    assert!(memberships.windows(2).all(|pair| pair[0].block_height <= pair[1].block_height));
+    // Range excluding the quorum, masternode out of the quorums and unknown masternode
+    assert!(processor.quorum_memberships(signer, 0..=122927, false, cache).iter()
+        .all(|membership| membership.llmq_hash != llmq_hash));
+    assert!(result.masternode_list.masternodes.contains_key(&non_member));
+    assert!(processor.quorum_memberships(non_member, 0..=123000, false, cache).is_empty());
+    assert!(processor.quorum_memberships(UInt256::MIN, 0..=123000, false, cache).is_empty());
+    assert!(cache.needed_masternode_lists.is_empty());
+}
@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+use crate::chain::common::ChainType;
+use crate::lib_tests::tests::message_from_file;
+use crate::models;
+use crate::models::LLMQVerificationContext;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, QuorumValidationReport};
+use crate::tests::block_store::init_mainnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+const MAINNET_DIFFS: [&str; 27] = [
+    "0_1870848",
+    "1870848_1871136",
+    "1871136_1871184",
+    "1871184_1871208",
+    "1871208_1871232",
+    "1871232_1871256",
+    "1871256_1871280",
+    "1871280_1871304",
+    "1871304_1871328",
+    "1871328_1871352",
+    "1871352_1871376",
+    "1871376_1871400",
+    "1871400_1871424",
+    "1871424_1871448",
+    "1871448_1871472",
+    "1871472_1871496",
+    "1871496_1871520",
+    "1871520_1871544",
+    "1871544_1871568",
+    "1871568_1871592",
+    "1871592_1871616",
+    "1871616_1871640",
+    "1871640_1871664",
+    "1871664_1871688",
+    "1871688_1871712",
+    "1871712_1871736",
+    "1871736_1871755",
+];
+
+/// Processes mainnet diffs and returns quorums of the last list which can be verified with the known lists
+fn load_mainnet_quorums(diffs: &[&str], cache: &mut MasternodeProcessorCache) -> (MasternodeProcessor<InMemoryProvider>, Vec<models::LLMQEntry>) {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    let mut masternode_list = models::MasternodeList::default();
+    diffs.iter().for_each(|name| {
+        let bytes = message_from_file(format!("mainnet/MNL_{}.dat", name).as_str());
+        let result = processor.mn_list_diff_result_from_message(&bytes, true, 70227, cache).unwrap();
+        processor.save_masternode_list(result.block_hash, &result.masternode_list);
+        masternode_list = result.masternode_list;
+    });
+    let quorums = masternode_list.quorums
+        .into_values()
+        .flat_map(|map| map.into_values())
+        .filter(|quorum| cache.mn_lists.contains_key(&quorum.llmq_hash))
+        .map(|mut quorum| {
+            quorum.verified = false;
+            quorum
+        })
+        .collect();
+    (processor, quorums)
+}
+
+fn verify_quorums(processor: &mut MasternodeProcessor<InMemoryProvider>, threads: usize, quorums: &[models::LLMQEntry], cache: &mut MasternodeProcessorCache) -> (Vec<models::LLMQEntry>, Vec<QuorumValidationReport>) {
+    processor.quorum_verification_threads = threads;
+    let (added_quorums, _, _, reports) = processor.classify_quorums(
+        BTreeMap::new(),
+        quorums.to_vec(),
+        BTreeMap::new(),
+        &BTreeMap::new(),
+        false,
+        LLMQVerificationContext::MNListDiff,
+        cache);
+    (added_quorums, reports)
+}
+
+#[test]
+fn test_parallel_quorum_verification_matches_sequential() {
+    let cache = &mut MasternodeProcessorCache::default();
+    let (mut processor, quorums) = load_mainnet_quorums(&MAINNET_DIFFS[..8], cache);
+    assert!(quorums.len() > 1, "Need several quorums to verify");
+    let (sequential_quorums, sequential_reports) = verify_quorums(&mut processor, 1, &quorums, cache);
+    for threads in [2, 3, quorums.len() + 1] {
+        let (parallel_quorums, parallel_reports) = verify_quorums(&mut processor, threads, &quorums, cache);
+        assert_eq!(parallel_reports, sequential_reports, "Reports must not depend on number of threads ({})", threads);
+        assert_eq!(parallel_quorums, sequential_quorums);
+        assert!(parallel_quorums.iter().zip(sequential_quorums.iter()).all(|(q1, q2)| q1.verified == q2.verified));
+    }
+}
+
+/// Run with `cargo test --release bench_parallel_quorum_verification -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_parallel_quorum_verification() {
+    let cache = &mut MasternodeProcessorCache::default();
+    let (mut processor, quorums) = load_mainnet_quorums(&MAINNET_DIFFS, cache);
+    let threads = std::thread::available_parallelism().map_or(4, |threads| threads.get());
+    let instant = Instant::now();
+    let (_, sequential_reports) = verify_quorums(&mut processor, 1, &quorums, cache);
+    let sequential_time = instant.elapsed();
+    let instant = Instant::now();
+    let (_, parallel_reports) = verify_quorums(&mut processor, threads, &quorums, cache);
+    let parallel_time = instant.elapsed();
+    assert_eq!(sequential_reports, parallel_reports);
+    println!("Verification of {} quorums: sequential: {:?}, {} threads: {:?} (x{:.2})",
+             quorums.len(),
+             sequential_time,
+             threads,
+             parallel_time,
+             sequential_time.as_secs_f64() / parallel_time.as_secs_f64());
+}
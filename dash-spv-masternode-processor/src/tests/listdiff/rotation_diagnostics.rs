@@ -0,0 +1,60 @@
+use crate::chain::common::ChainType;
+use crate::lib_tests::tests::message_from_file;
+use crate::models::LLMQIndexedHash;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, RotationError};
+use crate::tests::block_store::init_mainnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+#[test]
+fn test_rotation_diagnostics_match_computed_members() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let result = processor.qr_info_result_from_message(&message_from_file("QRINFO_0_1739226.dat"), true, true, 70221, cache).unwrap();
+    let quorum = result.last_quorum_per_index.first().unwrap();
+    let llmq_type = quorum.llmq_type;
+    let cycle_length = llmq_type.params().dkg_params.interval;
+    let quorum_height = processor.lookup_block_height_by_hash(quorum.llmq_hash);
+    let cycle_base_height = quorum_height - quorum_height % cycle_length;
+    let cycle_base_hash = processor.lookup_block_hash_by_height(cycle_base_height).unwrap();
+    let diagnostics = processor.rotation_diagnostics(llmq_type, cycle_base_height, true, cache).unwrap();
+    assert_eq!(diagnostics.quarter_at_h_c.quorum_base_block_height, cycle_base_height - cycle_length);
+    assert_eq!(diagnostics.quarter_at_h_3c.work_block_height, cycle_base_height - 3 * cycle_length - 8);
+    let work_block_hash = diagnostics.quarter_at_h_c.work_block_hash;
+    let snapshot = processor.find_snapshot(work_block_hash, &cache.llmq_snapshots).unwrap();
+    assert_eq!(diagnostics.quarter_at_h_c.skip_list_mode, u32::from(snapshot.skip_list_mode));
+    assert_eq!(diagnostics.quarter_at_h_c.active_quorum_members.len(), snapshot.member_count);
+    assert!(diagnostics.new_quarter.active_quorum_members.is_empty());
+    assert_eq!(diagnostics.new_quarter.skip_list_mode == 0, diagnostics.new_quarter.skip_list.is_empty());
+    assert!(!diagnostics.new_quarter.sorted_combined_masternodes.is_empty());
+    // Diagnostics are built along the same path as the members used for the validation
+    let members = processor.get_rotated_masternodes_for_quorum(
+        llmq_type,
+        quorum.llmq_hash,
+        quorum_height,
+        &mut cache.llmq_members,
+        &mut cache.llmq_indexed_members,
+        &cache.mn_lists,
+        &cache.llmq_snapshots,
+        &cache.cl_signatures,
+        &mut cache.needed_masternode_lists,
+        true,
+    ).unwrap();
+    let quorum_index = (quorum_height - cycle_base_height) as usize;
+    assert_eq!(diagnostics.quorum_members[quorum_index], members.iter().map(|node| node.provider_registration_transaction_hash).collect::<Vec<_>>());
+    assert!(cache.llmq_indexed_members[&llmq_type].contains_key(&LLMQIndexedHash::new(cycle_base_hash, quorum_index as u32)));
+    let quarter_size = llmq_type.params().size as usize / 4;
+    assert!(diagnostics.new_quarter.members.iter().all(|quarter| quarter.len() == quarter_size));
+    let json: serde_json::Value = serde_json::from_str(&diagnostics.to_json().unwrap()).unwrap();
+    assert_eq!(json["cycle_base_block_height"], cycle_base_height);
+    assert_eq!(json["quorum_members"].as_array().unwrap().len(), diagnostics.quorum_members.len());
+    assert_eq!(json["new_quarter"]["work_block_hash"].as_str().unwrap().len(), 64);
+    // Snapshot of the quarter reads as the one of Dash Core
+    let quarter: crate::tests::json_from_core_snapshot::Snapshot = serde_json::from_value(json["quarter_at_h_c"].clone()).unwrap();
+    assert_eq!(quarter.mn_skip_list_mode, i64::from(u32::from(snapshot.skip_list_mode)));
+    assert_eq!(quarter.mn_skip_list, snapshot.skip_list);
+    assert_eq!(quarter.active_quorum_members, diagnostics.quarter_at_h_c.active_quorum_members);
+    // Cycle whose data isn't known
+    let result = processor.rotation_diagnostics(llmq_type, cycle_base_height - 10 * cycle_length, true, cache);
+    assert!(matches!(result, Err(RotationError::MissingMasternodeList { .. } | RotationError::MissingBlockHash(..) | RotationError::MissingSnapshot { .. })));
+}
@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use crate::chain::common::{ChainType, LLMQType};
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::{message_from_file, MerkleBlock};
+use crate::models;
+use crate::models::LLMQVerificationContext;
+use crate::processing::{LLMQValidationStatus, MasternodeProcessor, MasternodeProcessorCache, RotationError};
+use crate::tests::block_store::init_mainnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+fn mainnet_processor(skip_height: Option<u32>) -> MasternodeProcessor<InMemoryProvider> {
+    let blocks = init_mainnet_store()
+        .into_iter()
+        .filter(|block| Some(block.height) != skip_height)
+        .collect();
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks, ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    processor
+}
+
+/// Processes qrinfo and returns the rotated quorum along with the masternode lists known after processing
+fn load_rotated_quorum() -> (models::LLMQEntry, MasternodeProcessorCache) {
+    let processor = mainnet_processor(None);
+    let cache = &mut MasternodeProcessorCache::default();
+    let result = processor.qr_info_result_from_message(&message_from_file("QRINFO_0_1739226.dat"), true, true, 70221, cache).unwrap();
+    let mut quorum = result.last_quorum_per_index.first().cloned().unwrap();
+    quorum.verified = false;
+    let mut mn_lists = cache.mn_lists.clone();
+    // Rotated quorum members are built from the lists at work blocks of the cycles,
+    // so any list at the quorum block is enough to get to the rotation
+    mn_lists.insert(quorum.llmq_hash, Arc::new(result.result_at_h.masternode_list));
+    let cache = MasternodeProcessorCache { mn_lists, ..Default::default() };
+    (quorum, cache)
+}
+
+#[test]
+fn test_rotated_members_missing_block_hash() {
+    let processor = mainnet_processor(None);
+    let cache = &mut MasternodeProcessorCache::default();
+    let llmq_type = LLMQType::Llmqtype60_75;
+    let block_height = 1738944;
+    let cycle_base_height = block_height - block_height % llmq_type.params().dkg_params.interval;
+    let err = MasternodeProcessor::new(InMemoryProvider::default()).get_rotated_masternodes_for_quorum(
+        llmq_type,
+        UInt256::MIN,
+        block_height,
+        &mut cache.llmq_members,
+        &mut cache.llmq_indexed_members,
+        &cache.mn_lists,
+        &cache.llmq_snapshots,
+        &cache.cl_signatures,
+        &mut cache.needed_masternode_lists,
+        false
+    ).unwrap_err();
+    assert_eq!(err, RotationError::MissingBlockHash(cycle_base_height));
+    // Incomplete members must not be cached
+    assert!(cache.llmq_members[&llmq_type].is_empty());
+    assert!(processor.lookup_block_hash_by_height(cycle_base_height).is_some());
+}
+
+#[test]
+fn test_rotated_quorum_missing_snapshot() {
+    let (mut quorum, mut cache) = load_rotated_quorum();
+    assert!(quorum.index.is_some());
+    let processor = mainnet_processor(None);
+    let report = processor.validate_quorum_with_report(&mut quorum, true, &mut cache);
+    match report.status {
+        LLMQValidationStatus::MissingRotationData(RotationError::MissingSnapshot { block_hash, .. }) => {
+            assert_eq!(cache.needed_snapshots, vec![block_hash]);
+        },
+        status => panic!("Unexpected status: {:?}", status)
+    }
+    assert!(report.is_not_critical());
+    assert!(report.members.is_empty());
+    assert!(cache.llmq_members.values().all(|members| !members.contains_key(&quorum.llmq_hash)));
+}
+
+#[test]
+fn test_rotated_quorum_missing_work_block() {
+    let (mut quorum, mut cache) = load_rotated_quorum();
+    let processor = mainnet_processor(None);
+    let llmq_height = processor.lookup_block_height_by_hash(quorum.llmq_hash);
+    let cycle_length = quorum.llmq_type.params().dkg_params.interval;
+    let cycle_base_height = llmq_height - llmq_height % cycle_length;
+    // Work block of the quorum at h-c
+    let missing_height = cycle_base_height - cycle_length - 8;
+    let processor = mainnet_processor(Some(missing_height));
+    let status = processor.validate_quorum(&mut quorum, true, &mut cache);
+    assert_eq!(status, LLMQValidationStatus::MissingRotationData(RotationError::MissingBlockHash(missing_height)));
+    assert_eq!(cache.needed_block_heights, vec![missing_height]);
+    // Host provides missing block and the members can be determined
+    let processor = mainnet_processor(None);
+    cache.needed_block_heights.clear();
+    let status = processor.validate_quorum(&mut quorum, true, &mut cache);
+    assert!(!matches!(status, LLMQValidationStatus::MissingRotationData(RotationError::MissingBlockHash(..))));
+}
+
+#[test]
+fn test_cl_signature_of_quorum_below_chain_lock_offset() {
+    let (quorum, _) = load_rotated_quorum();
+    let mut processor = MasternodeProcessor::new(InMemoryProvider {
+        blocks: vec![MerkleBlock { hash: quorum.llmq_hash, height: 5, merkleroot: UInt256::MIN }],
+        ..Default::default()
+    });
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let cl_signatures = BTreeMap::from([(UInt768([1u8; 96]), HashSet::from([0u16]))]);
+    let (_, _, signatures, _) = processor.classify_quorums(
+        BTreeMap::new(),
+        vec![quorum],
+        BTreeMap::new(),
+        &cl_signatures,
+        true,
+        LLMQVerificationContext::MNListDiff,
+        cache);
+    assert!(signatures.is_empty());
+    assert!(cache.cl_signatures.is_empty());
+}
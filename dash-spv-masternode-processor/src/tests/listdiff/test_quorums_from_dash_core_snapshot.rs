@@ -41,7 +41,7 @@ pub fn test_from_snapshot() {
             snapshot_destroy_default,
             should_process_diff_with_range_default)
     };
-    processor.opaque_context = context as *mut _ as *mut std::ffi::c_void;
+    processor.provider.opaque_context = context as *mut _ as *mut std::ffi::c_void;
     processor.use_insight_as_backup = true;
     processor.chain_type = context.chain;
 
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::sync::Arc;
 use bls_signatures::{BasicSchemeMPL, G1Element, G2Element, Scheme};
 use hashes::hex::FromHex;
 use crate::chain::common::{ChainType, LLMQType};
@@ -21,7 +22,7 @@ fn test_core19rc10() {
     assert_diff_result(context, result);
     let result = unsafe { &*result };
     unsafe {
-        context.cache.mn_lists.insert(UInt256(*result.block_hash), (*result.masternode_list).decode());
+        context.cache.mn_lists.insert(UInt256(*result.block_hash), Arc::new((*result.masternode_list).decode()));
     }
     // let result = process_mnlistdiff(message_from_file("MNL_530000_867700.dat".to_string()), processor, context, 70227);
     let result = process_mnlistdiff(message_from_file("MNL_530000_867700.dat"), processor, context, 70227, false, true);
@@ -38,7 +39,7 @@ fn test_core19_70224() {
     assert_diff_result(context, result);
     let result = unsafe { &*result };
     unsafe {
-        context.cache.mn_lists.insert(UInt256(*result.block_hash), (*result.masternode_list).decode());
+        context.cache.mn_lists.insert(UInt256(*result.block_hash), Arc::new((*result.masternode_list).decode()));
     }
     let result = process_mnlistdiff(message_from_file("MNL_530000_868301.dat"), processor, context, 70224, false, true);
     // let result = process_mnlistdiff(message_from_file("MNL_530000_868301.dat"), processor, context, 70227);
@@ -55,7 +56,7 @@ fn test_core19_70227() {
     // assert_diff_result(context, result);
     unsafe {
         let list = (*result.masternode_list).decode();
-        context.cache.mn_lists.insert(UInt256(*result.block_hash), list);
+        context.cache.mn_lists.insert(UInt256(*result.block_hash), Arc::new(list));
     }
     let result = process_mnlistdiff(message_from_file("testnet/MNL_530000_868321.dat"), processor, context, 70227, false, true);
     assert_diff_result(context, result);
@@ -407,7 +407,7 @@ fn testnet_test_retrieve_saved_hashes() {
     let entry_122088 = list_122088.masternodes.get(&entry_hash).unwrap();
     assert_ne!(entry_122064, entry_122088, "These should NOT be the same object (unless we changed how this worked)");
 
-    assert_eq!(entry_122088.clone().previous_entry_hashes.into_values().collect::<Vec<UInt256>>(),
+    assert_eq!(entry_122088.previous_entry_hashes.values().copied().collect::<Vec<UInt256>>(),
                vec![UInt256::from_hex("14d8f2de996a2515815abeb8f111a3ffe8582443ce7a43a8399c1a1c86c65543").unwrap()],
                "This is what it used to be");
 
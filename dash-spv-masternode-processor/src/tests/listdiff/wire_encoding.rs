@@ -0,0 +1,121 @@
+use crate::consensus::Encodable;
+use crate::crypto::byte_util::BytesDecodable;
+use crate::lib_tests::tests::message_from_file;
+use crate::models;
+
+// Core serializes masternode entries in the order of its hash map, so only the
+// length of the original message is preserved, while re-encoding must be stable
+fn assert_list_diffs_equal(decoded: &models::MNListDiff, original: &models::MNListDiff) {
+    assert_eq!(decoded.base_block_hash, original.base_block_hash);
+    assert_eq!(decoded.block_hash, original.block_hash);
+    assert_eq!(decoded.version, original.version);
+    assert_eq!(decoded.merkle_hashes, original.merkle_hashes);
+    assert_eq!(decoded.merkle_flags, original.merkle_flags);
+    assert_eq!(decoded.deleted_masternode_hashes, original.deleted_masternode_hashes);
+    assert_eq!(decoded.added_or_modified_masternodes, original.added_or_modified_masternodes);
+    assert_eq!(decoded.deleted_quorums, original.deleted_quorums);
+    assert_eq!(decoded.added_quorums, original.added_quorums);
+    assert_eq!(decoded.quorums_cls_sigs, original.quorums_cls_sigs);
+}
+
+fn assert_list_diff_round_trip(name: &str, protocol_version: u32) {
+    let message = message_from_file(name);
+    let list_diff = models::MNListDiff::read(&message, &mut 0, |_| 0, protocol_version).unwrap();
+    let data = list_diff.to_data(protocol_version);
+    assert_eq!(data.len(), message.len(), "encoded length of {} must match", name);
+    let decoded = models::MNListDiff::read(&data, &mut 0, |_| 0, protocol_version).unwrap();
+    assert_list_diffs_equal(&decoded, &list_diff);
+    assert_eq!(decoded.to_data(protocol_version), data, "re-encoded {} must be stable", name);
+}
+
+fn assert_qr_info_round_trip(name: &str, protocol_version: u32) {
+    let message = message_from_file(name);
+    let qr_info = models::LLMQRotationInfo::read(&message, &mut 0, |_| 0, protocol_version).unwrap();
+    let data = qr_info.to_data(protocol_version);
+    assert_eq!(data.len(), message.len(), "encoded length of {} must match", name);
+    let decoded = models::LLMQRotationInfo::read(&data, &mut 0, |_| 0, protocol_version).unwrap();
+    assert_eq!(decoded.extra_share, qr_info.extra_share);
+    assert_eq!(decoded.snapshot_at_h_c, qr_info.snapshot_at_h_c);
+    assert_eq!(decoded.snapshot_at_h_2c, qr_info.snapshot_at_h_2c);
+    assert_eq!(decoded.snapshot_at_h_3c, qr_info.snapshot_at_h_3c);
+    assert_eq!(decoded.snapshot_at_h_4c, qr_info.snapshot_at_h_4c);
+    assert_eq!(decoded.last_quorum_per_index, qr_info.last_quorum_per_index);
+    assert_eq!(decoded.quorum_snapshot_list, qr_info.quorum_snapshot_list);
+    assert_list_diffs_equal(&decoded.mn_list_diff_tip, &qr_info.mn_list_diff_tip);
+    assert_list_diffs_equal(&decoded.mn_list_diff_at_h, &qr_info.mn_list_diff_at_h);
+    assert_list_diffs_equal(&decoded.mn_list_diff_at_h_c, &qr_info.mn_list_diff_at_h_c);
+    assert_list_diffs_equal(&decoded.mn_list_diff_at_h_2c, &qr_info.mn_list_diff_at_h_2c);
+    assert_list_diffs_equal(&decoded.mn_list_diff_at_h_3c, &qr_info.mn_list_diff_at_h_3c);
+    assert_eq!(decoded.mn_list_diff_at_h_4c.is_some(), qr_info.mn_list_diff_at_h_4c.is_some());
+    if let (Some(decoded), Some(original)) = (&decoded.mn_list_diff_at_h_4c, &qr_info.mn_list_diff_at_h_4c) {
+        assert_list_diffs_equal(decoded, original);
+    }
+    assert_eq!(decoded.mn_list_diff_list.len(), qr_info.mn_list_diff_list.len());
+    decoded.mn_list_diff_list.iter()
+        .zip(qr_info.mn_list_diff_list.iter())
+        .for_each(|(decoded, original)| assert_list_diffs_equal(decoded, original));
+    assert_eq!(decoded.to_data(protocol_version), data, "re-encoded {} must be stable", name);
+}
+
+#[test]
+fn test_list_diff_round_trip_legacy() {
+    assert_list_diff_round_trip("ML1720000.dat", 70221);
+}
+
+#[test]
+fn test_list_diff_round_trip_core19() {
+    assert_list_diff_round_trip("mainnet/MNL_0_1870840.dat", 70227);
+    assert_list_diff_round_trip("mainnet/MNL_1870840_1871712.dat", 70227);
+    assert_list_diff_round_trip("testnet/MNL_0_530000_70228.dat", 70228);
+}
+
+#[test]
+fn test_list_diff_round_trip_core20() {
+    assert_list_diff_round_trip("testnet/MNL_530000_900096__70230.dat", 70230);
+    assert_list_diff_round_trip("testnet/MNL_122088_900603__70230.dat", 70230);
+}
+
+#[test]
+fn test_qr_info_round_trip() {
+    assert_qr_info_round_trip("QRINFO_0_1739226.dat", 70221);
+    assert_qr_info_round_trip("testnet/QRINFO_LAST.dat", 70230);
+}
+
+#[test]
+fn test_snapshot_and_quorum_entry_round_trip() {
+    let message = message_from_file("QRINFO_0_1739226.dat");
+    let qr_info = models::LLMQRotationInfo::read(&message, &mut 0, |_| 0, 70221).unwrap();
+    [&qr_info.snapshot_at_h_c, &qr_info.snapshot_at_h_2c, &qr_info.snapshot_at_h_3c].into_iter()
+        .chain(qr_info.quorum_snapshot_list.iter())
+        .for_each(|snapshot| {
+            let mut data = Vec::<u8>::new();
+            snapshot.enc(&mut data);
+            let decoded = models::LLMQSnapshot::from_bytes(&data, &mut 0).unwrap();
+            assert_eq!(&decoded, snapshot);
+            let mut re_encoded = Vec::<u8>::new();
+            decoded.enc(&mut re_encoded);
+            assert_eq!(re_encoded, data);
+        });
+    qr_info.last_quorum_per_index.iter().for_each(|entry| {
+        let mut data = Vec::<u8>::new();
+        entry.enc(&mut data);
+        let decoded = models::LLMQEntry::from_bytes(&data, &mut 0).unwrap();
+        assert_eq!(&decoded, entry);
+        assert_eq!(decoded.to_data(), data);
+    });
+}
+
+#[test]
+fn test_qr_info_extra_share_follows_h_4c_data() {
+    let message = message_from_file("QRINFO_0_1739226.dat");
+    let mut qr_info = models::LLMQRotationInfo::read(&message, &mut 0, |_| 0, 70221).unwrap();
+    // The flag without h-4c data must not produce the message which can't be parsed
+    qr_info.extra_share = true;
+    qr_info.snapshot_at_h_4c = None;
+    qr_info.mn_list_diff_at_h_4c = None;
+    let data = qr_info.to_data(70221);
+    let decoded = models::LLMQRotationInfo::read(&data, &mut 0, |_| 0, 70221).unwrap();
+    assert!(!decoded.extra_share);
+    assert!(decoded.snapshot_at_h_4c.is_none());
+    assert_eq!(decoded.quorum_snapshot_list, qr_info.quorum_snapshot_list);
+}
@@ -0,0 +1,91 @@
+use hashes::hex::FromHex;
+use crate::chain::common::{ChainType, IHaveChainSettings};
+use crate::common::{LLMQVersion, SignatureValidationStatus};
+use crate::consensus::Encodable;
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::message_from_file;
+use crate::models::ChainLock;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, MessageError, MessageField, MessageSection};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::tests::locks::instant_lock::{masternode_list_with_quorums, quorum_key, quorums};
+
+#[test]
+fn test_chain_lock_round_trip() {
+    let chain_lock = ChainLock::new(1_900_000, UInt256::sha256d([1u8]), UInt768::MIN);
+    let mut data = Vec::<u8>::new();
+    chain_lock.enc(&mut data);
+    assert_eq!(data.len(), 4 + 32 + 96);
+    assert_eq!(ChainLock::read(&data, &mut 0).unwrap(), chain_lock);
+    assert_eq!(
+        ChainLock::read(&data[..40], &mut 0),
+        Err(MessageError::parse(MessageSection::ChainLock, MessageField::Signature, 36, Some(chain_lock.block_hash))));
+}
+
+#[test]
+fn test_chain_lock_request_id() {
+    let chain_lock = ChainLock::new(1_900_000, UInt256::sha256d([1u8]), UInt768::MIN);
+    let mut buffer = vec![5u8];
+    buffer.extend_from_slice(b"clsig");
+    buffer.extend_from_slice(&1_900_000i32.to_le_bytes());
+    assert_eq!(chain_lock.request_id(), UInt256::sha256d(buffer));
+    assert_eq!(chain_lock.signing_block_height(), Some(1_899_992));
+}
+
+#[test]
+fn test_chain_lock_without_masternode_list() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider::default());
+    processor.chain_type = ChainType::MainNet;
+    let chain_lock = ChainLock::new(1_900_000, UInt256::sha256d([1u8]), UInt768::MIN);
+    let report = processor.verify_chain_lock(&chain_lock, &MasternodeProcessorCache::default());
+    assert_eq!(report.status, SignatureValidationStatus::NoMasternodeList);
+    assert_eq!(report.llmq_type, ChainType::MainNet.chain_locks_type());
+    assert_eq!(report.request_id, chain_lock.request_id());
+}
+
+#[test]
+fn test_chain_lock_verification() {
+    let chain_type = ChainType::MainNet;
+    let llmq_type = chain_type.chain_locks_type();
+    let list = masternode_list_with_quorums(quorums(llmq_type, 4, LLMQVersion::BLSBasicDefault, true));
+    let chain_lock = ChainLock::new(1_900_000, UInt256::sha256d([1u8]), UInt768::MIN);
+    let quorum = chain_lock.quorum_entry(&list, chain_type).unwrap();
+    let signature = quorum_key(llmq_type, quorum.index.unwrap_or(0), false).sign_digest(chain_lock.sign_hash(quorum));
+    let chain_lock = ChainLock { signature, ..chain_lock };
+    let report = chain_lock.verify_with_masternode_list(&list, chain_type);
+    assert_eq!(report.status, SignatureValidationStatus::Verified);
+    assert_eq!(report.quorum_hash, Some(quorum.llmq_hash));
+    let forged = ChainLock { block_hash: UInt256::sha256d([2u8]), ..chain_lock.clone() };
+    assert_eq!(forged.verify_with_masternode_list(&list, chain_type).status, SignatureValidationStatus::InvalidSignature);
+    assert_eq!(chain_lock.verify_with_masternode_list(&masternode_list_with_quorums(vec![]), chain_type).status, SignatureValidationStatus::NoQuorum);
+}
+
+#[test]
+fn test_testnet_chain_lock_verification() {
+    let chain_type = ChainType::TestNet;
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = chain_type;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNT530000.dat"), true, 70221, cache).unwrap();
+    let mut list = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_530000_904944__70230.dat"), true, 70230, cache).unwrap().masternode_list;
+    for height in (904944..905448).step_by(24) {
+        let file_name = format!("testnet/MNL_{}_{}__70230.dat", height, height + 24);
+        list = processor.mn_list_diff_result_from_message(&message_from_file(&file_name), true, 70230, cache).unwrap().masternode_list;
+    }
+    // Diff adding the quorum at 905472 carries the chain lock of the block 8 blocks below
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_905472_905496__70230.dat"), true, 70230, cache).unwrap();
+    let block_hash = processor.lookup_block_hash_by_height(905464).unwrap();
+    let signature = UInt768::from_hex("a67ab8e7c6ea5612ee45cf5a95cc59ab2a31d565c6c6b4431a42a6139045251bbe9efc9faaa301860a45ef720fb3cd7d172fd4ce22c63dd3a6c47f3c34db3ef7f0aea0e841bc2f95a0a500237445e2c11ef88680907237e81491db19a22b933e").unwrap();
+    assert_eq!(result.cl_signatures.get(&block_hash), Some(&signature));
+    let chain_lock = ChainLock::new(905464, block_hash, signature);
+    // Quorum is selected from the list at 905456, LLMQ_50_60 quorums there are the ones of the list at 905448
+    // (the quorum of 905448 is mined since 905458)
+    let quorum = chain_lock.quorum_entry(&list, chain_type).unwrap();
+    assert_eq!(quorum.llmq_hash, UInt256::from_hex("bdd698ec4e0bd9ca9d8d792b9c2d4096e45248db4668ff440e41f74b35000000").unwrap());
+    assert_eq!(processor.lookup_block_height_by_hash(quorum.llmq_hash), 905232);
+    let report = chain_lock.verify_with_masternode_list(&list, chain_type);
+    assert_eq!(report.quorum_hash, Some(quorum.llmq_hash));
+    assert_eq!(report.status, SignatureValidationStatus::Verified);
+    let forged = ChainLock::new(905464, processor.lookup_block_hash_by_height(905463).unwrap(), signature);
+    assert_eq!(forged.verify_with_masternode_list(&list, chain_type).status, SignatureValidationStatus::InvalidSignature);
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType};
+use crate::common::{LLMQVersion, SignatureValidationStatus};
+use crate::consensus::{encode::VarInt, Encodable};
+use crate::crypto::{byte_util::Reversable, UInt256, UInt384, UInt768};
+use crate::keys::BLSKey;
+use crate::lib_tests::tests::MerkleBlock;
+use crate::models::{InstantLock, LLMQEntry, MasternodeList};
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, MessageError, MessageField, MessageSection};
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::tx::TransactionOutPoint;
+
+pub fn quorum_key(llmq_type: LLMQType, index: u16, use_legacy: bool) -> BLSKey {
+    BLSKey::key_with_seed_data(&[u8::from(llmq_type), index as u8, 7, 7], use_legacy)
+}
+
+pub fn quorum_hash(llmq_type: LLMQType, index: u16) -> UInt256 {
+    UInt256::sha256d([u8::from(llmq_type), index as u8])
+}
+
+fn quorum(llmq_type: LLMQType, index: u16, version: LLMQVersion, public_key: UInt384) -> LLMQEntry {
+    let llmq_hash = quorum_hash(llmq_type, index);
+    let index = version.use_rotated_quorums().then_some(index);
+    LLMQEntry::new(version, llmq_type, llmq_hash, index, VarInt(0), VarInt(0), vec![], vec![], public_key, UInt256::MIN, UInt768::MIN, UInt768::MIN)
+}
+
+pub fn quorums(llmq_type: LLMQType, count: u16, version: LLMQVersion, with_keys: bool) -> Vec<LLMQEntry> {
+    (0..count)
+        .map(|i| {
+            let public_key = if with_keys { quorum_key(llmq_type, i, version.use_bls_legacy()).public_key_uint() } else { UInt384::MIN };
+            quorum(llmq_type, i, version, public_key)
+        })
+        .collect()
+}
+
+pub fn masternode_list_with_quorums(quorums: Vec<LLMQEntry>) -> MasternodeList {
+    let mut map = BTreeMap::<LLMQType, BTreeMap<UInt256, LLMQEntry>>::new();
+    quorums.into_iter().for_each(|quorum| {
+        map.entry(quorum.llmq_type).or_default().insert(quorum.llmq_hash, quorum);
+    });
+    MasternodeList::new(BTreeMap::new(), map, UInt256::MIN, 0, true)
+}
+
+fn signed_lock(instant_lock: InstantLock, list: &MasternodeList, chain_type: ChainType) -> InstantLock {
+    let quorum = instant_lock.quorum_entry(list, chain_type).unwrap();
+    let key = quorum_key(quorum.llmq_type, quorum.index.unwrap_or(0), quorum.version.use_bls_legacy());
+    let signature = key.sign_digest(instant_lock.sign_hash(quorum));
+    InstantLock { signature, ..instant_lock }
+}
+
+fn unsigned_lock(cycle_hash: Option<UInt256>) -> InstantLock {
+    InstantLock {
+        version: u8::from(cycle_hash.is_some()),
+        inputs: vec![
+            TransactionOutPoint::new(UInt256::sha256d([1u8]), 0),
+            TransactionOutPoint::new(UInt256::sha256d([2u8]), 3),
+        ],
+        transaction_hash: UInt256::sha256d([3u8]),
+        cycle_hash,
+        signature: UInt768::MIN,
+    }
+}
+
+#[test]
+fn test_instant_lock_round_trip() {
+    [None, Some(UInt256::sha256d([4u8]))].into_iter().for_each(|cycle_hash| {
+        let instant_lock = unsigned_lock(cycle_hash);
+        let mut data = Vec::<u8>::new();
+        instant_lock.enc(&mut data);
+        let is_deterministic = cycle_hash.is_some();
+        assert_eq!(data.len(), 1 + 2 * 36 + 32 + 96 + if is_deterministic { 33 } else { 0 });
+        assert_eq!(InstantLock::read(&data, &mut 0, is_deterministic).unwrap(), instant_lock);
+        let truncated = &data[..data.len() - 1];
+        assert_eq!(
+            InstantLock::read(truncated, &mut 0, is_deterministic),
+            Err(MessageError::parse(MessageSection::InstantLock, MessageField::Signature, data.len() - 96, None)));
+    });
+}
+
+#[test]
+fn test_instant_lock_request_id() {
+    let instant_lock = unsigned_lock(None);
+    let mut buffer = vec![6u8];
+    buffer.extend_from_slice(b"islock");
+    buffer.push(2);
+    instant_lock.inputs.iter().for_each(|input| {
+        buffer.extend_from_slice(&input.hash.0);
+        buffer.extend_from_slice(&input.index.to_le_bytes());
+    });
+    assert_eq!(instant_lock.request_id(), UInt256::sha256d(buffer));
+}
+
+#[test]
+fn test_instant_lock_quorum_selection() {
+    let chain_type = ChainType::MainNet;
+    let llmq_type = chain_type.is_llmq_type();
+    let list = masternode_list_with_quorums(quorums(llmq_type, 4, LLMQVersion::BLSBasicDefault, false));
+    let instant_lock = unsigned_lock(None);
+    let request_id = instant_lock.request_id();
+    let expected = list.quorums[&llmq_type].values()
+        .min_by_key(|quorum| quorum.ordering_hash_for_request_id(request_id, llmq_type).reverse())
+        .unwrap();
+    assert_eq!(instant_lock.quorum_entry(&list, chain_type), Some(expected));
+
+    let llmq_type = chain_type.isd_llmq_type();
+    let list = masternode_list_with_quorums(quorums(llmq_type, 32, LLMQVersion::BLSBasicIndexed, false));
+    let instant_lock = unsigned_lock(Some(UInt256::sha256d([4u8])));
+    let request_id = instant_lock.request_id();
+    let signer = (u64::from_le_bytes(request_id.0[24..].try_into().unwrap()) >> 58) & 31;
+    let quorum = instant_lock.quorum_entry(&list, chain_type).unwrap();
+    assert_eq!(quorum.index, Some(signer as u16));
+}
+
+#[test]
+fn test_instant_lock_verification() {
+    let chain_type = ChainType::MainNet;
+    let is_list = masternode_list_with_quorums(quorums(chain_type.is_llmq_type(), 4, LLMQVersion::BLSBasicDefault, true));
+    let isd_list = masternode_list_with_quorums(quorums(chain_type.isd_llmq_type(), 32, LLMQVersion::BLSBasicIndexed, true));
+    [(None, &is_list), (Some(UInt256::sha256d([4u8])), &isd_list)].into_iter().for_each(|(cycle_hash, list)| {
+        let instant_lock = signed_lock(unsigned_lock(cycle_hash), list, chain_type);
+        let report = instant_lock.verify_with_masternode_list(list, chain_type);
+        assert_eq!(report.status, SignatureValidationStatus::Verified);
+        assert_eq!(report.request_id, instant_lock.request_id());
+        let forged = InstantLock { transaction_hash: UInt256::sha256d([5u8]), ..instant_lock.clone() };
+        assert_eq!(forged.verify_with_masternode_list(list, chain_type).status, SignatureValidationStatus::InvalidSignature);
+        let empty_list = masternode_list_with_quorums(vec![]);
+        assert_eq!(instant_lock.verify_with_masternode_list(&empty_list, chain_type).status, SignatureValidationStatus::NoQuorum);
+    });
+}
+
+fn list_at(quorums: Vec<LLMQEntry>, block_height: u32) -> MasternodeList {
+    let mut list = masternode_list_with_quorums(quorums);
+    list.block_hash = UInt256::sha256d(block_height.to_le_bytes());
+    list.known_height = block_height;
+    list
+}
+
+fn block(block_height: u32) -> MerkleBlock {
+    MerkleBlock { hash: UInt256::sha256d(block_height.to_le_bytes()), height: block_height, merkleroot: UInt256::MIN }
+}
+
+#[test]
+fn test_deterministic_lock_signing_list_selection() {
+    let chain_type = ChainType::MainNet;
+    let llmq_type = chain_type.isd_llmq_type();
+    let interval = llmq_type.params().dkg_params.interval;
+    let cycle_height = interval * 6600;
+    let signing_height = cycle_height + interval - 1;
+    let instant_lock = unsigned_lock(Some(UInt256::sha256d(cycle_height.to_le_bytes())));
+    assert_eq!(instant_lock.signing_block_height(cycle_height, chain_type), Some(signing_height));
+    assert_eq!(instant_lock.signing_block_height(cycle_height + 1, chain_type), None);
+    assert_eq!(unsigned_lock(None).signing_block_height(cycle_height, chain_type), None);
+
+    // Quorums of the later list have other hashes, so the report tells which list was used
+    let signing_quorums = quorums(llmq_type, 32, LLMQVersion::BLSBasicIndexed, false);
+    let later_quorums = signing_quorums.iter()
+        .map(|quorum| LLMQEntry { llmq_hash: quorum.llmq_hash.reversed(), ..quorum.clone() })
+        .collect::<Vec<_>>();
+    let signing_list = list_at(signing_quorums, signing_height);
+    let later_list = list_at(later_quorums, signing_height + 100);
+    let expected_quorum = instant_lock.quorum_entry(&signing_list, chain_type).unwrap().llmq_hash;
+    let mut cache = MasternodeProcessorCache::default();
+    cache.add_masternode_list(signing_list.block_hash, signing_list.clone());
+    cache.add_masternode_list(later_list.block_hash, later_list.clone());
+
+    let processor_with_blocks = |blocks: Vec<MerkleBlock>| {
+        let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks, ..Default::default() });
+        processor.chain_type = chain_type;
+        processor
+    };
+    // Finished cycle: the list at its last block is used even if there are later ones
+    let processor = processor_with_blocks(vec![block(cycle_height), block(signing_height), block(signing_height + 100)]);
+    let report = processor.verify_instant_lock(&instant_lock, &cache);
+    assert_eq!(report.quorum_hash, Some(expected_quorum));
+    assert_ne!(report.status, SignatureValidationStatus::NoMasternodeList);
+
+    // Unknown cycle block
+    let processor = processor_with_blocks(vec![block(signing_height)]);
+    assert_eq!(processor.verify_instant_lock(&instant_lock, &cache).status, SignatureValidationStatus::NoMasternodeList);
+
+    // Cycle isn't finished yet: the latest list is used
+    let mut cache = MasternodeProcessorCache::default();
+    cache.add_masternode_list(signing_list.block_hash, signing_list);
+    let processor = processor_with_blocks(vec![block(cycle_height)]);
+    assert_eq!(processor.verify_instant_lock(&instant_lock, &cache).quorum_hash, Some(expected_quorum));
+    // but not the list from the previous cycles
+    let mut cache = MasternodeProcessorCache::default();
+    let stale_list = list_at(quorums(llmq_type, 32, LLMQVersion::BLSBasicIndexed, false), cycle_height - 1);
+    cache.add_masternode_list(stale_list.block_hash, stale_list);
+    assert_eq!(processor.verify_instant_lock(&instant_lock, &cache).status, SignatureValidationStatus::NoMasternodeList);
+}
@@ -0,0 +1,3 @@
+pub mod instant_lock;
+pub mod chain_lock;
+pub mod platform_signature;
@@ -0,0 +1,34 @@
+use crate::chain::common::{ChainType, IHaveChainSettings};
+use crate::common::{LLMQVersion, SignatureValidationStatus};
+use crate::crypto::{UInt256, UInt768};
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::tests::locks::instant_lock::{masternode_list_with_quorums, quorum_hash, quorum_key, quorums};
+
+#[test]
+fn test_platform_signature_with_unknown_quorum() {
+    let chain_type = ChainType::TestNet;
+    let list = masternode_list_with_quorums(quorums(chain_type.platform_type(), 2, LLMQVersion::BLSBasicDefault, false));
+    let status = list.verify_platform_signature(chain_type, UInt256::sha256d([9u8]), UInt256::sha256d([1u8]), UInt256::sha256d([2u8]), UInt768::MIN);
+    assert_eq!(status, SignatureValidationStatus::NoQuorum);
+    let mut processor = MasternodeProcessor::new(InMemoryProvider::default());
+    processor.chain_type = chain_type;
+    let status = processor.verify_platform_signature(UInt256::sha256d([9u8]), 1000, UInt256::sha256d([1u8]), UInt256::sha256d([2u8]), UInt768::MIN, &MasternodeProcessorCache::default());
+    assert_eq!(status, SignatureValidationStatus::NoMasternodeList);
+}
+
+#[test]
+fn test_platform_signature_verification() {
+    let chain_type = ChainType::TestNet;
+    let llmq_type = chain_type.platform_type();
+    [LLMQVersion::Default, LLMQVersion::BLSBasicDefault].into_iter().for_each(|version| {
+        let list = masternode_list_with_quorums(quorums(llmq_type, 2, version, true));
+        // The quorum built from the key with index 0
+        let quorum = &list.quorums[&llmq_type][&quorum_hash(llmq_type, 0)];
+        let request_id = UInt256::sha256d([1u8]);
+        let message_hash = UInt256::sha256d([2u8]);
+        let signature = quorum_key(llmq_type, 0, version.use_bls_legacy()).sign_digest(quorum.sign_hash_for_request_id(request_id, message_hash));
+        assert_eq!(list.verify_platform_signature(chain_type, quorum.llmq_hash, request_id, message_hash, signature), SignatureValidationStatus::Verified);
+        assert_eq!(list.verify_platform_signature(chain_type, quorum.llmq_hash, request_id, UInt256::sha256d([3u8]), signature), SignatureValidationStatus::InvalidSignature);
+    });
+}
@@ -4,6 +4,8 @@ pub mod indexes;
 pub mod json_from_core_snapshot;
 pub mod keys;
 pub mod listdiff;
+pub mod locks;
+pub mod storage;
 pub mod test_testnet_checkpoints;
 pub mod bindings;
 pub mod tx;
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use crate::chain::common::{ChainType, LLMQType};
+use crate::common::LLMQSnapshotSkipMode;
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::message_from_file;
+use crate::models;
+use crate::processing::{CachePolicy, MasternodeProcessor, MasternodeProcessorCache};
+use crate::storage::{cache_from_snapshot_data, cache_snapshot_data, load_cache_snapshot, save_cache_snapshot};
+use crate::tests::block_store::init_mainnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+fn snapshot_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join("dash-spv-masternode-processor");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{}_{}.cache", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn filled_cache() -> MasternodeProcessorCache {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("ML1720000.dat"), true, 70221, cache).unwrap();
+    let block_hash = *cache.mn_lists.keys().next().unwrap();
+    let members = cache.mn_lists[&block_hash].masternodes.values().take(10).map(|entry| entry.as_ref().clone()).collect::<Vec<_>>();
+    cache.add_snapshot(block_hash, models::LLMQSnapshot::new(vec![0xff, 0x01], 9, vec![1, 5, -1], LLMQSnapshotSkipMode::SkipFirst));
+    cache.add_cl_signature(block_hash, UInt768([7u8; 96]));
+    cache.llmq_members.insert(LLMQType::Llmqtype400_60, BTreeMap::from([(block_hash, members.clone())]));
+    cache.llmq_indexed_members.insert(LLMQType::Llmqtype60_75, BTreeMap::from([(models::LLMQIndexedHash::new(block_hash, 3), members)]));
+    cache.clone()
+}
+
+fn assert_same_data(restored: &MasternodeProcessorCache, cache: &MasternodeProcessorCache) {
+    assert_eq!(restored.mn_lists, cache.mn_lists);
+    assert_eq!(restored.cl_signatures, cache.cl_signatures);
+    assert_eq!(restored.llmq_members, cache.llmq_members);
+    assert_eq!(restored.llmq_indexed_members, cache.llmq_indexed_members);
+    assert_eq!(restored.llmq_snapshots.len(), cache.llmq_snapshots.len());
+    restored.llmq_snapshots.iter().zip(cache.llmq_snapshots.iter()).for_each(|((h1, s1), (h2, s2))| {
+        assert_eq!(h1, h2);
+        assert_eq!(s1.member_list, s2.member_list);
+        assert_eq!(s1.skip_list, s2.skip_list);
+        assert_eq!(s1.skip_list_mode, s2.skip_list_mode);
+    });
+}
+
+#[test]
+fn test_cache_snapshot_round_trip() {
+    let cache = filled_cache();
+    let data = cache_snapshot_data(&cache);
+    let restored = cache_from_snapshot_data(&data).unwrap();
+    assert_same_data(&restored, &cache);
+    // Restored data is encoded the same way
+    assert_eq!(cache_snapshot_data(&restored), data);
+}
+
+#[test]
+fn test_cache_snapshot_rejects_invalid_data() {
+    let data = cache_snapshot_data(&filled_cache());
+    let mut corrupted = data.clone();
+    corrupted[data.len() / 2] ^= 0xff;
+    assert!(cache_from_snapshot_data(&corrupted).is_err());
+    let mut unknown_version = data.clone();
+    unknown_version[4] = 0xff;
+    assert!(cache_from_snapshot_data(&unknown_version).is_err());
+    assert!(cache_from_snapshot_data(&data[..data.len() - 1]).is_err());
+    assert!(cache_from_snapshot_data(&[]).is_err());
+    let empty = MasternodeProcessorCache::default();
+    assert!(cache_from_snapshot_data(&cache_snapshot_data(&empty)).unwrap().mn_lists.is_empty());
+}
+
+#[test]
+fn test_cache_snapshot_file_keeps_policy() {
+    let path = snapshot_path("warm_start");
+    let cache = filled_cache();
+    save_cache_snapshot(&cache, &path).unwrap();
+    let policy = CachePolicy { max_masternode_lists: Some(10), ..Default::default() };
+    let mut warm = MasternodeProcessorCache::with_policy(policy);
+    warm.add_cl_signature(UInt256::MIN, UInt768::MIN);
+    warm.restore_from(load_cache_snapshot(&path).unwrap());
+    assert_same_data(&warm, &cache);
+    assert_eq!(warm.policy, policy);
+    let _ = std::fs::remove_file(&path);
+}
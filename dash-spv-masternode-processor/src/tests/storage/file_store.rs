@@ -0,0 +1,149 @@
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use crate::chain::common::ChainType;
+use crate::common::LLMQSnapshotSkipMode;
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::message_from_file;
+use crate::models;
+use crate::models::LLMQVerificationContext;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::storage::{FileStore, PersistentProvider, RecordKind};
+use crate::tests::block_store::init_mainnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+
+fn store_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join("dash-spv-masternode-processor")
+        .join(format!("{}_{}.dat", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn process_list(file: &str, processor: &MasternodeProcessor<impl crate::processing::CoreProvider>) -> models::MasternodeList {
+    let cache = &mut MasternodeProcessorCache::default();
+    let bytes = message_from_file(file);
+    let list_diff = processor.read_list_diff_from_message(&bytes, &mut 0, 70221)
+        .expect("Diff must be parsed");
+    processor.get_list_diff_result_internal_with_base_lookup(list_diff, LLMQVerificationContext::None, cache)
+        .masternode_list
+}
+
+#[test]
+fn test_file_store_round_trip() {
+    let path = store_path("round_trip");
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::MainNet;
+    let list = process_list("ML1720000.dat", &processor);
+    let snapshot = models::LLMQSnapshot::new(vec![0xff, 0x01], 9, vec![1, 5, -1], LLMQSnapshotSkipMode::SkipFirst);
+    let cl_signature = UInt768([7u8; 96]);
+    {
+        let store = FileStore::open(&path).unwrap();
+        store.save_masternode_list(list.block_hash, &list).unwrap();
+        store.save_snapshot(list.block_hash, &snapshot).unwrap();
+        store.save_cl_signature(list.block_hash, cl_signature).unwrap();
+    }
+    let store = FileStore::open(&path).unwrap();
+    let restored = store.masternode_list(list.block_hash).unwrap().expect("List must be restored");
+    assert_eq!(restored, list);
+    assert_eq!(restored.masternodes.len(), list.masternodes.len());
+    let restored_snapshot = store.snapshot(list.block_hash).unwrap().expect("Snapshot must be restored");
+    assert_eq!(restored_snapshot, snapshot);
+    assert_eq!(restored_snapshot.member_count, 9);
+    assert_eq!(store.cl_signature(list.block_hash).unwrap(), Some(cl_signature));
+    assert_eq!(store.cl_signature(UInt256::MIN).unwrap(), None);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_file_store_drops_torn_tail() {
+    let path = store_path("torn_tail");
+    {
+        let store = FileStore::open(&path).unwrap();
+        store.save_cl_signature(UInt256([1u8; 32]), UInt768([1u8; 96])).unwrap();
+        store.save_cl_signature(UInt256([2u8; 32]), UInt768([2u8; 96])).unwrap();
+    }
+    // Emulate crash in the middle of writing the last record
+    let len = std::fs::metadata(&path).unwrap().len();
+    OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 10).unwrap();
+    {
+        let store = FileStore::open(&path).unwrap();
+        assert_eq!(store.cl_signature(UInt256([1u8; 32])).unwrap(), Some(UInt768([1u8; 96])));
+        assert_eq!(store.cl_signature(UInt256([2u8; 32])).unwrap(), None);
+        store.save_cl_signature(UInt256([3u8; 32]), UInt768([3u8; 96])).unwrap();
+    }
+    // Emulate garbage at the end
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(&[3u8; 64]).unwrap();
+    let store = FileStore::open(&path).unwrap();
+    assert_eq!(store.cl_signature(UInt256([1u8; 32])).unwrap(), Some(UInt768([1u8; 96])));
+    assert_eq!(store.cl_signature(UInt256([3u8; 32])).unwrap(), Some(UInt768([3u8; 96])));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_file_store_rejects_previous_version() {
+    let path = store_path("previous_version");
+    {
+        let store = FileStore::open(&path).unwrap();
+        store.save_cl_signature(UInt256([1u8; 32]), UInt768([1u8; 96])).unwrap();
+    }
+    // Stores of version 1 kept snapshots in the legacy format
+    let mut file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.seek(SeekFrom::Start(4)).unwrap();
+    file.write_all(&1u16.to_le_bytes()).unwrap();
+    drop(file);
+    assert!(FileStore::open(&path).is_err());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_file_store_compaction() {
+    let path = store_path("compaction");
+    let store = FileStore::open(&path).unwrap();
+    for i in 0..10u8 {
+        store.save_cl_signature(UInt256([1u8; 32]), UInt768([i; 96])).unwrap();
+    }
+    store.save_cl_signature(UInt256([2u8; 32]), UInt768([2u8; 96])).unwrap();
+    store.remove(RecordKind::CLSignature, UInt256([2u8; 32])).unwrap();
+    let len_before = std::fs::metadata(&path).unwrap().len();
+    store.compact().unwrap();
+    let len_after = std::fs::metadata(&path).unwrap().len();
+    assert!(len_after < len_before);
+    assert_eq!(store.cl_signature(UInt256([1u8; 32])).unwrap(), Some(UInt768([9u8; 96])));
+    assert_eq!(store.cl_signature(UInt256([2u8; 32])).unwrap(), None);
+    drop(store);
+    let store = FileStore::open(&path).unwrap();
+    assert_eq!(store.cl_signature(UInt256([1u8; 32])).unwrap(), Some(UInt768([9u8; 96])));
+    assert!(!store.contains(RecordKind::CLSignature, UInt256([2u8; 32])));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_processor_resumes_from_file_store() {
+    let path = store_path("resume");
+    let block_hash = {
+        let provider = PersistentProvider::new(
+            InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() },
+            FileStore::open(&path).unwrap());
+        let mut processor = MasternodeProcessor::new(provider);
+        processor.chain_type = ChainType::MainNet;
+        let list = process_list("MNL_0_1090944.dat", &processor);
+        assert!(processor.save_masternode_list(list.block_hash, &list));
+        list.block_hash
+    };
+    // Restarted client takes the base list from the store instead of re-downloading diffs from genesis
+    let provider = PersistentProvider::new(
+        InMemoryProvider { blocks: init_mainnet_store(), ..Default::default() },
+        FileStore::open(&path).unwrap());
+    let latest = provider.store.latest_masternode_list().unwrap().expect("Latest list must be stored");
+    assert_eq!(latest.block_hash, block_hash);
+    let mut processor = MasternodeProcessor::new(provider);
+    processor.chain_type = ChainType::MainNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    let bytes = message_from_file("MNL_1090944_1091520.dat");
+    let list_diff = processor.read_list_diff_from_message(&bytes, &mut 0, 70221).unwrap();
+    assert_eq!(list_diff.base_block_hash, block_hash);
+    let result = processor.get_list_diff_result_internal_with_base_lookup(list_diff, LLMQVerificationContext::None, cache);
+    assert!(result.has_valid_mn_list_root, "Base list must be restored from the store");
+    let _ = std::fs::remove_file(&path);
+}
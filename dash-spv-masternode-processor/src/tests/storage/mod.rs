@@ -0,0 +1,2 @@
+pub mod file_store;
+pub mod cache_snapshot;
@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+use crate::chain::common::ChainType;
+use crate::common::SignatureValidationStatus;
+use crate::crypto::byte_util::BytesDecodable;
+use crate::crypto::{UInt256, UInt768};
+use crate::lib_tests::tests::message_from_file;
+use crate::models::{CreditPool, MasternodeList, MNListDiff};
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::tx::{AssetLockTransaction, AssetUnlockTransaction, Transaction, TransactionInput, TransactionOutput, TransactionType};
+
+fn output(amount: u64) -> TransactionOutput {
+    TransactionOutput {
+        amount,
+        script: Some(vec![0x76, 0xa9, 0x14, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 0x88, 0xac]),
+        address: None,
+    }
+}
+
+fn asset_lock(amount: u64) -> AssetLockTransaction {
+    let input = TransactionInput {
+        input_hash: UInt256([7u8; 32]),
+        index: 0,
+        script: None,
+        signature: Some(vec![]),
+        sequence: u32::MAX,
+    };
+    let burn = TransactionOutput { amount, script: Some(vec![0x6a, 0]), address: None };
+    let base = Transaction::new(TransactionType::AssetLock, vec![input], vec![burn], 0);
+    AssetLockTransaction::new(base, vec![output(amount / 2), output(amount - amount / 2)])
+}
+
+fn asset_unlock(index: u64, amount: u64) -> AssetUnlockTransaction {
+    let base = Transaction::new(TransactionType::AssetUnlock, vec![], vec![output(amount)], 0);
+    AssetUnlockTransaction::new(base, index, 1000, 900000, UInt256([2u8; 32]), UInt768([3u8; 96]))
+}
+
+#[test]
+pub fn test_asset_lock_serialization() {
+    let tx = asset_lock(100_000_000);
+    assert_eq!(tx.locked_amount(), 100_000_000);
+    let bytes = tx.to_data();
+    let parsed = AssetLockTransaction::from_bytes(&bytes, &mut 0).unwrap();
+    assert_eq!(parsed.to_data(), bytes);
+    assert_eq!(parsed.base.tx_type, TransactionType::AssetLock);
+    assert_eq!(parsed.base.tx_hash, Some(UInt256::sha256d(&bytes)));
+    assert_eq!(parsed.credit_outputs.len(), 2);
+    assert_eq!(parsed.locked_amount(), 100_000_000);
+}
+
+#[test]
+pub fn test_asset_unlock_serialization() {
+    let tx = asset_unlock(101, 50_000);
+    assert_eq!(tx.unlocked_amount(), 51_000);
+    let bytes = tx.to_data();
+    let parsed = AssetUnlockTransaction::from_bytes(&bytes, &mut 0).unwrap();
+    assert!(parsed.base.inputs.is_empty(), "Asset unlock has no inputs");
+    assert_eq!(parsed.to_data(), bytes);
+    assert_eq!(parsed.index, 101);
+    assert_eq!(parsed.fee, 1000);
+    assert_eq!(parsed.requested_height, 900000);
+    assert_eq!(parsed.quorum_hash, UInt256([2u8; 32]));
+    assert_eq!(parsed.quorum_signature, UInt768([3u8; 96]));
+    // quorum signs the transaction without the signature
+    let mut unsigned = parsed.clone();
+    unsigned.quorum_signature = UInt768::MIN;
+    assert_eq!(parsed.message_hash(), UInt256::sha256d(unsigned.to_data()));
+    assert_ne!(parsed.message_hash(), parsed.base.tx_hash.unwrap());
+    assert_ne!(parsed.request_id(), asset_unlock(102, 50_000).request_id());
+    assert!(!parsed.is_expired_at(900048));
+    assert!(parsed.is_expired_at(900049));
+}
+
+#[test]
+pub fn test_asset_unlock_verification_requires_platform_quorum() {
+    let tx = asset_unlock(1, 50_000);
+    let masternode_list = MasternodeList::new(BTreeMap::new(), BTreeMap::new(), UInt256::MIN, 900000, true);
+    assert_eq!(tx.verify_with_masternode_list(&masternode_list, ChainType::TestNet), SignatureValidationStatus::NoQuorum);
+    let processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    assert_eq!(processor.verify_asset_unlock(&tx, 900000, &MasternodeProcessorCache::default()), SignatureValidationStatus::NoMasternodeList);
+}
+
+#[test]
+pub fn test_credit_pool_balance_changes() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNT530000.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_530000_905465__70230.dat"), true, 70230, cache).unwrap();
+    let block_height = result.masternode_list.known_height;
+    let balance = cache.credit_pool.balance_at(block_height).expect("Core v20 coinbase reports the credit pool balance");
+
+    let message = message_from_file("testnet/MNL_530000_905465__70230.dat");
+    let mut coinbase = MNListDiff::new(&message, &mut 0, |_| block_height, 70230).unwrap().coinbase_transaction;
+    assert_eq!(coinbase.credit_pool_balance, Some(balance));
+    let mut pool = CreditPool::default();
+    assert!(pool.record_coinbase(&coinbase).is_none(), "There is no base balance yet");
+    coinbase.height += 1;
+    coinbase.credit_pool_balance = Some(balance + 5);
+    let change = pool.record_coinbase(&coinbase).unwrap();
+    assert_eq!(change.base_block_height, block_height);
+    assert_eq!(change.diff(), 5);
+    assert_eq!(pool.balance_change(block_height, block_height + 1), Some(change));
+    let expected = CreditPool::expected_balance(balance, &[asset_lock(10)], &[asset_unlock(1, 4)], 0);
+    assert_eq!(expected, balance + 10 - 1004);
+}
@@ -1 +1,8 @@
-pub mod coinbase;
\ No newline at end of file
+pub mod asset_lock;
+pub mod coinbase;
+pub mod provider;
+pub mod quorum_commitment;
+pub mod script_interpreter;
+pub mod signing;
+pub mod test_utils;
+pub mod transaction_builder;
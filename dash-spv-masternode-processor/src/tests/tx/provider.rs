@@ -0,0 +1,192 @@
+use crate::chain::common::ChainType;
+use crate::common::{MasternodeType, SocketAddress};
+use crate::crypto::byte_util::{BytesDecodable, Reversable};
+use crate::crypto::{UInt128, UInt160, UInt256, UInt384};
+use crate::keys::{BLSKey, ECDSAKey};
+use crate::models::OperatorPublicKey;
+use crate::tx::{ProviderRegistrationTransaction, ProviderRevocationReason, ProviderUpdateRegistrarTransaction, ProviderUpdateRevocationTransaction, ProviderUpdateServiceTransaction, Transaction, TransactionInput, TransactionOutPoint, TransactionOutput, TransactionType};
+
+fn base_transaction(tx_type: TransactionType) -> Transaction {
+    let input = TransactionInput {
+        input_hash: UInt256([7u8; 32]),
+        index: 1,
+        script: None,
+        signature: Some(vec![]),
+        sequence: u32::MAX,
+    };
+    let output = TransactionOutput {
+        amount: 99_990_000,
+        script: Some(vec![0x76, 0xa9, 0x14, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 0x88, 0xac]),
+        address: None,
+    };
+    Transaction::new(tx_type, vec![input], vec![output], 0)
+}
+
+fn socket_address() -> SocketAddress {
+    let mut ip_address = [0u8; 16];
+    ip_address[10..].copy_from_slice(&[0xff, 0xff, 35, 163, 144, 45]);
+    SocketAddress { ip_address: UInt128(ip_address), port: 19999 }
+}
+
+fn key(secret: &str) -> ECDSAKey {
+    ECDSAKey::key_with_secret_hex(secret, true).unwrap()
+}
+
+fn pro_reg_tx(operator_key_version: u16) -> ProviderRegistrationTransaction {
+    let collateral_key = key("0a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20212223242526272829");
+    ProviderRegistrationTransaction::new(
+        base_transaction(TransactionType::ProviderRegistration),
+        TransactionOutPoint::new(UInt256([3u8; 32]), 0),
+        socket_address(),
+        key("1a1b1c1d1e1f202122232425262728292a2b2c2d2e2f30313233343536373839").hash160(),
+        OperatorPublicKey { data: UInt384([5u8; 48]), version: operator_key_version },
+        UInt160([6u8; 20]),
+        500,
+        collateral_key.hash160().0.to_vec())
+}
+
+#[test]
+pub fn test_pro_reg_tx_collateral_signature() {
+    let chain_type = ChainType::TestNet;
+    let collateral_key = key("0a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20212223242526272829");
+    let mut tx = pro_reg_tx(2);
+    assert_eq!(tx.inputs_hash, tx.base.inputs_hash());
+    assert!(tx.has_external_collateral());
+    assert!(!tx.verify_payload_signature(&collateral_key.hash160(), chain_type), "Unsigned payload must not be verified");
+    tx.sign_payload_with_collateral_key(&collateral_key, chain_type);
+    assert_eq!(tx.payload_signature.len(), 65);
+    assert!(tx.verify_payload_signature(&collateral_key.hash160(), chain_type));
+    assert!(!tx.verify_payload_signature(&tx.owner_key_hash, chain_type), "Signature of the collateral key is expected");
+    let sign_string = tx.collateral_sign_string(chain_type);
+    assert_eq!(sign_string.split('|').count(), 5);
+    assert_eq!(sign_string.split('|').nth(1), Some("500"));
+    assert!(sign_string.ends_with(&tx.payload_hash().reversed().to_string()));
+    // signature isn't the part of the payload hash
+    let payload_hash = tx.payload_hash();
+    tx.payload_signature = vec![];
+    assert_eq!(tx.payload_hash(), payload_hash);
+}
+
+#[test]
+pub fn test_pro_reg_tx_serialization() {
+    let chain_type = ChainType::TestNet;
+    let collateral_key = key("0a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20212223242526272829");
+    for version in [1, 2] {
+        let mut tx = pro_reg_tx(version);
+        tx.sign_payload_with_collateral_key(&collateral_key, chain_type);
+        assert_eq!(tx.provider_registration_transaction_version, version);
+        let bytes = tx.to_data();
+        let parsed = ProviderRegistrationTransaction::from_bytes(&bytes, &mut 0).unwrap();
+        assert_eq!(parsed.to_data(), bytes);
+        assert_eq!(parsed.base.tx_hash, Some(UInt256::sha256d(&bytes)));
+        assert_eq!(parsed.base.tx_hash, tx.base.tx_hash);
+        assert_eq!(parsed.base.tx_type, TransactionType::ProviderRegistration);
+        assert_eq!(parsed.mn_type, MasternodeType::Regular);
+        assert_eq!(parsed.operator_key, tx.operator_key);
+        assert_eq!(parsed.socket_address, tx.socket_address);
+        assert_eq!(parsed.script_payout, tx.script_payout);
+        assert_eq!(parsed.payload_hash(), tx.payload_hash());
+        assert!(parsed.verify_payload_signature(&collateral_key.hash160(), chain_type));
+    }
+    // masternode type follows the version in the payloads of both versions
+    for version in [1, 2] {
+        assert_eq!(pro_reg_tx(version).payload_data()[..4], [version as u8, 0, 0, 0]);
+    }
+    assert_eq!(pro_reg_tx(2).payload_data().len(), pro_reg_tx(1).payload_data().len());
+}
+
+#[test]
+pub fn test_evo_pro_reg_tx_with_internal_collateral() {
+    // EvoNode requires the basic BLS operator key
+    assert!(pro_reg_tx(1).with_platform_node(UInt160([9u8; 20]), 36656, 1443).is_none());
+    let mut tx = pro_reg_tx(2)
+        .with_platform_node(UInt160([9u8; 20]), 36656, 1443)
+        .unwrap();
+    tx.collateral_outpoint = TransactionOutPoint::new(UInt256::MIN, 0);
+    assert!(!tx.has_external_collateral());
+    assert!(tx.verify_payload_signature(&UInt160::MIN, ChainType::TestNet), "Internal collateral requires no signature");
+    let bytes = tx.to_data();
+    let parsed = ProviderRegistrationTransaction::from_bytes(&bytes, &mut 0).unwrap();
+    assert_eq!(parsed.mn_type, MasternodeType::HighPerformance);
+    assert_eq!(parsed.platform_node_id, UInt160([9u8; 20]));
+    assert_eq!(parsed.platform_p2p_port, 36656);
+    assert_eq!(parsed.platform_http_port, 1443);
+    assert!(parsed.payload_signature.is_empty());
+    assert_eq!(parsed.to_data(), bytes);
+}
+
+#[test]
+pub fn test_pro_up_reg_tx_owner_signature() {
+    let owner_key = key("1a1b1c1d1e1f202122232425262728292a2b2c2d2e2f30313233343536373839");
+    let mut tx = ProviderUpdateRegistrarTransaction::new(
+        base_transaction(TransactionType::ProviderUpdateRegistrar),
+        UInt256([4u8; 32]),
+        OperatorPublicKey { data: UInt384([8u8; 48]), version: 2 },
+        UInt160([6u8; 20]),
+        vec![0x76, 0xa9, 0x14, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 0x88, 0xac]);
+    assert_eq!(tx.provider_update_registrar_transaction_version, 2);
+    tx.sign_payload_with_owner_key(&owner_key);
+    assert!(tx.verify_payload_signature(&owner_key.hash160()));
+    assert!(!tx.verify_payload_signature(&UInt160([6u8; 20])));
+    let bytes = tx.to_data();
+    let parsed = ProviderUpdateRegistrarTransaction::from_bytes(&bytes, &mut 0).unwrap();
+    assert_eq!(parsed.to_data(), bytes);
+    assert_eq!(parsed.operator_key, tx.operator_key);
+    assert!(parsed.verify_payload_signature(&owner_key.hash160()));
+}
+
+#[test]
+pub fn test_pro_up_serv_tx_serialization() {
+    let tx = ProviderUpdateServiceTransaction::new(
+        base_transaction(TransactionType::ProviderUpdateService),
+        2,
+        UInt256([4u8; 32]),
+        socket_address(),
+        vec![])
+        .with_platform_node(UInt160([9u8; 20]), 36656, 1443)
+        .unwrap();
+    let bytes = tx.to_data();
+    let parsed = ProviderUpdateServiceTransaction::from_bytes(&bytes, &mut 0).unwrap();
+    assert_eq!(parsed.to_data(), bytes);
+    assert_eq!(parsed.mn_type, MasternodeType::HighPerformance);
+    assert_eq!(parsed.socket_address, socket_address());
+    assert_eq!(parsed.platform_http_port, 1443);
+    assert_eq!(parsed.payload_hash(), tx.payload_hash());
+    // unknown payload version
+    let mut invalid = bytes.clone();
+    let payload_offset = bytes.len() - tx.payload_data().len();
+    invalid[payload_offset] = 3;
+    assert!(ProviderUpdateServiceTransaction::from_bytes(&invalid, &mut 0).is_none());
+    // legacy payload has no masternode type and can't update EvoNode
+    let legacy = ProviderUpdateServiceTransaction::new(
+        base_transaction(TransactionType::ProviderUpdateService),
+        1,
+        UInt256([4u8; 32]),
+        socket_address(),
+        vec![]);
+    assert_eq!(legacy.payload_data()[2..34], [4u8; 32]);
+    let parsed = ProviderUpdateServiceTransaction::from_bytes(&legacy.to_data(), &mut 0).unwrap();
+    assert_eq!(parsed.mn_type, MasternodeType::Regular);
+    assert_eq!(parsed.to_data(), legacy.to_data());
+    assert!(legacy.with_platform_node(UInt160([9u8; 20]), 36656, 1443).is_none());
+}
+
+#[test]
+pub fn test_pro_up_rev_tx_operator_signature() {
+    for use_legacy in [true, false] {
+        let operator_key = BLSKey::key_with_seed_data(&[1, 2, 3, 4, 5], use_legacy);
+        let operator_public_key = OperatorPublicKey::from(operator_key.clone());
+        let mut tx = ProviderUpdateRevocationTransaction::new(
+            base_transaction(TransactionType::ProviderUpdateRevocation),
+            operator_public_key.version,
+            UInt256([4u8; 32]),
+            ProviderRevocationReason::ChangeOfKeys);
+        tx.sign_payload_with_operator_key(&operator_key);
+        assert!(tx.verify_payload_signature(&operator_public_key));
+        let bytes = tx.to_data();
+        let parsed = ProviderUpdateRevocationTransaction::from_bytes(&bytes, &mut 0).unwrap();
+        assert_eq!(parsed.to_data(), bytes);
+        assert_eq!(parsed.reason, ProviderRevocationReason::ChangeOfKeys);
+        assert!(parsed.verify_payload_signature(&operator_public_key));
+    }
+}
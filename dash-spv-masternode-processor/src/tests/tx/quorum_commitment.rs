@@ -0,0 +1,95 @@
+use crate::chain::common::{ChainType, IHaveChainSettings};
+use crate::crypto::byte_util::BytesDecodable;
+use crate::crypto::{UInt256, UInt384};
+use crate::lib_tests::tests::message_from_file;
+use crate::models::LLMQEntry;
+use crate::processing::{MasternodeProcessor, MasternodeProcessorCache, QuorumCommitmentCheckStatus};
+use crate::tests::block_store::init_testnet_store;
+use crate::tests::listdiff::core_provider::InMemoryProvider;
+use crate::tx::{QuorumCommitmentTransaction, TransactionType};
+
+fn entry_with_public_key(entry: &LLMQEntry, public_key: UInt384, signers_bitset: Vec<u8>, valid_members_bitset: Vec<u8>) -> LLMQEntry {
+    LLMQEntry::new(
+        entry.version,
+        entry.llmq_type,
+        entry.llmq_hash,
+        entry.index,
+        entry.signers_count,
+        entry.valid_members_count,
+        signers_bitset,
+        valid_members_bitset,
+        public_key,
+        entry.verification_vector_hash,
+        entry.threshold_signature,
+        entry.all_commitment_aggregated_signature)
+}
+
+#[test]
+pub fn test_quorum_commitment_cross_check() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNT530000.dat"), true, 70221, cache).unwrap();
+    let result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_530000_852596.dat"), true, 70228, cache).unwrap();
+    let added = result.added_quorums.first().expect("Diff adds quorums").clone();
+
+    let mined = QuorumCommitmentTransaction::new(852000, added.clone());
+    let bytes = mined.to_data();
+    let parsed = QuorumCommitmentTransaction::from_bytes(&bytes, &mut 0).unwrap();
+    assert!(parsed.base.inputs.is_empty() && parsed.base.outputs.is_empty());
+    assert_eq!(parsed.base.tx_type, TransactionType::QuorumCommitment);
+    assert_eq!(parsed.to_data(), bytes);
+    assert_eq!(parsed.base.tx_hash, Some(UInt256::sha256d(&bytes)));
+    assert_eq!(parsed.quorum_commitment_height, 852000);
+    assert_eq!(parsed.quorum_entry.entry_hash, added.entry_hash);
+    assert!(!parsed.is_null());
+
+    let tampered = QuorumCommitmentTransaction::new(852000, entry_with_public_key(&added, UInt384([1u8; 48]), added.signers_bitset.clone(), added.valid_members_bitset.clone()));
+    let mut unknown_entry = added.clone();
+    unknown_entry.llmq_hash = UInt256([1u8; 32]);
+    let unknown = QuorumCommitmentTransaction::new(852000, unknown_entry);
+    let null = QuorumCommitmentTransaction::new(852000, entry_with_public_key(&added, UInt384::MIN, vec![0; added.signers_bitset.len()], vec![0; added.valid_members_bitset.len()]));
+    assert!(null.is_null());
+
+    // Diff over the range of blocks adds the quorums mined in the other blocks as well
+    let checks = result.cross_check_quorum_commitments(&[parsed, tampered, unknown, null], 530000, 852596);
+    assert_eq!(checks.len(), 3, "Null commitment must be skipped");
+    assert_eq!(checks[0].status, QuorumCommitmentCheckStatus::Matched);
+    assert!(checks[0].is_consistent());
+    assert_eq!(checks[1].status, QuorumCommitmentCheckStatus::Mismatch);
+    assert_eq!(checks[1].diff_entry_hash, Some(added.entry_hash));
+    assert_eq!(checks[2].status, QuorumCommitmentCheckStatus::NotInDiff);
+    assert_eq!(checks[2].diff_entry_hash, None);
+    assert!(result.cross_check_quorum_commitments(&[], 530000, 852596).is_empty());
+}
+
+#[test]
+pub fn test_quorum_commitment_cross_check_single_block_diff() {
+    let mut processor = MasternodeProcessor::new(InMemoryProvider { blocks: init_testnet_store(), ..Default::default() });
+    processor.chain_type = ChainType::TestNet;
+    let cache = &mut MasternodeProcessorCache::default();
+    processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNT530000.dat"), true, 70221, cache).unwrap();
+    let base = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_530000_905522__70230.dat"), true, 70230, cache).unwrap();
+    let mut result = processor.mn_list_diff_result_from_message(&message_from_file("testnet/MNL_905522_905523__70230.dat"), true, 70230, cache).unwrap();
+    let base_block_height = processor.lookup_block_height_by_hash(result.base_block_hash);
+    let block_height = processor.lookup_block_height_by_hash(result.block_hash);
+    assert_eq!((base_block_height, block_height), (905522, 905523));
+    assert!(result.is_valid());
+    // Block out of the mining window has only null commitments and the diff adds no quorums
+    assert!(result.added_quorums.is_empty());
+    let quorum = base.added_quorums.iter().find(|entry| entry.llmq_type == ChainType::TestNet.chain_locks_type()).unwrap();
+    let null = QuorumCommitmentTransaction::new(block_height, entry_with_public_key(quorum, UInt384::MIN, vec![0; quorum.signers_bitset.len()], vec![0; quorum.valid_members_bitset.len()]));
+    assert!(null.is_null());
+    assert!(result.cross_check_quorum_commitments(std::slice::from_ref(&null), base_block_height, block_height).is_empty());
+    // Quorum added by the diff over the block must be mined in it
+    result.added_quorums.push(quorum.clone());
+    let checks = result.cross_check_quorum_commitments(&[null], base_block_height, block_height);
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].status, QuorumCommitmentCheckStatus::NotMined);
+    assert_eq!(checks[0].diff_entry_hash, Some(quorum.entry_hash));
+    assert!(checks[0].mined_entry_hash.is_none());
+    let mined = QuorumCommitmentTransaction::new(block_height, quorum.clone());
+    let checks = result.cross_check_quorum_commitments(&[mined], base_block_height, block_height);
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].status, QuorumCommitmentCheckStatus::Matched);
+}
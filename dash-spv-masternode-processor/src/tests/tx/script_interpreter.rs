@@ -0,0 +1,227 @@
+use hashes::hex::FromHex;
+use crate::blockdata::interpreter::{decode_num, encode_num, eval_script, is_unspendable, verify_script};
+use crate::blockdata::opcodes::all::*;
+use crate::blockdata::{BaseSignatureChecker, ScriptError, ScriptVerifyFlags, TransactionSignatureChecker};
+use crate::crypto::UInt256;
+use crate::keys::IKey;
+use crate::tests::tx::test_utils::{key, p2pkh_script, p2sh_script};
+use crate::tx::{SigHashType, Transaction, TransactionInput, TransactionOutput, TransactionType};
+use crate::tx::transaction_signing::{multisig_redeem_script, p2pkh_script_sig, p2sh_multisig_script_sig};
+use crate::util::data_append::DataAppend;
+
+fn push(data: &[u8]) -> Vec<u8> {
+    let mut script = Vec::<u8>::new();
+    data.to_vec().append_script_push_data(&mut script);
+    script
+}
+
+fn push_num(value: i64) -> Vec<u8> {
+    match value {
+        -1 => vec![OP_PUSHNUM_NEG1.into_u8()],
+        0 => vec![OP_PUSHBYTES_0.into_u8()],
+        1..=16 => vec![OP_PUSHNUM_1.into_u8() + value as u8 - 1],
+        _ => push(&encode_num(value)),
+    }
+}
+
+fn tx_spending(script: Vec<u8>, version: u16, lock_time: u32, sequence: u32) -> Transaction {
+    let input = TransactionInput { input_hash: UInt256([1u8; 32]), index: 0, script: Some(script), signature: None, sequence };
+    let output = TransactionOutput { amount: 1000, script: Some(p2pkh_script(&key(99))), address: None };
+    let mut tx = Transaction::new(TransactionType::Classic, vec![input], vec![output], lock_time);
+    tx.version = version;
+    tx
+}
+
+fn eval(script: &[u8], flags: ScriptVerifyFlags) -> Result<Vec<Vec<u8>>, ScriptError> {
+    let mut stack = Vec::new();
+    eval_script(&mut stack, script, flags, &BaseSignatureChecker).map(|()| stack)
+}
+
+#[test]
+pub fn test_script_numbers() {
+    for value in [0, 1, -1, 127, 128, -128, 255, 256, -32768, i32::MAX as i64, -(i32::MAX as i64)] {
+        let encoded = encode_num(value);
+        assert_eq!(decode_num(&encoded, true, 4), Ok(value), "{}", value);
+    }
+    assert_eq!(encode_num(128), vec![0x80, 0x00]);
+    assert_eq!(encode_num(-1), vec![0x81]);
+    assert_eq!(decode_num(&[0x01, 0x00], false, 4), Ok(1));
+    assert_eq!(decode_num(&[0x01, 0x00], true, 4), Err(ScriptError::UnknownError), "Non-minimal encoding");
+    assert_eq!(decode_num(&[1, 2, 3, 4, 5], false, 4), Err(ScriptError::UnknownError), "Overflow");
+}
+
+#[test]
+pub fn test_script_evaluation() {
+    let flags = ScriptVerifyFlags::STANDARD;
+    // 2 3 ADD 5 NUMEQUAL
+    let script = [OP_PUSHNUM_2.into_u8(), OP_PUSHNUM_3.into_u8(), OP_ADD.into_u8(), OP_PUSHNUM_5.into_u8(), OP_NUMEQUAL.into_u8()];
+    assert_eq!(eval(&script, flags), Ok(vec![vec![1]]));
+    // 0 IF 2 ELSE 3 ENDIF
+    let script = [OP_PUSHBYTES_0.into_u8(), OP_IF.into_u8(), OP_PUSHNUM_2.into_u8(), OP_ELSE.into_u8(), OP_PUSHNUM_3.into_u8(), OP_ENDIF.into_u8()];
+    assert_eq!(eval(&script, flags), Ok(vec![vec![3]]));
+    assert_eq!(eval(&script[..4], flags), Err(ScriptError::UnbalancedConditional));
+    assert_eq!(eval(&[OP_ENDIF.into_u8()], flags), Err(ScriptError::UnbalancedConditional));
+    // Disabled opcodes fail even in the unexecuted branch
+    let script = [OP_PUSHBYTES_0.into_u8(), OP_IF.into_u8(), OP_MUL.into_u8(), OP_ENDIF.into_u8()];
+    assert_eq!(eval(&script, flags), Err(ScriptError::DisabledOpcode));
+    assert_eq!(eval(&[OP_DROP.into_u8()], flags), Err(ScriptError::InvalidStackOperation));
+    assert_eq!(eval(&[OP_FROMALTSTACK.into_u8()], flags), Err(ScriptError::InvalidAltstackOperation));
+    assert_eq!(eval(&[OP_PUSHNUM_1.into_u8(), OP_VERIFY.into_u8(), OP_PUSHBYTES_0.into_u8(), OP_VERIFY.into_u8()], flags), Err(ScriptError::Verify));
+    // Truncated push
+    assert_eq!(eval(&[0x05, 1, 2], flags), Err(ScriptError::BadOpcode));
+    // 1 is pushed with OP_1 when the minimal data is required
+    assert_eq!(eval(&[0x01, 0x01], flags), Err(ScriptError::MinimalData));
+    assert_eq!(eval(&[0x01, 0x01], ScriptVerifyFlags::NONE), Ok(vec![vec![1]]));
+    // Stack operations: 1 2 3 ROT SWAP DUP
+    let script = [OP_PUSHNUM_1.into_u8(), OP_PUSHNUM_2.into_u8(), OP_PUSHNUM_3.into_u8(), OP_ROT.into_u8(), OP_SWAP.into_u8(), OP_DUP.into_u8()];
+    assert_eq!(eval(&script, flags), Ok(vec![vec![2], vec![1], vec![3], vec![3]]));
+    // OP_0 SHA256
+    assert_eq!(
+        eval(&[OP_PUSHBYTES_0.into_u8(), OP_SHA256.into_u8()], flags).unwrap()[0],
+        UInt256::from_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855").unwrap().0.to_vec());
+}
+
+#[test]
+pub fn test_script_dip0020_opcodes() {
+    let flags = ScriptVerifyFlags::STANDARD;
+    // 'ab' 'cd' CAT 1 SPLIT
+    let script = [push(b"ab"), push(b"cd"), vec![OP_CAT.into_u8(), OP_PUSHNUM_1.into_u8(), OP_SUBSTR.into_u8()]].concat();
+    assert_eq!(eval(&script, flags), Ok(vec![b"a".to_vec(), b"bcd".to_vec()]));
+    let mut legacy_flags = flags;
+    legacy_flags.remove(ScriptVerifyFlags::DIP0020_OPCODES);
+    assert_eq!(eval(&script, legacy_flags), Err(ScriptError::DisabledOpcode));
+    let script = [push(b"ab"), vec![OP_PUSHNUM_3.into_u8(), OP_SUBSTR.into_u8()]].concat();
+    assert_eq!(eval(&script, flags), Err(ScriptError::InvalidSplitRange));
+    // -1 4 NUM2BIN => 01 00 00 80, BIN2NUM trims it back
+    let script = [OP_PUSHNUM_NEG1.into_u8(), OP_PUSHNUM_4.into_u8(), OP_LEFT.into_u8()];
+    assert_eq!(eval(&script, flags), Ok(vec![vec![0x01, 0x00, 0x00, 0x80]]));
+    assert_eq!(eval(&[&script[..], &[OP_RIGHT.into_u8()]].concat(), flags), Ok(vec![vec![0x81]]));
+    let script = [push(&[1, 2, 3]), vec![OP_PUSHNUM_2.into_u8(), OP_LEFT.into_u8()]].concat();
+    assert_eq!(eval(&script, flags), Err(ScriptError::ImpossibleEncoding));
+    // 7 2 DIV, 7 2 MOD, 1 0 DIV
+    assert_eq!(eval(&[OP_PUSHNUM_7.into_u8(), OP_PUSHNUM_2.into_u8(), OP_DIV.into_u8()], flags), Ok(vec![vec![3]]));
+    assert_eq!(eval(&[OP_PUSHNUM_7.into_u8(), OP_PUSHNUM_2.into_u8(), OP_MOD.into_u8()], flags), Ok(vec![vec![1]]));
+    assert_eq!(eval(&[OP_PUSHNUM_1.into_u8(), OP_PUSHBYTES_0.into_u8(), OP_DIV.into_u8()], flags), Err(ScriptError::DivByZero));
+    let script = [push(&[0x0f, 0xf0]), push(&[0xff, 0x0f]), vec![OP_AND.into_u8()]].concat();
+    assert_eq!(eval(&script, flags), Ok(vec![vec![0x0f, 0x00]]));
+    let script = [push(&[0xf0]), push(&[0xff, 0x0f]), vec![OP_XOR.into_u8()]].concat();
+    assert_eq!(eval(&script, flags), Err(ScriptError::InvalidOperandSize));
+}
+
+#[test]
+pub fn test_verify_p2pkh_script() {
+    let signer = key(1);
+    let script_pub_key = p2pkh_script(&signer);
+    let mut tx = tx_spending(script_pub_key.clone(), 1, 0, u32::MAX);
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::STANDARD), Err(ScriptError::InvalidStackOperation));
+    tx.sign_p2pkh_input(0, &signer, SigHashType::All).unwrap();
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::STANDARD), Ok(()));
+    assert_eq!(tx.verify_input_script(0, &p2pkh_script(&key(2)), ScriptVerifyFlags::STANDARD), Err(ScriptError::EqualVerify));
+    tx.outputs[0].amount += 1;
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::STANDARD), Err(ScriptError::NullFail));
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::MANDATORY), Err(ScriptError::EvalFalse));
+    // Only pushes are allowed in scriptSig
+    let script_sig = [tx.inputs[0].signature.clone().unwrap(), vec![OP_NOP.into_u8()]].concat();
+    let checker = TransactionSignatureChecker::new(&tx, 0);
+    assert_eq!(verify_script(&script_sig, &script_pub_key, ScriptVerifyFlags::SIGPUSHONLY, &checker), Err(ScriptError::SigPushOnly));
+    // Extra elements are left on the stack
+    let script_sig = [vec![OP_PUSHNUM_1.into_u8()], tx.inputs[0].signature.clone().unwrap()].concat();
+    tx.outputs[0].amount -= 1;
+    let checker = TransactionSignatureChecker::new(&tx, 0);
+    assert_eq!(verify_script(&script_sig, &script_pub_key, ScriptVerifyFlags::MANDATORY, &checker), Ok(()));
+    assert_eq!(verify_script(&script_sig, &script_pub_key, ScriptVerifyFlags::STANDARD, &checker), Err(ScriptError::CleanStack));
+}
+
+#[test]
+pub fn test_verify_undefined_sighash_type() {
+    let signer = key(1);
+    let script_pub_key = p2pkh_script(&signer);
+    let mut tx = tx_spending(script_pub_key.clone(), 1, 0, u32::MAX);
+    // Undefined type signs like SIGHASH_ALL, but it's committed as is
+    let sighash_type = 0x41u32;
+    let hash = tx.signature_hash(0, &script_pub_key, sighash_type);
+    assert_ne!(hash, tx.signature_hash(0, &script_pub_key, SigHashType::All));
+    let mut signature = signer.sign(&hash.0);
+    signature.push(sighash_type as u8);
+    tx.inputs[0].signature = Some(p2pkh_script_sig(&signature, &signer.public_key_data()));
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::MANDATORY), Ok(()));
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::STRICTENC), Err(ScriptError::SigHashType));
+    tx.outputs[0].amount += 1;
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::MANDATORY), Err(ScriptError::EvalFalse));
+}
+
+#[test]
+pub fn test_verify_multisig_scripts() {
+    let keys = (1..=3).map(key).collect::<Vec<_>>();
+    let public_keys = keys.iter().map(|key| key.public_key_data()).collect::<Vec<_>>();
+    let redeem_script = multisig_redeem_script(2, &public_keys);
+    // P2SH
+    let script_pub_key = p2sh_script(&redeem_script);
+    let mut tx = tx_spending(script_pub_key.clone(), 1, 0, u32::MAX);
+    tx.sign_p2sh_multisig_input(0, &redeem_script, &keys[1..], SigHashType::All).unwrap();
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::STANDARD), Ok(()));
+    // Without P2SH the redeem script is only compared with the hash
+    let mut wrong_redeem_script = redeem_script.clone();
+    wrong_redeem_script[0] = OP_PUSHNUM_3.into_u8();
+    let signatures = [&keys[1], &keys[2]].iter()
+        .map(|key| tx.input_signature(0, &redeem_script, key, SigHashType::All).unwrap())
+        .collect::<Vec<_>>();
+    tx.inputs[0].signature = Some(p2sh_multisig_script_sig(&signatures, &wrong_redeem_script));
+    assert_eq!(tx.verify_input_script(0, &p2sh_script(&wrong_redeem_script), ScriptVerifyFlags::NONE), Ok(()));
+    assert_eq!(tx.verify_input_script(0, &p2sh_script(&wrong_redeem_script), ScriptVerifyFlags::MANDATORY), Err(ScriptError::InvalidStackOperation));
+    // Bare multisig
+    let mut tx = tx_spending(redeem_script.clone(), 1, 0, u32::MAX);
+    let signatures = [&keys[0], &keys[2]].iter()
+        .map(|key| tx.input_signature(0, &redeem_script, key, SigHashType::All).unwrap())
+        .collect::<Vec<_>>();
+    let script_sig = [vec![OP_PUSHBYTES_0.into_u8()], push(&signatures[0]), push(&signatures[1])].concat();
+    tx.inputs[0].signature = Some(script_sig.clone());
+    assert_eq!(tx.verify_input_script(0, &redeem_script, ScriptVerifyFlags::STANDARD), Ok(()));
+    // Non-empty dummy
+    tx.inputs[0].signature = Some([&[OP_PUSHNUM_1.into_u8()], &script_sig[1..]].concat());
+    assert_eq!(tx.verify_input_script(0, &redeem_script, ScriptVerifyFlags::MANDATORY), Ok(()));
+    assert_eq!(tx.verify_input_script(0, &redeem_script, ScriptVerifyFlags::STANDARD), Err(ScriptError::SigNullDummy));
+    // Signatures in the wrong order
+    let script_sig = [vec![OP_PUSHBYTES_0.into_u8()], push(&signatures[1]), push(&signatures[0])].concat();
+    tx.inputs[0].signature = Some(script_sig);
+    assert_eq!(tx.verify_input_script(0, &redeem_script, ScriptVerifyFlags::MANDATORY), Err(ScriptError::EvalFalse));
+    assert_eq!(tx.verify_input_script(0, &redeem_script, ScriptVerifyFlags::STANDARD), Err(ScriptError::NullFail));
+}
+
+#[test]
+pub fn test_verify_lock_time_scripts() {
+    let flags = ScriptVerifyFlags::STANDARD;
+    // <lock time> CHECKLOCKTIMEVERIFY DROP 1
+    let cltv = |lock_time: i64| [push_num(lock_time), vec![OP_CLTV.into_u8(), OP_DROP.into_u8(), OP_PUSHNUM_1.into_u8()]].concat();
+    let tx = tx_spending(vec![], 1, 600, 0xfffffffe);
+    assert_eq!(tx.verify_input_script(0, &cltv(500), flags), Ok(()));
+    assert_eq!(tx.verify_input_script(0, &cltv(700), flags), Err(ScriptError::UnsatisfiedLocktime));
+    assert_eq!(tx.verify_input_script(0, &cltv(-1), flags), Err(ScriptError::NegativeLocktime));
+    // Timestamp can't be compared with the block height
+    assert_eq!(tx.verify_input_script(0, &cltv(500_000_001), flags), Err(ScriptError::UnsatisfiedLocktime));
+    let final_tx = tx_spending(vec![], 1, 600, u32::MAX);
+    assert_eq!(final_tx.verify_input_script(0, &cltv(500), flags), Err(ScriptError::UnsatisfiedLocktime));
+    // It's NOP2 before BIP65
+    assert_eq!(final_tx.verify_input_script(0, &cltv(500), ScriptVerifyFlags::MANDATORY), Ok(()));
+    assert_eq!(final_tx.verify_input_script(0, &cltv(500), ScriptVerifyFlags::DISCOURAGE_UPGRADABLE_NOPS), Err(ScriptError::DiscourageUpgradableNops));
+    // <sequence> CHECKSEQUENCEVERIFY DROP 1
+    let csv = |sequence: i64| [push_num(sequence), vec![OP_CSV.into_u8(), OP_DROP.into_u8(), OP_PUSHNUM_1.into_u8()]].concat();
+    let tx = tx_spending(vec![], 2, 0, 10);
+    assert_eq!(tx.verify_input_script(0, &csv(5), flags), Ok(()));
+    assert_eq!(tx.verify_input_script(0, &csv(20), flags), Err(ScriptError::UnsatisfiedLocktime));
+    // Disabled relative lock time is always satisfied
+    assert_eq!(tx.verify_input_script(0, &csv(1 << 31), flags), Ok(()));
+    assert_eq!(tx_spending(vec![], 1, 0, 10).verify_input_script(0, &csv(5), flags), Err(ScriptError::UnsatisfiedLocktime));
+}
+
+#[test]
+pub fn test_verify_op_return_script() {
+    let script_pub_key = [vec![OP_RETURN.into_u8()], push(b"data")].concat();
+    assert!(is_unspendable(&script_pub_key));
+    assert!(!is_unspendable(&p2pkh_script(&key(1))));
+    let tx = tx_spending(script_pub_key.clone(), 1, 0, u32::MAX);
+    assert_eq!(tx.verify_input_script(0, &script_pub_key, ScriptVerifyFlags::STANDARD), Err(ScriptError::OpReturn));
+    // Unexecuted OP_RETURN doesn't fail the script
+    let script = [OP_PUSHBYTES_0.into_u8(), OP_IF.into_u8(), OP_RETURN.into_u8(), OP_ENDIF.into_u8(), OP_PUSHNUM_1.into_u8()];
+    assert_eq!(tx.verify_input_script(0, &script, ScriptVerifyFlags::STANDARD), Ok(()));
+}
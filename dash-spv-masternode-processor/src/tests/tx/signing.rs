@@ -0,0 +1,172 @@
+use crate::chain::params::TESTNET_PARAMS;
+use crate::consensus::Encodable;
+use crate::crypto::byte_util::BytesDecodable;
+use crate::crypto::UInt256;
+use crate::keys::IKey;
+use crate::tests::tx::test_utils::{key, p2pkh_script, p2sh_script};
+use crate::tx::{AssetLockTransaction, SigHashType, Transaction, TransactionBuilder, TransactionInput, TransactionOutPoint, TransactionOutput, TransactionSigningError, TransactionType, Utxo};
+use crate::tx::transaction::SIGHASH_ALL;
+use crate::tx::transaction_signing::{multisig_redeem_script, p2sh_multisig_script_sig, parse_multisig_redeem_script};
+use crate::util::data_append::DataAppend;
+use crate::util::script::ScriptElement;
+
+fn input(byte: u8, script: Vec<u8>) -> TransactionInput {
+    TransactionInput { input_hash: UInt256([byte; 32]), index: byte as u32, script: Some(script), signature: None, sequence: u32::MAX }
+}
+
+fn output(amount: u64) -> TransactionOutput {
+    TransactionOutput { amount, script: Some(p2pkh_script(&key(99))), address: None }
+}
+
+fn classic(inputs: Vec<TransactionInput>, outputs: Vec<TransactionOutput>) -> Transaction {
+    let mut tx = Transaction::new(TransactionType::Classic, inputs, outputs, 0);
+    tx.version = 1;
+    tx
+}
+
+#[test]
+pub fn test_signature_hash_types() {
+    let script = p2pkh_script(&key(1));
+    let tx = classic(vec![input(1, script.clone()), input(2, script.clone())], vec![output(1000), output(2000)]);
+    // SIGHASH_ALL is the same as the legacy subscript serialization
+    assert_eq!(tx.signature_hash(0, &script, SigHashType::All), UInt256::sha256d(tx.to_data_with_subscript_index(0)));
+    let mut other = tx.clone();
+    other.outputs[1].amount = 3000;
+    other.inputs[1].sequence = 0;
+    assert_ne!(tx.signature_hash(0, &script, SigHashType::All), other.signature_hash(0, &script, SigHashType::All));
+    // NONE doesn't commit to outputs, SINGLE commits only to the output with the same index
+    assert_eq!(tx.signature_hash(0, &script, SigHashType::None), other.signature_hash(0, &script, SigHashType::None));
+    assert_eq!(tx.signature_hash(0, &script, SigHashType::Single), other.signature_hash(0, &script, SigHashType::Single));
+    assert_ne!(tx.signature_hash(1, &script, SigHashType::Single), other.signature_hash(1, &script, SigHashType::Single));
+    // ANYONECANPAY doesn't commit to other inputs
+    let mut extended = tx.clone();
+    extended.inputs.push(input(3, script.clone()));
+    assert_eq!(tx.signature_hash(0, &script, SigHashType::AllPlusAnyoneCanPay), extended.signature_hash(0, &script, SigHashType::AllPlusAnyoneCanPay));
+    assert_ne!(tx.signature_hash(0, &script, SigHashType::All), extended.signature_hash(0, &script, SigHashType::All));
+    // SINGLE without the matching output signs the 'one' hash
+    let mut one = [0u8; 32];
+    one[0] = 1;
+    assert_eq!(extended.signature_hash(2, &script, SigHashType::Single), UInt256(one));
+    assert_eq!(SigHashType::try_from(0x83).unwrap(), SigHashType::SinglePlusAnyoneCanPay);
+    assert!(SigHashType::try_from(0x04).is_err());
+}
+
+#[test]
+pub fn test_signature_hash_skips_code_separators() {
+    let script = p2pkh_script(&key(1));
+    let tx = classic(vec![input(1, script.clone())], vec![output(1000)]);
+    // OP_CODESEPARATOR OP_DUP OP_HASH160 <hash> OP_CODESEPARATOR OP_EQUALVERIFY OP_CHECKSIG
+    let mut with_separators = vec![0xab];
+    with_separators.extend_from_slice(&script[..script.len() - 2]);
+    with_separators.push(0xab);
+    with_separators.extend_from_slice(&script[script.len() - 2..]);
+    assert_eq!(tx.signature_hash(0, &with_separators, SigHashType::All), tx.signature_hash(0, &script, SigHashType::All));
+    // Pushed 0xab is the data and it's signed
+    let push = [vec![0x01, 0xab], script.clone()].concat();
+    assert_ne!(tx.signature_hash(0, &push, SigHashType::All), tx.signature_hash(0, &script, SigHashType::All));
+    assert_eq!(tx.signature_hash(0, &[push.clone(), vec![0xab]].concat(), SigHashType::All), tx.signature_hash(0, &push, SigHashType::All));
+}
+
+#[test]
+pub fn test_special_transaction_signature_hash() {
+    let script = p2pkh_script(&key(1));
+    let burn = TransactionOutput { amount: 3000, script: Some(vec![0x6a, 0]), address: None };
+    let base = Transaction::new(TransactionType::AssetLock, vec![input(1, script.clone())], vec![burn], 0);
+    let mut tx = AssetLockTransaction::new(base, vec![output(1000), output(2000)]);
+    // Extra payload goes between the lock time and the sighash type
+    let mut data = tx.base.to_data_with_subscript_index(0);
+    data.truncate(data.len() - 4);
+    tx.payload_data().enc(&mut data);
+    SIGHASH_ALL.enc(&mut data);
+    assert_eq!(tx.base.signature_hash(0, &script, SigHashType::All), UInt256::sha256d(data));
+    // Payload is kept by the base transaction parsed from the special transaction bytes
+    let parsed = Transaction::from_bytes(&tx.to_data(), &mut 0).unwrap();
+    assert_eq!(parsed.extra_payload, Some(tx.payload_data()));
+    assert_eq!(parsed.signature_hash(0, &script, SigHashType::All), tx.base.signature_hash(0, &script, SigHashType::All));
+    tx.base.sign_p2pkh_input(0, &key(1), SigHashType::All).unwrap();
+    assert_eq!(tx.base.verify_input(0, &script), Ok(()));
+    let other = AssetLockTransaction::new(tx.base.clone(), vec![output(2000), output(1000)]);
+    assert_eq!(other.base.verify_input(0, &script), Err(TransactionSigningError::InvalidSignature));
+    // Classic transaction of the special version doesn't commit to the payload
+    let mut classic = tx.base.clone();
+    classic.tx_type = TransactionType::Classic;
+    let mut without_payload = classic.clone();
+    without_payload.extra_payload = None;
+    assert_eq!(classic.signature_hash(0, &script, SigHashType::All), without_payload.signature_hash(0, &script, SigHashType::All));
+}
+
+#[test]
+pub fn test_sign_p2pkh_input() {
+    let (key1, key2) = (key(1), key(2));
+    let utxos = vec![
+        Utxo::new(TransactionOutPoint::new(UInt256([1u8; 32]), 0), 500_000, p2pkh_script(&key1)),
+        Utxo::new(TransactionOutPoint::new(UInt256([2u8; 32]), 1), 700_000, p2pkh_script(&key2)),
+    ];
+    let mut tx = TransactionBuilder::new(TESTNET_PARAMS)
+        .add_utxos(utxos.clone())
+        .add_output_script(p2pkh_script(&key(3)), 1_000_000)
+        .change_script(p2pkh_script(&key1))
+        .build()
+        .unwrap();
+    assert_eq!(tx.inputs.len(), 2);
+    assert_eq!(tx.verify_input(0, &utxos[1].script), Err(TransactionSigningError::MissingSignature(0)));
+    assert_eq!(tx.sign_p2pkh_input(0, &key1, SigHashType::All), Err(TransactionSigningError::KeyMismatch));
+    tx.sign_p2pkh_input(0, &key2, SigHashType::All).unwrap();
+    tx.sign_p2pkh_input(1, &key1, SigHashType::SinglePlusAnyoneCanPay).unwrap();
+    assert_eq!(tx.verify_input(0, &utxos[1].script), Ok(()));
+    assert_eq!(tx.verify_input(1, &utxos[0].script), Ok(()));
+    assert_eq!(tx.verify_input(1, &utxos[1].script), Err(TransactionSigningError::KeyMismatch));
+    // Signed size fits the estimation
+    assert!(tx.to_data().len() as u64 <= TransactionBuilder::new(TESTNET_PARAMS).add_output_script(p2pkh_script(&key(3)), 0).estimated_size(2, true));
+    // Change of the first output invalidates SIGHASH_ALL, but not the SINGLE signature of the second input
+    tx.outputs[0].amount -= 1;
+    assert_eq!(tx.verify_input(0, &utxos[1].script), Err(TransactionSigningError::InvalidSignature));
+    assert_eq!(tx.verify_input(1, &utxos[0].script), Ok(()));
+    assert_eq!(tx.sign_p2pkh_input(2, &key1, SigHashType::All), Err(TransactionSigningError::InputOutOfRange(2)));
+}
+
+#[test]
+pub fn test_sign_p2sh_multisig_input() {
+    let keys = (1..=3).map(key).collect::<Vec<_>>();
+    let public_keys = keys.iter().map(|key| key.public_key_data()).collect::<Vec<_>>();
+    let redeem_script = multisig_redeem_script(2, &public_keys);
+    assert_eq!(parse_multisig_redeem_script(&redeem_script), Some((2, public_keys.clone())));
+    let script = p2sh_script(&redeem_script);
+    let mut tx = classic(vec![input(1, script.clone())], vec![output(1000)]);
+    assert_eq!(
+        tx.sign_p2sh_multisig_input(0, &redeem_script, &keys[2..], SigHashType::All),
+        Err(TransactionSigningError::NotEnoughKeys { required: 2, provided: 1 }));
+    assert_eq!(
+        tx.sign_p2sh_multisig_input(0, &multisig_redeem_script(1, &public_keys), &keys, SigHashType::All),
+        Err(TransactionSigningError::KeyMismatch));
+    // Signatures follow the order of the keys in the redeem script
+    tx.sign_p2sh_multisig_input(0, &redeem_script, &[keys[2].clone(), keys[0].clone()], SigHashType::All).unwrap();
+    assert_eq!(tx.verify_input(0, &script), Ok(()));
+    let script_sig = tx.inputs[0].signature.clone().unwrap();
+    match &script_sig.script_elements()[..] {
+        [ScriptElement::Number(0), ScriptElement::Data(..), ScriptElement::Data(..), ScriptElement::Data(redeem, _)] =>
+            assert_eq!(redeem.to_vec(), redeem_script),
+        elements => panic!("Unexpected scriptSig {:?}", elements),
+    }
+    // Out of order signatures are rejected like by CHECKMULTISIG
+    let signatures = [&keys[2], &keys[0]].iter()
+        .map(|key| tx.input_signature(0, &redeem_script, key, SigHashType::All).unwrap())
+        .collect::<Vec<_>>();
+    tx.inputs[0].signature = Some(p2sh_multisig_script_sig(&signatures, &redeem_script));
+    assert_eq!(tx.verify_input(0, &script), Err(TransactionSigningError::InvalidSignature));
+    tx.inputs[0].signature = Some(p2sh_multisig_script_sig(&signatures[..1], &redeem_script));
+    assert_eq!(tx.verify_input(0, &script), Err(TransactionSigningError::NotEnoughKeys { required: 2, provided: 1 }));
+}
+
+#[test]
+pub fn test_sign_p2sh_multisig_input_with_large_redeem_script() {
+    // 15 keys don't fit into OP_PUSHDATA1
+    let keys = (1..=15).map(key).collect::<Vec<_>>();
+    let public_keys = keys.iter().map(|key| key.public_key_data()).collect::<Vec<_>>();
+    let redeem_script = multisig_redeem_script(1, &public_keys);
+    assert!(redeem_script.len() > u8::MAX as usize);
+    let script = p2sh_script(&redeem_script);
+    let mut tx = classic(vec![input(1, script.clone())], vec![output(1000)]);
+    tx.sign_p2sh_multisig_input(0, &redeem_script, &keys[14..], SigHashType::All).unwrap();
+    assert_eq!(tx.verify_input(0, &script), Ok(()));
+}
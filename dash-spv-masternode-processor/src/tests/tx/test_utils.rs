@@ -0,0 +1,21 @@
+use crate::chain::params::ScriptMap;
+use crate::crypto::{UInt160, UInt256};
+use crate::keys::ECDSAKey;
+use crate::util::address::address;
+use crate::util::data_append::DataAppend;
+
+pub fn key(byte: u8) -> ECDSAKey {
+    ECDSAKey::key_with_secret(&UInt256([byte; 32]), true).unwrap()
+}
+
+pub fn p2pkh_script(key: &ECDSAKey) -> Vec<u8> {
+    let address = address::from_hash160_for_script_map(&key.hash160(), &ScriptMap::TESTNET);
+    Vec::<u8>::script_pub_key_for_address(&address, &ScriptMap::TESTNET)
+}
+
+pub fn p2sh_script(redeem_script: &[u8]) -> Vec<u8> {
+    let mut script = vec![0xa9, 0x14];
+    script.extend_from_slice(&UInt160::hash160(redeem_script).0);
+    script.push(0x87);
+    script
+}
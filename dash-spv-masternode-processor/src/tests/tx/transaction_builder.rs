@@ -0,0 +1,114 @@
+use crate::chain::params::{ScriptMap, TESTNET_PARAMS};
+use crate::crypto::{UInt160, UInt256};
+use crate::tx::{CoinSelection, TransactionBuilder, TransactionBuilderError, TransactionOutPoint, TransactionType, Utxo};
+use crate::util::address::address;
+use crate::util::data_append::DataAppend;
+
+fn testnet_address(byte: u8) -> String {
+    address::from_hash160_for_script_map(&UInt160([byte; 20]), &ScriptMap::TESTNET)
+}
+
+fn utxo(index: u32, amount: u64) -> Utxo {
+    let script = Vec::<u8>::script_pub_key_for_address(&testnet_address(9), &ScriptMap::TESTNET);
+    Utxo::new(TransactionOutPoint::new(UInt256([index as u8; 32]), index), amount, script)
+}
+
+fn builder(utxos: Vec<Utxo>) -> TransactionBuilder {
+    TransactionBuilder::new(TESTNET_PARAMS)
+        .add_utxos(utxos)
+        .add_output_address(&testnet_address(1), 100_000).unwrap()
+        .change_address(&testnet_address(2)).unwrap()
+}
+
+fn fee(tx: &crate::tx::Transaction, utxos: &[Utxo]) -> u64 {
+    let spent = tx.inputs.iter()
+        .map(|input| utxos.iter().find(|utxo| utxo.outpoint.hash == input.input_hash).unwrap().amount)
+        .sum::<u64>();
+    spent - tx.outputs.iter().map(|output| output.amount).sum::<u64>()
+}
+
+#[test]
+pub fn test_transaction_builder_size_estimation() {
+    // outpoint + scriptSig (signature with sighash and compressed public key) + sequence
+    assert_eq!(TransactionBuilder::estimated_input_size(), 32 + 4 + 1 + 110 + 4);
+    let builder = builder(vec![]);
+    // version + type + input count + input + output count + P2PKH output + lock time
+    assert_eq!(builder.estimated_size(1, false), 2 + 2 + 1 + 151 + 1 + 34 + 4);
+    assert_eq!(builder.estimated_size(1, true), builder.estimated_size(1, false) + 34);
+    assert_eq!(builder.estimated_fee(2, false), TESTNET_PARAMS.fee_for_tx_size(builder.estimated_size(2, false)));
+}
+
+#[test]
+pub fn test_transaction_builder_branch_and_bound() {
+    let exact = 100_000 + builder(vec![]).estimated_fee(1, false);
+    let utxos = vec![utxo(1, 1_000_000), utxo(2, exact + 100), utxo(3, 50_000)];
+    let tx = builder(utxos.clone()).build().unwrap();
+    assert_eq!(tx.tx_type, TransactionType::Classic);
+    assert_eq!(tx.version, TESTNET_PARAMS.transaction_version);
+    assert_eq!(tx.inputs.len(), 1);
+    assert_eq!(tx.inputs[0].input_hash, UInt256([2u8; 32]));
+    assert_eq!(tx.outputs.len(), 1, "Selection without change is preferred");
+    assert_eq!(fee(&tx, &utxos), exact + 100 - 100_000);
+    // Inputs are ready for signing: subscript is set, signature is not
+    let input = &tx.inputs[0];
+    assert!(input.signature.is_none());
+    assert_eq!(input.script.as_ref(), Some(&utxos[1].script));
+    assert_ne!(tx.to_data_with_subscript_index(0), tx.to_data());
+}
+
+#[test]
+pub fn test_transaction_builder_largest_first_with_change() {
+    let utxos = vec![utxo(1, 60_000), utxo(2, 1_000_000), utxo(3, 50_000)];
+    let builder = builder(utxos.clone()).coin_selection(CoinSelection::LargestFirst);
+    let tx = builder.build().unwrap();
+    assert_eq!(tx.inputs.len(), 1);
+    assert_eq!(tx.inputs[0].input_hash, UInt256([2u8; 32]));
+    assert_eq!(tx.outputs.len(), 2);
+    let change = &tx.outputs[1];
+    assert_eq!(change.script, builder.change_script);
+    assert_eq!(change.amount, 1_000_000 - 100_000 - builder.estimated_fee(1, true));
+    assert_eq!(fee(&tx, &utxos), builder.estimated_fee(1, true));
+    // Two small outputs are needed to cover the amount
+    let mut builder = builder;
+    builder.utxos = vec![utxo(1, 60_000), utxo(3, 50_000)];
+    let tx = builder.build().unwrap();
+    assert_eq!(tx.inputs.len(), 2);
+    assert_eq!(tx.outputs.len(), 2);
+}
+
+#[test]
+pub fn test_transaction_builder_dust() {
+    let min_output_amount = TESTNET_PARAMS.min_output_amount();
+    let result = TransactionBuilder::new(TESTNET_PARAMS)
+        .add_utxo(utxo(1, 1_000_000))
+        .add_output_address(&testnet_address(1), min_output_amount - 1).unwrap()
+        .build();
+    assert_eq!(result.unwrap_err(), TransactionBuilderError::DustOutput(min_output_amount - 1));
+    // The change below the dust limit goes to the fee
+    let builder = builder(vec![]);
+    let amount = 100_000 + builder.estimated_fee(1, true) + min_output_amount - 1;
+    let utxos = vec![utxo(1, amount)];
+    let tx = builder.add_utxos(utxos.clone()).coin_selection(CoinSelection::LargestFirst).build().unwrap();
+    assert_eq!(tx.outputs.len(), 1);
+    assert!(fee(&tx, &utxos) >= min_output_amount);
+}
+
+#[test]
+pub fn test_transaction_builder_errors() {
+    assert_eq!(TransactionBuilder::new(TESTNET_PARAMS).build().unwrap_err(), TransactionBuilderError::NoOutputs);
+    let mainnet_address = address::from_hash160_for_script_map(&UInt160([1u8; 20]), &ScriptMap::MAINNET);
+    assert_eq!(
+        TransactionBuilder::new(TESTNET_PARAMS).add_output_address(&mainnet_address, 100_000).unwrap_err(),
+        TransactionBuilderError::InvalidAddress(mainnet_address));
+    let utxos = vec![utxo(1, 60_000), utxo(2, 40_000)];
+    match builder(utxos).build().unwrap_err() {
+        TransactionBuilderError::InsufficientFunds { available, required } => {
+            assert_eq!(available, 100_000);
+            assert!(required > 100_000);
+        },
+        err => panic!("Unexpected error {}", err),
+    }
+    let mut builder = builder(vec![utxo(1, 1_000_000)]).coin_selection(CoinSelection::LargestFirst);
+    builder.change_script = None;
+    assert!(matches!(builder.build().unwrap_err(), TransactionBuilderError::NoChangeAddress(_)));
+}
@@ -0,0 +1,86 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::UInt256;
+use crate::tx::{Transaction, TransactionOutput, TransactionType::AssetLock};
+
+pub const ASSET_LOCK_PAYLOAD_VERSION: u8 = 1;
+
+/// Asset lock: moves the funds into the credit pool, credit outputs are topped up on Platform
+#[derive(Debug, Clone)]
+pub struct AssetLockTransaction {
+    pub base: Transaction,
+    pub asset_lock_payload_version: u8,
+    pub credit_outputs: Vec<TransactionOutput>,
+}
+
+impl<'a> TryRead<'a, Endian> for AssetLockTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let asset_lock_payload_version = bytes.read_with::<u8>(offset, endian)?;
+        let count = bytes.read_with::<VarInt>(offset, endian)?.0;
+        let mut credit_outputs = Vec::new();
+        for _i in 0..count {
+            credit_outputs.push(bytes.read_with::<TransactionOutput>(offset, endian)?);
+        }
+        base.tx_type = AssetLock;
+        base.payload_offset = *offset;
+        let mut tx = Self { base, asset_lock_payload_version, credit_outputs };
+        tx.update_tx_hash();
+        Ok((tx, *offset))
+    }
+}
+
+impl AssetLockTransaction {
+    /// Base transaction must have the OP_RETURN output with the sum of the credit outputs
+    pub fn new(base: Transaction, credit_outputs: Vec<TransactionOutput>) -> Self {
+        let mut tx = Self {
+            base: Transaction { tx_type: AssetLock, ..base },
+            asset_lock_payload_version: ASSET_LOCK_PAYLOAD_VERSION,
+            credit_outputs,
+        };
+        tx.update_tx_hash();
+        tx
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.asset_lock_payload_version.enc(&mut buffer);
+        VarInt(self.credit_outputs.len() as u64).enc(&mut buffer);
+        self.credit_outputs.iter().for_each(|output| {
+            output.amount.enc(&mut buffer);
+            output.script.clone().unwrap_or_default().enc(&mut buffer);
+        });
+        buffer
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.to_data_with_subscript_index(u64::MAX)
+    }
+
+    pub fn to_data_with_subscript_index(&self, subscript_index: u64) -> Vec<u8> {
+        let mut buffer = Transaction::data_with_subscript_index_static(
+            subscript_index,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        );
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    fn update_tx_hash(&mut self) {
+        self.base.extra_payload = Some(self.payload_data());
+        self.base.tx_hash = Some(UInt256::sha256d(self.to_data()));
+    }
+
+    /// Amount added to the credit pool
+    pub fn locked_amount(&self) -> u64 {
+        self.credit_outputs.iter().map(|output| output.amount).sum()
+    }
+}
@@ -0,0 +1,144 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use crate::chain::common::{ChainType, IHaveChainSettings, LLMQType};
+use crate::common::SignatureValidationStatus;
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::{UInt256, UInt768};
+use crate::models::MasternodeList;
+use crate::tx::{Transaction, TransactionType::AssetUnlock};
+
+pub const ASSET_UNLOCK_PAYLOAD_VERSION: u8 = 1;
+pub const ASSET_UNLOCK_REQUEST_ID_PREFIX: &str = "plwdtx";
+/// Asset unlock can't be mined after this number of blocks above the requested height
+pub const ASSET_UNLOCK_EXPIRATION_HEIGHT_DIFF: u32 = 48;
+
+/// Asset unlock: withdrawal from the credit pool signed by the Platform quorum
+#[derive(Debug, Clone)]
+pub struct AssetUnlockTransaction {
+    pub base: Transaction,
+    pub asset_unlock_payload_version: u8,
+    /// Unique index of the withdrawal
+    pub index: u64,
+    pub fee: u32,
+    pub requested_height: u32,
+    pub quorum_hash: UInt256,
+    pub quorum_signature: UInt768,
+}
+
+impl<'a> TryRead<'a, Endian> for AssetUnlockTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let asset_unlock_payload_version = bytes.read_with::<u8>(offset, endian)?;
+        let index = bytes.read_with::<u64>(offset, endian)?;
+        let fee = bytes.read_with::<u32>(offset, endian)?;
+        let requested_height = bytes.read_with::<u32>(offset, endian)?;
+        let quorum_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let quorum_signature = bytes.read_with::<UInt768>(offset, endian)?;
+        base.tx_type = AssetUnlock;
+        base.payload_offset = *offset;
+        let mut tx = Self {
+            base,
+            asset_unlock_payload_version,
+            index,
+            fee,
+            requested_height,
+            quorum_hash,
+            quorum_signature,
+        };
+        tx.update_tx_hash();
+        Ok((tx, *offset))
+    }
+}
+
+impl AssetUnlockTransaction {
+    /// Base transaction has no inputs, its outputs are paid from the credit pool
+    pub fn new(base: Transaction, index: u64, fee: u32, requested_height: u32, quorum_hash: UInt256, quorum_signature: UInt768) -> Self {
+        let mut tx = Self {
+            base: Transaction { tx_type: AssetUnlock, ..base },
+            asset_unlock_payload_version: ASSET_UNLOCK_PAYLOAD_VERSION,
+            index,
+            fee,
+            requested_height,
+            quorum_hash,
+            quorum_signature,
+        };
+        tx.update_tx_hash();
+        tx
+    }
+
+    fn payload_data_with_signature(&self, quorum_signature: UInt768) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.asset_unlock_payload_version.enc(&mut buffer);
+        self.index.enc(&mut buffer);
+        self.fee.enc(&mut buffer);
+        self.requested_height.enc(&mut buffer);
+        self.quorum_hash.enc(&mut buffer);
+        quorum_signature.enc(&mut buffer);
+        buffer
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        self.payload_data_with_signature(self.quorum_signature)
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.to_data_with_subscript_index(u64::MAX)
+    }
+
+    pub fn to_data_with_subscript_index(&self, subscript_index: u64) -> Vec<u8> {
+        let mut buffer = self.base_data(subscript_index);
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    fn base_data(&self, subscript_index: u64) -> Vec<u8> {
+        Transaction::data_with_subscript_index_static(
+            subscript_index,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        )
+    }
+
+    fn update_tx_hash(&mut self) {
+        self.base.extra_payload = Some(self.payload_data());
+        self.base.tx_hash = Some(UInt256::sha256d(self.to_data()));
+    }
+
+    pub fn llmq_type(chain_type: ChainType) -> LLMQType {
+        chain_type.platform_type()
+    }
+
+    pub fn request_id(&self) -> UInt256 {
+        let mut buffer: Vec<u8> = Vec::new();
+        ASSET_UNLOCK_REQUEST_ID_PREFIX.to_string().enc(&mut buffer);
+        self.index.enc(&mut buffer);
+        UInt256::sha256d(buffer)
+    }
+
+    /// Hash of the transaction with the empty quorum signature, this is what the quorum signs
+    pub fn message_hash(&self) -> UInt256 {
+        let mut buffer = self.base_data(u64::MAX);
+        self.payload_data_with_signature(UInt768::MIN).enc(&mut buffer);
+        UInt256::sha256d(buffer)
+    }
+
+    /// Amount withdrawn from the credit pool
+    pub fn unlocked_amount(&self) -> u64 {
+        self.base.outputs.iter().map(|output| output.amount).sum::<u64>() + self.fee as u64
+    }
+
+    pub fn is_expired_at(&self, block_height: u32) -> bool {
+        block_height > self.requested_height + ASSET_UNLOCK_EXPIRATION_HEIGHT_DIFF
+    }
+
+    /// Verify the quorum signature against the Platform quorum with this hash in the masternode list
+    pub fn verify_with_masternode_list(&self, masternode_list: &MasternodeList, chain_type: ChainType) -> SignatureValidationStatus {
+        masternode_list.verify_platform_signature(chain_type, self.quorum_hash, self.request_id(), self.message_hash(), self.quorum_signature)
+    }
+}
@@ -2,17 +2,46 @@ use byte::{BytesExt, LE};
 use crate::crypto::byte_util::BytesDecodable;
 use crate::impl_bytes_decodable;
 
+pub mod asset_lock_transaction;
+pub mod asset_unlock_transaction;
 pub mod coinbase_transaction;
+pub mod provider_registration_transaction;
+pub mod provider_update_registrar_transaction;
+pub mod provider_update_revocation_transaction;
+pub mod provider_update_service_transaction;
+pub mod quorum_commitment_transaction;
+pub mod sighash;
 pub mod transaction;
+pub mod transaction_builder;
+pub mod transaction_signing;
 
+pub use self::asset_lock_transaction::AssetLockTransaction;
+pub use self::asset_unlock_transaction::AssetUnlockTransaction;
 pub use self::coinbase_transaction::CoinbaseTransaction;
+pub use self::provider_registration_transaction::ProviderRegistrationTransaction;
+pub use self::provider_update_registrar_transaction::ProviderUpdateRegistrarTransaction;
+pub use self::provider_update_revocation_transaction::{ProviderRevocationReason, ProviderUpdateRevocationTransaction};
+pub use self::provider_update_service_transaction::ProviderUpdateServiceTransaction;
+pub use self::quorum_commitment_transaction::QuorumCommitmentTransaction;
+pub use self::sighash::SigHashType;
 pub use self::transaction::Transaction;
 pub use self::transaction::TransactionInput;
+pub use self::transaction::TransactionOutPoint;
 pub use self::transaction::TransactionOutput;
 pub use self::transaction::TransactionType;
 pub use self::transaction::TX_UNCONFIRMED;
+pub use self::transaction_builder::{CoinSelection, TransactionBuilder, TransactionBuilderError, Utxo};
+pub use self::transaction_signing::TransactionSigningError;
 
 impl_bytes_decodable!(TransactionInput);
+impl_bytes_decodable!(TransactionOutPoint);
 impl_bytes_decodable!(TransactionOutput);
 impl_bytes_decodable!(Transaction);
+impl_bytes_decodable!(AssetLockTransaction);
+impl_bytes_decodable!(AssetUnlockTransaction);
 impl_bytes_decodable!(CoinbaseTransaction);
+impl_bytes_decodable!(ProviderRegistrationTransaction);
+impl_bytes_decodable!(ProviderUpdateRegistrarTransaction);
+impl_bytes_decodable!(ProviderUpdateRevocationTransaction);
+impl_bytes_decodable!(ProviderUpdateServiceTransaction);
+impl_bytes_decodable!(QuorumCommitmentTransaction);
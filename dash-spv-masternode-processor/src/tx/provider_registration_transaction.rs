@@ -0,0 +1,274 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use hashes::hex::ToHex;
+use crate::chain::common::ChainType;
+use crate::common::{MasternodeType, SocketAddress};
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::byte_util::{AsBytes, Reversable, Zeroable};
+use crate::crypto::{UInt160, UInt256, VarBytes};
+use crate::keys::ECDSAKey;
+use crate::models::OperatorPublicKey;
+use crate::tx::{Transaction, TransactionOutPoint, TransactionType::ProviderRegistration};
+use crate::util::address::address;
+use crate::util::data_ops::DASH_MESSAGE_MAGIC;
+
+/// Provider transaction with the operator key in the legacy BLS scheme
+pub const PROVIDER_TX_VERSION_LEGACY_BLS: u16 = 1;
+/// Provider transaction with the operator key in the basic BLS scheme (required by EvoNodes)
+pub const PROVIDER_TX_VERSION_BASIC_BLS: u16 = 2;
+
+pub(crate) fn read_provider_tx_version(bytes: &[u8], offset: &mut usize, endian: Endian) -> byte::Result<u16> {
+    let version = bytes.read_with::<u16>(offset, endian)?;
+    if version == 0 || version > PROVIDER_TX_VERSION_BASIC_BLS {
+        return Err(byte::Error::BadInput { err: "Unsupported provider transaction version" });
+    }
+    Ok(version)
+}
+
+pub(crate) fn read_masternode_type(bytes: &[u8], offset: &mut usize, endian: Endian) -> byte::Result<MasternodeType> {
+    match bytes.read_with::<u16>(offset, endian)? {
+        mn_type @ 0..=1 => Ok(MasternodeType::from(mn_type)),
+        _ => Err(byte::Error::BadInput { err: "Unsupported masternode type" })
+    }
+}
+
+/// Platform node id, p2p and http ports of EvoNode
+pub(crate) fn read_platform_fields(bytes: &[u8], offset: &mut usize, endian: Endian, mn_type: MasternodeType) -> byte::Result<(UInt160, u16, u16)> {
+    if mn_type == MasternodeType::HighPerformance {
+        Ok((bytes.read_with::<UInt160>(offset, endian)?,
+            bytes.read_with::<u16>(offset, endian)?,
+            bytes.read_with::<u16>(offset, endian)?))
+    } else {
+        Ok((UInt160::MIN, 0, 0))
+    }
+}
+
+/// Digest of the message signed with the key of the masternode collateral (or any dash message)
+pub fn dash_message_digest(message: &str) -> UInt256 {
+    let mut writer = Vec::<u8>::new();
+    DASH_MESSAGE_MAGIC.to_string().enc(&mut writer);
+    message.to_string().enc(&mut writer);
+    UInt256::sha256d(&writer)
+}
+
+/// ProRegTx (DIP-3): registers the masternode
+#[derive(Debug, Clone)]
+pub struct ProviderRegistrationTransaction {
+    pub base: Transaction,
+    pub provider_registration_transaction_version: u16,
+    pub mn_type: MasternodeType,
+    pub provider_mode: u16,
+    /// Null hash means the collateral is the output (with this index) of this transaction
+    pub collateral_outpoint: TransactionOutPoint,
+    pub socket_address: SocketAddress,
+    pub owner_key_hash: UInt160,
+    pub operator_key: OperatorPublicKey,
+    pub voting_key_hash: UInt160,
+    /// Operator share of the masternode reward in 1/100 of percent
+    pub operator_reward: u16,
+    pub script_payout: Vec<u8>,
+    pub inputs_hash: UInt256,
+    pub platform_node_id: UInt160,
+    pub platform_p2p_port: u16,
+    pub platform_http_port: u16,
+    /// Message signature of the collateral key (empty if the collateral is in this transaction)
+    pub payload_signature: Vec<u8>,
+}
+
+impl<'a> TryRead<'a, Endian> for ProviderRegistrationTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let provider_registration_transaction_version = read_provider_tx_version(bytes, offset, endian)?;
+        let mn_type = read_masternode_type(bytes, offset, endian)?;
+        let provider_mode = bytes.read_with::<u16>(offset, endian)?;
+        let collateral_outpoint = bytes.read_with::<TransactionOutPoint>(offset, endian)?;
+        let socket_address = bytes.read_with::<SocketAddress>(offset, ())?;
+        let owner_key_hash = bytes.read_with::<UInt160>(offset, endian)?;
+        let operator_key = bytes.read_with::<OperatorPublicKey>(offset, (provider_registration_transaction_version, 0))?;
+        let voting_key_hash = bytes.read_with::<UInt160>(offset, endian)?;
+        let operator_reward = bytes.read_with::<u16>(offset, endian)?;
+        let script_payout = bytes.read_with::<VarBytes>(offset, endian)?.1.to_vec();
+        let inputs_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let (platform_node_id, platform_p2p_port, platform_http_port) = read_platform_fields(bytes, offset, endian, mn_type)?;
+        let payload_signature = bytes.read_with::<VarBytes>(offset, endian)?.1.to_vec();
+        base.tx_type = ProviderRegistration;
+        base.payload_offset = *offset;
+        let mut tx = Self {
+            base,
+            provider_registration_transaction_version,
+            mn_type,
+            provider_mode,
+            collateral_outpoint,
+            socket_address,
+            owner_key_hash,
+            operator_key,
+            voting_key_hash,
+            operator_reward,
+            script_payout,
+            inputs_hash,
+            platform_node_id,
+            platform_p2p_port,
+            platform_http_port,
+            payload_signature,
+        };
+        tx.update_tx_hash();
+        Ok((tx, *offset))
+    }
+}
+
+impl ProviderRegistrationTransaction {
+    /// Registration of the regular masternode, payload version follows the BLS scheme of the operator key.
+    /// Inputs hash is calculated from the inputs of the base transaction
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        base: Transaction,
+        collateral_outpoint: TransactionOutPoint,
+        socket_address: SocketAddress,
+        owner_key_hash: UInt160,
+        operator_key: OperatorPublicKey,
+        voting_key_hash: UInt160,
+        operator_reward: u16,
+        script_payout: Vec<u8>,
+    ) -> Self {
+        let provider_registration_transaction_version = if operator_key.is_legacy() {
+            PROVIDER_TX_VERSION_LEGACY_BLS
+        } else {
+            PROVIDER_TX_VERSION_BASIC_BLS
+        };
+        let mut tx = Self {
+            base: Transaction { tx_type: ProviderRegistration, ..base },
+            provider_registration_transaction_version,
+            mn_type: MasternodeType::Regular,
+            provider_mode: 0,
+            collateral_outpoint,
+            socket_address,
+            owner_key_hash,
+            operator_key,
+            voting_key_hash,
+            operator_reward,
+            script_payout,
+            inputs_hash: UInt256::MIN,
+            platform_node_id: UInt160::MIN,
+            platform_p2p_port: 0,
+            platform_http_port: 0,
+            payload_signature: vec![],
+        };
+        tx.update_inputs_hash();
+        tx
+    }
+
+    /// Turns the registration into EvoNode one, `None` unless the payload is of the basic BLS version
+    pub fn with_platform_node(mut self, platform_node_id: UInt160, platform_p2p_port: u16, platform_http_port: u16) -> Option<Self> {
+        if self.provider_registration_transaction_version < PROVIDER_TX_VERSION_BASIC_BLS {
+            return None;
+        }
+        self.mn_type = MasternodeType::HighPerformance;
+        self.platform_node_id = platform_node_id;
+        self.platform_p2p_port = platform_p2p_port;
+        self.platform_http_port = platform_http_port;
+        self.update_tx_hash();
+        Some(self)
+    }
+
+    fn payload_data_with_signature(&self, with_signature: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.provider_registration_transaction_version.enc(&mut buffer);
+        u16::from(self.mn_type).enc(&mut buffer);
+        self.provider_mode.enc(&mut buffer);
+        self.collateral_outpoint.enc(&mut buffer);
+        self.socket_address.enc(&mut buffer);
+        self.owner_key_hash.enc(&mut buffer);
+        self.operator_key.enc(&mut buffer);
+        self.voting_key_hash.enc(&mut buffer);
+        self.operator_reward.enc(&mut buffer);
+        self.script_payout.enc(&mut buffer);
+        self.inputs_hash.enc(&mut buffer);
+        if self.mn_type == MasternodeType::HighPerformance {
+            self.platform_node_id.enc(&mut buffer);
+            self.platform_p2p_port.enc(&mut buffer);
+            self.platform_http_port.enc(&mut buffer);
+        }
+        if with_signature {
+            self.payload_signature.enc(&mut buffer);
+        }
+        buffer
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        self.payload_data_with_signature(true)
+    }
+
+    /// Hash of the payload without the signature
+    pub fn payload_hash(&self) -> UInt256 {
+        UInt256::sha256d(self.payload_data_with_signature(false))
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.to_data_with_subscript_index(u64::MAX)
+    }
+
+    pub fn to_data_with_subscript_index(&self, subscript_index: u64) -> Vec<u8> {
+        let mut buffer = Transaction::data_with_subscript_index_static(
+            subscript_index,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        );
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    pub fn update_inputs_hash(&mut self) {
+        self.inputs_hash = self.base.inputs_hash();
+        self.update_tx_hash();
+    }
+
+    fn update_tx_hash(&mut self) {
+        self.base.extra_payload = Some(self.payload_data());
+        self.base.tx_hash = Some(UInt256::sha256d(self.to_data()));
+    }
+
+    pub fn has_external_collateral(&self) -> bool {
+        !self.collateral_outpoint.hash.is_zero()
+    }
+
+    /// Message to be signed with the key of the external collateral:
+    /// "payout address|operator reward|owner address|voting address|payload hash"
+    pub fn collateral_sign_string(&self, chain_type: ChainType) -> String {
+        Self::collateral_sign_string_for(&self.script_payout, self.operator_reward, &self.owner_key_hash, &self.voting_key_hash, self.payload_hash(), chain_type)
+    }
+
+    pub fn collateral_sign_string_for(script_payout: &Vec<u8>, operator_reward: u16, owner_key_hash: &UInt160, voting_key_hash: &UInt160, payload_hash: UInt256, chain_type: ChainType) -> String {
+        let script_map = chain_type.script_map();
+        let payout = address::with_script_pub_key(script_payout, &script_map)
+            .unwrap_or_else(|| script_payout.to_hex());
+        let owner_address = address::from_hash160_for_script_map(owner_key_hash, &script_map);
+        let voting_address = address::from_hash160_for_script_map(voting_key_hash, &script_map);
+        format!("{}|{}|{}|{}|{}", payout, operator_reward, owner_address, voting_address, payload_hash.reversed())
+    }
+
+    pub fn collateral_digest(&self, chain_type: ChainType) -> UInt256 {
+        dash_message_digest(&self.collateral_sign_string(chain_type))
+    }
+
+    /// Signs the payload with the key of the external collateral
+    pub fn sign_payload_with_collateral_key(&mut self, collateral_key: &ECDSAKey, chain_type: ChainType) {
+        self.payload_signature = collateral_key.compact_sign(self.collateral_digest(chain_type)).to_vec();
+        self.update_tx_hash();
+    }
+
+    /// Internal collateral requires no signature,
+    /// otherwise the signature must be made by the key of the collateral address
+    pub fn verify_payload_signature(&self, collateral_key_hash: &UInt160, chain_type: ChainType) -> bool {
+        if !self.has_external_collateral() {
+            return self.payload_signature.is_empty();
+        }
+        ECDSAKey::key_with_compact_sig(&self.payload_signature, self.collateral_digest(chain_type))
+            .is_some_and(|key| key.hash160().as_bytes().eq(collateral_key_hash.as_bytes()))
+    }
+}
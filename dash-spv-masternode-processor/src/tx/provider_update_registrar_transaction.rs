@@ -0,0 +1,148 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::byte_util::AsBytes;
+use crate::crypto::{UInt160, UInt256, VarBytes};
+use crate::keys::ECDSAKey;
+use crate::models::OperatorPublicKey;
+use crate::tx::{Transaction, TransactionType::ProviderUpdateRegistrar};
+use crate::tx::provider_registration_transaction::{PROVIDER_TX_VERSION_BASIC_BLS, PROVIDER_TX_VERSION_LEGACY_BLS, read_provider_tx_version};
+
+/// ProUpRegTx (DIP-3): updates the operator, voting key and payout, signed by the owner
+#[derive(Debug, Clone)]
+pub struct ProviderUpdateRegistrarTransaction {
+    pub base: Transaction,
+    pub provider_update_registrar_transaction_version: u16,
+    pub provider_registration_transaction_hash: UInt256,
+    pub provider_mode: u16,
+    pub operator_key: OperatorPublicKey,
+    pub voting_key_hash: UInt160,
+    pub script_payout: Vec<u8>,
+    pub inputs_hash: UInt256,
+    /// Compact signature of the owner key
+    pub payload_signature: Vec<u8>,
+}
+
+impl<'a> TryRead<'a, Endian> for ProviderUpdateRegistrarTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let provider_update_registrar_transaction_version = read_provider_tx_version(bytes, offset, endian)?;
+        let provider_registration_transaction_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let provider_mode = bytes.read_with::<u16>(offset, endian)?;
+        let operator_key = bytes.read_with::<OperatorPublicKey>(offset, (provider_update_registrar_transaction_version, 0))?;
+        let voting_key_hash = bytes.read_with::<UInt160>(offset, endian)?;
+        let script_payout = bytes.read_with::<VarBytes>(offset, endian)?.1.to_vec();
+        let inputs_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let payload_signature = bytes.read_with::<VarBytes>(offset, endian)?.1.to_vec();
+        base.tx_type = ProviderUpdateRegistrar;
+        base.payload_offset = *offset;
+        let mut tx = Self {
+            base,
+            provider_update_registrar_transaction_version,
+            provider_registration_transaction_hash,
+            provider_mode,
+            operator_key,
+            voting_key_hash,
+            script_payout,
+            inputs_hash,
+            payload_signature,
+        };
+        tx.update_tx_hash();
+        Ok((tx, *offset))
+    }
+}
+
+impl ProviderUpdateRegistrarTransaction {
+    /// Payload version follows the BLS scheme of the operator key
+    pub fn new(
+        base: Transaction,
+        provider_registration_transaction_hash: UInt256,
+        operator_key: OperatorPublicKey,
+        voting_key_hash: UInt160,
+        script_payout: Vec<u8>,
+    ) -> Self {
+        let provider_update_registrar_transaction_version = if operator_key.is_legacy() {
+            PROVIDER_TX_VERSION_LEGACY_BLS
+        } else {
+            PROVIDER_TX_VERSION_BASIC_BLS
+        };
+        let mut tx = Self {
+            base: Transaction { tx_type: ProviderUpdateRegistrar, ..base },
+            provider_update_registrar_transaction_version,
+            provider_registration_transaction_hash,
+            provider_mode: 0,
+            operator_key,
+            voting_key_hash,
+            script_payout,
+            inputs_hash: UInt256::MIN,
+            payload_signature: vec![],
+        };
+        tx.update_inputs_hash();
+        tx
+    }
+
+    fn payload_data_with_signature(&self, with_signature: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.provider_update_registrar_transaction_version.enc(&mut buffer);
+        self.provider_registration_transaction_hash.enc(&mut buffer);
+        self.provider_mode.enc(&mut buffer);
+        self.operator_key.enc(&mut buffer);
+        self.voting_key_hash.enc(&mut buffer);
+        self.script_payout.enc(&mut buffer);
+        self.inputs_hash.enc(&mut buffer);
+        if with_signature {
+            self.payload_signature.enc(&mut buffer);
+        }
+        buffer
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        self.payload_data_with_signature(true)
+    }
+
+    /// Hash of the payload without the signature
+    pub fn payload_hash(&self) -> UInt256 {
+        UInt256::sha256d(self.payload_data_with_signature(false))
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.to_data_with_subscript_index(u64::MAX)
+    }
+
+    pub fn to_data_with_subscript_index(&self, subscript_index: u64) -> Vec<u8> {
+        let mut buffer = Transaction::data_with_subscript_index_static(
+            subscript_index,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        );
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    pub fn update_inputs_hash(&mut self) {
+        self.inputs_hash = self.base.inputs_hash();
+        self.update_tx_hash();
+    }
+
+    fn update_tx_hash(&mut self) {
+        self.base.extra_payload = Some(self.payload_data());
+        self.base.tx_hash = Some(UInt256::sha256d(self.to_data()));
+    }
+
+    pub fn sign_payload_with_owner_key(&mut self, owner_key: &ECDSAKey) {
+        self.payload_signature = owner_key.compact_sign(self.payload_hash()).to_vec();
+        self.update_tx_hash();
+    }
+
+    /// Owner key hash is taken from the ProRegTx of the masternode
+    pub fn verify_payload_signature(&self, owner_key_hash: &UInt160) -> bool {
+        ECDSAKey::key_with_compact_sig(&self.payload_signature, self.payload_hash())
+            .is_some_and(|key| key.hash160().as_bytes().eq(owner_key_hash.as_bytes()))
+    }
+}
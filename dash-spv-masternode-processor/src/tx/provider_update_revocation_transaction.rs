@@ -0,0 +1,154 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::{UInt256, UInt768};
+use crate::keys::BLSKey;
+use crate::models::OperatorPublicKey;
+use crate::tx::{Transaction, TransactionType::ProviderUpdateRevocation};
+use crate::tx::provider_registration_transaction::read_provider_tx_version;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Ord)]
+pub enum ProviderRevocationReason {
+    NotSpecified = 0,
+    TerminationOfService = 1,
+    CompromisedKeys = 2,
+    ChangeOfKeys = 3,
+}
+
+impl From<ProviderRevocationReason> for u16 {
+    fn from(value: ProviderRevocationReason) -> Self {
+        value as u16
+    }
+}
+
+impl TryFrom<u16> for ProviderRevocationReason {
+    type Error = byte::Error;
+
+    fn try_from(orig: u16) -> Result<Self, Self::Error> {
+        match orig {
+            0 => Ok(Self::NotSpecified),
+            1 => Ok(Self::TerminationOfService),
+            2 => Ok(Self::CompromisedKeys),
+            3 => Ok(Self::ChangeOfKeys),
+            _ => Err(byte::Error::BadInput { err: "Unsupported revocation reason" })
+        }
+    }
+}
+
+/// ProUpRevTx (DIP-3): revokes the operator key, signed by the operator
+#[derive(Debug, Clone)]
+pub struct ProviderUpdateRevocationTransaction {
+    pub base: Transaction,
+    pub provider_update_revocation_transaction_version: u16,
+    pub provider_registration_transaction_hash: UInt256,
+    pub reason: ProviderRevocationReason,
+    pub inputs_hash: UInt256,
+    /// BLS signature of the operator (legacy or basic scheme by the payload version)
+    pub payload_signature: UInt768,
+}
+
+impl<'a> TryRead<'a, Endian> for ProviderUpdateRevocationTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let provider_update_revocation_transaction_version = read_provider_tx_version(bytes, offset, endian)?;
+        let provider_registration_transaction_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let reason = ProviderRevocationReason::try_from(bytes.read_with::<u16>(offset, endian)?)?;
+        let inputs_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let payload_signature = bytes.read_with::<UInt768>(offset, endian)?;
+        base.tx_type = ProviderUpdateRevocation;
+        base.payload_offset = *offset;
+        let mut tx = Self {
+            base,
+            provider_update_revocation_transaction_version,
+            provider_registration_transaction_hash,
+            reason,
+            inputs_hash,
+            payload_signature,
+        };
+        tx.update_tx_hash();
+        Ok((tx, *offset))
+    }
+}
+
+impl ProviderUpdateRevocationTransaction {
+    /// Payload version must match the BLS scheme of the operator key (1 for legacy, 2 for basic)
+    pub fn new(
+        base: Transaction,
+        provider_update_revocation_transaction_version: u16,
+        provider_registration_transaction_hash: UInt256,
+        reason: ProviderRevocationReason,
+    ) -> Self {
+        let mut tx = Self {
+            base: Transaction { tx_type: ProviderUpdateRevocation, ..base },
+            provider_update_revocation_transaction_version,
+            provider_registration_transaction_hash,
+            reason,
+            inputs_hash: UInt256::MIN,
+            payload_signature: UInt768::MIN,
+        };
+        tx.update_inputs_hash();
+        tx
+    }
+
+    fn payload_data_with_signature(&self, with_signature: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.provider_update_revocation_transaction_version.enc(&mut buffer);
+        self.provider_registration_transaction_hash.enc(&mut buffer);
+        u16::from(self.reason).enc(&mut buffer);
+        self.inputs_hash.enc(&mut buffer);
+        if with_signature {
+            self.payload_signature.enc(&mut buffer);
+        }
+        buffer
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        self.payload_data_with_signature(true)
+    }
+
+    /// Hash of the payload without the signature
+    pub fn payload_hash(&self) -> UInt256 {
+        UInt256::sha256d(self.payload_data_with_signature(false))
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.to_data_with_subscript_index(u64::MAX)
+    }
+
+    pub fn to_data_with_subscript_index(&self, subscript_index: u64) -> Vec<u8> {
+        let mut buffer = Transaction::data_with_subscript_index_static(
+            subscript_index,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        );
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    pub fn update_inputs_hash(&mut self) {
+        self.inputs_hash = self.base.inputs_hash();
+        self.update_tx_hash();
+    }
+
+    fn update_tx_hash(&mut self) {
+        self.base.extra_payload = Some(self.payload_data());
+        self.base.tx_hash = Some(UInt256::sha256d(self.to_data()));
+    }
+
+    pub fn sign_payload_with_operator_key(&mut self, operator_key: &BLSKey) {
+        self.payload_signature = operator_key.sign_digest(self.payload_hash());
+        self.update_tx_hash();
+    }
+
+    /// Operator key is taken from the masternode list (or the last ProRegTx/ProUpRegTx)
+    pub fn verify_payload_signature(&self, operator_key: &OperatorPublicKey) -> bool {
+        BLSKey::verify_with_public_key(self.payload_hash(), self.payload_signature, operator_key.data, operator_key.is_legacy())
+    }
+}
@@ -0,0 +1,172 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use crate::common::{MasternodeType, SocketAddress};
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::{UInt160, UInt256, UInt768, VarBytes};
+use crate::keys::BLSKey;
+use crate::models::OperatorPublicKey;
+use crate::tx::{Transaction, TransactionType::ProviderUpdateService};
+use crate::tx::provider_registration_transaction::{PROVIDER_TX_VERSION_BASIC_BLS, read_masternode_type, read_platform_fields, read_provider_tx_version};
+
+/// ProUpServTx (DIP-3): updates the service address and operator payout, signed by the operator
+#[derive(Debug, Clone)]
+pub struct ProviderUpdateServiceTransaction {
+    pub base: Transaction,
+    pub provider_update_service_transaction_version: u16,
+    pub mn_type: MasternodeType,
+    pub provider_registration_transaction_hash: UInt256,
+    pub socket_address: SocketAddress,
+    pub script_operator_payout: Vec<u8>,
+    pub inputs_hash: UInt256,
+    pub platform_node_id: UInt160,
+    pub platform_p2p_port: u16,
+    pub platform_http_port: u16,
+    /// BLS signature of the operator (legacy or basic scheme by the payload version)
+    pub payload_signature: UInt768,
+}
+
+impl<'a> TryRead<'a, Endian> for ProviderUpdateServiceTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let provider_update_service_transaction_version = read_provider_tx_version(bytes, offset, endian)?;
+        // Masternode type is the part of the payload since the basic BLS version
+        let mn_type = if provider_update_service_transaction_version >= PROVIDER_TX_VERSION_BASIC_BLS {
+            read_masternode_type(bytes, offset, endian)?
+        } else {
+            MasternodeType::Regular
+        };
+        let provider_registration_transaction_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let socket_address = bytes.read_with::<SocketAddress>(offset, ())?;
+        let script_operator_payout = bytes.read_with::<VarBytes>(offset, endian)?.1.to_vec();
+        let inputs_hash = bytes.read_with::<UInt256>(offset, endian)?;
+        let (platform_node_id, platform_p2p_port, platform_http_port) = read_platform_fields(bytes, offset, endian, mn_type)?;
+        let payload_signature = bytes.read_with::<UInt768>(offset, endian)?;
+        base.tx_type = ProviderUpdateService;
+        base.payload_offset = *offset;
+        let mut tx = Self {
+            base,
+            provider_update_service_transaction_version,
+            mn_type,
+            provider_registration_transaction_hash,
+            socket_address,
+            script_operator_payout,
+            inputs_hash,
+            platform_node_id,
+            platform_p2p_port,
+            platform_http_port,
+            payload_signature,
+        };
+        tx.update_tx_hash();
+        Ok((tx, *offset))
+    }
+}
+
+impl ProviderUpdateServiceTransaction {
+    /// Payload version must match the BLS scheme of the operator key (1 for legacy, 2 for basic)
+    pub fn new(
+        base: Transaction,
+        provider_update_service_transaction_version: u16,
+        provider_registration_transaction_hash: UInt256,
+        socket_address: SocketAddress,
+        script_operator_payout: Vec<u8>,
+    ) -> Self {
+        let mut tx = Self {
+            base: Transaction { tx_type: ProviderUpdateService, ..base },
+            provider_update_service_transaction_version,
+            mn_type: MasternodeType::Regular,
+            provider_registration_transaction_hash,
+            socket_address,
+            script_operator_payout,
+            inputs_hash: UInt256::MIN,
+            platform_node_id: UInt160::MIN,
+            platform_p2p_port: 0,
+            platform_http_port: 0,
+            payload_signature: UInt768::MIN,
+        };
+        tx.update_inputs_hash();
+        tx
+    }
+
+    /// Update of EvoNode service, `None` unless the payload is of the basic BLS version
+    pub fn with_platform_node(mut self, platform_node_id: UInt160, platform_p2p_port: u16, platform_http_port: u16) -> Option<Self> {
+        if self.provider_update_service_transaction_version < PROVIDER_TX_VERSION_BASIC_BLS {
+            return None;
+        }
+        self.mn_type = MasternodeType::HighPerformance;
+        self.platform_node_id = platform_node_id;
+        self.platform_p2p_port = platform_p2p_port;
+        self.platform_http_port = platform_http_port;
+        self.update_tx_hash();
+        Some(self)
+    }
+
+    fn payload_data_with_signature(&self, with_signature: bool) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.provider_update_service_transaction_version.enc(&mut buffer);
+        if self.provider_update_service_transaction_version >= PROVIDER_TX_VERSION_BASIC_BLS {
+            u16::from(self.mn_type).enc(&mut buffer);
+        }
+        self.provider_registration_transaction_hash.enc(&mut buffer);
+        self.socket_address.enc(&mut buffer);
+        self.script_operator_payout.enc(&mut buffer);
+        self.inputs_hash.enc(&mut buffer);
+        if self.mn_type == MasternodeType::HighPerformance {
+            self.platform_node_id.enc(&mut buffer);
+            self.platform_p2p_port.enc(&mut buffer);
+            self.platform_http_port.enc(&mut buffer);
+        }
+        if with_signature {
+            self.payload_signature.enc(&mut buffer);
+        }
+        buffer
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        self.payload_data_with_signature(true)
+    }
+
+    /// Hash of the payload without the signature
+    pub fn payload_hash(&self) -> UInt256 {
+        UInt256::sha256d(self.payload_data_with_signature(false))
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        self.to_data_with_subscript_index(u64::MAX)
+    }
+
+    pub fn to_data_with_subscript_index(&self, subscript_index: u64) -> Vec<u8> {
+        let mut buffer = Transaction::data_with_subscript_index_static(
+            subscript_index,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        );
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    pub fn update_inputs_hash(&mut self) {
+        self.inputs_hash = self.base.inputs_hash();
+        self.update_tx_hash();
+    }
+
+    fn update_tx_hash(&mut self) {
+        self.base.extra_payload = Some(self.payload_data());
+        self.base.tx_hash = Some(UInt256::sha256d(self.to_data()));
+    }
+
+    pub fn sign_payload_with_operator_key(&mut self, operator_key: &BLSKey) {
+        self.payload_signature = operator_key.sign_digest(self.payload_hash());
+        self.update_tx_hash();
+    }
+
+    /// Operator key is taken from the masternode list (or the last ProRegTx/ProUpRegTx)
+    pub fn verify_payload_signature(&self, operator_key: &OperatorPublicKey) -> bool {
+        BLSKey::verify_with_public_key(self.payload_hash(), self.payload_signature, operator_key.data, operator_key.is_legacy())
+    }
+}
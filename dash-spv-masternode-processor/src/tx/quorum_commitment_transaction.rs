@@ -0,0 +1,79 @@
+use byte::ctx::Endian;
+use byte::{BytesExt, TryRead};
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::byte_util::Zeroable;
+use crate::crypto::UInt256;
+use crate::models::LLMQEntry;
+use crate::tx::{Transaction, TransactionType::QuorumCommitment};
+
+pub const QUORUM_COMMITMENT_TX_VERSION: u16 = 1;
+
+/// Final commitment of the quorum mined in the block (it has no inputs and outputs)
+#[derive(Debug, Clone)]
+pub struct QuorumCommitmentTransaction {
+    pub base: Transaction,
+    pub quorum_commitment_transaction_version: u16,
+    /// Height of the block which includes the commitment
+    pub quorum_commitment_height: u32,
+    pub quorum_entry: LLMQEntry,
+}
+
+impl<'a> TryRead<'a, Endian> for QuorumCommitmentTransaction {
+    fn try_read(bytes: &'a [u8], endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let mut base = bytes.read_with::<Transaction>(offset, endian)?;
+        let _extra_payload_size = bytes.read_with::<VarInt>(offset, endian)?;
+        let quorum_commitment_transaction_version = bytes.read_with::<u16>(offset, endian)?;
+        let quorum_commitment_height = bytes.read_with::<u32>(offset, endian)?;
+        let quorum_entry = bytes.read_with::<LLMQEntry>(offset, endian)?;
+        base.tx_type = QuorumCommitment;
+        base.payload_offset = *offset;
+        let mut tx = Self { base, quorum_commitment_transaction_version, quorum_commitment_height, quorum_entry };
+        tx.base.tx_hash = Some(UInt256::sha256d(tx.to_data()));
+        Ok((tx, *offset))
+    }
+}
+
+impl QuorumCommitmentTransaction {
+    pub fn new(quorum_commitment_height: u32, quorum_entry: LLMQEntry) -> Self {
+        let mut tx = Self {
+            base: Transaction::new(QuorumCommitment, vec![], vec![], 0),
+            quorum_commitment_transaction_version: QUORUM_COMMITMENT_TX_VERSION,
+            quorum_commitment_height,
+            quorum_entry,
+        };
+        tx.base.tx_hash = Some(UInt256::sha256d(tx.to_data()));
+        tx
+    }
+
+    pub fn payload_data(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.quorum_commitment_transaction_version.enc(&mut buffer);
+        self.quorum_commitment_height.enc(&mut buffer);
+        self.quorum_entry.enc(&mut buffer);
+        buffer
+    }
+
+    pub fn to_data(&self) -> Vec<u8> {
+        let mut buffer = Transaction::data_with_subscript_index_static(
+            u64::MAX,
+            self.base.version,
+            self.base.tx_type,
+            &self.base.inputs,
+            &self.base.outputs,
+            self.base.lock_time,
+        );
+        self.payload_data().enc(&mut buffer);
+        buffer
+    }
+
+    /// Null commitment is mined when the quorum failed to form during the DKG,
+    /// it doesn't add the quorum to the list
+    pub fn is_null(&self) -> bool {
+        let entry = &self.quorum_entry;
+        entry.signers_bitset.iter().all(|byte| *byte == 0) &&
+            entry.valid_members_bitset.iter().all(|byte| *byte == 0) &&
+            entry.public_key.is_zero()
+    }
+}
@@ -0,0 +1,125 @@
+use crate::blockdata::interpreter::remove_code_separators;
+use crate::consensus::encode::VarInt;
+use crate::consensus::Encodable;
+use crate::crypto::UInt256;
+use crate::tx::{Transaction, TransactionType};
+use crate::tx::transaction::SPECIAL_TX_VERSION;
+
+const SIGHASH_ANYONECANPAY: u32 = 0x80;
+const SIGHASH_MODE_MASK: u32 = 0x1f;
+
+/// Legacy signature hash types (the last byte of the input signature)
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SigHashType {
+    All = 0x01,
+    None = 0x02,
+    Single = 0x03,
+    AllPlusAnyoneCanPay = 0x81,
+    NonePlusAnyoneCanPay = 0x82,
+    SinglePlusAnyoneCanPay = 0x83,
+}
+
+impl TryFrom<u32> for SigHashType {
+    type Error = byte::Error;
+
+    fn try_from(orig: u32) -> Result<Self, Self::Error> {
+        match orig {
+            0x01 => Ok(Self::All),
+            0x02 => Ok(Self::None),
+            0x03 => Ok(Self::Single),
+            0x81 => Ok(Self::AllPlusAnyoneCanPay),
+            0x82 => Ok(Self::NonePlusAnyoneCanPay),
+            0x83 => Ok(Self::SinglePlusAnyoneCanPay),
+            _ => Err(byte::Error::BadInput { err: "Non-standard sighash type" })
+        }
+    }
+}
+
+impl From<SigHashType> for u32 {
+    fn from(value: SigHashType) -> Self {
+        value as u32
+    }
+}
+
+impl SigHashType {
+    pub fn anyone_can_pay(&self) -> bool {
+        u32::from(*self) & SIGHASH_ANYONECANPAY != 0
+    }
+
+    /// Type without the ANYONECANPAY flag
+    pub fn base_type(&self) -> SigHashType {
+        match self {
+            SigHashType::All | SigHashType::AllPlusAnyoneCanPay => SigHashType::All,
+            SigHashType::None | SigHashType::NonePlusAnyoneCanPay => SigHashType::None,
+            SigHashType::Single | SigHashType::SinglePlusAnyoneCanPay => SigHashType::Single,
+        }
+    }
+}
+
+impl Transaction {
+    /// Legacy signature hash of the input, `script_code` is the scriptPubKey of the spent output
+    /// (or the redeem script for P2SH), its OP_CODESEPARATORs are not signed.
+    /// Special transactions commit to their extra payload as well.
+    /// Any `sighash_type` is accepted as in Core: the mode is taken from its lower 5 bits and the ANYONECANPAY flag,
+    /// undefined modes sign all the outputs
+    pub fn signature_hash(&self, input_index: usize, script_code: &[u8], sighash_type: impl Into<u32>) -> UInt256 {
+        let sighash_type = sighash_type.into();
+        let hash_none = sighash_type & SIGHASH_MODE_MASK == u32::from(SigHashType::None);
+        let hash_single = sighash_type & SIGHASH_MODE_MASK == u32::from(SigHashType::Single);
+        // Out of range indexes sign the 'one' hash (consensus bug inherited from Bitcoin)
+        if input_index >= self.inputs.len() || (hash_single && input_index >= self.outputs.len()) {
+            let mut one = [0u8; 32];
+            one[0] = 1;
+            return UInt256(one);
+        }
+        let mut buffer: Vec<u8> = Vec::new();
+        self.version.enc(&mut buffer);
+        u16::from(self.tx_type).enc(&mut buffer);
+        let signed_inputs = if sighash_type & SIGHASH_ANYONECANPAY != 0 {
+            input_index..input_index + 1
+        } else {
+            0..self.inputs.len()
+        };
+        VarInt(signed_inputs.len() as u64).enc(&mut buffer);
+        for i in signed_inputs {
+            let input = &self.inputs[i];
+            input.input_hash.enc(&mut buffer);
+            input.index.enc(&mut buffer);
+            if i == input_index {
+                remove_code_separators(script_code).enc(&mut buffer);
+                input.sequence.enc(&mut buffer);
+            } else {
+                VarInt(0).enc(&mut buffer);
+                // Other inputs can be updated for NONE and SINGLE
+                let sequence = if hash_none || hash_single { 0 } else { input.sequence };
+                sequence.enc(&mut buffer);
+            }
+        }
+        if hash_none {
+            VarInt(0).enc(&mut buffer);
+        } else if hash_single {
+            VarInt(input_index as u64 + 1).enc(&mut buffer);
+            // Outputs before the signed one are blanked
+            for _ in 0..input_index {
+                u64::MAX.enc(&mut buffer);
+                VarInt(0).enc(&mut buffer);
+            }
+            let output = &self.outputs[input_index];
+            output.amount.enc(&mut buffer);
+            output.script.clone().unwrap_or_default().enc(&mut buffer);
+        } else {
+            VarInt(self.outputs.len() as u64).enc(&mut buffer);
+            self.outputs.iter().for_each(|output| {
+                output.amount.enc(&mut buffer);
+                output.script.clone().unwrap_or_default().enc(&mut buffer);
+            });
+        }
+        self.lock_time.enc(&mut buffer);
+        if self.version >= SPECIAL_TX_VERSION && self.tx_type != TransactionType::Classic {
+            self.extra_payload.clone().unwrap_or_default().enc(&mut buffer);
+        }
+        sighash_type.enc(&mut buffer);
+        UInt256::sha256d(buffer)
+    }
+}
@@ -10,6 +10,9 @@ pub const TX_UNCONFIRMED: i32 = i32::MAX;
 
 pub static SIGHASH_ALL: u32 = 1;
 
+/// Version of the transaction which has the type and the extra payload (DIP-2)
+pub const SPECIAL_TX_VERSION: u16 = 3;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum TransactionType {
@@ -61,7 +64,40 @@ impl TransactionType {
         *self as u16
     }
     pub fn requires_inputs(&self) -> bool {
-        true
+        // withdrawal from the credit pool and mined quorum commitment have no inputs
+        !matches!(self, TransactionType::AssetUnlock | TransactionType::QuorumCommitment)
+    }
+}
+
+/// Reference to the output of the previous transaction
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TransactionOutPoint {
+    pub hash: UInt256,
+    pub index: u32,
+}
+
+impl TransactionOutPoint {
+    pub fn new(hash: UInt256, index: u32) -> Self {
+        Self { hash, index }
+    }
+}
+
+impl<'a> TryRead<'a, Endian> for TransactionOutPoint {
+    fn try_read(bytes: &'a [u8], _endian: Endian) -> byte::Result<(Self, usize)> {
+        let offset = &mut 0;
+        let hash = bytes.read_with::<UInt256>(offset, LE)?;
+        let index = bytes.read_with::<u32>(offset, LE)?;
+        Ok((Self { hash, index }, *offset))
+    }
+}
+
+impl Encodable for TransactionOutPoint {
+    #[inline]
+    fn consensus_encode<W: std::io::Write>(&self, mut writer: W) -> Result<usize, std::io::Error> {
+        let mut offset = 0;
+        offset += self.hash.consensus_encode(&mut writer)?;
+        offset += self.index.consensus_encode(&mut writer)?;
+        Ok(offset)
     }
 }
 
@@ -171,10 +207,27 @@ pub struct Transaction {
     pub tx_hash: Option<UInt256>,
     pub tx_type: TransactionType,
     pub payload_offset: usize,
+    /// Extra payload of the special transaction (DIP-2), the signature hash commits to it
+    pub extra_payload: Option<Vec<u8>>,
     pub block_height: u32,
 }
 
 impl Transaction {
+    /// Special transaction without payload (it's serialized by the typed transaction)
+    pub fn new(tx_type: TransactionType, inputs: Vec<TransactionInput>, outputs: Vec<TransactionOutput>, lock_time: u32) -> Self {
+        Self {
+            inputs,
+            outputs,
+            lock_time,
+            version: SPECIAL_TX_VERSION,
+            tx_hash: None,
+            tx_type,
+            payload_offset: 0,
+            extra_payload: None,
+            block_height: TX_UNCONFIRMED as u32,
+        }
+    }
+
     pub fn to_data(&self) -> Vec<u8> {
         self.to_data_with_subscript_index(u64::MAX)
     }
@@ -250,6 +303,17 @@ impl Transaction {
         buffer
     }
 
+    /// Hash of all the input outpoints, it's committed in the payload of provider transactions
+    /// to protect them from the replay
+    pub fn inputs_hash(&self) -> UInt256 {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.inputs.iter().for_each(|input| {
+            input.input_hash.enc(&mut buffer);
+            input.index.enc(&mut buffer);
+        });
+        UInt256::sha256d(buffer)
+    }
+
     pub fn input_addresses(&self) -> Vec<Vec<u8>> {
         /*let script_map = ScriptMap::MAINNET;
         self.inputs.iter().filter_map(|input| {
@@ -308,6 +372,11 @@ impl<'a> TryRead<'a, Endian> for Transaction {
             outputs.push(bytes.read_with::<TransactionOutput>(offset, endian)?);
         }
         let lock_time = bytes.read_with::<u32>(offset, endian)?;
+        // Payload is parsed by the typed transaction, so it's only looked ahead here
+        let extra_payload = (version >= SPECIAL_TX_VERSION && tx_type != TransactionType::Classic)
+            .then(|| bytes.read_with::<VarBytes>(&mut offset.clone(), endian).ok())
+            .flatten()
+            .map(|payload| payload.1.to_vec());
         let mut tx = Self {
             inputs,
             outputs,
@@ -315,6 +384,7 @@ impl<'a> TryRead<'a, Endian> for Transaction {
             tx_type,
             lock_time,
             payload_offset: *offset,
+            extra_payload,
             tx_hash: None,
             block_height: TX_UNCONFIRMED as u32,
         };
@@ -0,0 +1,314 @@
+use std::cmp::Reverse;
+use crate::chain::params::{Params, ScriptMap, TX_MAX_SIZE};
+use crate::chain::tx::protocol::{MAX_ECDSA_SIGNATURE_SIZE, TX_LOCKTIME, TXIN_SEQUENCE};
+use crate::consensus::encode::VarInt;
+use crate::tx::{Transaction, TransactionInput, TransactionOutPoint, TransactionOutput, TransactionType};
+use crate::util::address::address;
+use crate::util::data_append::DataAppend;
+
+/// Size of the compressed public key pushed into the P2PKH scriptSig
+const COMPRESSED_PUBLIC_KEY_SIZE: u64 = 33;
+/// Size of the P2PKH scriptPubKey (used for the change output)
+const P2PKH_SCRIPT_SIZE: u64 = 25;
+/// Upper bound of the branch-and-bound search steps before falling back to the largest-first
+const BRANCH_AND_BOUND_MAX_TRIES: usize = 100000;
+
+/// Unspent output the transaction can spend
+#[derive(Clone, Debug, PartialEq)]
+pub struct Utxo {
+    pub outpoint: TransactionOutPoint,
+    pub amount: u64,
+    /// scriptPubKey of the output (it's used as the subscript during signing)
+    pub script: Vec<u8>,
+}
+
+impl Utxo {
+    pub fn new(outpoint: TransactionOutPoint, amount: u64, script: Vec<u8>) -> Self {
+        Self { outpoint, amount, script }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum CoinSelection {
+    /// Spend the biggest outputs first
+    LargestFirst,
+    /// Look for the set of outputs which doesn't need the change,
+    /// falls back to the largest-first if there is no such set
+    #[default]
+    BranchAndBound,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransactionBuilderError {
+    NoOutputs,
+    InvalidAddress(String),
+    /// Output amount is below the `min_output_amount`
+    DustOutput(u64),
+    InsufficientFunds {
+        available: u64,
+        required: u64,
+    },
+    /// Selected outputs leave the change, but there is no change address
+    NoChangeAddress(u64),
+    /// Estimated size exceeds the `TX_MAX_SIZE`
+    TransactionTooLarge(u64),
+}
+
+impl std::fmt::Display for TransactionBuilderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionBuilderError::NoOutputs =>
+                write!(f, "Transaction has no outputs"),
+            TransactionBuilderError::InvalidAddress(address) =>
+                write!(f, "Invalid address {}", address),
+            TransactionBuilderError::DustOutput(amount) =>
+                write!(f, "Output amount {} is below the dust limit", amount),
+            TransactionBuilderError::InsufficientFunds { available, required } =>
+                write!(f, "Insufficient funds: {} available, {} required", available, required),
+            TransactionBuilderError::NoChangeAddress(change) =>
+                write!(f, "No change address for the change {}", change),
+            TransactionBuilderError::TransactionTooLarge(size) =>
+                write!(f, "Transaction size {} exceeds the limit", size),
+        }
+    }
+}
+
+impl std::error::Error for TransactionBuilderError {}
+
+/// Builds the unsigned classic transaction: inputs carry the scriptPubKey of the spent output
+/// as the subscript and have no signature yet
+#[derive(Clone, Debug)]
+pub struct TransactionBuilder {
+    pub params: Params,
+    pub utxos: Vec<Utxo>,
+    pub outputs: Vec<TransactionOutput>,
+    pub change_script: Option<Vec<u8>>,
+    pub coin_selection: CoinSelection,
+    pub lock_time: u32,
+}
+
+impl TransactionBuilder {
+    pub fn new(params: Params) -> Self {
+        Self {
+            params,
+            utxos: vec![],
+            outputs: vec![],
+            change_script: None,
+            coin_selection: CoinSelection::default(),
+            lock_time: TX_LOCKTIME,
+        }
+    }
+
+    fn script_map(&self) -> ScriptMap {
+        self.params.chain_type.script_map()
+    }
+
+    fn script_for_address(&self, address: &str) -> Result<Vec<u8>, TransactionBuilderError> {
+        let script_map = self.script_map();
+        if address::is_valid_dash_address_for_script_map(address, &script_map) {
+            Ok(Vec::<u8>::script_pub_key_for_address(address, &script_map))
+        } else {
+            Err(TransactionBuilderError::InvalidAddress(address.to_string()))
+        }
+    }
+
+    pub fn add_utxo(mut self, utxo: Utxo) -> Self {
+        self.utxos.push(utxo);
+        self
+    }
+
+    pub fn add_utxos(mut self, utxos: impl IntoIterator<Item = Utxo>) -> Self {
+        self.utxos.extend(utxos);
+        self
+    }
+
+    pub fn add_output_script(mut self, script: Vec<u8>, amount: u64) -> Self {
+        self.outputs.push(TransactionOutput { amount, script: Some(script), address: None });
+        self
+    }
+
+    pub fn add_output_address(self, address: &str, amount: u64) -> Result<Self, TransactionBuilderError> {
+        let script = self.script_for_address(address)?;
+        Ok(self.add_output_script(script, amount))
+    }
+
+    pub fn change_address(mut self, address: &str) -> Result<Self, TransactionBuilderError> {
+        self.change_script = Some(self.script_for_address(address)?);
+        Ok(self)
+    }
+
+    pub fn change_script(mut self, script: Vec<u8>) -> Self {
+        self.change_script = Some(script);
+        self
+    }
+
+    pub fn coin_selection(mut self, coin_selection: CoinSelection) -> Self {
+        self.coin_selection = coin_selection;
+        self
+    }
+
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Estimated size of the P2PKH input with the biggest possible signature
+    pub fn estimated_input_size() -> u64 {
+        let script_sig_size = 1 + MAX_ECDSA_SIGNATURE_SIZE as u64 + 1 + COMPRESSED_PUBLIC_KEY_SIZE;
+        32 + 4 + VarInt(script_sig_size).len() as u64 + script_sig_size + 4
+    }
+
+    pub fn output_size(script_size: u64) -> u64 {
+        8 + VarInt(script_size).len() as u64 + script_size
+    }
+
+    /// Estimated size of the signed transaction
+    pub fn estimated_size(&self, input_count: usize, with_change: bool) -> u64 {
+        let mut output_sizes = self.outputs.iter()
+            .map(|output| Self::output_size(output.script.as_ref().map_or(0, |script| script.len() as u64)))
+            .sum::<u64>();
+        let mut output_count = self.outputs.len() as u64;
+        if with_change {
+            output_sizes += self.change_output_size();
+            output_count += 1;
+        }
+        // version + type + lock_time
+        2 + 2 + 4 +
+            VarInt(input_count as u64).len() as u64 + input_count as u64 * Self::estimated_input_size() +
+            VarInt(output_count).len() as u64 + output_sizes
+    }
+
+    pub fn estimated_fee(&self, input_count: usize, with_change: bool) -> u64 {
+        self.params.fee_for_tx_size(self.estimated_size(input_count, with_change))
+    }
+
+    fn change_output_size(&self) -> u64 {
+        Self::output_size(self.change_script.as_ref().map_or(P2PKH_SCRIPT_SIZE, |script| script.len() as u64))
+    }
+
+    fn largest_first(&self, target: u64) -> Option<Vec<usize>> {
+        let mut indexes = (0..self.utxos.len()).collect::<Vec<_>>();
+        indexes.sort_by_key(|index| Reverse(self.utxos[*index].amount));
+        let mut total = 0;
+        for (count, index) in indexes.iter().enumerate() {
+            total += self.utxos[*index].amount;
+            if total >= target + self.estimated_fee(count + 1, false) {
+                indexes.truncate(count + 1);
+                return Some(indexes);
+            }
+        }
+        None
+    }
+
+    /// Depth-first search for the set of outputs which covers the target and the fee
+    /// and leaves less than the cost of the change, the set with the least excess wins
+    fn branch_and_bound(&self, target: u64) -> Option<Vec<usize>> {
+        let input_fee = self.params.fee_for_tx_size(Self::estimated_input_size());
+        // Only outputs which are worth more than the fee of their spending
+        let mut candidates = self.utxos.iter()
+            .enumerate()
+            .filter(|(_, utxo)| utxo.amount > input_fee)
+            .map(|(index, utxo)| (index, utxo.amount - input_fee))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, value)| Reverse(*value));
+        // Fee of the transaction without inputs (input count varint is considered as 1 byte)
+        let target = target + self.estimated_fee(0, false);
+        let cost_of_change = self.params.fee_for_tx_size(self.change_output_size()) + self.params.min_output_amount();
+        let upper_bound = target + cost_of_change;
+        let mut remaining = candidates.iter().map(|(_, value)| value).sum::<u64>();
+        if remaining < target {
+            return None;
+        }
+        let mut selected = vec![false; candidates.len()];
+        let mut best: Option<(u64, Vec<bool>)> = None;
+        let mut total = 0;
+        let mut depth = 0;
+        for _ in 0..BRANCH_AND_BOUND_MAX_TRIES {
+            let backtrack = if total + remaining < target || total > upper_bound {
+                true
+            } else if total >= target {
+                let excess = total - target;
+                if best.as_ref().is_none_or(|(best_excess, _)| excess < *best_excess) {
+                    best = Some((excess, selected.clone()));
+                    if excess == 0 {
+                        break;
+                    }
+                }
+                true
+            } else {
+                false
+            };
+            if backtrack {
+                // Walk back to the last included output and omit it
+                while depth > 0 && !selected[depth - 1] {
+                    depth -= 1;
+                    remaining += candidates[depth].1;
+                }
+                if depth == 0 {
+                    break;
+                }
+                selected[depth - 1] = false;
+                total -= candidates[depth - 1].1;
+            } else {
+                // Include the next output
+                remaining -= candidates[depth].1;
+                total += candidates[depth].1;
+                selected[depth] = true;
+                depth += 1;
+            }
+        }
+        best.map(|(_, selected)| selected.iter()
+            .zip(candidates.iter())
+            .filter_map(|(included, (index, _))| included.then_some(*index))
+            .collect())
+    }
+
+    pub fn build(&self) -> Result<Transaction, TransactionBuilderError> {
+        if self.outputs.is_empty() {
+            return Err(TransactionBuilderError::NoOutputs);
+        }
+        let min_output_amount = self.params.min_output_amount();
+        if let Some(output) = self.outputs.iter().find(|output| output.amount < min_output_amount) {
+            return Err(TransactionBuilderError::DustOutput(output.amount));
+        }
+        let target = self.outputs.iter().map(|output| output.amount).sum::<u64>();
+        let selection = match self.coin_selection {
+            CoinSelection::LargestFirst => self.largest_first(target),
+            CoinSelection::BranchAndBound => self.branch_and_bound(target)
+                .or_else(|| self.largest_first(target)),
+        };
+        let indexes = selection.ok_or_else(|| TransactionBuilderError::InsufficientFunds {
+            available: self.utxos.iter().map(|utxo| utxo.amount).sum(),
+            required: target + self.estimated_fee(self.utxos.len(), false),
+        })?;
+        let total = indexes.iter().map(|index| self.utxos[*index].amount).sum::<u64>();
+        let mut outputs = self.outputs.clone();
+        let fee_with_change = self.estimated_fee(indexes.len(), true);
+        let change = total.saturating_sub(target + fee_with_change);
+        // The change below the dust limit goes to the fee
+        let with_change = change >= min_output_amount;
+        if with_change {
+            let script = self.change_script.clone().ok_or(TransactionBuilderError::NoChangeAddress(change))?;
+            outputs.push(TransactionOutput { amount: change, script: Some(script), address: None });
+        }
+        let size = self.estimated_size(indexes.len(), with_change);
+        if size > TX_MAX_SIZE {
+            return Err(TransactionBuilderError::TransactionTooLarge(size));
+        }
+        let inputs = indexes.iter()
+            .map(|index| {
+                let utxo = &self.utxos[*index];
+                TransactionInput {
+                    input_hash: utxo.outpoint.hash,
+                    index: utxo.outpoint.index,
+                    script: Some(utxo.script.clone()),
+                    signature: None,
+                    sequence: TXIN_SEQUENCE,
+                }
+            })
+            .collect();
+        let mut tx = Transaction::new(TransactionType::Classic, inputs, outputs, self.lock_time);
+        tx.version = self.params.transaction_version;
+        Ok(tx)
+    }
+}
@@ -0,0 +1,229 @@
+use crate::blockdata::interpreter::verify_script;
+use crate::blockdata::{ScriptError, ScriptVerifyFlags, TransactionSignatureChecker};
+use crate::blockdata::opcodes::all::{OP_CHECKMULTISIG, OP_PUSHBYTES_0, OP_PUSHNUM_1, OP_PUSHNUM_16};
+use crate::consensus::Encodable;
+use crate::crypto::byte_util::BytesDecodable;
+use crate::crypto::UInt160;
+use crate::keys::{ECDSAKey, IKey};
+use crate::tx::sighash::SigHashType;
+use crate::tx::Transaction;
+use crate::util::data_append::DataAppend;
+use crate::util::script::ScriptElement;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransactionSigningError {
+    InputOutOfRange(usize),
+    /// Input has no scriptPubKey of the spent output
+    MissingScript(usize),
+    /// Input has no scriptSig
+    MissingSignature(usize),
+    /// Script is neither P2PKH nor P2SH multisig
+    UnsupportedScript,
+    /// Key (or redeem script) doesn't match the hash in the scriptPubKey
+    KeyMismatch,
+    NotEnoughKeys {
+        required: usize,
+        provided: usize,
+    },
+    SigningFailed,
+    InvalidSignature,
+}
+
+impl std::fmt::Display for TransactionSigningError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransactionSigningError::InputOutOfRange(index) =>
+                write!(f, "Input {} is out of range", index),
+            TransactionSigningError::MissingScript(index) =>
+                write!(f, "Input {} has no script", index),
+            TransactionSigningError::MissingSignature(index) =>
+                write!(f, "Input {} isn't signed", index),
+            TransactionSigningError::UnsupportedScript =>
+                write!(f, "Unsupported script"),
+            TransactionSigningError::KeyMismatch =>
+                write!(f, "Key doesn't match the script"),
+            TransactionSigningError::NotEnoughKeys { required, provided } =>
+                write!(f, "Not enough keys: {} required, {} provided", required, provided),
+            TransactionSigningError::SigningFailed =>
+                write!(f, "Signing failed"),
+            TransactionSigningError::InvalidSignature =>
+                write!(f, "Invalid signature"),
+        }
+    }
+}
+
+impl std::error::Error for TransactionSigningError {}
+
+/// Hash of the pay-to-pubkey-hash scriptPubKey
+pub fn p2pkh_hash(script: &[u8]) -> Option<UInt160> {
+    match script.to_vec().script_elements()[..] {
+        [ScriptElement::Number(0x76/*OP_DUP*/), ScriptElement::Number(0xa9/*OP_HASH160*/), ScriptElement::Data(data, 20), ScriptElement::Number(0x88/*OP_EQUALVERIFY*/), ScriptElement::Number(0xac/*OP_CHECKSIG*/)] =>
+            UInt160::from_bytes(data, &mut 0),
+        _ => None
+    }
+}
+
+/// Hash of the pay-to-script-hash scriptPubKey
+pub fn p2sh_hash(script: &[u8]) -> Option<UInt160> {
+    match script.to_vec().script_elements()[..] {
+        [ScriptElement::Number(0xa9/*OP_HASH160*/), ScriptElement::Data(data, 20), ScriptElement::Number(0x87/*OP_EQUAL*/)] =>
+            UInt160::from_bytes(data, &mut 0),
+        _ => None
+    }
+}
+
+/// `OP_m <pubkey>... OP_n OP_CHECKMULTISIG`
+pub fn multisig_redeem_script(required: usize, public_keys: &[Vec<u8>]) -> Vec<u8> {
+    let mut script = Vec::<u8>::new();
+    (OP_PUSHNUM_1.into_u8() + required as u8 - 1).enc(&mut script);
+    public_keys.iter().for_each(|public_key| public_key.append_script_push_data(&mut script));
+    (OP_PUSHNUM_1.into_u8() + public_keys.len() as u8 - 1).enc(&mut script);
+    OP_CHECKMULTISIG.into_u8().enc(&mut script);
+    script
+}
+
+/// Number of required signatures and public keys of the multisig redeem script
+pub fn parse_multisig_redeem_script(script: &[u8]) -> Option<(usize, Vec<Vec<u8>>)> {
+    let script = script.to_vec();
+    let elements = script.script_elements();
+    let pushnum = |element: &ScriptElement| match element {
+        ScriptElement::Number(code) if (OP_PUSHNUM_1.into_u8()..=OP_PUSHNUM_16.into_u8()).contains(code) =>
+            Some((code - OP_PUSHNUM_1.into_u8() + 1) as usize),
+        _ => None
+    };
+    match &elements[..] {
+        [first, keys @ .., last, ScriptElement::Number(0xae/*OP_CHECKMULTISIG*/)] => {
+            let required = pushnum(first)?;
+            let public_keys = keys.iter()
+                .map(|element| match element {
+                    ScriptElement::Data(data, 33 | 65) => Some(data.to_vec()),
+                    _ => None
+                })
+                .collect::<Option<Vec<_>>>()?;
+            (pushnum(last)? == public_keys.len() && required <= public_keys.len())
+                .then_some((required, public_keys))
+        },
+        _ => None
+    }
+}
+
+pub fn p2pkh_script_sig(signature: &[u8], public_key: &[u8]) -> Vec<u8> {
+    let mut script = Vec::<u8>::new();
+    signature.to_vec().append_script_push_data(&mut script);
+    public_key.to_vec().append_script_push_data(&mut script);
+    script
+}
+
+/// `OP_0 <signature>... <redeem script>` (OP_0 is consumed by the CHECKMULTISIG off-by-one bug)
+pub fn p2sh_multisig_script_sig(signatures: &[Vec<u8>], redeem_script: &[u8]) -> Vec<u8> {
+    let mut script = Vec::<u8>::new();
+    OP_PUSHBYTES_0.into_u8().enc(&mut script);
+    signatures.iter().for_each(|signature| signature.append_script_push_data(&mut script));
+    redeem_script.to_vec().append_script_push_data(&mut script);
+    script
+}
+
+impl Transaction {
+    fn input_script(&self, input_index: usize) -> Result<&Vec<u8>, TransactionSigningError> {
+        self.inputs.get(input_index)
+            .ok_or(TransactionSigningError::InputOutOfRange(input_index))?
+            .script.as_ref()
+            .ok_or(TransactionSigningError::MissingScript(input_index))
+    }
+
+    /// DER signature of the input followed by the sighash type byte
+    pub fn input_signature(&self, input_index: usize, script_code: &[u8], key: &ECDSAKey, sighash_type: SigHashType) -> Result<Vec<u8>, TransactionSigningError> {
+        if input_index >= self.inputs.len() {
+            return Err(TransactionSigningError::InputOutOfRange(input_index));
+        }
+        let hash = self.signature_hash(input_index, script_code, sighash_type);
+        let mut signature = key.sign(&hash.0);
+        if signature.is_empty() {
+            return Err(TransactionSigningError::SigningFailed);
+        }
+        (u32::from(sighash_type) as u8).enc(&mut signature);
+        Ok(signature)
+    }
+
+    /// Sign the input spending P2PKH output, the input script must be the scriptPubKey of the spent output
+    pub fn sign_p2pkh_input(&mut self, input_index: usize, key: &ECDSAKey, sighash_type: SigHashType) -> Result<(), TransactionSigningError> {
+        let script = self.input_script(input_index)?.clone();
+        let hash = p2pkh_hash(&script).ok_or(TransactionSigningError::UnsupportedScript)?;
+        if hash != key.hash160() {
+            return Err(TransactionSigningError::KeyMismatch);
+        }
+        let signature = self.input_signature(input_index, &script, key, sighash_type)?;
+        self.inputs[input_index].signature = Some(p2pkh_script_sig(&signature, &key.public_key_data()));
+        Ok(())
+    }
+
+    /// Sign the input spending P2SH multisig output with the keys from the redeem script,
+    /// signatures are put in the order of the public keys in the redeem script
+    pub fn sign_p2sh_multisig_input(&mut self, input_index: usize, redeem_script: &[u8], keys: &[ECDSAKey], sighash_type: SigHashType) -> Result<(), TransactionSigningError> {
+        let script = self.input_script(input_index)?;
+        let hash = p2sh_hash(script).ok_or(TransactionSigningError::UnsupportedScript)?;
+        if hash != UInt160::hash160(redeem_script) {
+            return Err(TransactionSigningError::KeyMismatch);
+        }
+        let (required, public_keys) = parse_multisig_redeem_script(redeem_script)
+            .ok_or(TransactionSigningError::UnsupportedScript)?;
+        let signing_keys = public_keys.iter()
+            .filter_map(|public_key| keys.iter().find(|key| key.public_key_data().eq(public_key)))
+            .take(required)
+            .collect::<Vec<_>>();
+        if signing_keys.len() < required {
+            return Err(TransactionSigningError::NotEnoughKeys { required, provided: signing_keys.len() });
+        }
+        let signatures = signing_keys.into_iter()
+            .map(|key| self.input_signature(input_index, redeem_script, key, sighash_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.inputs[input_index].signature = Some(p2sh_multisig_script_sig(&signatures, redeem_script));
+        Ok(())
+    }
+
+    /// Run the scriptSig of the input and the scriptPubKey of the spent output through the interpreter
+    pub fn verify_input_script(&self, input_index: usize, script_pub_key: &[u8], flags: ScriptVerifyFlags) -> Result<(), ScriptError> {
+        let script_sig = self.inputs.get(input_index)
+            .ok_or(ScriptError::UnknownError)?
+            .signature.clone()
+            .unwrap_or_default();
+        verify_script(&script_sig, script_pub_key, flags, &TransactionSignatureChecker::new(self, input_index))
+    }
+
+    /// Check the scriptSig of the input against the scriptPubKey of the spent output:
+    /// the scripts are run through the interpreter with the standard flags once the keys match
+    pub fn verify_input(&self, input_index: usize, script_pub_key: &[u8]) -> Result<(), TransactionSigningError> {
+        let script_sig = self.inputs.get(input_index)
+            .ok_or(TransactionSigningError::InputOutOfRange(input_index))?
+            .signature.as_ref()
+            .filter(|script_sig| !script_sig.is_empty())
+            .ok_or(TransactionSigningError::MissingSignature(input_index))?;
+        let elements = script_sig.script_elements();
+        if let Some(hash) = p2pkh_hash(script_pub_key) {
+            match elements[..] {
+                [ScriptElement::Data(_, _), ScriptElement::Data(public_key, _)] if UInt160::hash160(public_key) != hash =>
+                    return Err(TransactionSigningError::KeyMismatch),
+                [ScriptElement::Data(_, _), ScriptElement::Data(_, _)] => {},
+                _ => return Err(TransactionSigningError::UnsupportedScript)
+            }
+        } else if let Some(hash) = p2sh_hash(script_pub_key) {
+            match &elements[..] {
+                [ScriptElement::Number(0/*OP_0*/), signatures @ .., ScriptElement::Data(redeem_script, _)] => {
+                    if UInt160::hash160(redeem_script) != hash {
+                        return Err(TransactionSigningError::KeyMismatch);
+                    }
+                    let (required, _) = parse_multisig_redeem_script(redeem_script)
+                        .ok_or(TransactionSigningError::UnsupportedScript)?;
+                    if signatures.len() != required {
+                        return Err(TransactionSigningError::NotEnoughKeys { required, provided: signatures.len() });
+                    }
+                },
+                _ => return Err(TransactionSigningError::UnsupportedScript)
+            }
+        } else {
+            return Err(TransactionSigningError::UnsupportedScript);
+        }
+        self.verify_input_script(input_index, script_pub_key, ScriptVerifyFlags::STANDARD)
+            .map_err(|_| TransactionSigningError::InvalidSignature)
+    }
+}
@@ -0,0 +1,31 @@
+use crate::chain::common::LLMQType;
+use crate::common::SignatureValidationStatus;
+use crate::ffi::unboxer::unbox_any;
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct ChainLockValidationReport {
+    pub llmq_type: LLMQType,
+    pub block_height: u32,
+    pub block_hash: *mut [u8; 32],
+    pub request_id: *mut [u8; 32],
+    // null if there is no quorum responsible for the request
+    pub quorum_hash: *mut [u8; 32],
+    pub sign_hash: *mut [u8; 32],
+    pub status: SignatureValidationStatus,
+}
+
+impl Drop for ChainLockValidationReport {
+    fn drop(&mut self) {
+        unsafe {
+            unbox_any(self.block_hash);
+            unbox_any(self.request_id);
+            if !self.quorum_hash.is_null() {
+                unbox_any(self.quorum_hash);
+            }
+            if !self.sign_hash.is_null() {
+                unbox_any(self.sign_hash);
+            }
+        }
+    }
+}
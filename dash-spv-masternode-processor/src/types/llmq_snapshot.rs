@@ -12,6 +12,8 @@ use crate::impl_bytes_decodable;
 pub struct LLMQSnapshot {
     pub member_list_length: usize,
     pub member_list: *mut u8,
+    // Number of bits in the member list (masternodeListSize)
+    pub member_count: usize,
     // Skip list at height n
     pub skip_list_length: usize,
     pub skip_list: *mut i32,
@@ -37,9 +39,9 @@ impl<'a> TryRead<'a, Endian> for LLMQSnapshot {
     fn try_read(bytes: &'a [u8], _endian: Endian) -> byte::Result<(Self, usize)> {
         let offset = &mut 0;
         let skip_list_mode = bytes.read_with::<LLMQSnapshotSkipMode>(offset, LE)?;
-        let member_list_length = bytes.read_with::<encode::VarInt>(offset, LE)?.0 as usize;
+        let member_count = bytes.read_with::<encode::VarInt>(offset, LE)?.0 as usize;
         let member_list: &[u8] =
-            bytes.read_with(offset, Bytes::Len((member_list_length + 7) / 8))?;
+            bytes.read_with(offset, Bytes::Len(member_count.div_ceil(8)))?;
         let skip_list_length = bytes.read_with::<encode::VarInt>(offset, LE)?.0 as usize;
         let mut skip_list_vec = Vec::with_capacity(skip_list_length);
         for _i in 0..skip_list_length {
@@ -47,8 +49,9 @@ impl<'a> TryRead<'a, Endian> for LLMQSnapshot {
         }
         Ok((
             Self {
-                member_list_length,
+                member_list_length: member_list.len(),
                 member_list: boxed_vec(member_list.to_vec()),
+                member_count,
                 skip_list_length,
                 skip_list: boxed_vec(skip_list_vec),
                 skip_list_mode,
@@ -0,0 +1,97 @@
+use crate::ffi::unboxer::unbox_any;
+use crate::processing::ProcessingError;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageErrorKind {
+    Parse = 0,
+    SnapshotCountMismatch = 1,
+    Rejected = 2,
+}
+
+/// Mirrors `processing::MessageSection`, indexed sections keep the index in `section_index`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageSection {
+    None = 0,
+    MNListDiff = 1,
+    SnapshotAtHC = 2,
+    SnapshotAtH2C = 3,
+    SnapshotAtH3C = 4,
+    SnapshotAtH4C = 5,
+    DiffAtTip = 6,
+    DiffAtH = 7,
+    DiffAtHC = 8,
+    DiffAtH2C = 9,
+    DiffAtH3C = 10,
+    DiffAtH4C = 11,
+    ExtraShare = 12,
+    LastQuorumPerIndex = 13,
+    QuorumSnapshotList = 14,
+    MNListDiffList = 15,
+    InstantLock = 16,
+    ChainLock = 17,
+}
+
+/// Mirrors `processing::MessageField`, indexed fields keep the index in `field_index`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageField {
+    None = 0,
+    Version = 1,
+    BaseBlockHash = 2,
+    BlockHash = 3,
+    BlockHeight = 4,
+    TotalTransactions = 5,
+    MerkleHashes = 6,
+    MerkleFlags = 7,
+    CoinbaseTransaction = 8,
+    DeletedMasternodes = 9,
+    AddedMasternodes = 10,
+    AddedMasternode = 11,
+    DeletedQuorums = 12,
+    AddedQuorums = 13,
+    AddedQuorum = 14,
+    QuorumsCLSigs = 15,
+    Snapshot = 16,
+    QuorumEntry = 17,
+    Count = 18,
+    Flag = 19,
+    Inputs = 20,
+    TransactionHash = 21,
+    CycleHash = 22,
+    Signature = 23,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct MessageError {
+    pub kind: MessageErrorKind,
+    pub section: MessageSection,
+    pub section_index: usize,
+    // Parse: field which can't be read and its offset in the message
+    pub field: MessageField,
+    pub field_index: usize,
+    pub offset: usize,
+    // Parse (if known) and Rejected: block of the diff
+    pub block_hash: *mut [u8; 32],
+    // Rejected: base block of the diff and the reason
+    pub base_block_hash: *mut [u8; 32],
+    pub processing_error: ProcessingError,
+    // SnapshotCountMismatch
+    pub snapshots_count: usize,
+    pub diffs_count: usize,
+}
+
+impl Drop for MessageError {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.block_hash.is_null() {
+                unbox_any(self.block_hash);
+            }
+            if !self.base_block_hash.is_null() {
+                unbox_any(self.base_block_hash);
+            }
+        }
+    }
+}
@@ -1,12 +1,15 @@
 use crate::types;
 use std::ptr::null_mut;
 use crate::ffi::unboxer::{unbox_any, unbox_any_vec_ptr};
-use crate::processing::ProcessingError;
+use crate::ffi::boxer::boxed;
+use crate::processing::{MessageError, ProcessingError};
 
 #[repr(C)]
 #[derive(Clone, Debug)]
 pub struct MNListDiffResult {
     pub error_status: ProcessingError,
+    // Details of the error if the message can't be read or is rejected
+    pub message_error: *mut types::MessageError,
     pub base_block_hash: *mut [u8; 32],
     pub block_hash: *mut [u8; 32],
     pub has_found_coinbase: bool,       //1 byte
@@ -23,20 +26,31 @@ pub struct MNListDiffResult {
     pub added_quorums_count: usize,
     pub needed_masternode_lists: *mut *mut [u8; 32],
     pub needed_masternode_lists_count: usize,
+    pub needed_block_heights: *mut u32,
+    pub needed_block_heights_count: usize,
+    pub needed_snapshots: *mut *mut [u8; 32],
+    pub needed_snapshots_count: usize,
     pub quorums_cl_signatures_hashes: *mut *mut [u8; 32],
     pub quorums_cl_signatures: *mut *mut [u8; 96],
     pub quorums_cl_sigs_count: usize,
+    pub quorum_validation_reports: *mut *mut types::QuorumValidationReport,
+    pub quorum_validation_reports_count: usize,
 }
 impl MNListDiffResult {
     pub fn default_with_error(error: ProcessingError) -> Self {
         Self { error_status: error, ..Default::default() }
     }
+
+    pub fn default_with_message_error(error: &MessageError) -> Self {
+        Self { error_status: ProcessingError::from(error), message_error: boxed(error.encode()), ..Default::default() }
+    }
 }
 
 impl Default for MNListDiffResult {
     fn default() -> Self {
         MNListDiffResult {
             error_status: ProcessingError::None,
+            message_error: null_mut(),
             base_block_hash: null_mut(),
             block_hash: null_mut(),
             has_found_coinbase: false,
@@ -53,9 +67,15 @@ impl Default for MNListDiffResult {
             added_quorums_count: 0,
             needed_masternode_lists: null_mut(),
             needed_masternode_lists_count: 0,
+            needed_block_heights: null_mut(),
+            needed_block_heights_count: 0,
+            needed_snapshots: null_mut(),
+            needed_snapshots_count: 0,
             quorums_cl_signatures_hashes: null_mut(),
             quorums_cl_signatures: null_mut(),
             quorums_cl_sigs_count: 0,
+            quorum_validation_reports: null_mut(),
+            quorum_validation_reports_count: 0,
         }
     }
 }
@@ -63,6 +83,9 @@ impl Default for MNListDiffResult {
 impl Drop for MNListDiffResult {
     fn drop(&mut self) {
         unsafe {
+            if !self.message_error.is_null() {
+                unbox_any(self.message_error);
+            }
             if !self.base_block_hash.is_null() {
                 unbox_any(self.base_block_hash);
             }
@@ -75,6 +98,12 @@ impl Drop for MNListDiffResult {
             if !self.needed_masternode_lists.is_null() {
                 unbox_any_vec_ptr(self.needed_masternode_lists, self.needed_masternode_lists_count);
             }
+            if !self.needed_block_heights.is_null() {
+                unbox_any(std::ptr::slice_from_raw_parts_mut::<u32>(self.needed_block_heights, self.needed_block_heights_count));
+            }
+            if !self.needed_snapshots.is_null() {
+                unbox_any_vec_ptr(self.needed_snapshots, self.needed_snapshots_count);
+            }
             if !self.added_masternodes.is_null() {
                 unbox_any_vec_ptr(self.added_masternodes, self.added_masternodes_count);
             }
@@ -90,6 +119,9 @@ impl Drop for MNListDiffResult {
             if !self.quorums_cl_signatures.is_null() {
                 unbox_any_vec_ptr(self.quorums_cl_signatures, self.quorums_cl_sigs_count);
             }
+            if !self.quorum_validation_reports.is_null() {
+                unbox_any_vec_ptr(self.quorum_validation_reports, self.quorum_validation_reports_count);
+            }
         }
     }
 }
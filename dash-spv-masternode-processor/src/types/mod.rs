@@ -1,5 +1,7 @@
 pub mod block;
+pub mod chain_lock_validation_report;
 pub mod coinbase_transaction;
+pub mod instant_lock_validation_report;
 pub mod llmq_entry;
 pub mod llmq_indexed_hash;
 pub mod llmq_map;
@@ -9,11 +11,13 @@ pub mod llmq_typed_hash;
 pub mod masternode_entry;
 pub mod masternode_entry_hash;
 pub mod masternode_list;
+pub mod message_error;
 pub mod mn_list_diff;
 pub mod mn_list_diff_result;
 pub mod operator_public_key;
 pub mod qr_info;
 pub mod qr_info_result;
+pub mod quorum_validation_report;
 pub mod quorums_cl_sigs_object;
 pub mod transaction;
 pub mod transaction_input;
@@ -23,7 +27,9 @@ pub mod var_int;
 pub mod opaque_key;
 
 pub use self::block::Block;
+pub use self::chain_lock_validation_report::ChainLockValidationReport;
 pub use self::coinbase_transaction::CoinbaseTransaction;
+pub use self::instant_lock_validation_report::InstantLockValidationReport;
 pub use self::llmq_entry::LLMQEntry;
 pub use self::llmq_indexed_hash::LLMQIndexedHash;
 pub use self::llmq_map::LLMQMap;
@@ -32,12 +38,14 @@ pub use self::llmq_typed_hash::LLMQTypedHash;
 pub use self::masternode_entry::MasternodeEntry;
 pub use self::masternode_entry_hash::MasternodeEntryHash;
 pub use self::masternode_list::MasternodeList;
+pub use self::message_error::MessageError;
 pub use self::mn_list_diff::MNListDiff;
 pub use self::mn_list_diff_result::MNListDiffResult;
 pub use self::operator_public_key::BlockOperatorPublicKey;
 pub use self::operator_public_key::OperatorPublicKey;
 pub use self::qr_info::QRInfo;
 pub use self::qr_info_result::QRInfoResult;
+pub use self::quorum_validation_report::QuorumValidationReport;
 pub use self::quorums_cl_sigs_object::QuorumsCLSigsObject;
 pub use self::transaction::Transaction;
 pub use self::transaction_input::TransactionInput;
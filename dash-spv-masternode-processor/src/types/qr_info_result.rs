@@ -1,12 +1,15 @@
 use std::ptr::null_mut;
 use crate::ffi::unboxer::{unbox_any, unbox_any_vec_ptr};
-use crate::processing::ProcessingError;
+use crate::ffi::boxer::boxed;
+use crate::processing::{MessageError, ProcessingError};
 use crate::types;
 
 #[repr(C)]
 #[derive(Clone)]
 pub struct QRInfoResult {
     pub error_status: ProcessingError,
+    // Details of the error if the message can't be read or is rejected
+    pub message_error: *mut types::MessageError,
     pub result_at_tip: *mut types::MNListDiffResult,
     pub result_at_h: *mut types::MNListDiffResult,
     pub result_at_h_c: *mut types::MNListDiffResult,
@@ -31,6 +34,7 @@ impl Default for QRInfoResult {
     fn default() -> Self {
         Self {
             error_status: ProcessingError::None,
+            message_error: null_mut(),
             result_at_tip: null_mut(),
             result_at_h: null_mut(),
             result_at_h_c: null_mut(),
@@ -55,6 +59,9 @@ impl Default for QRInfoResult {
 impl Drop for QRInfoResult {
     fn drop(&mut self) {
         unsafe {
+            if !self.message_error.is_null() {
+                unbox_any(self.message_error);
+            }
             if !self.result_at_tip.is_null() {
                 unbox_any(self.result_at_tip);
             }
@@ -104,4 +111,8 @@ impl QRInfoResult {
     pub fn default_with_error(error: ProcessingError) -> Self {
         Self { error_status: error, ..Default::default() }
     }
+
+    pub fn default_with_message_error(error: &MessageError) -> Self {
+        Self { error_status: ProcessingError::from(error), message_error: boxed(error.encode()), ..Default::default() }
+    }
 }
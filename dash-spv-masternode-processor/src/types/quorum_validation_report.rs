@@ -0,0 +1,43 @@
+use crate::chain::common::LLMQType;
+use crate::ffi::unboxer::{unbox_any, unbox_any_vec_ptr};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuorumValidationStatus {
+    Verified = 0,
+    InvalidSigners = 1,
+    InvalidMembers = 2,
+    SignersBelowThreshold = 3,
+    MembersBelowThreshold = 4,
+    InvalidAggregatedSignature = 5,
+    InvalidQuorumSignature = 6,
+    NoMasternodeList = 7,
+    MissingRotationData = 8,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct QuorumValidationReport {
+    pub llmq_type: LLMQType,
+    pub llmq_hash: *mut [u8; 32],
+    pub index: u16,
+    pub status: QuorumValidationStatus,
+    // signers or valid members bitset which is malformed
+    pub invalid_bitset: *mut u8,
+    pub invalid_bitset_length: usize,
+    // number of signers or valid members which is below threshold
+    pub actual_count: u64,
+    pub threshold: u64,
+    pub members: *mut *mut [u8; 32],
+    pub members_count: usize,
+}
+
+impl Drop for QuorumValidationReport {
+    fn drop(&mut self) {
+        unsafe {
+            unbox_any(self.llmq_hash);
+            unbox_any(std::ptr::slice_from_raw_parts_mut::<u8>(self.invalid_bitset, self.invalid_bitset_length));
+            unbox_any_vec_ptr(self.members, self.members_count);
+        }
+    }
+}
@@ -182,11 +182,11 @@ impl DataAppend for Vec<u8> /* io::Write */ {
             1..=0x4b => {
                 (len as u8).enc(&mut writer);
             }
-            0x4c..=0xffff => {
+            0x4c..=0xff => {
                 OP_PUSHDATA1.into_u8().enc(&mut writer);
                 (len as u8).enc(&mut writer);
             },
-            0x10000..=0xffffffff => {
+            0x100..=0xffff => {
                 OP_PUSHDATA2.into_u8().enc(&mut writer);
                 (len as u16).enc(&mut writer);
             },
@@ -257,7 +257,7 @@ impl DataAppend for Vec<u8> /* io::Write */ {
                     if i + std::mem::size_of::<u16>() > len {
                         break 'outer;
                     }
-                    *chunk_size = (self[i] as u16).swap_bytes() as usize;
+                    *chunk_size = u16::from_le_bytes([self[i], self[i + 1]]) as usize;
                     i += std::mem::size_of::<u16>();
                 },
                 0x4e => { // OP_PUSHDATA4
@@ -265,7 +265,7 @@ impl DataAppend for Vec<u8> /* io::Write */ {
                     if i + std::mem::size_of::<u32>() > len {
                         break 'outer;
                     }
-                    *chunk_size = (self[i] as u32).swap_bytes() as usize;
+                    *chunk_size = u32::from_le_bytes([self[i], self[i + 1], self[i + 2], self[i + 3]]) as usize;
                     i += std::mem::size_of::<u32>();
                 },
                 _ => {